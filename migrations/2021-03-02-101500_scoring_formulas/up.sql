@@ -0,0 +1,151 @@
+-- Your SQL goes here
+
+-- Scoring formulas are immutable once created. Changing the formula means creating a new one and activating it,
+-- which gives us a version history for free (the ID doubles as the version number).
+CREATE TYPE scoring_curve AS ENUM ('EXPONENTIAL', 'PIECEWISE');
+
+CREATE TABLE scoring_formulas (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    curve SCORING_CURVE NOT NULL,
+
+    -- Points for a completion of the demon at position 1 and at position list_size respectively. Only used by
+    -- the exponential curve, the piecewise curve has its constants baked in (see formula_base_score)
+    max_score FLOAT NOT NULL DEFAULT 250.0,
+    min_score FLOAT NOT NULL DEFAULT 15.0,
+
+    -- Demons below this position do not award any points
+    list_size SMALLINT NOT NULL,
+
+    -- Demons below this position only award points for 100% records, regardless of their record requirement.
+    -- NULL means the demon's own requirement is always used
+    full_completion_after SMALLINT NULL,
+
+    -- A record with the requirement as progress awards (score at 100%) * progress_base^0 / progress_divisor points,
+    -- which then grows exponentially up to (score at 100%) * progress_base / progress_divisor at 99%.
+    progress_base FLOAT NOT NULL DEFAULT 5.0,
+    progress_divisor FLOAT NOT NULL DEFAULT 10.0,
+
+    active BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+    activated_at TIMESTAMP WITHOUT TIME ZONE NULL,
+
+    CHECK (max_score > 0 AND min_score > 0),
+    CHECK (list_size > 1),
+    CHECK (progress_base > 0 AND progress_divisor > 0)
+);
+
+-- At most one formula can be active at any given time
+CREATE UNIQUE INDEX scoring_formulas_single_active ON scoring_formulas (active) WHERE active;
+
+-- This is the formula from the 2021-02-24-123230_new_formula_once_again migration
+INSERT INTO scoring_formulas (name, curve, list_size, full_completion_after, active, activated_at)
+VALUES ('Piecewise (February 2021)', 'PIECEWISE', 150, 75, TRUE, (NOW() AT TIME ZONE 'utc'));
+
+-- This is the formula that was hardcoded into Demon::score
+INSERT INTO scoring_formulas (name, curve, max_score, min_score, list_size, full_completion_after)
+VALUES ('Exponential (75 demons)', 'EXPONENTIAL', 250.0, 15.0, 75, NULL);
+
+CREATE FUNCTION formula_base_score(curve SCORING_CURVE, max_score FLOAT, min_score FLOAT, list_size FLOAT, demon FLOAT) RETURNS FLOAT AS
+$formula_base_score$
+SELECT CASE curve
+           WHEN 'EXPONENTIAL' THEN
+               max_score * EXP(LN(max_score / min_score) / (1.0 - list_size) * (demon - 1.0))
+           WHEN 'PIECEWISE' THEN
+               CASE
+                   WHEN 125 < demon THEN
+                       150.0 * EXP(((1.0 - demon) * LN(1.0 / 30.0)) / -149.0)
+                   WHEN 50 < demon THEN
+                       60 * (EXP(LN(2.333) * ((51.0 - demon) * (LN(30.0) / 99.0)))) + 1.884
+                   WHEN 20 < demon THEN
+                       -100.0 * (EXP(LN(1.01327) * (demon - 26.489))) + 200.0
+                   ELSE
+                       (250 - 100.39) * (EXP(LN(1.168) * (1 - demon))) + 100.39
+               END
+       END;
+$formula_base_score$
+    LANGUAGE SQL IMMUTABLE;
+
+CREATE FUNCTION formula_score(formula INTEGER, progress FLOAT, demon FLOAT, requirement FLOAT) RETURNS FLOAT AS
+$formula_score$
+SELECT CASE
+           WHEN demon > list_size THEN
+               0.0
+           WHEN full_completion_after IS NOT NULL AND demon > full_completion_after AND progress < 100 THEN
+               0.0
+           WHEN progress = 100 THEN
+               formula_base_score(curve, max_score, min_score, list_size, demon)
+           WHEN progress < requirement THEN
+               0.0
+           ELSE
+               formula_base_score(curve, max_score, min_score, list_size, demon) * EXP(LN(progress_base) * (progress - requirement) / (100 - requirement)) / progress_divisor
+       END
+FROM scoring_formulas
+WHERE id = formula;
+$formula_score$
+    LANGUAGE SQL STABLE;
+
+DROP VIEW players_with_score;
+DROP FUNCTION record_score(FLOAT, FLOAT, FLOAT, FLOAT);
+
+-- Computes the player ranking as it would look like if the given formula was active
+CREATE FUNCTION player_ranking(formula INTEGER)
+    RETURNS TABLE (id INTEGER, name CITEXT, rank BIGINT, score FLOAT, index BIGINT, iso_country_code VARCHAR(2), nation CITEXT) AS
+$player_ranking$
+SELECT players.id,
+       players.name,
+       RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation
+FROM
+    (
+        SELECT pseudo_records.player,
+               SUM(formula_score(formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, pseudo_records.requirement)) as total_score
+        FROM (
+                 SELECT player,
+                        progress,
+                        position,
+                        requirement
+                 FROM records
+                          INNER JOIN demons
+                                     ON demons.id = demon
+                 WHERE status_ = 'APPROVED'
+
+                 UNION
+
+                 SELECT verifier as player,
+                        100.0::FLOAT as progress,
+                        position,
+                        100.0::FLOAT
+                 FROM demons
+
+                 UNION
+
+                 SELECT publisher as player,
+                        0.0::FLOAT as progress,
+                        position,
+                        100.0::FLOAT
+                 FROM demons
+
+                 UNION
+
+                 SELECT creator as player,
+                        0.0::FLOAT as progress,
+                        1.0::FLOAT as position, -- doesn't matter
+                        100.0::FLOAT
+                 FROM creators
+             ) AS pseudo_records
+        GROUP BY player
+    ) scores
+        INNER JOIN players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+$player_ranking$
+    LANGUAGE SQL STABLE;
+
+CREATE VIEW players_with_score AS
+SELECT * FROM player_ranking((SELECT id FROM scoring_formulas WHERE active));
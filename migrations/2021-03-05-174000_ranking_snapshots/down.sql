@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DROP FUNCTION take_ranking_snapshot();
+DROP TABLE ranking_snapshot_entries;
+DROP TABLE ranking_snapshots;
@@ -0,0 +1,55 @@
+-- Your SQL goes here
+
+CREATE TABLE ranking_snapshots (
+    id SERIAL PRIMARY KEY,
+    taken_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+
+    -- The scoring formula that was active when the snapshot was taken
+    formula INTEGER REFERENCES scoring_formulas(id) NOT NULL,
+
+    -- The most recent audit log entry at the time the snapshot was taken. If no new entries were written since, the
+    -- ranking cannot have changed (unless a different formula got activated), so there is no point in taking another
+    -- snapshot.
+    last_audit_id INTEGER NOT NULL
+);
+
+CREATE TABLE ranking_snapshot_entries (
+    snapshot INTEGER REFERENCES ranking_snapshots(id) ON DELETE CASCADE NOT NULL,
+    player INTEGER REFERENCES players(id) ON DELETE CASCADE NOT NULL,
+    rank BIGINT NOT NULL,
+    score FLOAT NOT NULL,
+    index BIGINT NOT NULL,
+    nationality VARCHAR(2) NULL,
+
+    PRIMARY KEY (snapshot, player)
+);
+
+CREATE INDEX ranking_snapshot_entries_player ON ranking_snapshot_entries(player);
+
+-- Returns the ID of the new snapshot, or NULL if nothing changed since the previous one
+CREATE FUNCTION take_ranking_snapshot() RETURNS INTEGER AS
+$take_ranking_snapshot$
+DECLARE
+    current_audit_id INTEGER;
+    current_formula INTEGER;
+    snapshot_id INTEGER;
+BEGIN
+    SELECT COALESCE(MAX(audit_id), 0) INTO current_audit_id FROM audit_log2;
+    SELECT id INTO current_formula FROM scoring_formulas WHERE active;
+
+    IF EXISTS (SELECT FROM ranking_snapshots
+               WHERE id = (SELECT MAX(id) FROM ranking_snapshots)
+                 AND last_audit_id = current_audit_id
+                 AND formula = current_formula) THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO ranking_snapshots (formula, last_audit_id) VALUES (current_formula, current_audit_id) RETURNING id INTO snapshot_id;
+
+    INSERT INTO ranking_snapshot_entries (snapshot, player, rank, score, index, nationality)
+    SELECT snapshot_id, id, rank, score, index, iso_country_code FROM players_with_score;
+
+    RETURN snapshot_id;
+END;
+$take_ranking_snapshot$
+    LANGUAGE plpgsql;
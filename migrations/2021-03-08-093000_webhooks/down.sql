@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE webhook_deliveries;
+DROP TABLE webhooks;
+DROP TYPE delivery_status;
+DROP TYPE webhook_format;
@@ -0,0 +1,40 @@
+-- Your SQL goes here
+
+CREATE TYPE webhook_format AS ENUM ('DISCORD', 'JSON', 'SLACK');
+CREATE TYPE delivery_status AS ENUM ('PENDING', 'DELIVERED', 'FAILED');
+
+CREATE TABLE webhooks (
+    id SERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    format WEBHOOK_FORMAT NOT NULL,
+
+    -- The events this webhook is subscribed to, e.g. 'record_submitted'. See WebhookEvent in the rust code for the
+    -- possible values
+    events TEXT[] NOT NULL DEFAULT '{}',
+
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL
+);
+
+CREATE TABLE webhook_deliveries (
+    id SERIAL PRIMARY KEY,
+    webhook INTEGER REFERENCES webhooks(id) ON DELETE CASCADE NOT NULL,
+    event TEXT NOT NULL,
+
+    -- The fully rendered request body. Rendered when the event happens, so retries send the exact same payload
+    payload TEXT NOT NULL,
+
+    status DELIVERY_STATUS NOT NULL DEFAULT 'PENDING',
+    attempts SMALLINT NOT NULL DEFAULT 0,
+
+    -- The HTTP status code of the last response, or NULL if the request failed before a response was received
+    last_status SMALLINT NULL,
+    last_error TEXT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+    next_attempt_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+    delivered_at TIMESTAMP WITHOUT TIME ZONE NULL
+);
+
+CREATE INDEX webhook_deliveries_pending ON webhook_deliveries(next_attempt_at) WHERE status = 'PENDING';
+CREATE INDEX webhook_deliveries_webhook ON webhook_deliveries(webhook);
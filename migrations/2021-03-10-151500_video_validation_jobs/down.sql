@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE video_validation_jobs;
+DROP TYPE validation_status;
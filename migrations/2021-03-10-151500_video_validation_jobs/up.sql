@@ -0,0 +1,24 @@
+-- Your SQL goes here
+
+CREATE TYPE validation_status AS ENUM ('PENDING', 'VALID', 'NEEDS_REVIEW', 'DEAD');
+
+-- At most one job per record. Re-checking a record's video resets its job instead of creating a new one.
+CREATE TABLE video_validation_jobs (
+    record INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE,
+
+    -- PENDING: not yet checked, or the last check failed for a reason that might be temporary (timeouts, 5xx, ...)
+    -- VALID: the video host confirmed the video exists
+    -- NEEDS_REVIEW: the video host claims the video doesn't exist. A list helper has to take a look
+    -- DEAD: the check kept failing for temporary reasons until we ran out of attempts
+    status VALIDATION_STATUS NOT NULL DEFAULT 'PENDING',
+    attempts SMALLINT NOT NULL DEFAULT 0,
+
+    last_status SMALLINT NULL,
+    last_error TEXT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+    next_attempt_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+    finished_at TIMESTAMP WITHOUT TIME ZONE NULL
+);
+
+CREATE INDEX video_validation_jobs_pending ON video_validation_jobs(next_attempt_at) WHERE status = 'PENDING';
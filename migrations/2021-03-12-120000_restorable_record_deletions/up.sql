@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- The copy of a deleted record stored in record_modifications does not contain the submitter or the
+-- raw footage, both of which are needed to restore the record. Neither are references, see the
+-- comment at the top of the 2019-01-05 audit log migration for why.
+ALTER TABLE record_deletions ADD COLUMN submitter INTEGER NULL;
+ALTER TABLE record_deletions ADD COLUMN raw_footage TEXT NULL;
+
+CREATE OR REPLACE FUNCTION audit_record_deletion() RETURNS trigger AS $record_deletion_trigger$
+    BEGIN
+        INSERT INTO record_modifications (userid, id, progress, video, status_, player, demon)
+            (SELECT id, OLD.id, OLD.progress, OLD.video, OLD.status_, OLD.player, OLD.demon
+            FROM active_user LIMIT 1);
+
+        INSERT INTO record_deletions (userid, id, submitter, raw_footage)
+            (SELECT id, OLD.id, OLD.submitter, OLD.raw_footage FROM active_user LIMIT 1);
+
+        RETURN NULL;
+    END;
+$record_deletion_trigger$ LANGUAGE plpgsql;
@@ -0,0 +1,111 @@
+-- Your SQL goes here
+
+-- Claims list helpers put on records while they review them. A claim is only valid until `expires_at`. Expired claims are
+-- not cleaned up, they are simply ignored (and overwritten by the next claim on the record)
+CREATE TABLE record_claims (
+    record INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE NOT NULL,
+    member INTEGER REFERENCES members(member_id) ON DELETE CASCADE NOT NULL,
+    claimed_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+    expires_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+-- Logged whenever a record is claimed or an existing claim is renewed
+CREATE TABLE record_claim_additions (
+    id INTEGER NOT NULL, -- REFERENCES records(id)
+    member INTEGER NOT NULL, -- REFERENCES members(member_id)
+    expires_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+) INHERITS (audit_log2);
+
+CREATE FUNCTION audit_record_claim_addition() RETURNS trigger AS $record_claim_add_trigger$
+    BEGIN
+        INSERT INTO record_claim_additions (userid, id, member, expires_at) (SELECT id, NEW.record, NEW.member, NEW.expires_at FROM active_user LIMIT 1);
+        RETURN NEW;
+    END;
+$record_claim_add_trigger$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_claim_addition_trigger AFTER INSERT OR UPDATE ON record_claims FOR EACH ROW EXECUTE PROCEDURE audit_record_claim_addition();
+
+-- Logged whenever a claim is released before it expired. `member` is the holder of the released claim, which is not
+-- necessarily the user that released it
+CREATE TABLE record_claim_deletions (
+    id INTEGER NOT NULL, -- REFERENCES records(id)
+    member INTEGER NOT NULL -- REFERENCES members(member_id)
+) INHERITS (audit_log2);
+
+CREATE FUNCTION audit_record_claim_deletion() RETURNS trigger AS $record_claim_deletion_trigger$
+    BEGIN
+        IF (OLD.expires_at > (NOW() AT TIME ZONE 'utc')) THEN
+            INSERT INTO record_claim_deletions (userid, id, member) (SELECT id, OLD.record, OLD.member FROM active_user LIMIT 1);
+        END IF;
+        RETURN NULL;
+    END;
+$record_claim_deletion_trigger$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_claim_deletion_trigger AFTER DELETE ON record_claims FOR EACH ROW EXECUTE PROCEDURE audit_record_claim_deletion();
+
+-- Make claims show up in the global audit log feed
+CREATE OR REPLACE VIEW audit_log_feed AS
+    SELECT time, audit_id, userid, 'record' AS model, 'addition' AS action, id AS target, NULL::TEXT AS details
+    FROM record_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(record_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM record_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'record', 'deletion', id,
+           jsonb_strip_nulls(to_jsonb(record_deletions) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM record_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_note', 'addition', id, NULL::TEXT
+    FROM record_notes_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_note', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(record_notes_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM record_notes_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_note', 'deletion', id, NULL::TEXT
+    FROM record_notes_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'demon', 'addition', id, NULL::TEXT
+    FROM demon_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'demon', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(demon_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM demon_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'creator', 'addition', demon, jsonb_build_object('creator', creator)::TEXT
+    FROM creator_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'creator', 'deletion', demon, jsonb_build_object('creator', creator)::TEXT
+    FROM creator_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'player', 'addition', id, NULL::TEXT
+    FROM player_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'player', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(player_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM player_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'player', 'deletion', id, NULL::TEXT
+    FROM player_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'submitter', 'modification', submitter,
+           jsonb_strip_nulls(to_jsonb(submitter_modifications) - 'time' - 'audit_id' - 'userid' - 'submitter')::TEXT
+    FROM submitter_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'user', 'addition', id, NULL::TEXT
+    FROM user_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'user', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(user_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM user_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'user', 'deletion', id, NULL::TEXT
+    FROM user_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_claim', 'addition', id,
+           jsonb_build_object('member', member, 'expires_at', expires_at)::TEXT
+    FROM record_claim_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_claim', 'deletion', id, jsonb_build_object('member', member)::TEXT
+    FROM record_claim_deletions;
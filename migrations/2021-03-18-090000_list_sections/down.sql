@@ -0,0 +1,23 @@
+-- This file should undo anything in `up.sql`
+
+CREATE OR REPLACE FUNCTION formula_score(formula INTEGER, progress FLOAT, demon FLOAT, requirement FLOAT) RETURNS FLOAT AS
+$formula_score$
+SELECT CASE
+           WHEN demon > list_size THEN
+               0.0
+           WHEN full_completion_after IS NOT NULL AND demon > full_completion_after AND progress < 100 THEN
+               0.0
+           WHEN progress = 100 THEN
+               formula_base_score(curve, max_score, min_score, list_size, demon)
+           WHEN progress < requirement THEN
+               0.0
+           ELSE
+               formula_base_score(curve, max_score, min_score, list_size, demon) * EXP(LN(progress_base) * (progress - requirement) / (100 - requirement)) / progress_divisor
+       END
+FROM scoring_formulas
+WHERE id = formula;
+$formula_score$
+    LANGUAGE SQL STABLE;
+
+DROP FUNCTION section_weight(FLOAT);
+DROP TABLE list_sections;
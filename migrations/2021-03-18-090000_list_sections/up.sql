@@ -0,0 +1,67 @@
+-- Your SQL goes here
+
+-- Sections of the list (main list, legacy list, ...) used to be defined by the LIST_SIZE and EXTENDED_LIST_SIZE
+-- environment variables. Sections are contiguous position ranges that cannot overlap. Positions not covered by any
+-- section behave like they were part of a section with default settings.
+CREATE TABLE list_sections (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+
+    first_position SMALLINT NOT NULL,
+    -- NULL means the section extends to the end of the list
+    last_position SMALLINT NULL,
+
+    -- The minimal progress submissions for demons in this section need to have. NULL means only the demon's own
+    -- record requirement applies
+    min_progress SMALLINT NULL,
+    submissions_allowed BOOLEAN NOT NULL DEFAULT TRUE,
+
+    -- Factor the score awarded by the active scoring formula is multiplied with for demons in this section
+    scoring_weight FLOAT NOT NULL DEFAULT 1.0,
+
+    -- Demons in legacy sections are not shown on the list overview and their positions are not displayed
+    legacy BOOLEAN NOT NULL DEFAULT FALSE,
+
+    CHECK (first_position >= 1),
+    CHECK (last_position IS NULL OR last_position >= first_position),
+    CHECK (min_progress IS NULL OR (min_progress >= 0 AND min_progress <= 100)),
+    CHECK (scoring_weight >= 0),
+    EXCLUDE USING gist (int4range(first_position, last_position, '[]') WITH &&)
+);
+
+-- These mirror the defaults of the old environment variables (LIST_SIZE = EXTENDED_LIST_SIZE = 75). Deployments that
+-- overrode them need to adjust the sections after migrating
+INSERT INTO list_sections (name, description, first_position, last_position, min_progress, submissions_allowed, legacy)
+VALUES ('Main List', 'The main list section, holding the top hardest challenges. Records here are given a large amounts of points.',
+        1, 75, NULL, TRUE, FALSE),
+       ('Legacy List', 'These are the challenges that have fallen out of grace, and no longer are eligible to be given points.',
+        76, NULL, 100, TRUE, TRUE);
+
+CREATE FUNCTION section_weight(position FLOAT) RETURNS FLOAT AS
+$section_weight$
+SELECT COALESCE(
+    (SELECT scoring_weight FROM list_sections WHERE first_position <= position AND (last_position IS NULL OR position <= last_position)),
+    1.0
+);
+$section_weight$
+    LANGUAGE SQL STABLE;
+
+CREATE OR REPLACE FUNCTION formula_score(formula INTEGER, progress FLOAT, demon FLOAT, requirement FLOAT) RETURNS FLOAT AS
+$formula_score$
+SELECT (CASE
+           WHEN demon > list_size THEN
+               0.0
+           WHEN full_completion_after IS NOT NULL AND demon > full_completion_after AND progress < 100 THEN
+               0.0
+           WHEN progress = 100 THEN
+               formula_base_score(curve, max_score, min_score, list_size, demon)
+           WHEN progress < requirement THEN
+               0.0
+           ELSE
+               formula_base_score(curve, max_score, min_score, list_size, demon) * EXP(LN(progress_base) * (progress - requirement) / (100 - requirement)) / progress_divisor
+       END) * section_weight(demon)
+FROM scoring_formulas
+WHERE id = formula;
+$formula_score$
+    LANGUAGE SQL STABLE;
@@ -0,0 +1,53 @@
+-- Your SQL goes here
+
+-- Demons flagged as completion only only accept 100% records, regardless of their requirement. This allows keeping
+-- the requirement around (for example to re-enable progress records later) without it having any effect.
+ALTER TABLE demons ADD COLUMN completion_only BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE demon_modifications ADD COLUMN completion_only BOOLEAN NULL;
+
+CREATE OR REPLACE FUNCTION audit_demon_modification() RETURNS trigger AS $demon_modification_trigger$
+    DECLARE
+        name_change CITEXT;
+        position_change SMALLINT;
+        requirement_change SMALLINT;
+        video_change VARCHAR(200);
+        verifier_change INT;
+        publisher_change INT;
+        completion_only_change BOOLEAN;
+    BEGIN
+        IF (OLD.name <> NEW.name) THEN
+            name_change = OLD.name;
+        END IF;
+
+        IF (OLD.position <> NEW.position) THEN
+            position_change = OLD.position;
+        END IF;
+
+        IF (OLD.requirement <> NEW.requirement) THEN
+            requirement_change = OLD.requirement;
+        END IF;
+
+        IF (OLD.video <> NEW.video) THEN
+            video_change = OLD.video;
+        END IF;
+
+        IF (OLD.verifier <> NEW.verifier) THEN
+            verifier_change = OLD.verifier;
+        END IF;
+
+        IF (OLD.publisher <> NEW.publisher) THEN
+            publisher_change = OLD.publisher;
+        END IF;
+
+        IF (OLD.completion_only <> NEW.completion_only) THEN
+            completion_only_change = OLD.completion_only;
+        END IF;
+
+        INSERT INTO demon_modifications (userid, name, position, requirement, video, verifier, publisher, completion_only, id)
+            (SELECT id, name_change, position_change, requirement_change, video_change, verifier_change, publisher_change, completion_only_change, NEW.id
+            FROM active_user LIMIT 1);
+
+        RETURN NEW;
+    END;
+$demon_modification_trigger$ LANGUAGE plpgsql;
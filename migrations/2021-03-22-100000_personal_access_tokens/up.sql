@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- Named, long-lived tokens users can hand out to bots and other programs. The tokens themselves are never stored, they
+-- are signed JWTs referencing a row in this table. Deleting the row revokes the token.
+CREATE TABLE personal_access_tokens (
+    id SERIAL PRIMARY KEY,
+    member INTEGER REFERENCES members(member_id) ON DELETE CASCADE NOT NULL,
+    name TEXT NOT NULL,
+
+    -- The permissions requests authenticated with this token are allowed to use. The permissions of the token's owner
+    -- still apply, so revoking a permission from a user also revokes it from all of their tokens
+    scopes BIT(16) NOT NULL DEFAULT B'0000000000000000'::BIT(16),
+
+    created_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+    -- NULL means the token never expires
+    expires_at TIMESTAMP WITHOUT TIME ZONE NULL,
+    last_used TIMESTAMP WITHOUT TIME ZONE NULL,
+
+    UNIQUE (member, name)
+);
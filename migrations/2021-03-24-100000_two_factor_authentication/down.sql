@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE security_policy;
+DROP TABLE two_factor_recovery_codes;
+DROP TABLE two_factor_secrets;
@@ -0,0 +1,32 @@
+-- Your SQL goes here
+
+-- TOTP secrets of accounts using two-factor authentication. A secret is only used for authentication once its enrollment
+-- has been confirmed by providing a valid code. Kept out of `members` so that logging in doesn't show up in the audit log
+CREATE TABLE two_factor_secrets (
+    member INTEGER PRIMARY KEY REFERENCES members(member_id) ON DELETE CASCADE,
+    secret BYTEA NOT NULL,
+    confirmed BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- The time step of the last code used to log in. Codes from this or earlier time steps are rejected, so that every
+    -- code can only be used once
+    last_step BIGINT NULL
+);
+
+-- Single use codes that can be used instead of a TOTP code in case the authenticator is lost. Only their bcrypt hashes
+-- are stored
+CREATE TABLE two_factor_recovery_codes (
+    id SERIAL PRIMARY KEY,
+    member INTEGER REFERENCES members(member_id) ON DELETE CASCADE NOT NULL,
+    code_hash TEXT NOT NULL
+);
+
+-- Account security settings that can be changed by administrators. This table always contains exactly one row
+CREATE TABLE security_policy (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+
+    -- If set, members of the list team without two-factor authentication lose their list team permissions until they
+    -- enable it
+    list_team_requires_2fa BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+INSERT INTO security_policy DEFAULT VALUES;
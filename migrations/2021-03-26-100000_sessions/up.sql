@@ -0,0 +1,19 @@
+-- Your SQL goes here
+
+-- One row per device a user is logged in on. Access tokens handed out for a session are short-lived and reference its row,
+-- so deleting the row logs the device out. Refresh tokens carry the session's current generation, which is incremented
+-- every time one is used.
+CREATE TABLE sessions (
+    id SERIAL PRIMARY KEY,
+    member INTEGER REFERENCES members(member_id) ON DELETE CASCADE NOT NULL,
+    generation INTEGER NOT NULL DEFAULT 0,
+
+    -- IP address and user agent of the last login or refresh
+    ip_address INET NOT NULL,
+    user_agent TEXT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+    last_used TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL
+);
+
+CREATE INDEX ON sessions(member);
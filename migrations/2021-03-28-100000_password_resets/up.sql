@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- Email addresses password reset tokens can be sent to. Kept out of the members table so that they never end up in
+-- the audit log
+CREATE TABLE member_emails (
+    member INTEGER PRIMARY KEY REFERENCES members(member_id) ON DELETE CASCADE,
+    email TEXT NOT NULL
+);
+
+-- The outstanding password reset token of a member, if any. Only a hash of the token is stored.
+CREATE TABLE password_resets (
+    member INTEGER PRIMARY KEY REFERENCES members(member_id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL,
+
+    -- The administrator who issued the token. NULL if it was requested via email
+    issued_by INTEGER NULL REFERENCES members(member_id) ON DELETE SET NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL,
+    expires_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+-- Rate limit state when using RATELIMIT_STORE=postgres. For each limited key, stores the "theoretical arrival time" of
+-- the next request according to the generic cell rate algorithm. Rows whose tat lies in the past carry no information
+-- and are periodically deleted.
+CREATE TABLE ratelimits (
+    scope TEXT NOT NULL,
+
+    -- The IP address the limit applies to, or the empty string for global limits
+    key TEXT NOT NULL,
+
+    tat TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+
+    PRIMARY KEY (scope, key)
+);
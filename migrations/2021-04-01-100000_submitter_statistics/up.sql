@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- Per-submitter statistics about their submissions. When a submitter submitted a record is taken from the audit log.
+-- Restoring a deleted record adds a second audit log entry for it, hence the DISTINCTs
+CREATE VIEW submitter_statistics AS
+    SELECT submitters.submitter_id,
+           COUNT(DISTINCT records.id) AS submitted,
+           COUNT(DISTINCT records.id) FILTER (WHERE records.status_ = 'APPROVED') AS approved,
+           COUNT(DISTINCT records.id) FILTER (WHERE records.status_ = 'REJECTED') AS rejected,
+           COUNT(DISTINCT records.id) FILTER (WHERE records.status_ = 'UNDER_CONSIDERATION') AS under_consideration,
+           MIN(record_additions.time) AS first_seen,
+           MAX(record_additions.time) AS last_seen
+    FROM submitters
+    LEFT OUTER JOIN records ON records.submitter = submitters.submitter_id
+    LEFT OUTER JOIN record_additions ON record_additions.id = records.id
+    GROUP BY submitters.submitter_id;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Logged in users submit under a submitter linked to their account instead of one for their IP address. For those,
+-- ip_address is the address they first submitted from.
+ALTER TABLE submitters ADD COLUMN member INTEGER NULL UNIQUE REFERENCES members(member_id) ON DELETE SET NULL;
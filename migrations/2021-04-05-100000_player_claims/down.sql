@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE players DROP COLUMN youtube_channel;
+
+DROP TABLE player_claims;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- A user can only ever hold a single claim (pending or verified), and every player can only be claimed by a single user. There
+-- can however be multiple pending claims on the same player, since a moderator has to decide which one is legitimate.
+CREATE TABLE player_claims (
+    id SERIAL PRIMARY KEY,
+    member INTEGER NOT NULL UNIQUE REFERENCES members(member_id) ON DELETE CASCADE,
+    player INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    verified BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE UNIQUE INDEX player_claims_verified_player ON player_claims(player) WHERE verified;
+
+-- Can be set by the player themselves once they verified their claim, and is linked to from the stats viewer
+ALTER TABLE players ADD COLUMN youtube_channel VARCHAR(200) NULL;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+-- Remembers which legacy configuration (such as the DISCORD_WEBHOOK environment variable) has already been imported into
+-- the database, so that it isn't imported again after an admin deleted the imported object
+CREATE TABLE legacy_imports (
+    name TEXT PRIMARY KEY,
+    imported_at TIMESTAMP WITHOUT TIME ZONE DEFAULT (NOW() AT TIME ZONE 'utc') NOT NULL
+);
+
+-- Until now, DISCORD_WEBHOOK was imported on every startup, so if the registry was ever used, it has already been imported
+INSERT INTO legacy_imports (name) SELECT 'DISCORD_WEBHOOK' WHERE EXISTS (SELECT FROM webhooks);
@@ -0,0 +1,17 @@
+-- This file should undo anything in `up.sql`
+
+-- Postgres cannot remove values from an enum, so we have to recreate the type
+UPDATE video_validation_jobs SET status = 'VALID' WHERE status = 'SKIPPED';
+
+DROP INDEX video_validation_jobs_pending;
+
+ALTER TYPE validation_status RENAME TO validation_status_old;
+CREATE TYPE validation_status AS ENUM ('PENDING', 'VALID', 'NEEDS_REVIEW', 'DEAD');
+
+ALTER TABLE video_validation_jobs ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE video_validation_jobs ALTER COLUMN status TYPE validation_status USING status::text::validation_status;
+ALTER TABLE video_validation_jobs ALTER COLUMN status SET DEFAULT 'PENDING';
+
+DROP TYPE validation_status_old;
+
+CREATE INDEX video_validation_jobs_pending ON video_validation_jobs(next_attempt_at) WHERE status = 'PENDING';
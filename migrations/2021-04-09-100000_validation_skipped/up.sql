@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- SKIPPED: the record no longer had a video by the time its job was processed, so there was nothing to check
+ALTER TYPE validation_status ADD VALUE 'SKIPPED';
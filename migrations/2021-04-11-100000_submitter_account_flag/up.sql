@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- Deleting an account sets the member column of its submitter to NULL, which made the submitter indistinguishable from the
+-- one for the IP address it first submitted from (and turned an account ban into an IP ban). This column remembers that
+-- the submitter belongs to an account, even after that account is gone.
+ALTER TABLE submitters ADD COLUMN is_account BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE submitters SET is_account = TRUE WHERE member IS NOT NULL;
+
+ALTER TABLE submitters ADD CONSTRAINT submitters_member_is_account CHECK (member IS NULL OR is_account);
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+-- Whether the record_submitted webhook event has been sent for the job's record. Not reset when the job is re-queued, so
+-- that changing or re-checking the video of a record doesn't announce it as a new submission again.
+ALTER TABLE video_validation_jobs ADD COLUMN announced BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Until now, the event was sent whenever a job finished as VALID
+UPDATE video_validation_jobs SET announced = TRUE WHERE status = 'VALID';
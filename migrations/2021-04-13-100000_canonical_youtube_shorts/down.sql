@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+-- There is no way of telling which videos used to be stored in the shorts format, and the 'watch' form is valid for all
+-- of them, so there is nothing to undo
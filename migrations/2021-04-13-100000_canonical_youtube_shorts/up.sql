@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- YouTube Shorts used to be stored as 'https://www.youtube.com/shorts/{id}', even though they are regular YouTube videos that
+-- are also reachable via 'https://www.youtube.com/watch?v={id}'. Store them in the latter form, so that the same video
+-- cannot be used as proof for two records. Records whose video is already used by another record in the 'watch' form are
+-- left alone, so that they can be dealt with by hand.
+UPDATE records
+SET video = 'https://www.youtube.com/watch?v=' || SUBSTRING(video FROM 32)
+WHERE video LIKE 'https://www.youtube.com/shorts/%'
+  AND NOT EXISTS (
+    SELECT 1 FROM records AS other WHERE other.video = 'https://www.youtube.com/watch?v=' || SUBSTRING(records.video FROM 32)
+  );
+
+UPDATE demons
+SET video = 'https://www.youtube.com/watch?v=' || SUBSTRING(video FROM 32)
+WHERE video LIKE 'https://www.youtube.com/shorts/%';
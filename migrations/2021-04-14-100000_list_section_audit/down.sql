@@ -0,0 +1,79 @@
+-- This file should undo anything in `up.sql`
+
+CREATE OR REPLACE VIEW audit_log_feed AS
+    SELECT time, audit_id, userid, 'record' AS model, 'addition' AS action, id AS target, NULL::TEXT AS details
+    FROM record_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(record_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM record_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'record', 'deletion', id,
+           jsonb_strip_nulls(to_jsonb(record_deletions) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM record_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_note', 'addition', id, NULL::TEXT
+    FROM record_notes_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_note', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(record_notes_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM record_notes_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_note', 'deletion', id, NULL::TEXT
+    FROM record_notes_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'demon', 'addition', id, NULL::TEXT
+    FROM demon_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'demon', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(demon_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM demon_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'creator', 'addition', demon, jsonb_build_object('creator', creator)::TEXT
+    FROM creator_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'creator', 'deletion', demon, jsonb_build_object('creator', creator)::TEXT
+    FROM creator_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'player', 'addition', id, NULL::TEXT
+    FROM player_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'player', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(player_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM player_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'player', 'deletion', id, NULL::TEXT
+    FROM player_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'submitter', 'modification', submitter,
+           jsonb_strip_nulls(to_jsonb(submitter_modifications) - 'time' - 'audit_id' - 'userid' - 'submitter')::TEXT
+    FROM submitter_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'user', 'addition', id, NULL::TEXT
+    FROM user_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'user', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(user_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM user_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'user', 'deletion', id, NULL::TEXT
+    FROM user_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_claim', 'addition', id,
+           jsonb_build_object('member', member, 'expires_at', expires_at)::TEXT
+    FROM record_claim_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_claim', 'deletion', id, jsonb_build_object('member', member)::TEXT
+    FROM record_claim_deletions;
+
+DROP TRIGGER list_section_deletion_trigger ON list_sections;
+DROP TRIGGER list_section_modification_trigger ON list_sections;
+DROP TRIGGER list_section_addition_trigger ON list_sections;
+
+DROP FUNCTION audit_list_section_deletion();
+DROP FUNCTION audit_list_section_modification();
+DROP FUNCTION audit_list_section_addition();
+
+DROP TABLE list_section_deletions;
+DROP TABLE list_section_modifications;
+DROP TABLE list_section_additions;
@@ -0,0 +1,181 @@
+-- Your SQL goes here
+
+-- Changing a section's bounds or scoring weight changes the score of every record in it (see `section_weight()`), so
+-- these changes need to show up in the audit log for `take_ranking_snapshot()` to notice them
+CREATE TABLE list_section_additions (
+    id INTEGER NOT NULL -- REFERENCES list_sections(id)
+) INHERITS (audit_log2);
+
+CREATE TABLE list_section_modifications (
+    id INTEGER NOT NULL, -- which section was changed?
+    name TEXT NULL,
+    description TEXT NULL,
+    first_position SMALLINT NULL,
+    last_position SMALLINT NULL,
+    min_progress SMALLINT NULL,
+    submissions_allowed BOOLEAN NULL,
+    scoring_weight FLOAT NULL,
+    legacy BOOLEAN NULL
+) INHERITS (audit_log2);
+
+CREATE TABLE list_section_deletions (
+    id INTEGER NOT NULL
+) INHERITS (audit_log2);
+
+CREATE FUNCTION audit_list_section_addition() RETURNS trigger AS $list_section_add_trigger$
+    BEGIN
+        INSERT INTO list_section_additions (userid, id) (SELECT id, NEW.id FROM active_user LIMIT 1);
+        RETURN NEW;
+    END;
+$list_section_add_trigger$ LANGUAGE plpgsql;
+
+CREATE FUNCTION audit_list_section_modification() RETURNS trigger AS $list_section_modification_trigger$
+    DECLARE
+        name_change TEXT;
+        description_change TEXT;
+        first_position_change SMALLINT;
+        last_position_change SMALLINT;
+        min_progress_change SMALLINT;
+        submissions_allowed_change BOOLEAN;
+        scoring_weight_change FLOAT;
+        legacy_change BOOLEAN;
+    BEGIN
+        IF (OLD.name <> NEW.name) THEN
+            name_change = OLD.name;
+        END IF;
+
+        IF (OLD.description <> NEW.description) THEN
+            description_change = OLD.description;
+        END IF;
+
+        IF (OLD.first_position <> NEW.first_position) THEN
+            first_position_change = OLD.first_position;
+        END IF;
+
+        IF (OLD.last_position IS DISTINCT FROM NEW.last_position) THEN
+            last_position_change = OLD.last_position;
+        END IF;
+
+        IF (OLD.min_progress IS DISTINCT FROM NEW.min_progress) THEN
+            min_progress_change = OLD.min_progress;
+        END IF;
+
+        IF (OLD.submissions_allowed <> NEW.submissions_allowed) THEN
+            submissions_allowed_change = OLD.submissions_allowed;
+        END IF;
+
+        IF (OLD.scoring_weight <> NEW.scoring_weight) THEN
+            scoring_weight_change = OLD.scoring_weight;
+        END IF;
+
+        IF (OLD.legacy <> NEW.legacy) THEN
+            legacy_change = OLD.legacy;
+        END IF;
+
+        INSERT INTO list_section_modifications (userid, id, name, description, first_position, last_position, min_progress,
+                                                submissions_allowed, scoring_weight, legacy)
+            (SELECT id, OLD.id, name_change, description_change, first_position_change, last_position_change, min_progress_change,
+                    submissions_allowed_change, scoring_weight_change, legacy_change
+             FROM active_user LIMIT 1);
+
+        RETURN NEW;
+    END;
+$list_section_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE FUNCTION audit_list_section_deletion() RETURNS trigger AS $list_section_deletion_trigger$
+    BEGIN
+        INSERT INTO list_section_modifications (userid, id, name, description, first_position, last_position, min_progress,
+                                                submissions_allowed, scoring_weight, legacy)
+            (SELECT id, OLD.id, OLD.name, OLD.description, OLD.first_position, OLD.last_position, OLD.min_progress,
+                    OLD.submissions_allowed, OLD.scoring_weight, OLD.legacy
+             FROM active_user LIMIT 1);
+
+        INSERT INTO list_section_deletions (userid, id) (SELECT id, OLD.id FROM active_user LIMIT 1);
+
+        RETURN NULL;
+    END;
+$list_section_deletion_trigger$ LANGUAGE plpgsql;
+
+CREATE TRIGGER list_section_addition_trigger AFTER INSERT ON list_sections
+    FOR EACH ROW EXECUTE PROCEDURE audit_list_section_addition();
+CREATE TRIGGER list_section_modification_trigger AFTER UPDATE ON list_sections
+    FOR EACH ROW EXECUTE PROCEDURE audit_list_section_modification();
+CREATE TRIGGER list_section_deletion_trigger AFTER DELETE ON list_sections
+    FOR EACH ROW EXECUTE PROCEDURE audit_list_section_deletion();
+
+-- Make section changes show up in the global audit log feed
+CREATE OR REPLACE VIEW audit_log_feed AS
+    SELECT time, audit_id, userid, 'record' AS model, 'addition' AS action, id AS target, NULL::TEXT AS details
+    FROM record_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(record_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM record_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'record', 'deletion', id,
+           jsonb_strip_nulls(to_jsonb(record_deletions) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM record_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_note', 'addition', id, NULL::TEXT
+    FROM record_notes_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_note', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(record_notes_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM record_notes_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_note', 'deletion', id, NULL::TEXT
+    FROM record_notes_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'demon', 'addition', id, NULL::TEXT
+    FROM demon_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'demon', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(demon_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM demon_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'creator', 'addition', demon, jsonb_build_object('creator', creator)::TEXT
+    FROM creator_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'creator', 'deletion', demon, jsonb_build_object('creator', creator)::TEXT
+    FROM creator_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'player', 'addition', id, NULL::TEXT
+    FROM player_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'player', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(player_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM player_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'player', 'deletion', id, NULL::TEXT
+    FROM player_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'submitter', 'modification', submitter,
+           jsonb_strip_nulls(to_jsonb(submitter_modifications) - 'time' - 'audit_id' - 'userid' - 'submitter')::TEXT
+    FROM submitter_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'user', 'addition', id, NULL::TEXT
+    FROM user_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'user', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(user_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM user_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'user', 'deletion', id, NULL::TEXT
+    FROM user_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_claim', 'addition', id,
+           jsonb_build_object('member', member, 'expires_at', expires_at)::TEXT
+    FROM record_claim_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'record_claim', 'deletion', id, jsonb_build_object('member', member)::TEXT
+    FROM record_claim_deletions
+    UNION ALL
+    SELECT time, audit_id, userid, 'list_section', 'addition', id, NULL::TEXT
+    FROM list_section_additions
+    UNION ALL
+    SELECT time, audit_id, userid, 'list_section', 'modification', id,
+           jsonb_strip_nulls(to_jsonb(list_section_modifications) - 'time' - 'audit_id' - 'userid' - 'id')::TEXT
+    FROM list_section_modifications
+    UNION ALL
+    SELECT time, audit_id, userid, 'list_section', 'deletion', id, NULL::TEXT
+    FROM list_section_deletions;
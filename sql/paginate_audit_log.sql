@@ -0,0 +1,7 @@
+SELECT time, audit_id, userid, members.name::TEXT AS username, model, action, target, details
+FROM audit_log_feed
+LEFT OUTER JOIN members ON members.member_id = userid
+WHERE (userid = $5 OR $5 IS NULL)
+  AND (model = $6 OR $6 IS NULL)
+  AND (time >= $7 OR $7 IS NULL)
+  AND (time <= $8 OR $8 IS NULL)
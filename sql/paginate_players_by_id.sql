@@ -1,11 +1,8 @@
-SELECT id, name::TEXT, banned, nation::TEXT, iso_country_code::TEXT
+SELECT id, name::TEXT, banned, nation::TEXT, iso_country_code::TEXT, youtube_channel::TEXT, COALESCE(ranking.score, 0.0) AS score
 FROM players
 LEFT OUTER JOIN nationalities ON nationality = iso_country_code
-WHERE (id < $1 OR $1 IS NULL)
-  AND (id > $2 OR $2 IS NULL)
-  AND (name = $3::CITEXT OR $3 is NULL)
-  AND (STRPOS(name, $4::CITEXT) > 0 OR $4 is NULL)
-  AND (banned = $5 OR $5 IS NULL)
-  AND (nationality = $6 OR iso_country_code = $6 OR (nationality IS NULL AND $7) OR ($6 IS NULL AND NOT $7))
-ORDER BY id {}
-LIMIT $8
\ No newline at end of file
+LEFT OUTER JOIN (SELECT id AS ranked_id, score FROM players_with_score) AS ranking ON ranking.ranked_id = players.id
+WHERE (name = $5::CITEXT OR $5 is NULL)
+  AND (STRPOS(name, $6::CITEXT) > 0 OR $6 is NULL)
+  AND (banned = $7 OR $7 IS NULL)
+  AND (nationality = $8 OR iso_country_code = $8 OR (nationality IS NULL AND $9) OR ($8 IS NULL AND NOT $9))
\ No newline at end of file
@@ -0,0 +1,7 @@
+SELECT players.id, players.name::TEXT, entries.rank, entries.score, entries.index::INTEGER AS index, nationalities.nation::TEXT, nationalities.iso_country_code::TEXT
+FROM ranking_snapshot_entries AS entries
+INNER JOIN players ON players.id = entries.player
+LEFT OUTER JOIN nationalities ON nationalities.iso_country_code = entries.nationality
+WHERE entries.snapshot = $5
+  AND (STRPOS(players.name, $6::CITEXT) > 0 OR $6 is NULL)
+  AND (nationalities.nation = $7 OR nationalities.iso_country_code = $7 OR (nationalities.nation IS NULL AND $8) OR ($7 IS NULL AND NOT $8))
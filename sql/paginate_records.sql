@@ -1,22 +1,22 @@
 SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, status_::text AS status,
        players.id AS player_id, players.name::text AS player_name, players.banned AS player_banned,
-       demons.id AS demon_id, demons.name::text AS demon_name, demons.position
+       demons.id AS demon_id, demons.name::text AS demon_name, demons.position,
+       record_claims.member AS claim_member, members.name AS claim_member_name, record_claims.claimed_at, record_claims.expires_at
 FROM records
 INNER JOIN players ON records.player = players.id
 INNER JOIN demons ON records.demon = demons.id
-WHERE (records.id < $1 OR $1 IS NULL)
-  AND (records.id > $2 OR $2 IS NULL)
-  AND (progress = $3 OR $3 IS NULL)
-  AND (progress < $4 OR $4 IS NULL)
-  AND (progress > $5 OR $5 IS NULL)
-  AND (position = $6 OR $6 IS NULL)
-  AND (position < $7 OR $7 IS NULL)
-  AND (position > $8 OR $8 IS NULL)
-  AND (status_ = CAST($9::TEXT AS record_status) OR $9 IS NULL)
-  AND (demons.name = $10::CITEXT OR $10 IS NULL)
-  AND (demons.id = $11 OR $11 IS NULL)
-  AND (records.video = $12 OR (records.video IS NULL AND $13) OR ($12 IS NULL AND NOT $13))
-  AND (players.id = $14 OR $14 IS NULL)
-  AND (records.submitter = $15 OR $15 IS NULL)
-ORDER BY id {}
-LIMIT $16
\ No newline at end of file
+LEFT OUTER JOIN record_claims ON record_claims.record = records.id AND record_claims.expires_at > (NOW() AT TIME ZONE 'utc')
+LEFT OUTER JOIN members ON members.member_id = record_claims.member
+WHERE (progress = $5 OR $5 IS NULL)
+  AND (progress < $6 OR $6 IS NULL)
+  AND (progress > $7 OR $7 IS NULL)
+  AND (position = $8 OR $8 IS NULL)
+  AND (position < $9 OR $9 IS NULL)
+  AND (position > $10 OR $10 IS NULL)
+  AND (status_ = CAST($11::TEXT AS record_status) OR $11 IS NULL)
+  AND (demons.name = $12::CITEXT OR $12 IS NULL)
+  AND (demons.id = $13 OR $13 IS NULL)
+  AND (records.video = $14 OR (records.video IS NULL AND $15) OR ($14 IS NULL AND NOT $15))
+  AND (players.id = $16 OR $16 IS NULL)
+  AND (records.submitter = $17 OR $17 IS NULL)
+  AND ((record_claims.record IS NOT NULL) = $18 OR $18 IS NULL)
\ No newline at end of file
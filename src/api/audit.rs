@@ -0,0 +1,26 @@
+//! Handlers for all endpoints under the `/api/v1/audit` prefix
+
+use crate::{
+    extractor::auth::TokenAuth,
+    model::audit::{AuditLogPagination, AuditLogSortColumn},
+    pagination::PaginationParameters,
+    permissions::Capabilities,
+    state::PointercrateState,
+    ApiResult,
+};
+use actix_web::{web::Query, HttpResponse};
+use actix_web_codegen::get;
+
+#[get("/")]
+pub async fn paginate(
+    TokenAuth(user): TokenAuth, state: PointercrateState, pagination: Query<AuditLogPagination>,
+    parameters: Query<PaginationParameters<AuditLogSortColumn>>,
+) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ViewAuditLog)?;
+
+    let mut connection = state.connection().await?;
+
+    let page = pagination.page(&parameters, &mut connection).await?;
+
+    Ok(page.response("/api/v1/audit/", &*pagination, &parameters))
+}
@@ -1,44 +1,118 @@
 //! Handlers for all endpoints under the `/api/v1/auth` prefix
 
 use crate::{
+    error::PointercrateError,
     extractor::{
         auth::{BasicAuth, TokenAuth},
         if_match::IfMatch,
         ip::Ip,
+        user_agent::UserAgent,
     },
-    model::user::{AuthenticatedUser, Authorization, PatchMe, Registration},
+    model::{
+        demonlist::{player::PlayerClaim, record},
+        user::{
+            AuthenticatedUser, Authorization, ConfirmTwoFactor, PatchMe, PatchSecurityPolicy, PostAccessToken, RefreshSession, Registration,
+            RequestPasswordReset, ResetPassword, SecurityPolicy,
+        },
+    },
+    permissions::Capabilities,
     ratelimit::RatelimitScope,
     state::PointercrateState,
     util::HttpResponseBuilderExt,
     ApiResult,
 };
-use actix_web::{web::Json, HttpResponse};
+use actix_web::{
+    web::{Json, Path},
+    HttpResponse,
+};
 use actix_web_codegen::{delete, get, patch, post};
 use serde_json::json;
 
 #[post("/register/")]
 pub async fn register(Ip(ip): Ip, body: Json<Registration>, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let ratelimits = state.ratelimits.prepare(ip);
+
     let mut connection = state.transaction().await?;
-    let user = AuthenticatedUser::register(body.into_inner(), &mut connection, Some(state.ratelimits.prepare(ip))).await?;
+    let user = AuthenticatedUser::register(body.into_inner(), &mut connection, Some(&ratelimits)).await?;
 
-    Ok(HttpResponse::Created()
+    Ok(ratelimits
+        .headers(&mut HttpResponse::Created())
         .header("Location", "/api/v1/auth/me/")
         .json_with_etag(user.inner()))
 }
 
 #[post("/")]
-pub async fn login(Ip(ip): Ip, user: BasicAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
-    state.ratelimits.check(RatelimitScope::Login, ip)?;
+pub async fn login(
+    Ip(ip): Ip, UserAgent(user_agent): UserAgent, BasicAuth(mut user): BasicAuth, state: PointercrateState,
+) -> ApiResult<HttpResponse> {
+    let ratelimits = state.ratelimits.prepare(ip);
 
-    Ok(HttpResponse::Ok().etag(user.0.inner()).json(json! {{
-        "data": user.0.inner(),
-        "token": user.0.generate_token(&state.secret)
+    ratelimits.check(RatelimitScope::Login).await?;
+
+    let tokens = user
+        .create_session(ip, user_agent.as_deref(), &state.secret, &mut *state.connection().await?)
+        .await?;
+
+    Ok(ratelimits.headers(&mut HttpResponse::Ok()).etag(user.inner()).json(json! {{
+        "data": user.inner(),
+        "token": tokens.token,
+        "refresh_token": tokens.refresh_token,
+        "expires_in": tokens.expires_in
     }}))
 }
 
+#[post("/refresh/")]
+pub async fn refresh(
+    Ip(ip): Ip, UserAgent(user_agent): UserAgent, body: Json<RefreshSession>, state: PointercrateState,
+) -> ApiResult<HttpResponse> {
+    let (_, tokens) = AuthenticatedUser::refresh_session(
+        &body.refresh_token,
+        ip,
+        user_agent.as_deref(),
+        &state.secret,
+        &mut *state.connection().await?,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+#[post("/reset/request/")]
+pub async fn request_password_reset(Ip(ip): Ip, body: Json<RequestPasswordReset>, state: PointercrateState) -> ApiResult<HttpResponse> {
+    // Without a way to send mails, resets can only be issued by administrators
+    let mailer = state.mailer.as_ref().ok_or(PointercrateError::NotFound)?;
+
+    let ratelimits = state.ratelimits.prepare(ip);
+
+    ratelimits.check(RatelimitScope::PasswordReset).await?;
+
+    AuthenticatedUser::request_password_reset(&body.username, &**mailer, &mut *state.connection().await?).await?;
+
+    // Same response regardless of whether a mail was sent, so that this endpoint can't be used to find
+    // out who set an email address
+    Ok(ratelimits.headers(&mut HttpResponse::Accepted()).finish())
+}
+
+#[post("/reset/")]
+pub async fn reset_password(Ip(ip): Ip, body: Json<ResetPassword>, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let ratelimits = state.ratelimits.prepare(ip);
+
+    ratelimits.check(RatelimitScope::PasswordReset).await?;
+
+    let mut connection = state.transaction().await?;
+
+    AuthenticatedUser::reset_password(body.into_inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(ratelimits.headers(&mut HttpResponse::NoContent()).finish())
+}
+
 #[post("/invalidate/")]
-pub async fn invalidate(authorization: Authorization, state: PointercrateState) -> ApiResult<HttpResponse> {
-    AuthenticatedUser::invalidate_all_tokens(authorization, &mut *state.connection().await?).await?;
+pub async fn invalidate(Ip(ip): Ip, authorization: Authorization, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let ratelimits = state.ratelimits.prepare(ip);
+
+    AuthenticatedUser::invalidate_all_tokens(authorization, &mut *state.connection().await?, Some(&ratelimits)).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
@@ -83,3 +157,151 @@ pub async fn delete_me(if_match: IfMatch, BasicAuth(user): BasicAuth, state: Poi
 
     Ok(HttpResponse::NoContent().finish())
 }
+
+#[get("/me/tokens/")]
+pub async fn get_access_tokens(TokenAuth(user): TokenAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let tokens = user.access_tokens(&mut *state.connection().await?).await?;
+
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+#[post("/me/tokens/")]
+pub async fn post_access_token(
+    BasicAuth(user): BasicAuth, state: PointercrateState, data: Json<PostAccessToken>,
+) -> ApiResult<HttpResponse> {
+    let (token, access_token) = user
+        .create_access_token(data.into_inner(), &state.secret, &mut *state.connection().await?)
+        .await?;
+
+    Ok(HttpResponse::Created().json(json! {{
+        "data": token,
+        "token": access_token
+    }}))
+}
+
+#[delete("/me/tokens/{token_id}/")]
+pub async fn delete_access_token(TokenAuth(user): TokenAuth, state: PointercrateState, token_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.revoke_access_token(token_id.into_inner(), &mut *state.connection().await?).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/me/sessions/")]
+pub async fn get_sessions(TokenAuth(user): TokenAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let sessions = user.sessions(&mut *state.connection().await?).await?;
+
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+#[get("/me/submissions/")]
+pub async fn get_submissions(TokenAuth(user): TokenAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let records = record::records_submitted_by(user.inner().id, &mut *state.connection().await?).await?;
+
+    Ok(HttpResponse::Ok().json(records))
+}
+
+#[get("/me/claim/")]
+pub async fn get_claim(TokenAuth(user): TokenAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let claim = PlayerClaim::of_member(user.inner().id, &mut *state.connection().await?)
+        .await?
+        .ok_or_else(|| {
+            PointercrateError::ModelNotFound {
+                model: "PlayerClaim",
+                identified_by: user.inner().id.to_string(),
+            }
+        })?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&claim))
+}
+
+#[delete("/me/claim/")]
+pub async fn delete_claim(TokenAuth(user): TokenAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    if let Some(claim) = PlayerClaim::of_member(user.inner().id, &mut connection).await? {
+        claim.delete(&mut connection).await?;
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[delete("/me/sessions/{session_id}/")]
+pub async fn delete_session(TokenAuth(user): TokenAuth, state: PointercrateState, session_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.revoke_session(session_id.into_inner(), &mut *state.connection().await?).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/me/2fa/")]
+pub async fn begin_two_factor(BasicAuth(user): BasicAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let enrollment = user.begin_two_factor_enrollment(&mut *state.connection().await?).await?;
+
+    Ok(HttpResponse::Ok().json(enrollment))
+}
+
+#[post("/me/2fa/confirm/")]
+pub async fn confirm_two_factor(
+    BasicAuth(mut user): BasicAuth, state: PointercrateState, data: Json<ConfirmTwoFactor>,
+) -> ApiResult<HttpResponse> {
+    let mut connection = state.transaction().await?;
+
+    let recovery_codes = user.confirm_two_factor_enrollment(data.into_inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json(json! {{
+        "recovery_codes": recovery_codes
+    }}))
+}
+
+#[post("/me/2fa/recovery-codes/")]
+pub async fn regenerate_recovery_codes(BasicAuth(user): BasicAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let mut connection = state.transaction().await?;
+
+    let recovery_codes = user.generate_recovery_codes(&mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json(json! {{
+        "recovery_codes": recovery_codes
+    }}))
+}
+
+#[delete("/me/2fa/")]
+pub async fn disable_two_factor(BasicAuth(mut user): BasicAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    let mut connection = state.transaction().await?;
+
+    user.disable_two_factor(&mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/policy/")]
+pub async fn get_policy(TokenAuth(user): TokenAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ManageUsers)?;
+
+    let policy = SecurityPolicy::load(&mut *state.connection().await?).await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&policy))
+}
+
+#[patch("/policy/")]
+pub async fn patch_policy(
+    if_match: IfMatch, TokenAuth(user): TokenAuth, state: PointercrateState, patch: Json<PatchSecurityPolicy>,
+) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ManageUsers)?;
+
+    let mut connection = state.transaction().await?;
+
+    let policy = SecurityPolicy::load(&mut connection).await?;
+
+    if_match.require_etag_match(&policy)?;
+
+    let policy = policy.apply_patch(patch.into_inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&policy))
+}
@@ -1,7 +1,7 @@
 use crate::{
     extractor::auth::TokenAuth,
     model::demonlist::demon::{FullDemon, PostDemon},
-    permissions::Permissions,
+    permissions::Capabilities,
     state::PointercrateState,
     util::HttpResponseBuilderExt,
     ApiResult,
@@ -11,7 +11,7 @@ use actix_web_codegen::post;
 
 #[post("/")]
 pub async fn post(TokenAuth(user): TokenAuth, state: PointercrateState, data: Json<PostDemon>) -> ApiResult<HttpResponse> {
-    user.inner().require_permissions(Permissions::ListModerator)?;
+    user.inner().require_capability(Capabilities::ManageDemons)?;
 
     let mut connection = state.audited_transaction(&user).await?;
 
@@ -27,10 +27,11 @@ pub mod v1 {
         extractor::{auth::TokenAuth, if_match::IfMatch},
         model::demonlist::{
             creator::{Creator, PostCreator},
-            demon::{Demon, DemonPositionPagination, FullDemon, PatchDemon},
+            demon::{Demon, DemonPositionPagination, DemonPositionSortColumn, FullDemon, PatchDemon},
             player::DatabasePlayer,
         },
-        permissions::Permissions,
+        pagination::PaginationParameters,
+        permissions::Capabilities,
         state::PointercrateState,
         util::HttpResponseBuilderExt,
         ApiResult,
@@ -42,22 +43,15 @@ pub mod v1 {
     use actix_web_codegen::{delete, get, patch, post};
 
     #[get("/")]
-    pub async fn paginate(state: PointercrateState, mut pagination: Query<DemonPositionPagination>) -> ApiResult<HttpResponse> {
+    pub async fn paginate(
+        state: PointercrateState, pagination: Query<DemonPositionPagination>,
+        parameters: Query<PaginationParameters<DemonPositionSortColumn>>,
+    ) -> ApiResult<HttpResponse> {
         let mut connection = state.connection().await?;
 
-        let mut demons = pagination.page(&mut connection).await?;
-        let max_position = Demon::max_position(&mut connection).await?;
-
-        pagination_response!(
-            "/api/v1/demons/",
-            demons,
-            pagination,
-            1,
-            max_position,
-            before_position,
-            after_position,
-            base.position
-        )
+        let page = pagination.page(&parameters, &mut connection).await?;
+
+        Ok(page.response("/api/v1/demons/", &*pagination, &parameters))
     }
 
     #[get("/{position}/")]
@@ -73,7 +67,7 @@ pub mod v1 {
     pub async fn patch(
         TokenAuth(user): TokenAuth, if_match: IfMatch, state: PointercrateState, patch: Json<PatchDemon>, position: Path<i16>,
     ) -> ApiResult<HttpResponse> {
-        user.inner().require_permissions(Permissions::ListModerator)?;
+        user.inner().require_capability(Capabilities::ManageDemons)?;
 
         let mut connection = state.audited_transaction(&user).await?;
         let demon = FullDemon::by_position(position.into_inner(), &mut connection).await?;
@@ -93,7 +87,7 @@ pub mod v1 {
     pub async fn post_creator(
         TokenAuth(user): TokenAuth, state: PointercrateState, position: Path<i16>, creator: Json<PostCreator>,
     ) -> ApiResult<HttpResponse> {
-        user.inner().require_permissions(Permissions::ListModerator)?;
+        user.inner().require_capability(Capabilities::ManageDemons)?;
 
         let mut connection = state.audited_connection(&user).await?;
 
@@ -112,7 +106,7 @@ pub mod v1 {
 
     #[delete("/{position}/creators/{player_id}/")]
     pub async fn delete_creator(TokenAuth(user): TokenAuth, state: PointercrateState, path: Path<(i16, i32)>) -> ApiResult<HttpResponse> {
-        user.inner().require_permissions(Permissions::ListModerator)?;
+        user.inner().require_capability(Capabilities::ManageDemons)?;
 
         let mut connection = state.audited_connection(&user).await?;
 
@@ -132,13 +126,17 @@ pub mod v1 {
 
 pub mod v2 {
     use crate::{
+        error::PointercrateError,
         extractor::{auth::TokenAuth, if_match::IfMatch},
         model::demonlist::{
             creator::{Creator, PostCreator},
-            demon::{Demon, DemonIdPagination, DemonPositionPagination, FullDemon, PatchDemon},
+            demon::{
+                audit, Demon, DemonIdPagination, DemonPositionPagination, DemonPositionSortColumn, DemonSortColumn, FullDemon, PatchDemon,
+            },
             player::DatabasePlayer,
         },
-        permissions::Permissions,
+        pagination::PaginationParameters,
+        permissions::Capabilities,
         state::PointercrateState,
         util::HttpResponseBuilderExt,
         ApiResult,
@@ -150,33 +148,27 @@ pub mod v2 {
     use actix_web_codegen::{delete, get, patch, post};
 
     #[get("/")]
-    pub async fn paginate(state: PointercrateState, mut pagination: Query<DemonIdPagination>) -> ApiResult<HttpResponse> {
+    pub async fn paginate(
+        state: PointercrateState, pagination: Query<DemonIdPagination>, parameters: Query<PaginationParameters<DemonSortColumn>>,
+    ) -> ApiResult<HttpResponse> {
         let mut connection = state.connection().await?;
 
-        let mut demons = pagination.page(&mut connection).await?;
-        let (max_id, min_id) = Demon::extremal_demon_ids(&mut connection).await?;
+        let page = pagination.page(&parameters, &mut connection).await?;
 
-        pagination_response!("/api/v2/demons/", demons, pagination, min_id, max_id, before_id, after_id, base.id)
+        Ok(page.response("/api/v2/demons/", &*pagination, &parameters))
     }
 
     // Same as /api/v1/demons/
     #[get("/listed/")]
-    pub async fn paginate_listed(state: PointercrateState, mut pagination: Query<DemonPositionPagination>) -> ApiResult<HttpResponse> {
+    pub async fn paginate_listed(
+        state: PointercrateState, pagination: Query<DemonPositionPagination>,
+        parameters: Query<PaginationParameters<DemonPositionSortColumn>>,
+    ) -> ApiResult<HttpResponse> {
         let mut connection = state.connection().await?;
 
-        let mut demons = pagination.page(&mut connection).await?;
-        let max_position = Demon::max_position(&mut connection).await?;
-
-        pagination_response!(
-            "/api/v2/demons/listed/",
-            demons,
-            pagination,
-            1,
-            max_position,
-            before_position,
-            after_position,
-            base.position
-        )
+        let page = pagination.page(&parameters, &mut connection).await?;
+
+        Ok(page.response("/api/v2/demons/listed/", &*pagination, &parameters))
     }
 
     #[get("/{demon_id}/")]
@@ -188,11 +180,31 @@ pub mod v2 {
         Ok(HttpResponse::Ok().json_with_etag(&demon))
     }
 
+    #[get("/{demon_id}/audit/")]
+    pub async fn audit_log(TokenAuth(user): TokenAuth, state: PointercrateState, id: Path<i32>) -> ApiResult<HttpResponse> {
+        user.inner().require_capability(Capabilities::ManageDemons)?;
+
+        let mut connection = state.connection().await?;
+
+        let demon_id = id.into_inner();
+        let log = audit::entries_for_demon(demon_id, &mut connection).await?;
+
+        if log.is_empty() {
+            Err(PointercrateError::ModelNotFound {
+                model: "Demon",
+                identified_by: demon_id.to_string(),
+            }
+            .into())
+        } else {
+            Ok(HttpResponse::Ok().json(log))
+        }
+    }
+
     #[patch("/{demon_id}/")]
     pub async fn patch(
         TokenAuth(user): TokenAuth, if_match: IfMatch, state: PointercrateState, patch: Json<PatchDemon>, id: Path<i32>,
     ) -> ApiResult<HttpResponse> {
-        user.inner().require_permissions(Permissions::ListModerator)?;
+        user.inner().require_capability(Capabilities::ManageDemons)?;
 
         let mut connection = state.audited_transaction(&user).await?;
         let demon = FullDemon::by_id(id.into_inner(), &mut connection).await?;
@@ -212,7 +224,7 @@ pub mod v2 {
     pub async fn post_creator(
         TokenAuth(user): TokenAuth, state: PointercrateState, id: Path<i32>, creator: Json<PostCreator>,
     ) -> ApiResult<HttpResponse> {
-        user.inner().require_permissions(Permissions::ListModerator)?;
+        user.inner().require_capability(Capabilities::ManageDemons)?;
 
         let mut connection = state.audited_connection(&user).await?;
 
@@ -231,7 +243,7 @@ pub mod v2 {
 
     #[delete("/{demon_id}/creators/{player_id}/")]
     pub async fn delete_creator(TokenAuth(user): TokenAuth, state: PointercrateState, path: Path<(i32, i32)>) -> ApiResult<HttpResponse> {
-        user.inner().require_permissions(Permissions::ListModerator)?;
+        user.inner().require_capability(Capabilities::ManageDemons)?;
 
         let mut connection = state.audited_connection(&user).await?;
 
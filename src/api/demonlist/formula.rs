@@ -0,0 +1,88 @@
+use crate::{
+    extractor::auth::TokenAuth,
+    model::demonlist::formula::{PostScoringFormula, ScoringFormula},
+    permissions::Capabilities,
+    state::PointercrateState,
+    util::HttpResponseBuilderExt,
+    ApiResult,
+};
+use actix_web::{
+    web::{Json, Path, Query},
+    HttpResponse,
+};
+use actix_web_codegen::{get, post};
+use serde::Deserialize;
+
+#[get("/")]
+pub async fn list(state: PointercrateState) -> ApiResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    Ok(HttpResponse::Ok().json(ScoringFormula::all(&mut connection).await?))
+}
+
+#[get("/active/")]
+pub async fn active(state: PointercrateState) -> ApiResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&ScoringFormula::active(&mut connection).await?))
+}
+
+#[get("/{formula_id}/")]
+pub async fn get(state: PointercrateState, formula_id: Path<i32>) -> ApiResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&ScoringFormula::by_id(formula_id.into_inner(), &mut connection).await?))
+}
+
+#[post("/")]
+pub async fn post(TokenAuth(user): TokenAuth, state: PointercrateState, data: Json<PostScoringFormula>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let formula = ScoringFormula::create_from(data.into_inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Created()
+        .header("Location", format!("/api/v1/formulas/{}/", formula.id))
+        .json_with_etag(&formula))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewQuery {
+    #[serde(default = "default_preview_limit")]
+    limit: i64,
+}
+
+fn default_preview_limit() -> i64 {
+    100
+}
+
+#[get("/{formula_id}/preview/")]
+pub async fn preview(
+    TokenAuth(user): TokenAuth, state: PointercrateState, formula_id: Path<i32>, query: Query<PreviewQuery>,
+) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.connection().await?;
+
+    let formula = ScoringFormula::by_id(formula_id.into_inner(), &mut connection).await?;
+    let entries = formula.preview(query.limit.max(1).min(500), &mut connection).await?;
+
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+#[post("/{formula_id}/activate/")]
+pub async fn activate(TokenAuth(user): TokenAuth, state: PointercrateState, formula_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let formula = ScoringFormula::by_id(formula_id.into_inner(), &mut connection).await?;
+    let formula = formula.activate(&mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&formula))
+}
@@ -1,14 +1,25 @@
-use crate::config;
+use crate::{model::demonlist::section::ListSection, state::PointercrateState, ApiResult};
 use actix_web::HttpResponse;
 use actix_web_codegen::get;
 use serde_json::json;
 
 #[get("/list_information/")]
-pub fn list_information() -> HttpResponse {
-    HttpResponse::Ok().json(json! {
+pub async fn list_information(state: PointercrateState) -> ApiResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    let sections = ListSection::all(&mut connection).await?;
+
+    // These used to be configured directly. They are kept for compatibility with clients that only
+    // know about the main and extended list, and are derived from the non-legacy sections
+    let listed = sections.iter().filter(|section| !section.legacy).collect::<Vec<_>>();
+    let list_size = listed.first().and_then(|section| section.last_position);
+    let extended_list_size = listed.last().and_then(|section| section.last_position);
+
+    Ok(HttpResponse::Ok().json(json! {
         {
-            "list_size": config::list_size(),
-            "extended_list_size": config::extended_list_size()
+            "list_size": list_size,
+            "extended_list_size": extended_list_size,
+            "sections": sections
         }
-    })
+    }))
 }
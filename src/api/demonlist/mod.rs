@@ -1,5 +1,7 @@
 pub mod demon;
+pub mod formula;
 pub mod misc;
 pub mod player;
 pub mod record;
+pub mod section;
 pub mod submitter;
@@ -1,7 +1,12 @@
 use crate::{
+    error::PointercrateError,
     extractor::{auth::TokenAuth, if_match::IfMatch},
-    model::demonlist::player::{PatchPlayer, Player, PlayerPagination, RankedPlayer, RankingPagination},
-    permissions::Permissions,
+    model::demonlist::player::{
+        audit, DatabasePlayer, HistoricalRankingPagination, PatchPlayer, PatchPlayerClaim, Player, PlayerClaim, PlayerClaimPagination,
+        PlayerClaimSortColumn, PlayerPagination, PlayerSortColumn, RankingPagination, RankingSnapshot, RankingSortColumn,
+    },
+    pagination::PaginationParameters,
+    permissions::Capabilities,
     state::PointercrateState,
     util::HttpResponseBuilderExt,
     ApiResult,
@@ -10,35 +15,48 @@ use actix_web::{
     web::{Json, Path, Query},
     HttpResponse,
 };
-use actix_web_codegen::{get, patch};
+use actix_web_codegen::{delete, get, patch, post};
 
 #[get("/")]
-pub async fn paginate(state: PointercrateState, mut pagination: Query<PlayerPagination>) -> ApiResult<HttpResponse> {
+pub async fn paginate(
+    state: PointercrateState, pagination: Query<PlayerPagination>, parameters: Query<PaginationParameters<PlayerSortColumn>>,
+) -> ApiResult<HttpResponse> {
     let mut connection = state.connection().await?;
 
-    let mut demons = pagination.page(&mut connection).await?;
-    let (max_id, min_id) = Player::extremal_player_ids(&mut connection).await?;
+    let page = pagination.page(&parameters, &mut connection).await?;
 
-    pagination_response!("/api/v1/players/", demons, pagination, min_id, max_id, before_id, after_id, base.id)
+    Ok(page.response("/api/v1/players/", &*pagination, &parameters))
 }
 
 #[get("/ranking/")]
-pub async fn ranking(state: PointercrateState, mut pagination: Query<RankingPagination>) -> ApiResult<HttpResponse> {
+pub async fn ranking(
+    state: PointercrateState, pagination: Query<RankingPagination>, parameters: Query<PaginationParameters<RankingSortColumn>>,
+) -> ApiResult<HttpResponse> {
     let mut connection = state.connection().await?;
 
-    let mut demons = pagination.page(&mut connection).await?;
-    let max_index = RankedPlayer::max_index(&mut connection).await?;
-
-    pagination_response!(
-        "/api/v1/players/ranking/",
-        demons,
-        pagination,
-        1,
-        max_index,
-        before_index,
-        after_index,
-        index
-    )
+    let page = pagination.page(&parameters, &mut connection).await?;
+
+    Ok(page.response("/api/v1/players/ranking/", &*pagination, &parameters))
+}
+
+#[get("/ranking/history/")]
+pub async fn ranking_history(
+    state: PointercrateState, mut pagination: Query<HistoricalRankingPagination>,
+    parameters: Query<PaginationParameters<RankingSortColumn>>,
+) -> ApiResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    let snapshot = pagination.resolve_snapshot(&mut connection).await?;
+    let page = pagination.page(&snapshot, &parameters, &mut connection).await?;
+
+    Ok(page.response("/api/v1/players/ranking/history/", &*pagination, &parameters))
+}
+
+#[get("/ranking/history/snapshots/")]
+pub async fn ranking_snapshots(state: PointercrateState) -> ApiResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    Ok(HttpResponse::Ok().json(RankingSnapshot::all(&mut connection).await?))
 }
 
 #[get("/{player_id}/")]
@@ -53,24 +71,129 @@ pub async fn get(state: PointercrateState, path: Path<i32>) -> ApiResult<HttpRes
     Ok(HttpResponse::Ok().json_with_etag(&player))
 }
 
+#[get("/{player_id}/audit/")]
+pub async fn audit_log(TokenAuth(user): TokenAuth, state: PointercrateState, path: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ManagePlayers)?;
+
+    let mut connection = state.connection().await?;
+
+    let player_id = path.into_inner();
+    let log = audit::entries_for_player(player_id, &mut connection).await?;
+
+    if log.is_empty() {
+        Err(PointercrateError::ModelNotFound {
+            model: "Player",
+            identified_by: player_id.to_string(),
+        }
+        .into())
+    } else {
+        Ok(HttpResponse::Ok().json(log))
+    }
+}
+
 #[patch("/{player_id}/")]
 pub async fn patch(
     TokenAuth(user): TokenAuth, if_match: IfMatch, state: PointercrateState, data: Json<PatchPlayer>, path: Path<i32>,
 ) -> ApiResult<HttpResponse> {
-    user.inner().require_permissions(Permissions::ListModerator)?;
-
     let mut connection = state.audited_transaction(&user).await?;
 
-    let player = Player::by_id(path.into_inner(), &mut connection)
+    let player_id = path.into_inner();
+    let patch = data.into_inner();
+
+    // Whoever claimed a player can change some of their details without being a list moderator.
+    // This is checked before looking up the player, so that we don't leak whether it exists
+    if !user.inner().has_capability(Capabilities::ManagePlayers) {
+        match PlayerClaim::of_member(user.inner().id, &mut connection).await? {
+            Some(claim) if claim.verified && claim.player.id == player_id => patch.require_claimant_safe()?,
+            _ => user.inner().require_capability(Capabilities::ManagePlayers)?,
+        }
+    }
+
+    let player = Player::by_id(player_id, &mut connection)
         .await?
         .upgrade(&mut connection)
         .await?;
 
     if_match.require_etag_match(&player)?;
 
-    let player = player.apply_patch(data.into_inner(), &mut connection).await?;
+    let player = player.apply_patch(patch, &mut connection).await?;
 
     connection.commit().await?;
 
     Ok(HttpResponse::Ok().json_with_etag(&player))
 }
+
+#[post("/{player_id}/claims/")]
+pub async fn claim(TokenAuth(user): TokenAuth, state: PointercrateState, path: Path<i32>) -> ApiResult<HttpResponse> {
+    let mut connection = state.transaction().await?;
+
+    let player = DatabasePlayer::by_id(path.into_inner(), &mut connection).await?;
+    let claim = PlayerClaim::create(user.inner(), player, &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Created()
+        .header("Location", format!("/api/v1/players/claims/{}/", claim.id))
+        .json_with_etag(&claim))
+}
+
+#[get("/claims/")]
+pub async fn paginate_claims(
+    TokenAuth(user): TokenAuth, state: PointercrateState, pagination: Query<PlayerClaimPagination>,
+    parameters: Query<PaginationParameters<PlayerClaimSortColumn>>,
+) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ManagePlayers)?;
+
+    let mut connection = state.connection().await?;
+
+    let page = pagination.page(&parameters, &mut connection).await?;
+
+    Ok(page.response("/api/v1/players/claims/", &*pagination, &parameters))
+}
+
+#[get("/claims/{claim_id}/")]
+pub async fn get_claim(TokenAuth(user): TokenAuth, state: PointercrateState, path: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ManagePlayers)?;
+
+    let claim = PlayerClaim::by_id(path.into_inner(), &mut *state.connection().await?).await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&claim))
+}
+
+#[patch("/claims/{claim_id}/")]
+pub async fn patch_claim(
+    TokenAuth(user): TokenAuth, if_match: IfMatch, state: PointercrateState, data: Json<PatchPlayerClaim>, path: Path<i32>,
+) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ManagePlayers)?;
+
+    let mut connection = state.transaction().await?;
+
+    let claim = PlayerClaim::by_id(path.into_inner(), &mut connection).await?;
+
+    if_match.require_etag_match(&claim)?;
+
+    let claim = claim.apply_patch(data.into_inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&claim))
+}
+
+#[delete("/claims/{claim_id}/")]
+pub async fn delete_claim(
+    TokenAuth(user): TokenAuth, if_match: IfMatch, state: PointercrateState, path: Path<i32>,
+) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ManagePlayers)?;
+
+    let mut connection = state.transaction().await?;
+
+    let claim = PlayerClaim::by_id(path.into_inner(), &mut connection).await?;
+
+    if_match.require_etag_match(&claim)?;
+
+    claim.delete(&mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,16 +1,17 @@
 use crate::{
-    config,
     error::{JsonError, PointercrateError},
     extractor::{auth::TokenAuth, if_match::IfMatch, ip::Ip},
     model::demonlist::{
         record::{
             audit,
             note::{NewNote, Note, PatchNote},
-            FullRecord, PatchRecord, RecordPagination, RecordStatus, Submission,
+            BulkPatchRecord, FullRecord, PatchRecord, RecordClaim, RecordPagination, RecordSortColumn, RecordStatus, Submission,
+            VideoValidation,
         },
         submitter::Submitter,
     },
-    permissions::Permissions,
+    pagination::PaginationParameters,
+    permissions::Capabilities,
     state::{audit_connection, PointercrateState},
     util::HttpResponseBuilderExt,
     ApiResult,
@@ -24,6 +25,7 @@ use actix_web_codegen::{delete, get, patch, post};
 #[get("/")]
 pub async fn paginate(
     user: ApiResult<TokenAuth>, state: PointercrateState, mut pagination: Query<RecordPagination>,
+    parameters: Query<PaginationParameters<RecordSortColumn>>,
 ) -> ApiResult<HttpResponse> {
     let mut connection = state.connection().await?;
 
@@ -33,14 +35,20 @@ pub async fn paginate(
 
     if pagination.submitter.is_some() {
         match user {
-            Ok(TokenAuth(ref user)) => user.inner().require_permissions(Permissions::ListModerator)?,
+            Ok(TokenAuth(ref user)) => user.inner().require_capability(Capabilities::ManageSubmitters)?,
             Err(error) => return Err(error),
         }
     }
 
+    // Only list helpers get to know who is reviewing which records
+    let show_claims = user
+        .as_ref()
+        .map(|user| user.0.inner().has_capability(Capabilities::ReviewRecords))
+        .unwrap_or(false);
+
     match user {
         Ok(TokenAuth(user)) if user.inner().extended_list_access() => (),
-        Ok(TokenAuth(user)) => user.inner().require_permissions(Permissions::ExtendedAccess)?,
+        Ok(TokenAuth(user)) => user.inner().require_capability(Capabilities::ViewUnapprovedRecords)?,
         _ =>
             match pagination.status {
                 None => pagination.status = Some(RecordStatus::Approved),
@@ -49,11 +57,15 @@ pub async fn paginate(
             },
     }
 
-    let mut records = pagination.page(&mut connection).await?;
+    let mut page = pagination.page(&parameters, &mut connection).await?;
 
-    let (max_id, min_id) = FullRecord::extremal_record_ids(&mut connection).await?;
+    if !show_claims {
+        for record in &mut page.items {
+            record.claim = None;
+        }
+    }
 
-    pagination_response!("/api/v1/records/", records, pagination, min_id, max_id, before_id, after_id, id)
+    Ok(page.response("/api/v1/records/", &*pagination, &parameters))
 }
 
 #[post("/")]
@@ -65,39 +77,43 @@ pub async fn submit(
     // NOTE: don't abort if authentication fails! We might not need it!
     // This prevents invalid auth data in cookies to interfere with record submission
 
-    let shall_ratelimit = user.as_ref().map(|user| !user.0.inner().list_team_member()).unwrap_or(true);
+    // the list team is exempt from the submission ratelimits by default (see `RATELIMIT_RECORD_SUBMISSION_EXEMPT`)
+    let ratelimits = match user {
+        Ok(ref user) => state.ratelimits.prepare(ip).authenticated_as(user.0.inner().permissions),
+        Err(_) => state.ratelimits.prepare(ip),
+    };
+
+    let show_raw_footage = user
+        .as_ref()
+        .map(|user| user.0.inner().has_capability(Capabilities::ReviewRecords))
+        .unwrap_or(false);
 
     // only members of the list team can directly add approved records, or add records without video
     if submission.status != RecordStatus::Submitted || submission.video.is_none() {
         // do abort if it fails here!
-        let user = user?.0;
+        let user = user.as_ref().map_err(|err| JsonError(err.0.clone()))?.0.inner();
 
-        user.inner().require_permissions(Permissions::ListHelper)?;
-        audit_connection(&mut connection, user.inner().id).await?; // might as well
+        user.require_capability(Capabilities::ApproveRecords)?;
+        audit_connection(&mut connection, user.id).await?; // might as well
     }
 
-    let ratelimiter = state.ratelimits.prepare(ip);
-
-    let submitter = Submitter::by_ip_or_create(ip, &mut connection, Some(ratelimiter)).await?;
-
-    let record = if shall_ratelimit {
-        FullRecord::create_from(submitter, submission.into_inner(), &mut connection, Some(ratelimiter)).await?
-    } else {
-        FullRecord::create_from(submitter, submission.into_inner(), &mut connection, None).await?
+    // logged in users submit under their account, everyone else is identified by their IP address
+    let submitter = match user {
+        Ok(ref user) => Submitter::by_member_or_create(user.0.inner(), ip, &mut connection).await?,
+        Err(_) => Submitter::by_ip_or_create(ip, &mut connection, Some(&ratelimits)).await?,
     };
+    let mut record = FullRecord::create_from(submitter, submission.into_inner(), &mut connection, Some(&ratelimits)).await?;
 
     connection.commit().await?;
 
-    let response = HttpResponse::Created()
-        .header("Location", format!("/api/v1/records/{}/", record.id))
-        .json_with_etag(&record);
-
-    // spawn background task to validate record
-    if record.status == RecordStatus::Submitted {
-        actix_rt::spawn(record.validate(state));
+    if !show_raw_footage {
+        record.raw_footage = None;
     }
 
-    Ok(response)
+    Ok(ratelimits
+        .headers(&mut HttpResponse::Created())
+        .header("Location", format!("/api/v1/records/{}/", record.id))
+        .json_with_etag(&record))
 }
 
 #[get("/{record_id}/")]
@@ -108,17 +124,23 @@ pub async fn get(user: ApiResult<TokenAuth>, state: PointercrateState, record_id
     match user {
         Ok(TokenAuth(user)) => {
             if record.status != RecordStatus::Approved {
-                user.inner().require_permissions(Permissions::ExtendedAccess)?;
+                user.inner().require_capability(Capabilities::ViewUnapprovedRecords)?;
             }
-            if !user.inner().has_permission(Permissions::ListHelper) {
-                record.notes.clear()
+            if !user.inner().has_capability(Capabilities::ReviewRecords) {
+                record.notes.clear();
+                record.validation = None;
+                record.claim = None;
+                record.raw_footage = None;
             }
         },
         _ => {
             if record.status != RecordStatus::Approved {
                 return Err(JsonError(PointercrateError::Unauthorized))
             }
-            record.notes.clear()
+            record.notes.clear();
+            record.validation = None;
+            record.claim = None;
+            record.raw_footage = None;
         },
     }
 
@@ -129,7 +151,7 @@ pub async fn get(user: ApiResult<TokenAuth>, state: PointercrateState, record_id
 pub async fn audit_log(TokenAuth(user): TokenAuth, state: PointercrateState, record_id: Path<i32>) -> ApiResult<HttpResponse> {
     let mut connection = state.connection().await?;
 
-    user.inner().require_permissions(Permissions::ListHelper)?;
+    user.inner().require_capability(Capabilities::ReviewRecords)?;
 
     let record_id = record_id.into_inner();
     let log = audit::entries_for_record(record_id, &mut connection).await?;
@@ -151,21 +173,14 @@ pub async fn patch(
 ) -> ApiResult<HttpResponse> {
     let mut connection = state.audited_transaction(&user).await?;
 
-    //user.inner().require_permissions(Permissions::ListHelper)?;
-
     // FIXME: prevent lost updates by using SELECT ... FOR UPDATE
     let mut record = FullRecord::by_id(record_id.into_inner(), &mut connection).await?;
 
-    if record.demon.position > config::extended_list_size() {
-        // only list mods can modify legacy records
-        // haha not anymore smelly
-        user.inner().require_permissions(Permissions::ListHelper)?;
-        //user.inner().require_permissions(Permissions::ListModerator)?;
-    } else {
-        user.inner().require_permissions(Permissions::ListHelper)?;
-    }
+    // legacy records used to be restricted to list mods, but no longer are
+    user.inner().require_capability(Capabilities::ApproveRecords)?;
 
     if_match.require_etag_match(&record)?;
+    record.require_claim_held_by(user.inner())?;
 
     record = record.apply_patch(data.into_inner(), &mut connection).await?;
 
@@ -174,6 +189,23 @@ pub async fn patch(
     Ok(HttpResponse::Ok().json_with_etag(&record))
 }
 
+/// Applies the same patch to many records at once
+///
+/// Records are patched one by one inside the same transaction, and failures for individual records
+/// are reported in the response instead of aborting the whole request
+#[patch("/")]
+pub async fn bulk_patch(TokenAuth(user): TokenAuth, state: PointercrateState, data: Json<BulkPatchRecord>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ApproveRecords)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let results = data.into_inner().apply(user.inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json(results))
+}
+
 #[delete("/{record_id}/")]
 pub async fn delete(
     TokenAuth(user): TokenAuth, if_match: IfMatch, state: PointercrateState, record_id: Path<i32>,
@@ -192,15 +224,16 @@ pub async fn delete(
         .await?
         .was_modified
         {
-            user.inner().require_permissions(Permissions::ListModerator)?;
+            user.inner().require_capability(Capabilities::DeleteRecords)?;
         } else {
-            user.inner().require_permissions(Permissions::ListHelper)?;
+            user.inner().require_capability(Capabilities::ApproveRecords)?;
         }
     } else {
-        user.inner().require_permissions(Permissions::ListModerator)?;
+        user.inner().require_capability(Capabilities::DeleteRecords)?;
     }
 
     if_match.require_etag_match(&record)?;
+    record.require_claim_held_by(user.inner())?;
 
     record.delete(&mut connection).await?;
 
@@ -209,13 +242,92 @@ pub async fn delete(
     Ok(HttpResponse::NoContent().finish())
 }
 
+#[post("/{record_id}/audit/{audit_id}/revert/")]
+pub async fn revert(TokenAuth(user): TokenAuth, state: PointercrateState, path: Path<(i32, i32)>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::DeleteRecords)?;
+
+    let (record_id, audit_id) = path.into_inner();
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let record = FullRecord::by_id(record_id, &mut connection).await?;
+    let record = record.revert_to(audit_id, &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&record))
+}
+
+#[post("/{record_id}/restore/")]
+pub async fn restore(Ip(ip): Ip, TokenAuth(user): TokenAuth, state: PointercrateState, record_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::DeleteRecords)?;
+
+    let ratelimits = state.ratelimits.prepare(ip).authenticated_as(user.inner().permissions);
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let record = FullRecord::restore(record_id.into_inner(), ip, Some(&ratelimits), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(ratelimits
+        .headers(&mut HttpResponse::Created())
+        .header("Location", format!("/api/v1/records/{}/", record.id))
+        .json_with_etag(&record))
+}
+
+#[post("/{record_id}/validation/")]
+pub async fn revalidate(TokenAuth(user): TokenAuth, state: PointercrateState, record_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ReviewRecords)?;
+
+    let mut connection = state.connection().await?;
+
+    let record = FullRecord::by_id(record_id.into_inner(), &mut connection).await?;
+
+    if record.video.is_none() {
+        return Err(JsonError(PointercrateError::BadRequest {
+            message: "This record has no video that could be checked".to_owned(),
+        }))
+    }
+
+    let validation = VideoValidation::enqueue(record.id, &mut connection).await?;
+
+    Ok(HttpResponse::Ok().json(validation))
+}
+
+#[post("/{record_id}/claim/")]
+pub async fn claim(TokenAuth(user): TokenAuth, state: PointercrateState, record_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ReviewRecords)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let claim = RecordClaim::claim(record_id.into_inner(), user.inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json(claim))
+}
+
+#[delete("/{record_id}/claim/")]
+pub async fn release_claim(TokenAuth(user): TokenAuth, state: PointercrateState, record_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ReviewRecords)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    RecordClaim::release(record_id.into_inner(), user.inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
 #[post("/{record_id}/notes/")]
 pub async fn add_note(
     TokenAuth(user): TokenAuth, data: Json<NewNote>, record_id: Path<i32>, state: PointercrateState,
 ) -> ApiResult<HttpResponse> {
     let mut connection = state.audited_connection(&user).await?;
 
-    user.inner().require_permissions(Permissions::ListHelper)?;
+    user.inner().require_capability(Capabilities::ReviewRecords)?;
 
     let record = FullRecord::by_id(record_id.into_inner(), &mut connection).await?;
     let mut note = Note::create_on(&record, data.into_inner(), &mut connection).await?;
@@ -239,9 +351,9 @@ pub async fn patch_note(
 
     // Generally you can only modify your own notes
     if note.author.as_ref() != Some(&user.inner().name) {
-        user.inner().require_permissions(Permissions::ListAdministrator)?;
+        user.inner().require_capability(Capabilities::ManageNotes)?;
     } else {
-        user.inner().require_permissions(Permissions::ListHelper)?;
+        user.inner().require_capability(Capabilities::ReviewRecords)?;
     }
 
     if note.record != record_id {
@@ -269,9 +381,9 @@ pub async fn delete_note(TokenAuth(user): TokenAuth, ids: Path<(i32, i32)>, stat
 
     // Generally you can only delete your own notes
     if note.author.as_ref() != Some(&user.inner().name) {
-        user.inner().require_permissions(Permissions::ListAdministrator)?;
+        user.inner().require_capability(Capabilities::ManageNotes)?;
     } else {
-        user.inner().require_permissions(Permissions::ListHelper)?;
+        user.inner().require_capability(Capabilities::ReviewRecords)?;
     }
 
     if note.record != record_id {
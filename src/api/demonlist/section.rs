@@ -0,0 +1,78 @@
+use crate::{
+    extractor::{auth::TokenAuth, if_match::IfMatch},
+    model::demonlist::section::{ListSection, PatchListSection, PostListSection},
+    permissions::Capabilities,
+    state::PointercrateState,
+    util::HttpResponseBuilderExt,
+    ApiResult,
+};
+use actix_web::{
+    web::{Json, Path},
+    HttpResponse,
+};
+use actix_web_codegen::{delete, get, patch, post};
+
+#[get("/")]
+pub async fn list(state: PointercrateState) -> ApiResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    Ok(HttpResponse::Ok().json(ListSection::all(&mut connection).await?))
+}
+
+#[get("/{section_id}/")]
+pub async fn get(state: PointercrateState, section_id: Path<i32>) -> ApiResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&ListSection::by_id(section_id.into_inner(), &mut connection).await?))
+}
+
+#[post("/")]
+pub async fn post(TokenAuth(user): TokenAuth, state: PointercrateState, data: Json<PostListSection>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let section = ListSection::create_from(data.into_inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Created()
+        .header("Location", format!("/api/v1/sections/{}/", section.id))
+        .json_with_etag(&section))
+}
+
+#[patch("/{section_id}/")]
+pub async fn patch(
+    if_match: IfMatch, TokenAuth(user): TokenAuth, state: PointercrateState, section_id: Path<i32>, data: Json<PatchListSection>,
+) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let section = ListSection::by_id(section_id.into_inner(), &mut connection).await?;
+
+    if_match.require_etag_match(&section)?;
+
+    let section = section.apply_patch(data.into_inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&section))
+}
+
+#[delete("/{section_id}/")]
+pub async fn delete(if_match: IfMatch, TokenAuth(user): TokenAuth, state: PointercrateState, section_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let section = ListSection::by_id(section_id.into_inner(), &mut connection).await?;
+
+    if_match.require_etag_match(&section)?;
+
+    section.delete(&mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,7 +1,8 @@
 use crate::{
     extractor::{auth::TokenAuth, if_match::IfMatch},
-    model::demonlist::submitter::{PatchSubmitter, Submitter, SubmitterPagination},
-    permissions::Permissions,
+    model::demonlist::submitter::{PatchSubmitter, Submitter, SubmitterPagination, SubmitterSortColumn},
+    pagination::PaginationParameters,
+    permissions::Capabilities,
     state::PointercrateState,
     util::HttpResponseBuilderExt,
     ApiResult,
@@ -14,35 +15,28 @@ use actix_web_codegen::{get, patch};
 
 #[get("/")]
 pub async fn paginate(
-    TokenAuth(user): TokenAuth, state: PointercrateState, mut pagination: Query<SubmitterPagination>,
+    TokenAuth(user): TokenAuth, state: PointercrateState, pagination: Query<SubmitterPagination>,
+    parameters: Query<PaginationParameters<SubmitterSortColumn>>,
 ) -> ApiResult<HttpResponse> {
-    user.inner().require_permissions(Permissions::ListAdministrator)?;
+    user.inner().require_capability(Capabilities::ListSubmitters)?;
 
     let mut connection = state.connection().await?;
 
-    let mut submitters = pagination.page(&mut connection).await?;
+    let page = pagination.page(&parameters, &mut connection).await?;
 
-    let (max_id, min_id) = Submitter::extremal_submitter_ids(&mut connection).await?;
-
-    pagination_response!(
-        "/api/v1/submitters/",
-        submitters,
-        pagination,
-        min_id,
-        max_id,
-        before_id,
-        after_id,
-        id
-    )
+    Ok(page.response("/api/v1/submitters/", &*pagination, &parameters))
 }
 
 #[get("/{submitter_id}/")]
 pub async fn get(TokenAuth(user): TokenAuth, state: PointercrateState, submitter_id: Path<i32>) -> ApiResult<HttpResponse> {
-    user.inner().require_permissions(Permissions::ListModerator)?;
+    user.inner().require_capability(Capabilities::ManageSubmitters)?;
 
     let mut connection = state.connection().await?;
 
-    let submitter = Submitter::by_id(submitter_id.into_inner(), &mut connection).await?;
+    let submitter = Submitter::by_id(submitter_id.into_inner(), &mut connection)
+        .await?
+        .upgrade(&mut connection)
+        .await?;
 
     Ok(HttpResponse::Ok().json_with_etag(&submitter))
 }
@@ -51,7 +45,7 @@ pub async fn get(TokenAuth(user): TokenAuth, state: PointercrateState, submitter
 pub async fn patch(
     if_match: IfMatch, TokenAuth(user): TokenAuth, state: PointercrateState, submitter_id: Path<i32>, patch: Json<PatchSubmitter>,
 ) -> ApiResult<HttpResponse> {
-    user.inner().require_permissions(Permissions::ListModerator)?;
+    user.inner().require_capability(Capabilities::ManageSubmitters)?;
 
     let mut connection = state.audited_transaction(&user).await?;
 
@@ -59,7 +53,11 @@ pub async fn patch(
 
     if_match.require_etag_match(&submitter)?;
 
-    let submitter = submitter.apply_patch(patch.into_inner(), &mut connection).await?;
+    let submitter = submitter
+        .apply_patch(patch.into_inner(), &mut connection)
+        .await?
+        .upgrade(&mut connection)
+        .await?;
 
     connection.commit().await?;
 
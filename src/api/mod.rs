@@ -1,9 +1,12 @@
 use crate::error::PointercrateError;
 use actix_web::{http::Method, HttpRequest, HttpResponse, ResponseError};
 
+pub mod audit;
 pub mod auth;
 pub mod demonlist;
+pub mod role;
 pub mod user;
+pub mod webhook;
 
 pub fn handle_404_or_405(request: HttpRequest) -> HttpResponse {
     let path = request.path();
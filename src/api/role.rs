@@ -0,0 +1,10 @@
+//! Handlers for all endpoints under the `/api/v1/roles` prefix
+
+use crate::permissions::ROLES;
+use actix_web::HttpResponse;
+use actix_web_codegen::get;
+
+#[get("/")]
+pub async fn list() -> HttpResponse {
+    HttpResponse::Ok().json(&*ROLES)
+}
@@ -3,8 +3,9 @@
 use crate::{
     error::{JsonError, PointercrateError},
     extractor::{auth::TokenAuth, if_match::IfMatch},
-    model::user::{PatchUser, User, UserPagination},
-    permissions::Permissions,
+    model::user::{PatchUser, User, UserPagination, UserSortColumn},
+    pagination::PaginationParameters,
+    permissions::Capabilities,
     state::PointercrateState,
     util::HttpResponseBuilderExt,
     ApiResult,
@@ -13,11 +14,13 @@ use actix_web::{
     web::{Json, Path, Query},
     HttpResponse,
 };
-use actix_web_codegen::{delete, get, patch};
+use actix_web_codegen::{delete, get, patch, post};
+use serde_json::json;
 
 #[get("/")]
 pub async fn paginate(
     TokenAuth(user): TokenAuth, state: PointercrateState, mut pagination: Query<UserPagination>,
+    parameters: Query<PaginationParameters<UserSortColumn>>,
 ) -> ApiResult<HttpResponse> {
     let mut connection = state.connection().await?;
 
@@ -27,7 +30,7 @@ pub async fn paginate(
         return Err(JsonError(PointercrateError::Forbidden))
     }
 
-    if !user.inner().has_permission(Permissions::Moderator) {
+    if !user.inner().has_capability(Capabilities::ModerateUsers) {
         // Pointercrate staff need to be able to see all users, not only those whose permissions they can
         // assign
         pagination.any_permissions = match pagination.any_permissions {
@@ -36,11 +39,9 @@ pub async fn paginate(
         };
     }
 
-    let mut users = pagination.page(&mut connection).await?;
+    let page = pagination.page(&parameters, &mut connection).await?;
 
-    let (max_id, min_id) = User::extremal_member_ids(&mut connection).await?;
-
-    pagination_response!("/api/v1/users/", users, pagination, min_id, max_id, before_id, after_id, id)
+    Ok(page.response("/api/v1/users/", &*pagination, &parameters))
 }
 
 #[get("/{user_id}/")]
@@ -49,10 +50,9 @@ pub async fn get(TokenAuth(user): TokenAuth, state: PointercrateState, user_id:
 
     let gotten_user = User::by_id(user_id.into_inner(), &mut connection).await?;
 
-    // We are only allowed to retrieve users who already have permissions we can set.
-    // We're also using that ListModerator implies ListHelper
-    if !user.inner().has_permission(Permissions::Administrator)
-        && !(user.inner().has_permission(Permissions::ListAdministrator) && gotten_user.has_permission(Permissions::ListHelper))
+    // We are only allowed to retrieve users who already have permissions we can set
+    if !user.inner().has_capability(Capabilities::ManageUsers)
+        && !user.inner().permissions.assigns().intersects(gotten_user.permissions.implied())
     {
         return Err(JsonError(PointercrateError::ModelNotFound {
             model: "User",
@@ -70,7 +70,7 @@ pub async fn patch(
     let mut connection = state.audited_transaction(&user.0).await?;
 
     if data.display_name.is_some() || data.youtube_channel.is_some() {
-        user.0.inner().require_permissions(Permissions::Moderator)?;
+        user.0.inner().require_capability(Capabilities::ModerateUsers)?;
     }
 
     // FIXME: Prevent "Lost Update" by using SELECT ... FOR UPDATE
@@ -84,8 +84,8 @@ pub async fn patch(
     // correct ETag, which means we previously retrieved this user successfully and passed the
     // permissions check at GET. However, one might guess the ETag. Or use an ETag value they got from
     // before they were demoted.
-    if !user.0.inner().has_permission(Permissions::Administrator)
-        && !(user.0.inner().has_permission(Permissions::ListAdministrator) && gotten_user.has_permission(Permissions::ListHelper))
+    if !user.0.inner().has_capability(Capabilities::ManageUsers)
+        && !user.0.inner().permissions.assigns().intersects(gotten_user.permissions.implied())
     {
         return Err(JsonError(PointercrateError::ModelNotFound {
             model: "User",
@@ -94,11 +94,13 @@ pub async fn patch(
     }
 
     if let Some(assign) = data.permissions {
-        if !user.0.inner().has_permission(Permissions::Administrator) {
-            // XOR here gets us the set of permissions that _changed_ which is what we really care about!
-            user.0
-                .inner()
-                .require_permissions((assign ^ gotten_user.permissions).required_for_assignment())?;
+        // XOR here gets us the set of permissions that _changed_ which is what we really care about!
+        let changed = assign ^ gotten_user.permissions;
+
+        if !user.0.inner().permissions.can_assign(changed) {
+            return Err(JsonError(PointercrateError::MissingPermissions {
+                required: changed.required_for_assignment(),
+            }))
         }
     }
 
@@ -121,7 +123,7 @@ pub async fn delete(if_match: IfMatch, user: TokenAuth, state: PointercrateState
         return Err(PointercrateError::DeleteSelf.into())
     }
 
-    user.0.inner().require_permissions(Permissions::Administrator)?;
+    user.0.inner().require_capability(Capabilities::ManageUsers)?;
 
     // FIXME: Prevent "Lost Update" by using SELECT ... FOR UPDATE
     let to_delete = User::by_id(to_delete, &mut connection).await?;
@@ -134,3 +136,17 @@ pub async fn delete(if_match: IfMatch, user: TokenAuth, state: PointercrateState
 
     Ok(HttpResponse::NoContent().finish())
 }
+
+#[post("/{user_id}/password-reset/")]
+pub async fn post_password_reset(TokenAuth(user): TokenAuth, state: PointercrateState, user_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ManageUsers)?;
+
+    let mut connection = state.connection().await?;
+
+    let to_reset = User::by_id(user_id.into_inner(), &mut connection).await?;
+    let token = to_reset.issue_password_reset(Some(user.inner()), &mut connection).await?;
+
+    Ok(HttpResponse::Created().json(json! {{
+        "data": token
+    }}))
+}
@@ -0,0 +1,118 @@
+//! Handlers for all endpoints under the `/api/v1/webhooks` prefix
+
+use crate::{
+    extractor::{auth::TokenAuth, if_match::IfMatch},
+    model::webhook::{DeliveryPagination, DeliverySortColumn, PatchWebhook, PostWebhook, Webhook, WebhookDelivery},
+    pagination::PaginationParameters,
+    permissions::Capabilities,
+    state::PointercrateState,
+    util::HttpResponseBuilderExt,
+    ApiResult,
+};
+use actix_web::{
+    web::{Json, Path, Query},
+    HttpResponse,
+};
+use actix_web_codegen::{delete, get, patch, post};
+
+#[get("/")]
+pub async fn list(TokenAuth(user): TokenAuth, state: PointercrateState) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.connection().await?;
+
+    Ok(HttpResponse::Ok().json(Webhook::all(&mut connection).await?))
+}
+
+#[post("/")]
+pub async fn post(TokenAuth(user): TokenAuth, state: PointercrateState, data: Json<PostWebhook>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let webhook = Webhook::create_from(data.into_inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Created()
+        .header("Location", format!("/api/v1/webhooks/{}/", webhook.id))
+        .json_with_etag(&webhook))
+}
+
+#[get("/{webhook_id}/")]
+pub async fn get(TokenAuth(user): TokenAuth, state: PointercrateState, webhook_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.connection().await?;
+
+    let webhook = Webhook::by_id(webhook_id.into_inner(), &mut connection).await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&webhook))
+}
+
+#[patch("/{webhook_id}/")]
+pub async fn patch(
+    if_match: IfMatch, TokenAuth(user): TokenAuth, state: PointercrateState, webhook_id: Path<i32>, data: Json<PatchWebhook>,
+) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let webhook = Webhook::by_id(webhook_id.into_inner(), &mut connection).await?;
+
+    if_match.require_etag_match(&webhook)?;
+
+    let webhook = webhook.apply_patch(data.into_inner(), &mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::Ok().json_with_etag(&webhook))
+}
+
+#[delete("/{webhook_id}/")]
+pub async fn delete(if_match: IfMatch, TokenAuth(user): TokenAuth, state: PointercrateState, webhook_id: Path<i32>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.audited_transaction(&user).await?;
+
+    let webhook = Webhook::by_id(webhook_id.into_inner(), &mut connection).await?;
+
+    if_match.require_etag_match(&webhook)?;
+
+    webhook.delete(&mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/{webhook_id}/deliveries/")]
+pub async fn deliveries(
+    TokenAuth(user): TokenAuth, state: PointercrateState, webhook_id: Path<i32>, pagination: Query<DeliveryPagination>,
+    parameters: Query<PaginationParameters<DeliverySortColumn>>,
+) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let mut connection = state.connection().await?;
+
+    let webhook = Webhook::by_id(webhook_id.into_inner(), &mut connection).await?;
+
+    let page = pagination.page(&webhook, &parameters, &mut connection).await?;
+
+    Ok(page.response(&format!("/api/v1/webhooks/{}/deliveries/", webhook.id), &*pagination, &parameters))
+}
+
+#[post("/{webhook_id}/deliveries/{delivery_id}/retry/")]
+pub async fn retry_delivery(TokenAuth(user): TokenAuth, state: PointercrateState, path: Path<(i32, i32)>) -> ApiResult<HttpResponse> {
+    user.inner().require_capability(Capabilities::ConfigureList)?;
+
+    let (webhook_id, delivery_id) = path.into_inner();
+
+    let mut connection = state.connection().await?;
+
+    let webhook = Webhook::by_id(webhook_id, &mut connection).await?;
+    let delivery = WebhookDelivery::by_id(&webhook, delivery_id, &mut connection).await?;
+    let delivery = delivery.retry(&mut connection).await?;
+
+    Ok(HttpResponse::Ok().json(delivery))
+}
@@ -18,12 +18,136 @@ pub fn guidelines_location() -> String {
     from_env_or_default("GUIDELINES", format!("{}/guidelines/", env!("OUT_DIR")))
 }
 
-pub fn list_size() -> i16 {
-    from_env_or_default("LIST_SIZE", 75)
+/// How often (in seconds) the player ranking should be snapshotted. A value of `0` disables
+/// snapshots
+pub fn ranking_snapshot_interval() -> u64 {
+    from_env_or_default("RANKING_SNAPSHOT_INTERVAL", 60 * 60 * 24)
 }
 
-pub fn extended_list_size() -> i16 {
-    from_env_or_default("EXTENDED_LIST_SIZE", 75) // for now
+/// How often (in seconds) the queue of pending webhook deliveries is checked
+pub fn webhook_delivery_interval() -> u64 {
+    from_env_or_default("WEBHOOK_DELIVERY_INTERVAL", 10)
+}
+
+/// How often (in seconds) the queue of pending video validation jobs is checked
+pub fn video_validation_interval() -> u64 {
+    from_env_or_default("VIDEO_VALIDATION_INTERVAL", 15)
+}
+
+/// How long (in minutes) a claim on a record lasts before it expires
+pub fn record_claim_duration() -> i32 {
+    from_env_or_default("RECORD_CLAIM_DURATION", 30)
+}
+
+/// How long (in hours) a password reset token stays valid
+pub fn password_reset_duration() -> i32 {
+    from_env_or_default("PASSWORD_RESET_DURATION", 24)
+}
+
+/// The minimum number of characters a password needs to have
+pub fn password_min_length() -> usize {
+    from_env_or_default("PASSWORD_MIN_LENGTH", 10)
+}
+
+/// Whether passwords on the bundled list of commonly used passwords are rejected
+pub fn password_forbid_common() -> bool {
+    from_env_or_default("PASSWORD_FORBID_COMMON", true)
+}
+
+/// Whether passwords containing the account's username are rejected
+pub fn password_forbid_username() -> bool {
+    from_env_or_default("PASSWORD_FORBID_USERNAME", true)
+}
+
+/// The algorithm new password hashes are computed with, either `bcrypt` or `argon2`. Existing
+/// hashes using a different algorithm (or outdated parameters) are upgraded on login
+pub fn password_hash_algorithm() -> String {
+    from_env_or_default("PASSWORD_HASH_ALGORITHM", "bcrypt".into())
+}
+
+/// The cost factor used for bcrypt password hashes
+pub fn bcrypt_cost() -> u32 {
+    from_env_or_default("BCRYPT_COST", bcrypt::DEFAULT_COST)
+}
+
+/// The amount of memory (in KiB) used for computing argon2 password hashes
+pub fn argon2_memory_cost() -> u32 {
+    from_env_or_default("ARGON2_MEMORY_COST", 19 * 1024)
+}
+
+/// The number of iterations used for computing argon2 password hashes
+pub fn argon2_time_cost() -> u32 {
+    from_env_or_default("ARGON2_TIME_COST", 2)
+}
+
+/// How emails should be delivered, see [`crate::mail::from_config`]. If unset, no emails are sent
+pub fn mail_transport() -> Option<String> {
+    std::env::var("MAIL_TRANSPORT").ok()
+}
+
+/// The limit configured for some [`crate::ratelimit::RatelimitScope`] via `RATELIMIT_<SCOPE>`
+/// (or the roles exempt from it via `RATELIMIT_<SCOPE>_EXEMPT`). If unset, the scope's default is
+/// used
+pub fn ratelimit(scope: &str) -> Option<String> {
+    std::env::var(format!("RATELIMIT_{}", scope)).ok()
+}
+
+/// Where rate limit state is kept, either `memory` or `postgres`. Only the latter survives restarts
+/// and is shared between multiple instances
+pub fn ratelimit_store() -> String {
+    from_env_or_default("RATELIMIT_STORE", "memory".into())
+}
+
+/// The minimum number of approved records a submitter needs to be considered trusted
+pub fn submitter_trusted_min_approved() -> i64 {
+    from_env_or_default("SUBMITTER_TRUSTED_MIN_APPROVED", 10)
+}
+
+/// The maximum share of rejected records (among all approved and rejected ones) a trusted
+/// submitter may have
+pub fn submitter_trusted_max_rejection_ratio() -> f64 {
+    from_env_or_default("SUBMITTER_TRUSTED_MAX_REJECTION_RATIO", 0.1)
+}
+
+/// The number of rejected records after which a submitter can be flagged
+pub fn submitter_flagged_min_rejected() -> i64 {
+    from_env_or_default("SUBMITTER_FLAGGED_MIN_REJECTED", 5)
+}
+
+/// The share of rejected records (among all approved and rejected ones) at which a submitter is
+/// flagged, provided they have at least [`submitter_flagged_min_rejected`] rejected records
+pub fn submitter_flagged_min_rejection_ratio() -> f64 {
+    from_env_or_default("SUBMITTER_FLAGGED_MIN_REJECTION_RATIO", 0.5)
+}
+
+/// Comma separated list of the networks (in CIDR notation) of reverse proxies whose forwarding
+/// headers are trusted, see [`crate::extractor::ip`]
+pub fn trusted_proxies() -> String {
+    from_env_or_default("TRUSTED_PROXIES", "127.0.0.1/32,::1/128".into())
+}
+
+/// The header our reverse proxies report forwarded-for addresses in. Either `x-forwarded-for` or
+/// `forwarded`. Only this header is read, see [`crate::extractor::ip`]
+pub fn trusted_proxy_header() -> String {
+    from_env_or_default("TRUSTED_PROXY_HEADER", "x-forwarded-for".into())
+}
+
+/// Whether the `CF-Connecting-IP` header set by Cloudflare is trusted when a request was delivered
+/// by one of the networks in [`cloudflare_networks`]
+pub fn trust_cf_connecting_ip() -> bool {
+    from_env_or_default("TRUST_CF_CONNECTING_IP", false)
+}
+
+/// Comma separated list of the networks (in CIDR notation) Cloudflare forwards requests from.
+/// Defaults to the ones published at https://www.cloudflare.com/ips/
+pub fn cloudflare_networks() -> String {
+    from_env_or_default(
+        "CLOUDFLARE_NETWORKS",
+        "173.245.48.0/20,103.21.244.0/22,103.22.200.0/22,103.31.4.0/22,141.101.64.0/18,108.162.192.0/18,190.93.240.0/20,188.114.96.0/20,\
+         197.234.240.0/22,198.41.128.0/17,162.158.0.0/15,104.16.0.0/13,104.24.0.0/14,172.64.0.0/13,131.0.72.0/22,2400:cb00::/32,\
+         2606:4700::/32,2803:f800::/32,2405:b500::/32,2405:8100::/32,2a06:98c0::/29,2c0f:f248::/32"
+            .into(),
+    )
 }
 
 pub fn secret() -> Vec<u8> {
@@ -1,17 +1,19 @@
 //! Moduling containing the [`PointercrateError`] enum.
 
 use crate::{
-    model::demonlist::{demon::MinimalDemon, record::RecordStatus},
+    model::demonlist::{demon::MinimalDemon, player::DatabasePlayer, record::RecordStatus},
     permissions::Permissions,
-    ratelimit::RatelimitScope,
+    ratelimit::{self, RatelimitScope},
     util::preferred_mime_type,
     view::{error::ErrorPage, Page},
 };
 use actix_web::{
+    dev::HttpResponseBuilder,
     error::{JsonPayloadError, PathError, QueryPayloadError},
     http::{HeaderMap, Method, StatusCode},
     HttpResponse, ResponseError,
 };
+use chrono::NaiveDateTime;
 use derive_more::Display;
 use log::error;
 use serde::{
@@ -76,6 +78,29 @@ pub enum PointercrateError {
     )]
     Unauthorized,
 
+    /// `401 UNAUTHORIZED` error returned when the correct password was provided for an account
+    /// that has two-factor authentication enabled, but no second factor was provided
+    ///
+    /// Error Code `40101`
+    #[display(
+        fmt = "This account has two-factor authentication enabled. Please provide a code from your authenticator app or one of your \
+               recovery codes in the 'X-TOTP-Code' header"
+    )]
+    TwoFactorRequired,
+
+    /// `401 UNAUTHORIZED` error returned when the provided TOTP or recovery code is invalid
+    ///
+    /// Error Code `40102`
+    #[display(fmt = "The provided two-factor authentication code is invalid or has already been used")]
+    InvalidTwoFactorCode,
+
+    /// `401 UNAUTHORIZED` error returned when a password reset token is invalid, has already been
+    /// used or has expired
+    ///
+    /// Error Code `40103`
+    #[display(fmt = "The provided password reset token is invalid or has expired")]
+    InvalidResetToken,
+
     /// `403 FORBIDDEN`
     ///
     /// Error Code `40300`
@@ -119,6 +144,23 @@ pub enum PointercrateError {
     #[display(fmt = "You are banned from submitting records to the demonlist!")]
     BannedFromSubmissions,
 
+    /// `403 FORBIDDEN` error returned when an endpoint that manages account credentials is accessed
+    /// using a personal access token
+    ///
+    /// Error Code `40305`
+    #[display(fmt = "This endpoint cannot be accessed using a personal access token. Please log in with your password instead")]
+    PersonalAccessTokenForbidden,
+
+    /// `403 FORBIDDEN` error returned when a user who claimed a player tries to modify one of that
+    /// player's fields only list moderators are allowed to change
+    ///
+    /// Error Code `40306`
+    #[display(fmt = "You cannot change the '{}' of the player you claimed. Please contact a list moderator", field)]
+    ClaimantPatch {
+        /// The field that was attempted to be modified
+        field: &'static str,
+    },
+
     /// `404 NOT FOUND`
     ///
     /// Error Code `40400`
@@ -186,11 +228,88 @@ pub enum PointercrateError {
     #[display(fmt = "This player is already registered as a creator on this demon")]
     CreatorExists,
 
-    /// `409 CONFLICT` variant
+    /// `409 CONFLICT` error returned if a video is already used as proof by another record
     ///
     /// Error Code `40906`
-    #[display(fmt = "This video is already used by record #{}", id)]
-    DuplicateVideo { id: i32 },
+    #[display(fmt = "This video is already used by the {} record #{} of {} on {}", status, id, player, demon)]
+    DuplicateVideo {
+        /// The ID of the record already using the video
+        id: i32,
+
+        /// The progress of the record already using the video
+        progress: i16,
+
+        /// The [`RecordStatus`] of the record already using the video
+        status: RecordStatus,
+
+        /// The holder of the record already using the video
+        player: DatabasePlayer,
+
+        /// The demon the record already using the video is on
+        demon: MinimalDemon,
+    },
+
+    /// `409 CONFLICT` error returned if a deleted record is attempted to be restored, but a record
+    /// with its id already exists (e.g. because it has already been restored)
+    ///
+    /// Error Code `40907`
+    #[display(fmt = "A record with id {} already exists", id)]
+    RecordExists { id: i32 },
+
+    /// `409 CONFLICT` error returned if a record is claimed (or modified) by someone while another
+    /// list helper holds a claim on it
+    ///
+    /// Error Code `40908`
+    #[display(fmt = "Record {} is currently being reviewed by {} (claim expires {})", record, claimed_by, expires_at)]
+    RecordClaimed {
+        record: i32,
+        claimed_by: String,
+        expires_at: NaiveDateTime,
+    },
+
+    /// `409 CONFLICT` error returned if the position range of a list section would overlap with
+    /// that of another section
+    ///
+    /// Error Code `40909`
+    #[display(fmt = "The given position range overlaps with the '{}' section", section)]
+    SectionOverlap { section: String },
+
+    /// `409 CONFLICT` error returned if a video submitted as a record's proof is the showcase video
+    /// of a demon
+    ///
+    /// Error Code `40910`
+    #[display(fmt = "This video is the showcase video of {}", demon)]
+    VideoIsShowcase {
+        /// The demon using the video as its showcase
+        demon: MinimalDemon,
+    },
+
+    /// `409 CONFLICT` error returned when trying to enroll in two-factor authentication while it is
+    /// already enabled
+    ///
+    /// Error Code `40911`
+    #[display(fmt = "Two-factor authentication is already enabled for this account. Disable it first to enroll a new authenticator")]
+    TwoFactorEnabled,
+
+    /// `409 CONFLICT` error returned if someone tries to claim a player whose claim has already been
+    /// verified for another user
+    ///
+    /// Error Code `40912`
+    #[display(fmt = "{} has already been claimed by another user", player)]
+    PlayerClaimed {
+        /// The player that was attempted to be claimed
+        player: DatabasePlayer,
+    },
+
+    /// `409 CONFLICT` error returned if a user who already holds a claim on some player tries to
+    /// claim another one
+    ///
+    /// Error Code `40913`
+    #[display(fmt = "You already claimed {}. Withdraw that claim before claiming another player", player)]
+    ClaimExists {
+        /// The player the user already claimed
+        player: DatabasePlayer,
+    },
 
     /// `411 LENGTH REQUIRED`
     ///
@@ -238,11 +357,14 @@ pub enum PointercrateError {
     InvalidUsername,
 
     /// `422 UNPROCESSABLE ENTITY` variant returned if the password provided during registration
-    /// (or account update) is shorter than 10 characters
+    /// (or account update) is shorter than the configured minimum length
     ///
     /// Error Code `42204`
-    #[display(fmt = "Invalid password! The password must be at least 10 characters long")]
-    InvalidPassword,
+    #[display(fmt = "Invalid password! The password must be at least {} characters long", min_length)]
+    InvalidPassword {
+        /// The minimum number of characters a password needs to have
+        min_length: usize,
+    },
 
     /// `422 UNPRECESSABLE ENTITY` variant returned if the `limit` parameter provided for
     /// pagination is too large or too small
@@ -304,17 +426,19 @@ pub enum PointercrateError {
     #[display(fmt = "The given player is banned and thus cannot have non-rejected records on the list!")]
     PlayerBanned,
 
-    /// `422 UNPROCESSABLE ENTITY` variant
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record is submitted for a demon in a list
+    /// section that doesn't accept submissions
     ///
     /// Error Code 42219
-    #[display(fmt = "You cannot submit records for legacy demons")]
-    SubmitLegacy,
+    #[display(fmt = "You cannot submit records for demons in the {} section", section)]
+    SubmissionsClosed { section: String },
 
-    /// `422 UNPROCESSABLE ENTITY` variant
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record is submitted for a demon in a list
+    /// section with a minimal progress, and the record's progress is below that value
     ///
     /// Error Code 42220
-    #[display(fmt = "Only 100% records can be submitted for the extended section of the list")]
-    Non100Extended,
+    #[display(fmt = "Only records with at least {}% can be submitted for the {} section", requirement, section)]
+    SectionRequirement { section: String, requirement: i16 },
 
     /// `422 UNPROCESSABLE ENTITY` variant
     ///
@@ -331,7 +455,10 @@ pub enum PointercrateError {
     /// `422 UNPROCESSABLE ENTITY` variant
     ///
     /// Error Code `42224`
-    #[display(fmt = "The given video host is not supported. Supported are 'youtube', 'vimeo', 'everyplay', 'twitch' and 'bilibili'")]
+    #[display(
+        fmt = "The given video host is not supported. Supported are 'youtube', 'vimeo', 'everyplay', 'twitch', 'bilibili', 'medal', \
+               'streamable' and 'google drive'"
+    )]
     UnsupportedVideoHost,
 
     /// `422 UNPROCESSABLE ENTITY` variant
@@ -352,15 +479,6 @@ pub enum PointercrateError {
     #[display(fmt = "The given URL is no YouTube URL")]
     NotYouTube,
 
-    /// `422 UNPROCESSABLE ENTITY` variant
-    ///
-    /// Error Code `42227`
-    #[display(
-        fmt = "The 'after' value provided for pagination is smaller than the 'before' value. This would result in an empty response is \
-               most likely a bug"
-    )]
-    AfterSmallerBefore,
-
     /// `422 UNPROCESSABLE ENTITY` variant
     ///
     /// Error Code `42228`
@@ -379,6 +497,67 @@ pub enum PointercrateError {
     #[display(fmt = "Notes mustn't be empty!")]
     NoteEmpty,
 
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42231`
+    #[display(fmt = "Invalid value for scoring formula parameter '{}': {}", parameter, reason)]
+    InvalidFormulaParameter {
+        /// The name of the offending parameter
+        parameter: &'static str,
+
+        /// Why the value was rejected
+        reason: &'static str,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42232`
+    #[display(fmt = "Invalid value for list section parameter '{}': {}", parameter, reason)]
+    InvalidSectionParameter {
+        /// The name of the offending parameter
+        parameter: &'static str,
+
+        /// Why the value was rejected
+        reason: &'static str,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42234`
+    #[display(fmt = "Invalid value for personal access token parameter '{}': {}", parameter, reason)]
+    InvalidAccessTokenParameter {
+        /// The name of the offending parameter
+        parameter: &'static str,
+
+        /// Why the value was rejected
+        reason: &'static str,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42235`
+    #[display(fmt = "The provided email address is invalid")]
+    InvalidEmail,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the password provided during registration
+    /// (or account update) is long enough, but otherwise violates the password policy
+    ///
+    /// Error Code `42236`
+    #[display(fmt = "Insecure password! {}", reason)]
+    WeakPassword {
+        /// Why the password was rejected
+        reason: &'static str,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42233`
+    #[display(
+        fmt = "The given raw footage host is not supported. Raw footage needs to be uploaded to Google Drive, Mega or Dropbox, or be a \
+               direct link to a video file"
+    )]
+    UnsupportedRawFootageHost,
+
     /// `428 PRECONDITION REQUIRED`
     ///
     /// Error Code `42800`
@@ -393,6 +572,10 @@ pub enum PointercrateError {
         #[serde(skip)]
         scope: RatelimitScope,
 
+        /// The number of requests allowed per period in the exceeded scope
+        #[serde(skip)]
+        limit: u32,
+
         remaining: Duration,
     },
 
@@ -456,12 +639,17 @@ impl PointercrateError {
             PointercrateError::InvalidHeaderValue { .. } => 40002,
 
             PointercrateError::Unauthorized => 40100,
+            PointercrateError::TwoFactorRequired => 40101,
+            PointercrateError::InvalidTwoFactorCode => 40102,
+            PointercrateError::InvalidResetToken => 40103,
 
             PointercrateError::Forbidden => 40300,
             PointercrateError::MissingPermissions { .. } => 40301,
             PointercrateError::DeleteSelf => 40302,
             PointercrateError::PatchSelf => 40303,
             PointercrateError::BannedFromSubmissions => 40304,
+            PointercrateError::PersonalAccessTokenForbidden => 40305,
+            PointercrateError::ClaimantPatch { .. } => 40306,
 
             PointercrateError::NotFound => 40400,
             PointercrateError::ModelNotFound { .. } => 40401,
@@ -473,6 +661,13 @@ impl PointercrateError {
             PointercrateError::DemonExists { .. } => 40904,
             PointercrateError::CreatorExists => 40905,
             PointercrateError::DuplicateVideo { .. } => 40906,
+            PointercrateError::RecordExists { .. } => 40907,
+            PointercrateError::RecordClaimed { .. } => 40908,
+            PointercrateError::SectionOverlap { .. } => 40909,
+            PointercrateError::VideoIsShowcase { .. } => 40910,
+            PointercrateError::TwoFactorEnabled => 40911,
+            PointercrateError::PlayerClaimed { .. } => 40912,
+            PointercrateError::ClaimExists { .. } => 40913,
 
             PointercrateError::LengthRequired => 41100,
 
@@ -484,7 +679,7 @@ impl PointercrateError {
 
             PointercrateError::UnprocessableEntity => 42200,
             PointercrateError::InvalidUsername => 42202,
-            PointercrateError::InvalidPassword => 42204,
+            PointercrateError::InvalidPassword { .. } => 42204,
             PointercrateError::InvalidPaginationLimit => 42207,
             PointercrateError::UnexpectedNull { .. } => 42211,
             PointercrateError::InvalidRequirement => 42212,
@@ -492,17 +687,22 @@ impl PointercrateError {
             PointercrateError::InvalidProgress { .. } => 42215,
             PointercrateError::SubmissionExists { .. } => 42217,
             PointercrateError::PlayerBanned => 42218,
-            PointercrateError::SubmitLegacy => 42219,
-            PointercrateError::Non100Extended => 42220,
+            PointercrateError::SubmissionsClosed { .. } => 42219,
+            PointercrateError::SectionRequirement { .. } => 42220,
             PointercrateError::InvalidUrlScheme => 42222,
             PointercrateError::UrlAuthenticated => 42223,
             PointercrateError::UnsupportedVideoHost => 42224,
             PointercrateError::InvalidUrlFormat { .. } => 42225,
             PointercrateError::NotYouTube => 42226,
-            PointercrateError::AfterSmallerBefore => 42227,
             PointercrateError::DemonNameNotUnique { .. } => 42228,
             PointercrateError::MutuallyExclusive => 42229,
             PointercrateError::NoteEmpty => 42230,
+            PointercrateError::InvalidFormulaParameter { .. } => 42231,
+            PointercrateError::InvalidSectionParameter { .. } => 42232,
+            PointercrateError::UnsupportedRawFootageHost => 42233,
+            PointercrateError::InvalidAccessTokenParameter { .. } => 42234,
+            PointercrateError::InvalidEmail => 42235,
+            PointercrateError::WeakPassword { .. } => 42236,
 
             PointercrateError::PreconditionRequired => 42800,
 
@@ -603,6 +803,8 @@ impl ResponseError for JsonError {
             response.header("Allow", allowed_methods.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(","));
         }*/
 
+        ratelimit_headers(&self.0, &mut response);
+
         response.json(json!({
             "code": self.0.error_code(),
             "message": self.0.to_string(),
@@ -619,10 +821,24 @@ impl ResponseError for HtmlError {
     fn error_response(&self) -> HttpResponse {
         let html = ErrorPage::new(&self.0).render();
 
-        HttpResponse::Ok()
-            .content_type("text/html; charset=utf-8")
-            .status(self.status_code())
-            .body(html.0)
+        let mut response = HttpResponse::build(self.status_code());
+
+        ratelimit_headers(&self.0, &mut response);
+
+        response.content_type("text/html; charset=utf-8").body(html.0)
+    }
+}
+
+/// Tells clients that ran into a rate limit when they can try again
+fn ratelimit_headers(error: &PointercrateError, response: &mut HttpResponseBuilder) {
+    if let PointercrateError::Ratelimited { limit, remaining, .. } = error {
+        let retry_after = ratelimit::ceil_seconds(*remaining).to_string();
+
+        response
+            .header("Retry-After", retry_after.clone())
+            .header("RateLimit-Limit", limit.to_string())
+            .header("RateLimit-Remaining", "0")
+            .header("RateLimit-Reset", retry_after);
     }
 }
 
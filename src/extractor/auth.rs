@@ -2,6 +2,7 @@
 
 use crate::{
     error::{JsonError, PointercrateError},
+    extractor::ip::Ip,
     model::user::{AuthenticatedUser, Authorization},
     state::PointercrateState,
     util::header,
@@ -60,10 +61,19 @@ impl FromRequest for BasicAuth {
             Err(error) => return Either::Right(err(error.into())),
         };
 
+        // Needed to ratelimit invalid second factors
+        let ip = match Ip::of(&req) {
+            Ok(Ip(ip)) => ip,
+            Err(error) => return Either::Right(err(error.into())),
+        };
+
         Either::Left(Box::pin(async move {
+            let ratelimits = state.ratelimits.prepare(ip);
             let mut connection = state.connection().await?;
 
-            Ok(BasicAuth(AuthenticatedUser::basic_auth(&auth, &mut connection).await?))
+            Ok(BasicAuth(
+                AuthenticatedUser::basic_auth(&auth, &mut connection, Some(&ratelimits)).await?,
+            ))
         }))
     }
 }
@@ -99,6 +109,7 @@ fn process_authorization_header(request: &HttpRequest) -> Result<Authorization,
                     Ok(Authorization::Basic {
                         username: (*username).to_string(),
                         password: (*password).to_string(),
+                        second_factor: header(request.headers(), "X-TOTP-Code")?.map(ToString::to_string),
                     })
                 } else {
                     warn!("Malformed 'Authorization' header");
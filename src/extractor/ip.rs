@@ -1,59 +1,449 @@
-use crate::error::{JsonError, PointercrateError};
+//! Module containing the extractor for the IP address a request originated from
+//!
+//! Pointercrate usually runs behind one or more reverse proxies, meaning the peer address of a
+//! connection is that of the last proxy, not that of the client. Proxies report the addresses they
+//! received requests from via the `Forwarded` (RFC 7239) or `X-Forwarded-For` headers, each
+//! appending to whatever the previous hop sent. Which of the two our proxies set is configured via
+//! `TRUSTED_PROXY_HEADER`, and only that header is ever read: proxies usually pass on the other one
+//! untouched, so a client could put whatever it wants in there.
+//!
+//! Since clients can send the configured header too, the chain is walked from right to left,
+//! skipping addresses of proxies we trust (configured via `TRUSTED_PROXIES`). The first untrusted
+//! address is the client's, and everything to its left is ignored.
+//!
+//! If `TRUST_CF_CONNECTING_IP` is set, the `CF-Connecting-IP` header set by Cloudflare takes
+//! precedence over both of the above, but only if the address found that way belongs to Cloudflare
+//! (configured via `CLOUDFLARE_NETWORKS`). Otherwise, anyone able to reach our proxies directly
+//! could claim to be whoever they want.
+
+use crate::{
+    config,
+    error::{JsonError, PointercrateError},
+};
 use actix_web::{
     dev::{Payload, PayloadStream},
+    http::HeaderMap,
     FromRequest, HttpRequest,
 };
-use futures::future::{err, ok, ready, Ready};
+use futures::future::{ready, Ready};
+use lazy_static::lazy_static;
 use log::{error, warn};
-use std::net::{IpAddr, Ipv4Addr};
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    str::FromStr,
+};
+
+lazy_static! {
+    static ref TRUSTED_PROXIES: Vec<IpNetwork> = config::trusted_proxies()
+        .split(',')
+        .filter(|network| !network.trim().is_empty())
+        .map(|network| {
+            network
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid network '{}' in TRUSTED_PROXIES", network))
+        })
+        .collect();
+    static ref PROXY_HEADER: ProxyHeader = {
+        let header = config::trusted_proxy_header();
+
+        header
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid TRUSTED_PROXY_HEADER '{}', expected 'x-forwarded-for' or 'forwarded'", header))
+    };
+    static ref CLOUDFLARE_NETWORKS: Vec<IpNetwork> = if config::trust_cf_connecting_ip() {
+        config::cloudflare_networks()
+            .split(',')
+            .filter(|network| !network.trim().is_empty())
+            .map(|network| {
+                network
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid network '{}' in CLOUDFLARE_NETWORKS", network))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+}
+
+/// Parses the proxy configuration, so that a misconfiguration is noticed at startup instead of
+/// during the first request
+pub fn initialize() {
+    lazy_static::initialize(&TRUSTED_PROXIES);
+    lazy_static::initialize(&PROXY_HEADER);
+    lazy_static::initialize(&CLOUDFLARE_NETWORKS);
+}
 
 pub struct Ip(pub IpAddr);
 
+/// A network in CIDR notation, such as `10.0.0.0/8`. A plain address is a network containing only
+/// itself
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct IpNetwork {
+    address: IpAddr,
+    prefix: u32,
+}
+
+impl IpNetwork {
+    fn contains(&self, ip: IpAddr) -> bool {
+        let (network, ip, bits) = match (self.address, canonical(ip)) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => (u32::from(network).into(), u32::from(ip).into(), 32),
+            (IpAddr::V6(network), IpAddr::V6(ip)) => (u128::from(network), u128::from(ip), 128),
+            _ => return false,
+        };
+
+        masked(network, self.prefix, bits) == masked(ip, self.prefix, bits)
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (address, prefix) = match s.find('/') {
+            Some(idx) => (&s[..idx], Some(&s[idx + 1..])),
+            None => (s, None),
+        };
+
+        let address = canonical(address.parse().map_err(|_| format!("Invalid network '{}'", s))?);
+        let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+
+        let prefix = match prefix {
+            None => max_prefix,
+            Some(prefix) =>
+                match prefix.parse() {
+                    Ok(prefix) if prefix <= max_prefix => prefix,
+                    _ => return Err(format!("Invalid network '{}'", s)),
+                },
+        };
+
+        Ok(IpNetwork { address, prefix })
+    }
+}
+
+/// Keeps only the first `prefix` of the `bits` lowest bits of `value`
+fn masked(value: u128, prefix: u32, bits: u32) -> u128 {
+    match prefix {
+        0 => 0,
+        _ => value >> (bits - prefix),
+    }
+}
+
+/// Turns IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`, which is what dual stack sockets report for
+/// IPv4 peers) into proper IPv4 addresses
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) =>
+            match v6.segments() {
+                [0, 0, 0, 0, 0, 0xffff, high, low] => IpAddr::V4(Ipv4Addr::from(((high as u32) << 16) | low as u32)),
+                _ => ip,
+            },
+        _ => ip,
+    }
+}
+
+fn is_trusted(ip: IpAddr, trusted: &[IpNetwork]) -> bool {
+    trusted.iter().any(|network| network.contains(ip))
+}
+
+/// The header our trusted proxies use to tell us who they forwarded a request for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ProxyHeader {
+    XForwardedFor,
+    Forwarded,
+}
+
+impl FromStr for ProxyHeader {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.trim().to_ascii_lowercase()[..] {
+            "x-forwarded-for" => Ok(ProxyHeader::XForwardedFor),
+            "forwarded" => Ok(ProxyHeader::Forwarded),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ProxyHeader {
+    fn name(self) -> &'static str {
+        match self {
+            ProxyHeader::XForwardedFor => "X-FORWARDED-FOR",
+            ProxyHeader::Forwarded => "FORWARDED",
+        }
+    }
+
+    /// Extracts the address from a single element of the forwarding chain
+    ///
+    /// Returns [`None`] for anything that isn't an address. Note that RFC 7239 explicitly allows
+    /// proxies to send `for=unknown` or obfuscated identifiers such as `for=_hidden`.
+    fn parse_element(self, element: &str) -> Option<IpAddr> {
+        match self {
+            ProxyHeader::XForwardedFor => element.trim().parse().ok(),
+            ProxyHeader::Forwarded =>
+                element
+                    .split(';')
+                    .filter_map(|pair| {
+                        let mut parts = pair.splitn(2, '=');
+
+                        match (parts.next(), parts.next()) {
+                            (Some(key), Some(value)) if key.trim().eq_ignore_ascii_case("for") => Some(value),
+                            _ => None,
+                        }
+                    })
+                    .next()
+                    .and_then(parse_forwarded_node),
+        }
+    }
+}
+
+/// Parses the node identifier in a `for` parameter of a `Forwarded` header, which is either an
+/// IPv4 address or a bracketed IPv6 address, both optionally followed by a port
+fn parse_forwarded_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+
+    if node.starts_with('[') {
+        let end = node.find(']')?;
+
+        node[1..end].parse().ok()
+    } else {
+        node.split(':').next()?.parse().ok()
+    }
+}
+
+/// Gets the comma separated elements of all occurrences of the given header, in order
+fn collect_header<'a>(headers: &'a HeaderMap, header: &'static str) -> Result<Vec<&'a str>, PointercrateError> {
+    let mut elements = Vec::new();
+
+    for value in headers.get_all(header) {
+        let value = value.to_str().map_err(|_| PointercrateError::InvalidHeaderValue { header })?;
+
+        elements.extend(value.split(',').filter(|element| !element.trim().is_empty()));
+    }
+
+    Ok(elements)
+}
+
+/// Determines the address of the client that made a request received from `peer`
+///
+/// The `CF-Connecting-IP` header is only looked at if the request reached our own infrastructure
+/// from one of the `cloudflare` networks.
+///
+/// Returns [`None`] if the peer is a trusted proxy, but didn't tell us who it forwarded the request
+/// for.
+fn client_ip(
+    peer: IpAddr, headers: &HeaderMap, trusted: &[IpNetwork], header: ProxyHeader, cloudflare: &[IpNetwork],
+) -> Result<Option<IpAddr>, PointercrateError> {
+    let client = match forwarded_for(canonical(peer), headers, trusted, header)? {
+        Some(client) => client,
+        None => return Ok(None),
+    };
+
+    if is_trusted(client, cloudflare) {
+        if let Some(value) = headers.get("CF-CONNECTING-IP") {
+            return value
+                .to_str()
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .map(|ip| Some(canonical(ip)))
+                .ok_or(PointercrateError::InvalidHeaderValue { header: "CF-CONNECTING-IP" })
+        }
+    }
+
+    Ok(Some(client))
+}
+
+/// Determines the first address not belonging to one of our own proxies that a request received
+/// from `peer` passed through
+fn forwarded_for(
+    peer: IpAddr, headers: &HeaderMap, trusted: &[IpNetwork], header: ProxyHeader,
+) -> Result<Option<IpAddr>, PointercrateError> {
+    if !is_trusted(peer, trusted) {
+        return Ok(Some(peer))
+    }
+
+    let chain = collect_header(headers, header.name())?;
+
+    if chain.is_empty() {
+        return Ok(None)
+    }
+
+    // Everything to the left of the first untrusted address could have been made up by the client,
+    // so we don't even look at it. We only fail if one of our own proxies sent us garbage. If the
+    // whole chain is trusted, the request originated from within our own infrastructure.
+    let mut client = peer;
+
+    for element in chain.into_iter().rev() {
+        client = header
+            .parse_element(element)
+            .map(canonical)
+            .ok_or(PointercrateError::InvalidHeaderValue { header: header.name() })?;
+
+        if !is_trusted(client, trusted) {
+            break
+        }
+    }
+
+    Ok(Some(client))
+}
+
+impl Ip {
+    /// Determines the address of the client that made the given request
+    pub fn of(request: &HttpRequest) -> Result<Ip, PointercrateError> {
+        if let Some(sockaddr) = request.peer_addr() {
+            match client_ip(sockaddr.ip(), request.headers(), &TRUSTED_PROXIES, *PROXY_HEADER, &CLOUDFLARE_NETWORKS)? {
+                Some(ip) => Ok(Ip(ip)),
+                None =>
+                    if cfg!(debug_assertions) {
+                        warn!(
+                            "Request from trusted proxy {}, but no forwarding header is set. Allowing, since this is a debug build",
+                            sockaddr.ip()
+                        );
+
+                        Ok(Ip(canonical(sockaddr.ip())))
+                    } else {
+                        error!(
+                            "Request from trusted proxy {}, but no forwarding header is set. Since this is a release build, this is a \
+                             configuration error!",
+                            sockaddr.ip()
+                        );
+
+                        Err(PointercrateError::InternalServerError)
+                    },
+            }
+        } else {
+            warn!("Remote address for request to {} not retrievable, aborting!", request.uri());
+
+            Err(PointercrateError::Unauthorized)
+        }
+    }
+}
+
 impl FromRequest for Ip {
     type Config = ();
     type Error = JsonError;
     type Future = Ready<Result<Ip, JsonError>>;
 
     fn from_request(request: &HttpRequest, _payload: &mut Payload<PayloadStream>) -> Self::Future {
-        if let Some(sockaddr) = request.peer_addr() {
-            // We'll have nginx reverse-proxying for us, so we gotta check this
-            if IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)) == sockaddr.ip() {
-                match request.headers().get("X-FORWARDED-FOR") {
-                    Some(value) =>
-                        ready(
-                            value
-                                .to_str()
-                                .map_err(|_| PointercrateError::InvalidHeaderValue { header: "X-FORWARDED-FOR" }.into())
-                                .and_then(|forwarded_for| {
-                                    forwarded_for
-                                        .parse()
-                                        .map_err(|_| PointercrateError::InvalidHeaderValue { header: "X-FORWARDED-FOR" }.into())
-                                })
-                                .map(Ip),
-                        ),
-                    None =>
-                        if cfg!(debug_assertions) {
-                            warn!(
-                                "Request from local machine, but no 'X-FORWARDED-FOR' header is set. Allowing, since this is a debug build"
-                            );
-
-                            ok(Ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))))
-                        } else {
-                            error!(
-                                "Request from local machine, but no 'X-FORWARDED-FOR' header is set. Since this is a release build, this \
-                                 is a configuration error!"
-                            );
-
-                            err(PointercrateError::InternalServerError.into())
-                        },
-                }
-            } else {
-                ok(Ip(sockaddr.ip()))
-            }
-        } else {
-            warn!("Remote address for request to {} not retrievable, aborting!", request.uri());
+        ready(Ip::of(request).map_err(JsonError))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{client_ip, IpNetwork, ProxyHeader};
+    use crate::error::PointercrateError;
+    use actix_web::http::{HeaderMap, HeaderName, HeaderValue};
+    use std::net::IpAddr;
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
 
-            err(PointercrateError::Unauthorized.into())
+    fn headers(headers: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+
+        for (name, value) in headers {
+            map.append(HeaderName::from_static(name), HeaderValue::from_static(value));
         }
+
+        map
+    }
+
+    fn trusted() -> Vec<IpNetwork> {
+        vec!["127.0.0.1".parse().unwrap(), "::1/128".parse().unwrap(), "10.0.0.0/8".parse().unwrap()]
+    }
+
+    #[test]
+    fn test_ip_network() {
+        let network: IpNetwork = "10.0.0.0/8".parse().unwrap();
+
+        assert!(network.contains(ip("10.1.2.3")));
+        assert!(network.contains(ip("::ffff:10.1.2.3")));
+        assert!(!network.contains(ip("11.0.0.1")));
+        assert!("0.0.0.0/0".parse::<IpNetwork>().unwrap().contains(ip("1.2.3.4")));
+        assert!("2001:db8::/32".parse::<IpNetwork>().unwrap().contains(ip("2001:db8:cafe::17")));
+        assert!("10.0.0.0/33".parse::<IpNetwork>().is_err());
+    }
+
+    #[test]
+    fn test_untrusted_peer() {
+        let headers = headers(&[("x-forwarded-for", "1.1.1.1")]);
+
+        assert_eq!(
+            client_ip(ip("2.2.2.2"), &headers, &trusted(), ProxyHeader::XForwardedFor, &[]),
+            Ok(Some(ip("2.2.2.2")))
+        );
+    }
+
+    #[test]
+    fn test_x_forwarded_for_chain() {
+        let xff = ProxyHeader::XForwardedFor;
+
+        // a client spoofing 1.1.1.1, connecting via two of our proxies
+        let chain = headers(&[("x-forwarded-for", "1.1.1.1, 2.2.2.2"), ("x-forwarded-for", "10.0.0.5")]);
+
+        assert_eq!(client_ip(ip("::1"), &chain, &trusted(), xff, &[]), Ok(Some(ip("2.2.2.2"))));
+        assert_eq!(client_ip(ip("::1"), &headers(&[]), &trusted(), xff, &[]), Ok(None));
+
+        // garbage sent by the client is never looked at
+        let chain = headers(&[("x-forwarded-for", "nonsense, 2.2.2.2")]);
+
+        assert_eq!(client_ip(ip("127.0.0.1"), &chain, &trusted(), xff, &[]), Ok(Some(ip("2.2.2.2"))));
+
+        // garbage sent by one of our proxies is an error
+        assert_eq!(
+            client_ip(ip("127.0.0.1"), &headers(&[("x-forwarded-for", "nonsense")]), &trusted(), xff, &[]),
+            Err(PointercrateError::InvalidHeaderValue { header: "X-FORWARDED-FOR" })
+        );
+    }
+
+    #[test]
+    fn test_forwarded() {
+        let headers = headers(&[
+            ("x-forwarded-for", "1.1.1.1"),
+            ("forwarded", "for=unknown, for=1.1.1.1;proto=https, For=\"[2001:db8:cafe::17]:4711\""),
+            ("forwarded", "for=10.0.0.5:8080;by=10.0.0.6"),
+        ]);
+
+        assert_eq!(
+            client_ip(ip("127.0.0.1"), &headers, &trusted(), ProxyHeader::Forwarded, &[]),
+            Ok(Some(ip("2001:db8:cafe::17")))
+        );
+    }
+
+    #[test]
+    fn test_ignores_other_header() {
+        // our proxy only appends to X-Forwarded-For, and passes on whatever Forwarded header the client sent
+        let headers = headers(&[("forwarded", "for=1.2.3.4"), ("x-forwarded-for", "5.5.5.5")]);
+
+        assert_eq!(
+            client_ip(ip("127.0.0.1"), &headers, &trusted(), ProxyHeader::XForwardedFor, &[]),
+            Ok(Some(ip("5.5.5.5")))
+        );
+    }
+
+    #[test]
+    fn test_cf_connecting_ip() {
+        let xff = ProxyHeader::XForwardedFor;
+        let cloudflare: Vec<IpNetwork> = vec!["162.158.0.0/15".parse().unwrap()];
+
+        // a client connecting via cloudflare and our local proxy
+        let headers = headers(&[("cf-connecting-ip", "3.3.3.3"), ("x-forwarded-for", "162.158.1.1")]);
+
+        assert_eq!(client_ip(ip("127.0.0.1"), &headers, &trusted(), xff, &cloudflare), Ok(Some(ip("3.3.3.3"))));
+        assert_eq!(client_ip(ip("127.0.0.1"), &headers, &trusted(), xff, &[]), Ok(Some(ip("162.158.1.1"))));
+    }
+
+    #[test]
+    fn test_spoofed_cf_connecting_ip() {
+        let xff = ProxyHeader::XForwardedFor;
+        let cloudflare: Vec<IpNetwork> = vec!["162.158.0.0/15".parse().unwrap()];
+
+        // a client connecting to our local proxy directly, bypassing cloudflare
+        let headers = headers(&[("cf-connecting-ip", "3.3.3.3"), ("x-forwarded-for", "4.4.4.4")]);
+
+        assert_eq!(client_ip(ip("127.0.0.1"), &headers, &trusted(), xff, &cloudflare), Ok(Some(ip("4.4.4.4"))));
+        assert_eq!(client_ip(ip("4.4.4.4"), &headers, &trusted(), xff, &cloudflare), Ok(Some(ip("4.4.4.4"))));
     }
 }
@@ -10,6 +10,7 @@ use futures::future::{ok, Ready};
 pub mod auth;
 pub mod if_match;
 pub mod ip;
+pub mod user_agent;
 
 impl FromRequest for PointercrateState {
     type Config = ();
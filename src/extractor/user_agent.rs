@@ -0,0 +1,27 @@
+use crate::{error::JsonError, util::header};
+use actix_web::{
+    dev::{Payload, PayloadStream},
+    FromRequest, HttpRequest,
+};
+use futures::future::{ready, Ready};
+
+/// The value of the request's `User-Agent` header, if one was sent
+///
+/// Truncated to a sensible length, since it ends up in the database
+pub struct UserAgent(pub Option<String>);
+
+const MAX_LENGTH: usize = 256;
+
+impl FromRequest for UserAgent {
+    type Config = ();
+    type Error = JsonError;
+    type Future = Ready<Result<UserAgent, JsonError>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload<PayloadStream>) -> Self::Future {
+        ready(
+            header(request.headers(), "User-Agent")
+                .map(|user_agent| UserAgent(user_agent.map(|user_agent| user_agent.chars().take(MAX_LENGTH).collect())))
+                .map_err(JsonError),
+        )
+    }
+}
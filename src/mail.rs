@@ -0,0 +1,79 @@
+//! Module containing the transports pointercrate can send emails through
+//!
+//! Pointercrate doesn't run a mail server, so how mails get delivered depends on the deployment.
+//! Each way of delivering mail is modelled by a [`MailTransport`], and the one to use is selected
+//! via the `MAIL_TRANSPORT` environment variable (see [`from_config`]). If none is configured,
+//! features relying on email (such as password resets via email) are disabled.
+
+use crate::{config, error::PointercrateError, Result};
+use log::{error, info};
+use std::{
+    fs::File,
+    io::Write,
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Trait describing a way of delivering emails
+pub trait MailTransport: Send + Sync {
+    fn send(&self, mail: &Mail) -> Result<()>;
+}
+
+/// Transport that simply logs all mails instead of delivering them. Meant for development
+pub struct LogTransport;
+
+/// Transport that writes every mail into a separate file in some directory, from which some
+/// external program (or a developer) can pick them up
+pub struct FileTransport {
+    directory: PathBuf,
+}
+
+impl MailTransport for LogTransport {
+    fn send(&self, mail: &Mail) -> Result<()> {
+        info!("Mail to {} with subject '{}':\n{}", mail.to, mail.subject, mail.body);
+
+        Ok(())
+    }
+}
+
+impl MailTransport for FileTransport {
+    fn send(&self, mail: &Mail) -> Result<()> {
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).expect("time went backwards");
+        let path = self.directory.join(format!("{}.eml", since_epoch.as_nanos()));
+
+        File::create(&path)
+            .and_then(|mut file| write!(file, "To: {}\r\nSubject: {}\r\n\r\n{}", mail.to, mail.subject, mail.body))
+            .map_err(|err| {
+                error!("Failed to write mail to {:?}: {}", path, err);
+
+                PointercrateError::InternalServerError
+            })
+    }
+}
+
+/// Constructs the transport configured via the `MAIL_TRANSPORT` environment variable
+///
+/// Supported values are `log` for [`LogTransport`] and `file:<directory>` for [`FileTransport`].
+pub fn from_config() -> Option<Arc<dyn MailTransport>> {
+    let transport = config::mail_transport()?;
+
+    match transport.as_str() {
+        "log" => Some(Arc::new(LogTransport)),
+        _ =>
+            match transport.strip_prefix("file:") {
+                Some(directory) =>
+                    Some(Arc::new(FileTransport {
+                        directory: directory.into(),
+                    })),
+                None => panic!("Unknown mail transport '{}'", transport),
+            },
+    }
+}
@@ -16,9 +16,9 @@ use actix_web::{
     App, HttpRequest, HttpServer,
 };
 use api::{
-    auth,
-    demonlist::{demon, misc, player, record, submitter},
-    user,
+    audit, auth,
+    demonlist::{demon, formula, misc, player, record, section, submitter},
+    role, user, webhook,
 };
 use std::net::SocketAddr;
 
@@ -31,10 +31,13 @@ mod documentation;
 mod error;
 mod extractor;
 mod gd;
+mod mail;
 mod middleware;
 mod model;
+mod pagination;
 mod permissions;
 mod ratelimit;
+mod scheduler;
 mod state;
 mod video;
 mod view;
@@ -54,6 +57,8 @@ async fn main() -> std::io::Result<()> {
 
     let application_state = PointercrateState::initialize().await;
 
+    scheduler::start(&application_state);
+
     HttpServer::new(move || {
         let json_config =
             JsonConfig::default().error_handler(|error, request| PointercrateError::from(error).dynamic(request.headers()).into());
@@ -82,7 +87,9 @@ async fn main() -> std::io::Result<()> {
             .service(view::home::index)
             .service(view::login::index)
             .service(view::login::post)
+            .service(view::login::refresh)
             .service(view::login::register)
+            .service(view::demonlist::legacy)
             .service(view::demonlist::page)
             .service(view::demonlist::index)
             .service(view::account::index)
@@ -99,16 +106,46 @@ async fn main() -> std::io::Result<()> {
                             .service(auth::delete_me)
                             .service(auth::get_me)
                             .service(auth::invalidate)
+                            .service(auth::request_password_reset)
+                            .service(auth::reset_password)
                             .service(auth::login)
-                            .service(auth::patch_me),
+                            .service(auth::refresh)
+                            .service(auth::patch_me)
+                            .service(auth::get_access_tokens)
+                            .service(auth::post_access_token)
+                            .service(auth::delete_access_token)
+                            .service(auth::get_sessions)
+                            .service(auth::delete_session)
+                            .service(auth::get_submissions)
+                            .service(auth::get_claim)
+                            .service(auth::delete_claim)
+                            .service(auth::begin_two_factor)
+                            .service(auth::confirm_two_factor)
+                            .service(auth::regenerate_recovery_codes)
+                            .service(auth::disable_two_factor)
+                            .service(auth::get_policy)
+                            .service(auth::patch_policy),
                     )
                     .service(
                         scope("/users")
                             .service(user::paginate)
                             .service(user::get)
                             .service(user::delete)
+                            .service(user::post_password_reset)
                             .service(user::patch),
                     )
+                    .service(scope("/roles").service(role::list))
+                    .service(scope("/audit").service(audit::paginate))
+                    .service(
+                        scope("/webhooks")
+                            .service(webhook::list)
+                            .service(webhook::post)
+                            .service(webhook::get)
+                            .service(webhook::patch)
+                            .service(webhook::delete)
+                            .service(webhook::deliveries)
+                            .service(webhook::retry_delivery),
+                    )
                     .service(
                         scope("/submitters")
                             .service(submitter::get)
@@ -124,23 +161,54 @@ async fn main() -> std::io::Result<()> {
                             .service(demon::v1::post_creator)
                             .service(demon::post),
                     )
+                    .service(
+                        scope("/formulas")
+                            .service(formula::list)
+                            .service(formula::active)
+                            .service(formula::get)
+                            .service(formula::post)
+                            .service(formula::preview)
+                            .service(formula::activate),
+                    )
+                    .service(
+                        scope("/sections")
+                            .service(section::list)
+                            .service(section::get)
+                            .service(section::post)
+                            .service(section::patch)
+                            .service(section::delete),
+                    )
                     .service(
                         scope("/records")
                             .service(record::delete)
                             .service(record::get)
                             .service(record::paginate)
                             .service(record::patch)
+                            .service(record::bulk_patch)
                             .service(record::submit)
                             .service(record::add_note)
                             .service(record::patch_note)
                             .service(record::delete_note)
-                            .service(record::audit_log),
+                            .service(record::audit_log)
+                            .service(record::revalidate)
+                            .service(record::revert)
+                            .service(record::restore)
+                            .service(record::claim)
+                            .service(record::release_claim),
                     )
                     .service(
                         scope("/players")
                             .service(player::patch)
+                            .service(player::audit_log)
                             .service(player::paginate)
                             .service(player::ranking)
+                            .service(player::ranking_history)
+                            .service(player::ranking_snapshots)
+                            .service(player::claim)
+                            .service(player::paginate_claims)
+                            .service(player::get_claim)
+                            .service(player::patch_claim)
+                            .service(player::delete_claim)
                             .service(player::get),
                     ),
             )
@@ -151,6 +219,7 @@ async fn main() -> std::io::Result<()> {
                         .service(demon::v2::get)
                         .service(demon::v2::paginate)
                         .service(demon::v2::patch)
+                        .service(demon::v2::audit_log)
                         .service(demon::v2::delete_creator)
                         .service(demon::v2::post_creator)
                         .service(demon::post),
@@ -0,0 +1,154 @@
+//! Module containing the global audit log feed
+//!
+//! The per-object audit logs (see for example [`crate::model::demonlist::record::audit`]) give a
+//! typed view of the history of a single object. The feed in this module instead lists every entry
+//! in every audit log table, in a uniform format, so that administrators can see who changed what
+//! across the whole list. It is backed by the `audit_log_feed` view.
+
+use crate::{
+    error::PointercrateError,
+    pagination::{Page, PaginationParameters, SortColumn},
+    util::non_nullable,
+    Result,
+};
+use chrono::NaiveDateTime;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgArguments, query::Query, PgConnection, Postgres, Row};
+
+/// The values the `model` field of an [`AuditLogEntry`] can take
+const MODELS: [&str; 9] = [
+    "record",
+    "record_note",
+    "record_claim",
+    "demon",
+    "creator",
+    "player",
+    "submitter",
+    "user",
+    "list_section",
+];
+
+/// An id of an object together with its name, if it (still) has one
+#[derive(Serialize, Debug, Hash, Clone)]
+pub struct NamedId {
+    pub id: i32,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AuditLogEntry {
+    pub time: NaiveDateTime,
+    pub audit_id: i32,
+    pub user: NamedId,
+    pub model: String,
+    pub action: String,
+    pub target: i32,
+    pub details: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditLogSortColumn {
+    Id,
+}
+
+impl Default for AuditLogSortColumn {
+    fn default() -> Self {
+        AuditLogSortColumn::Id
+    }
+}
+
+impl SortColumn for AuditLogSortColumn {
+    const ALL: &'static [Self] = &[AuditLogSortColumn::Id];
+
+    fn name(self) -> &'static str {
+        "id"
+    }
+
+    fn column(self) -> &'static str {
+        "audit_id"
+    }
+
+    fn sql_type(self) -> &'static str {
+        "INTEGER"
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AuditLogPagination {
+    #[serde(default, deserialize_with = "non_nullable")]
+    user: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    model: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    after_time: Option<NaiveDateTime>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    before_time: Option<NaiveDateTime>,
+}
+
+impl AuditLogPagination {
+    pub async fn page(
+        &self, parameters: &PaginationParameters<AuditLogSortColumn>, connection: &mut PgConnection,
+    ) -> Result<Page<AuditLogEntry>> {
+        parameters.validate()?;
+
+        if let Some(ref model) = self.model {
+            if !MODELS.contains(&model.as_str()) {
+                return Err(PointercrateError::BadRequest {
+                    message: format!("Unknown model type '{}'. Valid values are: {}", model, MODELS.join(", ")),
+                })
+            }
+        }
+
+        let query = include_str!("../../sql/paginate_audit_log.sql");
+        let page_query = parameters.page_query(query);
+
+        let mut entries = Vec::new();
+        let mut stream = self.bind(parameters.bind_cursors(sqlx::query(&page_query))).fetch(&mut *connection);
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            let details: Option<String> = row.get("details");
+
+            let entry = AuditLogEntry {
+                time: row.get("time"),
+                audit_id: row.get("audit_id"),
+                user: NamedId {
+                    id: row.get("userid"),
+                    name: row.get("username"),
+                },
+                model: row.get("model"),
+                action: row.get("action"),
+                target: row.get("target"),
+                details: details.and_then(|details| serde_json::from_str(&details).ok()),
+            };
+
+            entries.push((parameters.cursor(&row)?, entry))
+        }
+
+        drop(stream);
+
+        let mut page = Page::new(parameters, entries);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                self.bind(parameters.bind_no_cursors(sqlx::query(&count_query)))
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
+        }
+
+        Ok(page)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query.bind(self.user).bind(&self.model).bind(self.after_time).bind(self.before_time)
+    }
+}
@@ -1,13 +1,13 @@
 pub use self::{
     get::{published_by, verified_by},
-    paginate::{DemonIdPagination, DemonPositionPagination},
+    paginate::{DemonIdPagination, DemonPositionPagination, DemonPositionSortColumn, DemonSortColumn},
     patch::PatchDemon,
     post::PostDemon,
 };
 use crate::{
     cistring::{CiStr, CiString},
     error::PointercrateError,
-    model::demonlist::{player::DatabasePlayer, record::MinimalRecordP},
+    model::demonlist::{formula::ScoringFormula, player::DatabasePlayer, record::MinimalRecordP},
     Result,
 };
 use derive_more::Display;
@@ -16,6 +16,7 @@ use serde::Serialize;
 use sqlx::PgConnection;
 use std::hash::{Hash, Hasher};
 
+pub mod audit;
 mod get;
 mod paginate;
 mod patch;
@@ -32,6 +33,10 @@ pub struct Demon {
     /// accepted
     pub requirement: i16,
 
+    /// Whether only 100% records are accepted for this [`Demon`], regardless of its
+    /// [`requirement`](Demon::requirement)
+    pub completion_only: bool,
+
     pub fps: Option<String>,
 
     pub video: Option<String>,
@@ -90,13 +95,19 @@ impl Hash for FullDemon {
 }
 
 impl MinimalDemon {
-    /// Queries the record requirement for this demon from the database without collecting any of
-    /// the other data
+    /// Queries the minimal progress records on this demon need to have from the database without
+    /// collecting any of the other data
+    ///
+    /// This is the demon's record requirement, unless the demon is flagged as completion only, in
+    /// which case it is 100
     pub async fn requirement(&self, connection: &mut PgConnection) -> Result<i16> {
-        Ok(sqlx::query!("SELECT requirement FROM demons WHERE id = $1", self.id)
-            .fetch_one(connection)
-            .await?
-            .requirement)
+        Ok(sqlx::query!(
+            r#"SELECT CASE WHEN completion_only THEN 100 ELSE requirement END AS "requirement!: i16" FROM demons WHERE id = $1"#,
+            self.id
+        )
+        .fetch_one(connection)
+        .await?
+        .requirement)
     }
 }
 
@@ -158,6 +169,16 @@ impl FullDemon {
 }
 
 impl Demon {
+    /// The minimal progress records on this demon need to have, taking the completion only flag
+    /// into account
+    pub fn effective_requirement(&self) -> i16 {
+        if self.completion_only {
+            100
+        } else {
+            self.requirement
+        }
+    }
+
     pub fn validate_requirement(requirement: i16) -> Result<()> {
         if requirement < 0 || requirement > 100 {
             return Err(PointercrateError::InvalidRequirement)
@@ -209,48 +230,8 @@ impl Demon {
             .ok_or(PointercrateError::NotFound)
     }
 
-    /// Gets the maximal and minimal submitter id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_demon_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(id) AS "max_id!: i32", MIN(id) AS "min_id!: i32" FROM demons"#)
-            .fetch_one(connection)
-            .await?;
-        Ok((row.max_id, row.min_id))
-    }
-
-    pub fn score(&self, progress: i16) -> f64 {
-        //let beaten_score = 150f64 * f64::exp((1f64 - f64::from(self.base.position)) * (1f64 / 30f64).ln() / (-149f64));
-        //(5 + ((POWER(((0.08 * demon) + 6), 3)) * 1.15)) / 10
-        //let base: f64 = (-0.08 *  f64::from(self.base.position)) + 6f64;
-        //let beaten_score = (5f64 + (base.powf(3f64) * 1.180865));
-        /*let position = self.base.position;
-
-        let beaten_score = if 125 < position && position <= 150 {
-            150f64 * f64::exp((1f64 - f64::from(position)) * (1f64 / 30f64).ln() / (-149f64))
-        } else if 50 < position && position <= 125 {
-            let a = 2.333f64;
-            let b = 1.884f64;
-            60f64 * (a.powf((51 - position) as f64 * ((30f64.ln()) / 99f64))) + b
-        } else if 20 < position && position <= 50 {
-            let c = 1.01327f64;
-            let d = 26.489f64;
-            -100f64 * (c.powf(position as f64 - d)) + 200f64
-        } else if 0 < position && position <= 20 {
-            let e = 1.168f64;
-            let f = 100.39f64;
-            (250f64 - f) * (e.powf(1f64 - position as f64) as f64) + f
-        } else {
-            0f64
-        };*/
-
-        //let base = (250f64 / 100f64)
-        let beaten_score = 250f64 * f64::exp(f64::ln(250f64 / 15f64) / (1f64 - 75f64) * (f64::from(self.base.position) - 1f64));
-
-        // just incase :floshed:
-        // let base: f64 = (-0.055 * f64::from(self.base.position)) + 6f64;
-        // let beaten_score = (5f64 + (base.powf(3f64) * 1.16603));
-
-        beaten_score
+    /// The score a record with the given progress on this demon awards under the given formula
+    pub fn score(&self, formula: &ScoringFormula, progress: i16) -> f64 {
+        formula.score(self.base.position, progress, self.requirement)
     }
 }
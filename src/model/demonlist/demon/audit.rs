@@ -0,0 +1,154 @@
+use crate::{model::audit::NamedId, Result};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+#[derive(Serialize)]
+pub struct DemonModificationData {
+    name: Option<String>,
+    position: Option<i16>,
+    requirement: Option<i16>,
+    video: Option<String>,
+    verifier: Option<NamedId>,
+    publisher: Option<NamedId>,
+    completion_only: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct DemonEntry {
+    time: NaiveDateTime,
+    audit_id: i32,
+    demon_id: i32,
+    user: NamedId,
+    r#type: DemonEntryType,
+}
+
+#[derive(Serialize)]
+pub enum DemonEntryType {
+    Addition,
+    Modification(DemonModificationData),
+    CreatorAddition(NamedId),
+    CreatorDeletion(NamedId),
+}
+
+/// Gets all audit log entries for the given demon, including changes to its creator list, in
+/// chronological order
+pub async fn entries_for_demon(demon_id: i32, connection: &mut PgConnection) -> Result<Vec<DemonEntry>> {
+    let mut entries = Vec::new();
+
+    let addition_rows = sqlx::query!(
+        r#"SELECT time, audit_id, userid, members.name::TEXT AS "name?"
+           FROM demon_additions LEFT OUTER JOIN members ON members.member_id = userid WHERE id = $1"#,
+        demon_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for addition in addition_rows {
+        entries.push(DemonEntry {
+            time: addition.time,
+            audit_id: addition.audit_id,
+            demon_id,
+            user: NamedId {
+                name: addition.name,
+                id: addition.userid,
+            },
+            r#type: DemonEntryType::Addition,
+        });
+    }
+
+    let modification_rows = sqlx::query!(
+        r#"SELECT time,
+                  audit_id,
+                  userid,
+                  members.name::TEXT AS "username?",
+                  demon_modifications.name::TEXT,
+                  demon_modifications.position,
+                  demon_modifications.requirement,
+                  demon_modifications.video::TEXT,
+                  demon_modifications.verifier AS verifier_id,
+                  verifiers.name::TEXT AS verifier_name,
+                  demon_modifications.publisher AS publisher_id,
+                  publishers.name::TEXT AS publisher_name,
+                  demon_modifications.completion_only
+                  FROM demon_modifications
+                  LEFT OUTER JOIN members ON members.member_id = userid
+                  LEFT OUTER JOIN players AS verifiers ON verifiers.id = demon_modifications.verifier
+                  LEFT OUTER JOIN players AS publishers ON publishers.id = demon_modifications.publisher
+                  WHERE demon_modifications.id = $1"#,
+        demon_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for modification in modification_rows {
+        entries.push(DemonEntry {
+            time: modification.time,
+            audit_id: modification.audit_id,
+            demon_id,
+            user: NamedId {
+                name: modification.username,
+                id: modification.userid,
+            },
+            r#type: DemonEntryType::Modification(DemonModificationData {
+                name: modification.name,
+                position: modification.position,
+                requirement: modification.requirement,
+                video: modification.video,
+                verifier: modification.verifier_id.map(|id| {
+                    NamedId {
+                        id,
+                        name: modification.verifier_name,
+                    }
+                }),
+                publisher: modification.publisher_id.map(|id| {
+                    NamedId {
+                        id,
+                        name: modification.publisher_name,
+                    }
+                }),
+                completion_only: modification.completion_only,
+            }),
+        })
+    }
+
+    let creator_rows = sqlx::query!(
+        r#"SELECT time AS "time!", audit_id AS "audit_id!", userid AS "userid!", members.name::TEXT AS "username?", creator AS "creator!",
+                  players.name::TEXT AS "creator_name?", added AS "added!: bool"
+           FROM (SELECT time, audit_id, userid, creator, demon, TRUE AS added FROM creator_additions
+                 UNION ALL
+                 SELECT time, audit_id, userid, creator, demon, FALSE AS added FROM creator_deletions) AS creator_changes
+           LEFT OUTER JOIN members ON members.member_id = userid
+           LEFT OUTER JOIN players ON players.id = creator
+           WHERE demon = $1"#,
+        demon_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for change in creator_rows {
+        let creator = NamedId {
+            id: change.creator,
+            name: change.creator_name,
+        };
+
+        entries.push(DemonEntry {
+            time: change.time,
+            audit_id: change.audit_id,
+            demon_id,
+            user: NamedId {
+                name: change.username,
+                id: change.userid,
+            },
+            r#type: if change.added {
+                DemonEntryType::CreatorAddition(creator)
+            } else {
+                DemonEntryType::CreatorDeletion(creator)
+            },
+        })
+    }
+
+    entries.sort_by_key(|entry| entry.audit_id);
+
+    Ok(entries)
+}
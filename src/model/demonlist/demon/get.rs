@@ -184,7 +184,8 @@ struct FetchedDemon {
     verifier_banned: bool,
     level_id: Option<i64>,
     fps: Option<String>,
-    hidden: bool
+    hidden: bool,
+    completion_only: bool,
 }
 
 impl Into<Demon> for FetchedDemon {
@@ -209,7 +210,8 @@ impl Into<Demon> for FetchedDemon {
             },
             level_id: self.level_id.map(|id| id as u64),
             fps: self.fps,
-            hidden: self.hidden
+            hidden: self.hidden,
+            completion_only: self.completion_only,
         }
     }
 }
@@ -1,30 +1,60 @@
 use crate::{
     cistring::CiString,
-    error::PointercrateError,
     model::demonlist::{
         demon::{Demon, MinimalDemon},
         player::DatabasePlayer,
     },
+    pagination::{Page, PaginationParameters, SortColumn},
     util::non_nullable,
     Result,
 };
 use futures::stream::StreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgConnection, Row};
+use sqlx::{postgres::PgArguments, query::Query, PgConnection, Postgres, Row};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct DemonIdPagination {
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "before")]
-    pub before_id: Option<i32>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DemonSortColumn {
+    Id,
+    Position,
+    Name,
+}
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "after")]
-    pub after_id: Option<i32>,
+impl Default for DemonSortColumn {
+    fn default() -> Self {
+        DemonSortColumn::Id
+    }
+}
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub limit: Option<u8>,
+impl SortColumn for DemonSortColumn {
+    const ALL: &'static [Self] = &[DemonSortColumn::Id, DemonSortColumn::Position, DemonSortColumn::Name];
+
+    fn name(self) -> &'static str {
+        match self {
+            DemonSortColumn::Id => "id",
+            DemonSortColumn::Position => "position",
+            DemonSortColumn::Name => "name",
+        }
+    }
 
+    fn column(self) -> &'static str {
+        match self {
+            DemonSortColumn::Id => "demon_id",
+            DemonSortColumn::Position => "position",
+            DemonSortColumn::Name => "demon_name",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            DemonSortColumn::Id => "INTEGER",
+            DemonSortColumn::Position => "SMALLINT",
+            DemonSortColumn::Name => "TEXT",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemonIdPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     name: Option<CiString>,
 
@@ -54,51 +84,22 @@ pub struct DemonIdPagination {
 }
 
 impl DemonIdPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<Demon>> {
-        if let Some(limit) = self.limit {
-            if limit < 1 || limit > 100 {
-                return Err(PointercrateError::InvalidPaginationLimit)
-            }
-        }
-
-        if let (Some(after), Some(before)) = (self.before_id, self.after_id) {
-            if after < before {
-                return Err(PointercrateError::AfterSmallerBefore)
-            }
-        }
-
-        let order = if self.after_id.is_none() && self.before_id.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
+    pub async fn page(&self, parameters: &PaginationParameters<DemonSortColumn>, connection: &mut PgConnection) -> Result<Page<Demon>> {
+        parameters.validate()?;
 
-        let query = format!(include_str!("../../../../sql/paginate_demons_by_id.sql"), order);
+        let query = include_str!("../../../../sql/paginate_demons_by_id.sql");
+        let page_query = parameters.page_query(query);
 
         // FIXME(sqlx) once CITEXT is supported
-        let mut stream = sqlx::query(&query)
-            .bind(self.before_id)
-            .bind(self.after_id)
-            .bind(self.name.as_ref().map(|s| s.as_str()))
-            .bind(self.requirement)
-            .bind(self.requirement_lt)
-            .bind(self.requirement_gt)
-            .bind(self.verifier_id)
-            .bind(self.verifier_name.as_ref().map(|s| s.as_str()))
-            .bind(self.publisher_id)
-            .bind(self.publisher_name.as_ref().map(|s| s.as_str()))
-            .bind(self.name_contains.as_ref().map(|s| s.as_str()))
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
-
         let mut demons = Vec::new();
+        let mut stream = self.bind(parameters.bind_cursors(sqlx::query(&page_query))).fetch(&mut *connection);
 
         while let Some(row) = stream.next().await {
             let row = row?;
 
             let video: Option<String> = row.get("video");
 
-            demons.push(Demon {
+            let demon = Demon {
                 base: MinimalDemon {
                     id: row.get("demon_id"),
                     name: CiString(row.get("demon_name")),
@@ -118,27 +119,78 @@ impl DemonIdPagination {
                 },
                 level_id: row.get::<Option<i64>, _>("level_id").map(|id| id as u64),
                 fps: row.get("fps"),
-                hidden: row.get("hidden")
-            })
+                hidden: row.get("hidden"),
+                completion_only: row.get("completion_only"),
+            };
+
+            demons.push((parameters.cursor(&row)?, demon))
         }
 
-        Ok(demons)
+        drop(stream);
+
+        let mut page = Page::new(parameters, demons);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                self.bind(parameters.bind_no_cursors(sqlx::query(&count_query)))
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
+        }
+
+        Ok(page)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.name.as_ref().map(|s| s.as_str()))
+            .bind(self.requirement)
+            .bind(self.requirement_lt)
+            .bind(self.requirement_gt)
+            .bind(self.verifier_id)
+            .bind(self.verifier_name.as_ref().map(|s| s.as_str()))
+            .bind(self.publisher_id)
+            .bind(self.publisher_name.as_ref().map(|s| s.as_str()))
+            .bind(self.name_contains.as_ref().map(|s| s.as_str()))
+    }
+}
+
+/// The order of the listing of all demons on the list, which is always by position
+///
+/// Positions are unique among listed demons, so they double as the ID field of the listing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DemonPositionSortColumn {
+    Position,
+}
+
+impl Default for DemonPositionSortColumn {
+    fn default() -> Self {
+        DemonPositionSortColumn::Position
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct DemonPositionPagination {
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "before")]
-    pub before_position: Option<i16>,
+impl SortColumn for DemonPositionSortColumn {
+    const ALL: &'static [Self] = &[DemonPositionSortColumn::Position];
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "after")]
-    pub after_position: Option<i16>,
+    fn name(self) -> &'static str {
+        "position"
+    }
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub limit: Option<u8>,
+    fn column(self) -> &'static str {
+        "position"
+    }
 
+    // The column is also used to break ties, and cursors store those as INTEGER
+    fn sql_type(self) -> &'static str {
+        "INTEGER"
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemonPositionPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     name: Option<CiString>,
 
@@ -168,55 +220,28 @@ pub struct DemonPositionPagination {
 }
 
 impl DemonPositionPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<Demon>> {
-        if let Some(limit) = self.limit {
-            if limit < 1 || limit > 100 {
-                return Err(PointercrateError::InvalidPaginationLimit)
-            }
-        }
+    pub async fn page(
+        &self, parameters: &PaginationParameters<DemonPositionSortColumn>, connection: &mut PgConnection,
+    ) -> Result<Page<Demon>> {
+        parameters.validate()?;
 
-        if let (Some(after), Some(before)) = (self.before_position, self.after_position) {
-            if after < before {
-                return Err(PointercrateError::AfterSmallerBefore)
-            }
-        }
-
-        let order = if self.after_position.is_none() && self.before_position.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
-
-        let query = format!(include_str!("../../../../sql/paginate_demons_by_position.sql"), order);
+        let query = include_str!("../../../../sql/paginate_demons_by_position.sql");
+        let page_query = parameters.page_query(query);
 
         // FIXME(sqlx) once CITEXT is supported
-        let mut stream = sqlx::query(&query)
-            .bind(self.before_position)
-            .bind(self.after_position)
-            .bind(self.name.as_ref().map(|s| s.as_str()))
-            .bind(self.requirement)
-            .bind(self.requirement_lt)
-            .bind(self.requirement_gt)
-            .bind(self.verifier_id)
-            .bind(self.verifier_name.as_ref().map(|s| s.as_str()))
-            .bind(self.publisher_id)
-            .bind(self.publisher_name.as_ref().map(|s| s.as_str()))
-            .bind(self.name_contains.as_ref().map(|s| s.as_str()))
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
-
         let mut demons = Vec::new();
+        let mut stream = self.bind(parameters.bind_cursors(sqlx::query(&page_query))).fetch(&mut *connection);
 
         while let Some(row) = stream.next().await {
             let row = row?;
 
             let video: Option<String> = row.get("video");
 
-            demons.push(Demon {
+            let demon = Demon {
                 base: MinimalDemon {
                     id: row.get("demon_id"),
                     name: CiString(row.get("demon_name")),
-                    position: row.get("position"),
+                    position: row.get::<i32, _>("position") as i16,
                 },
                 requirement: row.get("requirement"),
                 video,
@@ -232,10 +257,41 @@ impl DemonPositionPagination {
                 },
                 level_id: row.get::<Option<i64>, _>("level_id").map(|id| id as u64),
                 fps: row.get("fps"),
-                hidden: row.get("hidden")
-            })
+                hidden: row.get("hidden"),
+                completion_only: row.get("completion_only"),
+            };
+
+            demons.push((parameters.cursor(&row)?, demon))
         }
 
-        Ok(demons)
+        drop(stream);
+
+        let mut page = Page::new(parameters, demons);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                self.bind(parameters.bind_no_cursors(sqlx::query(&count_query)))
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
+        }
+
+        Ok(page)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.name.as_ref().map(|s| s.as_str()))
+            .bind(self.requirement)
+            .bind(self.requirement_lt)
+            .bind(self.requirement_gt)
+            .bind(self.verifier_id)
+            .bind(self.verifier_name.as_ref().map(|s| s.as_str()))
+            .bind(self.publisher_id)
+            .bind(self.publisher_name.as_ref().map(|s| s.as_str()))
+            .bind(self.name_contains.as_ref().map(|s| s.as_str()))
     }
 }
@@ -2,7 +2,10 @@ use super::{Demon, FullDemon};
 use crate::{
     cistring::CiString,
     error::PointercrateError,
-    model::demonlist::{demon::MinimalDemon, player::DatabasePlayer},
+    model::{
+        demonlist::{demon::MinimalDemon, player::DatabasePlayer},
+        webhook::{self, Event},
+    },
     util::{non_nullable, nullable},
     Result,
 };
@@ -35,6 +38,9 @@ pub struct PatchDemon {
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub hidden: Option<bool>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub completion_only: Option<bool>,
 }
 
 impl FullDemon {
@@ -101,6 +107,10 @@ impl Demon {
             self.set_hidden(hidden, connection).await?;
         }
 
+        if let Some(completion_only) = patch.completion_only {
+            self.set_completion_only(completion_only, connection).await?;
+        }
+
         Ok(self)
     }
 
@@ -167,6 +177,20 @@ impl Demon {
         Ok(())
     }
 
+    /// Sets whether only 100% records are accepted for this demon
+    ///
+    /// Unlike raising the requirement, this does not delete existing records with less progress. It
+    /// only affects records submitted or edited afterwards
+    pub async fn set_completion_only(&mut self, completion_only: bool, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("UPDATE demons SET completion_only = $1 WHERE id = $2", completion_only, self.base.id)
+            .execute(connection)
+            .await?;
+
+        self.completion_only = completion_only;
+
+        Ok(())
+    }
+
     pub async fn set_video(&mut self, video: String, connection: &mut PgConnection) -> Result<()> {
         let video = crate::video::validate(&video)?;
 
@@ -258,14 +282,16 @@ impl MinimalDemon {
         debug!("Performing actual move to position {}", to);
 
         sqlx::query!("UPDATE demons SET position = $2 WHERE id = $1", self.id, to)
-            .execute(connection)
+            .execute(&mut *connection)
             .await?;
 
         info!("Moved demon {} from {} to {} successfully!", self, self.position, to);
 
+        let from = self.position;
+
         self.position = to;
 
-        Ok(())
+        webhook::dispatch(Event::demon_moved(self, from), connection).await
     }
 }
 
@@ -303,6 +329,32 @@ mod tests {
         assert_eq!(demon, demon_reloaded);
     }
 
+    #[actix_rt::test]
+    async fn test_set_completion_only() {
+        let mut connection = crate::test::test_setup().await;
+
+        let patch = PatchDemon {
+            completion_only: Some(true),
+            ..Default::default()
+        };
+
+        let demon = Demon::by_position(1, &mut connection).await.unwrap();
+
+        let demon = demon.apply_patch(patch, &mut connection).await;
+
+        assert!(demon.is_ok(), "{:?}", demon.unwrap_err());
+
+        let demon = demon.unwrap();
+
+        assert!(demon.completion_only);
+        assert_eq!(demon.effective_requirement(), 100);
+        assert_eq!(demon.base.requirement(&mut connection).await.unwrap(), 100);
+
+        let demon_reloaded = Demon::by_position(1, &mut connection).await.unwrap();
+
+        assert_eq!(demon, demon_reloaded);
+    }
+
     #[actix_rt::test]
     async fn test_change_record_verifier() {
         let mut connection = crate::test::test_setup().await;
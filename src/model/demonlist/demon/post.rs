@@ -68,7 +68,8 @@ impl FullDemon {
             publisher,
             verifier,
             level_id: None,
-            hidden: false
+            hidden: false,
+            completion_only: false,
         };
 
         let mut creators = Vec::new();
@@ -0,0 +1,166 @@
+//! Module containing the scoring formulas used to compute player scores
+//!
+//! A scoring formula is never modified after it has been created. Instead, a new formula is created
+//! and then activated, which deactivates the previously active one. The `id` of a formula therefore
+//! doubles as its version number.
+//!
+//! The actual computation happens twice: Once in the database (the `formula_score` SQL function,
+//! which powers the `players_with_score` view) and once in [`ScoringFormula::score`]. Both only
+//! use the parameters stored in the `scoring_formulas` table, so as long as the curves below are
+//! kept in sync with `formula_base_score`, API scores and the player ranking agree.
+//!
+//! Note that the database additionally multiplies the formula's result with the scoring weight of
+//! the [list section](super::section) the demon is in (see the `section_weight` SQL function).
+//! [`ScoringFormula::score`] does not know about sections, callers need to apply the weight
+//! themselves.
+
+pub use self::{post::PostScoringFormula, preview::PreviewEntry};
+use chrono::NaiveDateTime;
+use derive_more::Display;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::hash::{Hash, Hasher};
+
+mod get;
+mod patch;
+mod post;
+mod preview;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum ScoringCurve {
+    /// `max_score * (min_score / max_score) ^ ((position - 1) / (list_size - 1))`
+    Exponential,
+
+    /// The piecewise curve from February 2021. Its constants are fixed, only `list_size` and the
+    /// requirement handling apply to it
+    Piecewise,
+}
+
+impl ScoringCurve {
+    fn to_sql(&self) -> String {
+        match self {
+            ScoringCurve::Exponential => "EXPONENTIAL",
+            ScoringCurve::Piecewise => "PIECEWISE",
+        }
+        .to_owned()
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "EXPONENTIAL" => ScoringCurve::Exponential,
+            "PIECEWISE" => ScoringCurve::Piecewise,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Serialize for ScoringCurve {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_sql().to_lowercase())
+    }
+}
+
+impl<'de> Deserialize<'de> for ScoringCurve {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?.to_lowercase();
+
+        match &string[..] {
+            "exponential" => Ok(ScoringCurve::Exponential),
+            "piecewise" => Ok(ScoringCurve::Piecewise),
+            _ =>
+                Err(serde::de::Error::invalid_value(
+                    serde::de::Unexpected::Str(&string),
+                    &"'exponential' or 'piecewise'",
+                )),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Display, Clone)]
+#[display(fmt = "{} (version {})", name, id)]
+pub struct ScoringFormula {
+    /// The formula's ID, which doubles as its version number
+    pub id: i32,
+    pub name: String,
+    pub curve: ScoringCurve,
+
+    /// The score awarded for a completion of the demon at position 1
+    pub max_score: f64,
+
+    /// The score awarded for a completion of the demon at position `list_size`
+    pub min_score: f64,
+
+    /// Demons placed below this position do not award any points
+    pub list_size: i16,
+
+    /// Demons placed below this position only award points for 100% records. If [`None`], the
+    /// demon's record requirement always applies
+    pub full_completion_after: Option<i16>,
+
+    pub progress_base: f64,
+    pub progress_divisor: f64,
+
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+    pub activated_at: Option<NaiveDateTime>,
+}
+
+// f64 doesn't implement Hash, but the formula is immutable apart from its activation status anyway
+impl Hash for ScoringFormula {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.active.hash(state);
+    }
+}
+
+impl ScoringFormula {
+    /// The score awarded for a 100% record on the demon at the given position
+    fn base_score(&self, position: i16) -> f64 {
+        let position = f64::from(position);
+
+        match self.curve {
+            ScoringCurve::Exponential =>
+                self.max_score * f64::exp(f64::ln(self.max_score / self.min_score) / (1f64 - f64::from(self.list_size)) * (position - 1f64)),
+            ScoringCurve::Piecewise =>
+                if 125f64 < position {
+                    150f64 * f64::exp((1f64 - position) * (1f64 / 30f64).ln() / -149f64)
+                } else if 50f64 < position {
+                    60f64 * f64::exp(2.333f64.ln() * ((51f64 - position) * (30f64.ln() / 99f64))) + 1.884f64
+                } else if 20f64 < position {
+                    -100f64 * f64::exp(1.01327f64.ln() * (position - 26.489f64)) + 200f64
+                } else {
+                    (250f64 - 100.39f64) * f64::exp(1.168f64.ln() * (1f64 - position)) + 100.39f64
+                },
+        }
+    }
+
+    /// Computes the score a record with the given progress on the demon at the given position (and
+    /// with the given requirement) awards.
+    ///
+    /// This mirrors the `formula_score` SQL function
+    pub fn score(&self, position: i16, progress: i16, requirement: i16) -> f64 {
+        if position > self.list_size {
+            return 0f64
+        }
+
+        if let Some(full_completion_after) = self.full_completion_after {
+            if position > full_completion_after && progress < 100 {
+                return 0f64
+            }
+        }
+
+        if progress == 100 {
+            self.base_score(position)
+        } else if progress < requirement {
+            0f64
+        } else {
+            self.base_score(position) * f64::exp(self.progress_base.ln() * f64::from(progress - requirement) / f64::from(100 - requirement))
+                / self.progress_divisor
+        }
+    }
+}
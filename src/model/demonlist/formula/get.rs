@@ -0,0 +1,89 @@
+use crate::{
+    error::PointercrateError,
+    model::demonlist::formula::{ScoringCurve, ScoringFormula},
+    Result,
+};
+use futures::StreamExt;
+use sqlx::{Error, PgConnection};
+
+impl ScoringFormula {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<ScoringFormula> {
+        let row = sqlx::query!(
+            r#"SELECT id, name, curve::text AS "curve!: String", max_score, min_score, list_size, full_completion_after, progress_base,
+             progress_divisor, active, created_at, activated_at FROM scoring_formulas WHERE id = $1"#,
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match row {
+            Ok(row) =>
+                Ok(ScoringFormula {
+                    id: row.id,
+                    name: row.name,
+                    curve: ScoringCurve::from_sql(&row.curve),
+                    max_score: row.max_score,
+                    min_score: row.min_score,
+                    list_size: row.list_size,
+                    full_completion_after: row.full_completion_after,
+                    progress_base: row.progress_base,
+                    progress_divisor: row.progress_divisor,
+                    active: row.active,
+                    created_at: row.created_at,
+                    activated_at: row.activated_at,
+                }),
+            Err(Error::RowNotFound) =>
+                Err(PointercrateError::ModelNotFound {
+                    model: "ScoringFormula",
+                    identified_by: id.to_string(),
+                }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets the currently active scoring formula
+    ///
+    /// The migration that introduced scoring formulas activates a formula, and activating a formula
+    /// always happens in a transaction that deactivates the previous one, so there is always exactly
+    /// one active formula.
+    pub async fn active(connection: &mut PgConnection) -> Result<ScoringFormula> {
+        let id = sqlx::query!("SELECT id FROM scoring_formulas WHERE active")
+            .fetch_one(&mut *connection)
+            .await?
+            .id;
+
+        ScoringFormula::by_id(id, connection).await
+    }
+
+    /// Gets all scoring formulas, most recent first
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<ScoringFormula>> {
+        let mut stream = sqlx::query!(
+            r#"SELECT id, name, curve::text AS "curve!: String", max_score, min_score, list_size, full_completion_after, progress_base,
+             progress_divisor, active, created_at, activated_at FROM scoring_formulas ORDER BY id DESC"#
+        )
+        .fetch(connection);
+
+        let mut formulas = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            formulas.push(ScoringFormula {
+                id: row.id,
+                name: row.name,
+                curve: ScoringCurve::from_sql(&row.curve),
+                max_score: row.max_score,
+                min_score: row.min_score,
+                list_size: row.list_size,
+                full_completion_after: row.full_completion_after,
+                progress_base: row.progress_base,
+                progress_divisor: row.progress_divisor,
+                active: row.active,
+                created_at: row.created_at,
+                activated_at: row.activated_at,
+            })
+        }
+
+        Ok(formulas)
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{model::demonlist::formula::ScoringFormula, Result};
+use log::info;
+use sqlx::PgConnection;
+
+impl ScoringFormula {
+    /// Makes this formula the one used for the player ranking and deactivates the previously active
+    /// one.
+    ///
+    /// Must be run within a transaction!
+    pub async fn activate(mut self, connection: &mut PgConnection) -> Result<ScoringFormula> {
+        if self.active {
+            return Ok(self)
+        }
+
+        info!("Activating scoring formula {}", self);
+
+        // Has to happen first because of the unique index on the active column
+        sqlx::query!("UPDATE scoring_formulas SET active = FALSE WHERE active")
+            .execute(&mut *connection)
+            .await?;
+
+        let activated_at = sqlx::query!(
+            "UPDATE scoring_formulas SET active = TRUE, activated_at = (NOW() AT TIME ZONE 'utc') WHERE id = $1 RETURNING activated_at",
+            self.id
+        )
+        .fetch_one(connection)
+        .await?
+        .activated_at;
+
+        self.active = true;
+        self.activated_at = activated_at;
+
+        Ok(self)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{
+    error::PointercrateError,
+    model::demonlist::formula::{ScoringCurve, ScoringFormula},
+    Result,
+};
+use log::info;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Deserialize, Debug)]
+pub struct PostScoringFormula {
+    name: String,
+    curve: ScoringCurve,
+    list_size: i16,
+
+    #[serde(default)]
+    full_completion_after: Option<i16>,
+
+    #[serde(default = "default_max_score")]
+    max_score: f64,
+
+    #[serde(default = "default_min_score")]
+    min_score: f64,
+
+    #[serde(default = "default_progress_base")]
+    progress_base: f64,
+
+    #[serde(default = "default_progress_divisor")]
+    progress_divisor: f64,
+}
+
+fn default_max_score() -> f64 {
+    250.0
+}
+
+fn default_min_score() -> f64 {
+    15.0
+}
+
+fn default_progress_base() -> f64 {
+    5.0
+}
+
+fn default_progress_divisor() -> f64 {
+    10.0
+}
+
+impl PostScoringFormula {
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(PointercrateError::InvalidFormulaParameter {
+                parameter: "name",
+                reason: "must not be empty",
+            })
+        }
+
+        if self.list_size < 2 {
+            return Err(PointercrateError::InvalidFormulaParameter {
+                parameter: "list_size",
+                reason: "must be at least 2",
+            })
+        }
+
+        if let Some(full_completion_after) = self.full_completion_after {
+            if full_completion_after < 1 || full_completion_after > self.list_size {
+                return Err(PointercrateError::InvalidFormulaParameter {
+                    parameter: "full_completion_after",
+                    reason: "must lie between 1 and list_size",
+                })
+            }
+        }
+
+        if !self.min_score.is_finite() || self.min_score <= 0.0 {
+            return Err(PointercrateError::InvalidFormulaParameter {
+                parameter: "min_score",
+                reason: "must be a positive number",
+            })
+        }
+
+        if !self.max_score.is_finite() || self.max_score < self.min_score {
+            return Err(PointercrateError::InvalidFormulaParameter {
+                parameter: "max_score",
+                reason: "must be greater than or equal to min_score",
+            })
+        }
+
+        if !self.progress_base.is_finite() || self.progress_base <= 0.0 {
+            return Err(PointercrateError::InvalidFormulaParameter {
+                parameter: "progress_base",
+                reason: "must be a positive number",
+            })
+        }
+
+        if !self.progress_divisor.is_finite() || self.progress_divisor <= 0.0 {
+            return Err(PointercrateError::InvalidFormulaParameter {
+                parameter: "progress_divisor",
+                reason: "must be a positive number",
+            })
+        }
+
+        Ok(())
+    }
+}
+
+impl ScoringFormula {
+    /// Creates a new, inactive scoring formula. Activating it is a separate step, so that its
+    /// effects on the ranking can be previewed first.
+    pub async fn create_from(data: PostScoringFormula, connection: &mut PgConnection) -> Result<ScoringFormula> {
+        info!("Creating new scoring formula from {:?}", data);
+
+        data.validate()?;
+
+        let row = sqlx::query!(
+            "INSERT INTO scoring_formulas (name, curve, max_score, min_score, list_size, full_completion_after, progress_base, \
+             progress_divisor) VALUES ($1, cast($2::text as scoring_curve), $3, $4, $5, $6, $7, $8) RETURNING id, created_at",
+            data.name.trim(),
+            data.curve.to_sql(),
+            data.max_score,
+            data.min_score,
+            data.list_size,
+            data.full_completion_after,
+            data.progress_base,
+            data.progress_divisor
+        )
+        .fetch_one(connection)
+        .await?;
+
+        Ok(ScoringFormula {
+            id: row.id,
+            name: data.name.trim().to_string(),
+            curve: data.curve,
+            max_score: data.max_score,
+            min_score: data.min_score,
+            list_size: data.list_size,
+            full_completion_after: data.full_completion_after,
+            progress_base: data.progress_base,
+            progress_divisor: data.progress_divisor,
+            active: false,
+            created_at: row.created_at,
+            activated_at: None,
+        })
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    cistring::CiString,
+    model::{demonlist::formula::ScoringFormula, nationality::Nationality},
+    Result,
+};
+use futures::StreamExt;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+/// A player's position in the ranking under the active formula, compared to their position under
+/// some other formula
+#[derive(Debug, Serialize)]
+pub struct PreviewEntry {
+    pub id: i32,
+    pub name: CiString,
+    pub nationality: Option<Nationality>,
+
+    /// The rank under the previewed formula
+    pub rank: i64,
+
+    /// The score under the previewed formula
+    pub score: f64,
+
+    /// The rank under the currently active formula, or [`None`] if the player is not ranked under
+    /// it
+    pub current_rank: Option<i64>,
+
+    /// The score under the currently active formula
+    pub current_score: Option<f64>,
+}
+
+impl ScoringFormula {
+    /// Computes what the top `limit` players of the ranking would look like if this formula was
+    /// activated
+    pub async fn preview(&self, limit: i64, connection: &mut PgConnection) -> Result<Vec<PreviewEntry>> {
+        let mut stream = sqlx::query!(
+            r#"SELECT candidate.id AS "id!", candidate.name::text AS "name!: String", candidate.rank AS "rank!", candidate.score AS "score!",
+             candidate.iso_country_code::text AS "iso_country_code?: String", candidate.nation::text AS "nation?: String",
+             current.rank AS "current_rank?", current.score AS "current_score?"
+             FROM player_ranking($1) AS candidate
+             LEFT OUTER JOIN players_with_score AS current ON current.id = candidate.id
+             ORDER BY candidate.index
+             LIMIT $2"#,
+            self.id,
+            limit
+        )
+        .fetch(connection);
+
+        let mut entries = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            let nationality = match (row.iso_country_code, row.nation) {
+                (Some(country_code), Some(nation)) =>
+                    Some(Nationality {
+                        iso_country_code: country_code,
+                        nation: CiString(nation),
+                    }),
+                _ => None,
+            };
+
+            entries.push(PreviewEntry {
+                id: row.id,
+                name: CiString(row.name),
+                nationality,
+                rank: row.rank,
+                score: row.score,
+                current_rank: row.current_rank,
+                current_score: row.current_score,
+            })
+        }
+
+        Ok(entries)
+    }
+}
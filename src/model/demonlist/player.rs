@@ -1,5 +1,7 @@
 pub use self::{
-    paginate::{PlayerPagination, RankingPagination},
+    claim::{PatchPlayerClaim, PlayerClaim, PlayerClaimPagination, PlayerClaimSortColumn},
+    history::{rank_history, HistoricalRankingPagination, RankHistoryEntry, RankingSnapshot},
+    paginate::{PlayerPagination, PlayerSortColumn, RankingPagination, RankingSortColumn},
     patch::PatchPlayer,
 };
 use crate::{
@@ -8,14 +10,15 @@ use crate::{
         demonlist::{demon::MinimalDemon, record::MinimalRecordD},
         nationality::Nationality,
     },
-    Result,
 };
 use derive_more::Display;
 use serde::Serialize;
-use sqlx::PgConnection;
 use std::hash::{Hash, Hasher};
 
+pub mod audit;
+mod claim;
 mod get;
+mod history;
 mod paginate;
 mod patch;
 
@@ -27,7 +30,7 @@ pub struct DatabasePlayer {
     pub banned: bool,
 }
 
-#[derive(Debug, Serialize, Display, PartialEq, Eq)]
+#[derive(Debug, Serialize, Display, PartialEq)]
 #[display(fmt = "{}", player)]
 pub struct FullPlayer {
     #[serde(flatten)]
@@ -36,6 +39,12 @@ pub struct FullPlayer {
     pub created: Vec<MinimalDemon>,
     pub verified: Vec<MinimalDemon>,
     pub published: Vec<MinimalDemon>,
+
+    /// The player's rank in each ranking snapshot they appear in, oldest first
+    pub rank_history: Vec<RankHistoryEntry>,
+
+    /// Whether some user has verified ownership of this player
+    pub claimed: bool,
 }
 
 #[derive(Debug, PartialEq, Serialize, Display)]
@@ -57,32 +66,15 @@ pub struct Player {
     pub base: DatabasePlayer,
 
     pub nationality: Option<Nationality>,
+
+    /// Can only be set by whoever claimed this player
+    pub youtube_channel: Option<String>,
 }
 
 impl Hash for FullPlayer {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.player.hash(state)
-    }
-}
-
-impl RankedPlayer {
-    /// Gets the highest index value generated by the `players_with_score` view
-    pub async fn max_index(connection: &mut PgConnection) -> Result<i64> {
-        Ok(sqlx::query!(r#"SELECT MAX(index) AS "max_index!: i64" FROM players_with_score"#)
-            .fetch_one(connection)
-            .await?
-            .max_index)
+        self.player.hash(state);
+        self.claimed.hash(state)
     }
 }
 
-impl Player {
-    /// Gets the maximal and minimal player id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_player_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(id) AS "max_id!: i32", MIN(id) AS "min_id!: i32" FROM players"#)
-            .fetch_one(connection)
-            .await?;
-        Ok((row.max_id, row.min_id))
-    }
-}
@@ -0,0 +1,104 @@
+use crate::{model::audit::NamedId, Result};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+#[derive(Serialize)]
+pub struct PlayerModificationData {
+    name: Option<String>,
+    banned: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct PlayerEntry {
+    time: NaiveDateTime,
+    audit_id: i32,
+    player_id: i32,
+    user: NamedId,
+    r#type: PlayerEntryType,
+}
+
+#[derive(Serialize)]
+pub enum PlayerEntryType {
+    Addition,
+    Modification(PlayerModificationData),
+    Deletion,
+}
+
+/// Gets all audit log entries for the given player, in chronological order
+///
+/// Players are deleted when they get merged into another player, so the log of a player that no
+/// longer exists can still be retrieved.
+pub async fn entries_for_player(player_id: i32, connection: &mut PgConnection) -> Result<Vec<PlayerEntry>> {
+    let mut entries = Vec::new();
+
+    let addition_rows = sqlx::query!(
+        r#"SELECT time, audit_id, userid, members.name::TEXT AS "name?"
+           FROM player_additions LEFT OUTER JOIN members ON members.member_id = userid WHERE id = $1"#,
+        player_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for addition in addition_rows {
+        entries.push(PlayerEntry {
+            time: addition.time,
+            audit_id: addition.audit_id,
+            player_id,
+            user: NamedId {
+                name: addition.name,
+                id: addition.userid,
+            },
+            r#type: PlayerEntryType::Addition,
+        });
+    }
+
+    let modification_rows = sqlx::query!(
+        r#"SELECT time, audit_id, userid, members.name::TEXT AS "username?", player_modifications.name::TEXT, player_modifications.banned
+           FROM player_modifications LEFT OUTER JOIN members ON members.member_id = userid WHERE id = $1"#,
+        player_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for modification in modification_rows {
+        entries.push(PlayerEntry {
+            time: modification.time,
+            audit_id: modification.audit_id,
+            player_id,
+            user: NamedId {
+                name: modification.username,
+                id: modification.userid,
+            },
+            r#type: PlayerEntryType::Modification(PlayerModificationData {
+                name: modification.name,
+                banned: modification.banned,
+            }),
+        })
+    }
+
+    let deletion_rows = sqlx::query!(
+        r#"SELECT time, audit_id, userid, members.name::TEXT AS "name?"
+           FROM player_deletions LEFT OUTER JOIN members ON members.member_id = userid WHERE id = $1"#,
+        player_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for deletion in deletion_rows {
+        entries.push(PlayerEntry {
+            time: deletion.time,
+            audit_id: deletion.audit_id,
+            player_id,
+            user: NamedId {
+                name: deletion.name,
+                id: deletion.userid,
+            },
+            r#type: PlayerEntryType::Deletion,
+        });
+    }
+
+    entries.sort_by_key(|entry| entry.audit_id);
+
+    Ok(entries)
+}
@@ -0,0 +1,334 @@
+//! Module containing the claims users can put on players to link them to their account
+//!
+//! Any user can request ownership of a single player. Until a list moderator verifies the claim,
+//! it has no effect. Once verified, the user can change some of the player's details (see
+//! [`PatchPlayer::require_claimant_safe`](super::PatchPlayer::require_claimant_safe)) themselves,
+//! and the player is marked as claimed in the stats viewer. Every player can only be claimed by a
+//! single user.
+
+use crate::{
+    cistring::CiString,
+    error::PointercrateError,
+    model::{audit::NamedId, demonlist::player::DatabasePlayer, user::User},
+    pagination::{Page, PaginationParameters, SortColumn},
+    util::non_nullable,
+    Result,
+};
+use chrono::NaiveDateTime;
+use futures::StreamExt;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+#[derive(Debug, Serialize, Hash)]
+pub struct PlayerClaim {
+    pub id: i32,
+    pub member: NamedId,
+    pub player: DatabasePlayer,
+    pub verified: bool,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchPlayerClaim {
+    #[serde(default, deserialize_with = "non_nullable")]
+    verified: Option<bool>,
+}
+
+// Required until https://github.com/launchbadge/sqlx/pull/108 is merged
+struct FetchedClaim {
+    id: i32,
+    member: i32,
+    member_name: String,
+    player: i32,
+    player_name: String,
+    banned: bool,
+    verified: bool,
+    created_at: NaiveDateTime,
+}
+
+impl From<FetchedClaim> for PlayerClaim {
+    fn from(row: FetchedClaim) -> Self {
+        PlayerClaim {
+            id: row.id,
+            member: NamedId {
+                id: row.member,
+                name: Some(row.member_name),
+            },
+            player: DatabasePlayer {
+                id: row.player,
+                name: CiString(row.player_name),
+                banned: row.banned,
+            },
+            verified: row.verified,
+            created_at: row.created_at,
+        }
+    }
+}
+
+impl PlayerClaim {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<PlayerClaim> {
+        sqlx::query_as!(
+            FetchedClaim,
+            r#"SELECT player_claims.id, member, members.name AS member_name, player, players.name AS "player_name: String", banned,
+             verified, created_at FROM player_claims INNER JOIN members ON member = member_id INNER JOIN players ON player = players.id
+             WHERE player_claims.id = $1"#,
+            id
+        )
+        .fetch_optional(connection)
+        .await?
+        .map(PlayerClaim::from)
+        .ok_or_else(|| {
+            PointercrateError::ModelNotFound {
+                model: "PlayerClaim",
+                identified_by: id.to_string(),
+            }
+        })
+    }
+
+    /// Gets the claim the given user holds, if any, regardless of whether it has been verified yet
+    pub async fn of_member(member_id: i32, connection: &mut PgConnection) -> Result<Option<PlayerClaim>> {
+        Ok(sqlx::query_as!(
+            FetchedClaim,
+            r#"SELECT player_claims.id, member, members.name AS member_name, player, players.name AS "player_name: String", banned,
+             verified, created_at FROM player_claims INNER JOIN members ON member = member_id INNER JOIN players ON player = players.id
+             WHERE member = $1"#,
+            member_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .map(PlayerClaim::from))
+    }
+
+    /// Gets the ID of the user whose claim on the given player has been verified, if any
+    pub async fn claimant_of(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Option<i32>> {
+        Ok(
+            sqlx::query!("SELECT member FROM player_claims WHERE player = $1 AND verified", player.id)
+                .fetch_optional(connection)
+                .await?
+                .map(|row| row.member),
+        )
+    }
+
+    /// Requests ownership of the given player for the given user
+    ///
+    /// Fails if the user already holds a claim, or if someone else's claim on the player has already
+    /// been verified
+    pub async fn create(user: &User, player: DatabasePlayer, connection: &mut PgConnection) -> Result<PlayerClaim> {
+        if player.banned {
+            return Err(PointercrateError::PlayerBanned)
+        }
+
+        if let Some(existing) = PlayerClaim::of_member(user.id, &mut *connection).await? {
+            return Err(PointercrateError::ClaimExists { player: existing.player })
+        }
+
+        if PlayerClaim::claimant_of(&player, &mut *connection).await?.is_some() {
+            return Err(PointercrateError::PlayerClaimed { player })
+        }
+
+        info!("{} is claiming player {}", user, player);
+
+        let row = sqlx::query!(
+            "INSERT INTO player_claims (member, player) VALUES ($1, $2) RETURNING id, created_at",
+            user.id,
+            player.id
+        )
+        .fetch_one(connection)
+        .await?;
+
+        Ok(PlayerClaim {
+            id: row.id,
+            member: NamedId {
+                id: user.id,
+                name: Some(user.name.clone()),
+            },
+            player,
+            verified: false,
+            created_at: row.created_at,
+        })
+    }
+
+    pub async fn apply_patch(mut self, patch: PatchPlayerClaim, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(verified) = patch.verified {
+            self.set_verified(verified, connection).await?;
+        }
+
+        Ok(self)
+    }
+
+    pub async fn set_verified(&mut self, verified: bool, connection: &mut PgConnection) -> Result<()> {
+        if verified {
+            match PlayerClaim::claimant_of(&self.player, &mut *connection).await? {
+                Some(claimant) if claimant != self.member.id =>
+                    return Err(PointercrateError::PlayerClaimed {
+                        player: self.player.clone(),
+                    }),
+                _ => (),
+            }
+        }
+
+        info!("Setting verification status of claim {} on {} to {}", self.id, self.player, verified);
+
+        sqlx::query!("UPDATE player_claims SET verified = $1 WHERE id = $2", verified, self.id)
+            .execute(connection)
+            .await?;
+
+        self.verified = verified;
+
+        Ok(())
+    }
+
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        info!("Deleting claim {} of {:?} on {}", self.id, self.member, self.player);
+
+        sqlx::query!("DELETE FROM player_claims WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayerClaimSortColumn {
+    Id,
+}
+
+impl Default for PlayerClaimSortColumn {
+    fn default() -> Self {
+        PlayerClaimSortColumn::Id
+    }
+}
+
+impl SortColumn for PlayerClaimSortColumn {
+    const ALL: &'static [Self] = &[PlayerClaimSortColumn::Id];
+
+    fn name(self) -> &'static str {
+        "id"
+    }
+
+    fn column(self) -> &'static str {
+        "id"
+    }
+
+    fn sql_type(self) -> &'static str {
+        "INTEGER"
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+pub struct PlayerClaimPagination {
+    #[serde(default, deserialize_with = "non_nullable")]
+    player: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    member: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    verified: Option<bool>,
+}
+
+impl PlayerClaimPagination {
+    pub async fn page(
+        &self, parameters: &PaginationParameters<PlayerClaimSortColumn>, connection: &mut PgConnection,
+    ) -> Result<Page<PlayerClaim>> {
+        parameters.validate()?;
+
+        let query = "SELECT player_claims.id, member, members.name AS member_name, player, players.name::TEXT AS player_name, banned, \
+                     verified, created_at FROM player_claims INNER JOIN members ON member = member_id INNER JOIN players ON player = \
+                     players.id WHERE (player = $5 OR $5 IS NULL) AND (member = $6 OR $6 IS NULL) AND (verified = $7 OR $7 IS NULL)";
+        let page_query = parameters.page_query(query);
+
+        let mut claims = Vec::new();
+        let mut stream = parameters
+            .bind_cursors(sqlx::query(&page_query))
+            .bind(self.player)
+            .bind(self.member)
+            .bind(self.verified)
+            .fetch(&mut *connection);
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            let claim = PlayerClaim {
+                id: row.get("id"),
+                member: NamedId {
+                    id: row.get("member"),
+                    name: row.get("member_name"),
+                },
+                player: DatabasePlayer {
+                    id: row.get("player"),
+                    name: CiString(row.get("player_name")),
+                    banned: row.get("banned"),
+                },
+                verified: row.get("verified"),
+                created_at: row.get("created_at"),
+            };
+
+            claims.push((parameters.cursor(&row)?, claim))
+        }
+
+        drop(stream);
+
+        let mut page = Page::new(parameters, claims);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                parameters
+                    .bind_no_cursors(sqlx::query(&count_query))
+                    .bind(self.player)
+                    .bind(self.member)
+                    .bind(self.verified)
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
+        }
+
+        Ok(page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cistring::CiStr,
+        error::PointercrateError,
+        model::{
+            demonlist::player::{DatabasePlayer, PlayerClaim},
+            user::User,
+        },
+    };
+
+    #[actix_rt::test]
+    async fn test_claim_player() {
+        let mut connection = crate::test::test_setup().await;
+
+        let user = User::by_name("stadust_existing", &mut connection).await.unwrap();
+        let player = DatabasePlayer::by_name(CiStr::from_str("stardust1971"), &mut connection)
+            .await
+            .unwrap();
+
+        let mut claim = PlayerClaim::create(&user, player.clone(), &mut connection).await.unwrap();
+
+        // Claims only take effect once verified by a moderator
+        assert_eq!(PlayerClaim::claimant_of(&player, &mut connection).await.unwrap(), None);
+
+        match PlayerClaim::create(&user, player.clone(), &mut connection).await {
+            Err(PointercrateError::ClaimExists { player: existing }) => assert_eq!(existing, player),
+            result => panic!("Expected ClaimExists error, got {:?}", result),
+        }
+
+        claim.set_verified(true, &mut connection).await.unwrap();
+
+        assert_eq!(PlayerClaim::claimant_of(&player, &mut connection).await.unwrap(), Some(user.id));
+        assert!(PlayerClaim::by_id(claim.id, &mut connection).await.unwrap().verified);
+
+        claim.delete(&mut connection).await.unwrap();
+
+        assert_eq!(PlayerClaim::claimant_of(&player, &mut connection).await.unwrap(), None);
+    }
+}
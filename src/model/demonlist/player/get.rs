@@ -5,7 +5,7 @@ use crate::{
         demonlist::{
             creator::created_by,
             demon::{published_by, verified_by},
-            player::{DatabasePlayer, FullPlayer, Player},
+            player::{rank_history, DatabasePlayer, FullPlayer, Player, PlayerClaim},
             record::approved_records_by,
         },
         nationality::Nationality,
@@ -21,6 +21,7 @@ struct FetchedPlayer {
     banned: bool,
     nation: Option<String>,
     iso_country_code: Option<String>,
+    youtube_channel: Option<String>,
 }
 
 impl Player {
@@ -29,6 +30,8 @@ impl Player {
         let published = published_by(&self.base, connection).await?;
         let verified = verified_by(&self.base, connection).await?;
         let created = created_by(self.base.id, connection).await?;
+        let rank_history = rank_history(&self.base, connection).await?;
+        let claimed = PlayerClaim::claimant_of(&self.base, connection).await?.is_some();
 
         Ok(FullPlayer {
             player: self,
@@ -36,14 +39,16 @@ impl Player {
             created,
             verified,
             published,
+            rank_history,
+            claimed,
         })
     }
 
     pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Player> {
         let result = sqlx::query_as!(
             FetchedPlayer,
-            r#"SELECT id, name AS "name: String", banned, nation::text, iso_country_code::text FROM players LEFT OUTER JOIN nationalities ON 
-             players.nationality = nationalities.iso_country_code WHERE id = $1"#,
+            r#"SELECT id, name AS "name: String", banned, nation::text, iso_country_code::text, youtube_channel::text FROM players
+             LEFT OUTER JOIN nationalities ON players.nationality = nationalities.iso_country_code WHERE id = $1"#,
             id
         )
         .fetch_one(connection)
@@ -66,6 +71,7 @@ impl Player {
                         banned: row.banned,
                     },
                     nationality,
+                    youtube_channel: row.youtube_channel,
                 })
             },
             Err(Error::RowNotFound) =>
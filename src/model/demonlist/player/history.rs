@@ -0,0 +1,239 @@
+//! Module for the persisted snapshots of the player ranking
+//!
+//! Snapshots are taken periodically by [`crate::scheduler`]. A snapshot is only taken if the audit
+//! log recorded changes (or a different scoring formula was activated) since the previous one.
+
+use crate::{
+    cistring::CiString,
+    error::PointercrateError,
+    model::demonlist::player::{DatabasePlayer, RankedPlayer, RankingSortColumn},
+    pagination::{Page, PaginationParameters},
+    util::{non_nullable, nullable_query},
+    Result,
+};
+use chrono::NaiveDateTime;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    postgres::{PgArguments, PgConnection},
+    query::Query,
+    Error, Postgres, Row,
+};
+
+#[derive(Debug, Serialize, Hash, PartialEq, Eq, Clone)]
+pub struct RankingSnapshot {
+    pub id: i32,
+    pub taken_at: NaiveDateTime,
+
+    /// The ID of the scoring formula that was active when the snapshot was taken
+    pub formula: i32,
+}
+
+/// A player's position in a single ranking snapshot
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RankHistoryEntry {
+    pub snapshot: i32,
+    pub taken_at: NaiveDateTime,
+    pub rank: i64,
+    pub score: f64,
+}
+
+impl RankingSnapshot {
+    /// Persists the current state of the `players_with_score` view
+    ///
+    /// Returns [`None`] if nothing changed since the previous snapshot
+    pub async fn take(connection: &mut PgConnection) -> Result<Option<RankingSnapshot>> {
+        let id = sqlx::query!("SELECT take_ranking_snapshot() AS id")
+            .fetch_one(&mut *connection)
+            .await?
+            .id;
+
+        match id {
+            Some(id) => Ok(Some(RankingSnapshot::by_id(id, connection).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<RankingSnapshot> {
+        let result = sqlx::query_as!(RankingSnapshot, "SELECT id, taken_at, formula FROM ranking_snapshots WHERE id = $1", id)
+            .fetch_one(connection)
+            .await;
+
+        match result {
+            Ok(snapshot) => Ok(snapshot),
+            Err(Error::RowNotFound) =>
+                Err(PointercrateError::ModelNotFound {
+                    model: "RankingSnapshot",
+                    identified_by: id.to_string(),
+                }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets the most recent snapshot taken at or before the given time
+    pub async fn at(time: NaiveDateTime, connection: &mut PgConnection) -> Result<RankingSnapshot> {
+        let result = sqlx::query_as!(
+            RankingSnapshot,
+            "SELECT id, taken_at, formula FROM ranking_snapshots WHERE taken_at <= $1 ORDER BY taken_at DESC LIMIT 1",
+            time
+        )
+        .fetch_one(connection)
+        .await;
+
+        match result {
+            Ok(snapshot) => Ok(snapshot),
+            Err(Error::RowNotFound) =>
+                Err(PointercrateError::ModelNotFound {
+                    model: "RankingSnapshot",
+                    identified_by: time.to_string(),
+                }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn latest(connection: &mut PgConnection) -> Result<RankingSnapshot> {
+        let result = sqlx::query_as!(RankingSnapshot, "SELECT id, taken_at, formula FROM ranking_snapshots ORDER BY id DESC LIMIT 1")
+            .fetch_one(connection)
+            .await;
+
+        match result {
+            Ok(snapshot) => Ok(snapshot),
+            Err(Error::RowNotFound) => Err(PointercrateError::NotFound),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets all snapshots, most recent first
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<RankingSnapshot>> {
+        Ok(
+            sqlx::query_as!(RankingSnapshot, "SELECT id, taken_at, formula FROM ranking_snapshots ORDER BY id DESC")
+                .fetch_all(connection)
+                .await?,
+        )
+    }
+}
+
+/// Gets the given player's rank in every snapshot they appear in, oldest first
+pub async fn rank_history(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<RankHistoryEntry>> {
+    let mut stream = sqlx::query!(
+        "SELECT snapshot, taken_at, rank, score FROM ranking_snapshot_entries INNER JOIN ranking_snapshots ON snapshot = id WHERE player = \
+         $1 ORDER BY taken_at",
+        player.id
+    )
+    .fetch(connection);
+
+    let mut history = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        history.push(RankHistoryEntry {
+            snapshot: row.snapshot,
+            taken_at: row.taken_at,
+            rank: row.rank,
+            score: row.score,
+        })
+    }
+
+    Ok(history)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoricalRankingPagination {
+    #[serde(default, deserialize_with = "non_nullable")]
+    snapshot: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    at: Option<NaiveDateTime>,
+
+    #[serde(default, with = "nullable_query", skip_serializing_if = "Option::is_none")]
+    nation: Option<Option<String>>,
+    #[serde(default, deserialize_with = "non_nullable")]
+    name_contains: Option<CiString>,
+}
+
+impl HistoricalRankingPagination {
+    /// Determines the snapshot this pagination request refers to
+    ///
+    /// Afterwards, `snapshot` is always set and `at` never is, so that the links generated for the
+    /// next/previous pages keep referring to the same snapshot.
+    pub async fn resolve_snapshot(&mut self, connection: &mut PgConnection) -> Result<RankingSnapshot> {
+        let snapshot = match (self.snapshot, self.at) {
+            (Some(_), Some(_)) => return Err(PointercrateError::MutuallyExclusive),
+            (Some(id), None) => RankingSnapshot::by_id(id, connection).await?,
+            (None, Some(time)) => RankingSnapshot::at(time, connection).await?,
+            (None, None) => RankingSnapshot::latest(connection).await?,
+        };
+
+        self.snapshot = Some(snapshot.id);
+        self.at = None;
+
+        Ok(snapshot)
+    }
+
+    pub async fn page(
+        &self, snapshot: &RankingSnapshot, parameters: &PaginationParameters<RankingSortColumn>, connection: &mut PgConnection,
+    ) -> Result<Page<RankedPlayer>> {
+        parameters.validate()?;
+
+        let query = include_str!("../../../../sql/paginate_ranking_history.sql");
+        let page_query = parameters.page_query(query);
+
+        let mut players = Vec::new();
+        let mut stream = self
+            .bind(snapshot, parameters.bind_cursors(sqlx::query(&page_query)))
+            .fetch(&mut *connection);
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            players.push((parameters.cursor(&row)?, RankedPlayer::from_row(&row)))
+        }
+
+        drop(stream);
+
+        let mut page = Page::new(parameters, players);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                self.bind(snapshot, parameters.bind_no_cursors(sqlx::query(&count_query)))
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
+        }
+
+        Ok(page)
+    }
+
+    fn bind<'q>(&'q self, snapshot: &RankingSnapshot, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(snapshot.id)
+            .bind(self.name_contains.as_ref().map(|s| s.as_str()))
+            .bind(&self.nation)
+            .bind(self.nation == Some(None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::demonlist::player::RankingSnapshot;
+
+    #[actix_rt::test]
+    async fn test_section_change_takes_snapshot() {
+        let mut connection = crate::test::test_setup().await;
+
+        RankingSnapshot::take(&mut connection).await.unwrap();
+
+        assert!(RankingSnapshot::take(&mut connection).await.unwrap().is_none());
+
+        sqlx::query!("UPDATE list_sections SET scoring_weight = 2.0 WHERE name = 'Main List'")
+            .execute(&mut connection)
+            .await
+            .unwrap();
+
+        assert!(RankingSnapshot::take(&mut connection).await.unwrap().is_some());
+    }
+}
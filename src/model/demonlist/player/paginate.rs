@@ -1,30 +1,60 @@
 use crate::{
     cistring::CiString,
-    error::PointercrateError,
     model::{
         demonlist::player::{DatabasePlayer, Player, RankedPlayer},
         nationality::Nationality,
     },
-    util::{non_nullable, nullable},
+    pagination::{Page, PaginationParameters, SortColumn},
+    util::{non_nullable, nullable_query},
     Result,
 };
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgConnection, PgRow},
+    query::Query,
+    Postgres, Row,
+};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct PlayerPagination {
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "before")]
-    pub before_id: Option<i32>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayerSortColumn {
+    Id,
+    Name,
+    Score,
+}
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "after")]
-    pub after_id: Option<i32>,
+impl Default for PlayerSortColumn {
+    fn default() -> Self {
+        PlayerSortColumn::Id
+    }
+}
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub limit: Option<u8>,
+impl SortColumn for PlayerSortColumn {
+    const ALL: &'static [Self] = &[PlayerSortColumn::Id, PlayerSortColumn::Name, PlayerSortColumn::Score];
+
+    fn name(self) -> &'static str {
+        match self {
+            PlayerSortColumn::Id => "id",
+            PlayerSortColumn::Name => "name",
+            PlayerSortColumn::Score => "score",
+        }
+    }
 
+    fn column(self) -> &'static str {
+        self.name()
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            PlayerSortColumn::Id => "INTEGER",
+            PlayerSortColumn::Name => "TEXT",
+            PlayerSortColumn::Score => "DOUBLE PRECISION",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlayerPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     name: Option<CiString>,
 
@@ -34,39 +64,20 @@ pub struct PlayerPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     banned: Option<bool>,
 
-    #[serde(default, deserialize_with = "nullable")]
+    #[serde(default, with = "nullable_query", skip_serializing_if = "Option::is_none")]
     nation: Option<Option<String>>,
 }
 
 impl PlayerPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<Player>> {
-        if let Some(limit) = self.limit {
-            if limit < 1 || limit > 100 {
-                return Err(PointercrateError::InvalidPaginationLimit)
-            }
-        }
-
-        let order = if self.after_id.is_none() && self.before_id.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
+    pub async fn page(&self, parameters: &PaginationParameters<PlayerSortColumn>, connection: &mut PgConnection) -> Result<Page<Player>> {
+        parameters.validate()?;
 
-        let query = format!(include_str!("../../../../sql/paginate_players_by_id.sql"), order);
+        let query = include_str!("../../../../sql/paginate_players_by_id.sql");
+        let page_query = parameters.page_query(query);
 
         // FIXME(sqlx) once CITEXT is supported
-        let mut stream = sqlx::query(&query)
-            .bind(self.before_id)
-            .bind(self.after_id)
-            .bind(self.name.as_ref().map(|s| s.as_str()))
-            .bind(self.name_contains.as_ref().map(|s| s.as_str()))
-            .bind(self.banned)
-            .bind(&self.nation)
-            .bind(self.nation == Some(None))
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
-
         let mut players = Vec::new();
+        let mut stream = self.bind(parameters.bind_cursors(sqlx::query(&page_query))).fetch(&mut *connection);
 
         while let Some(row) = stream.next().await {
             let row = row?;
@@ -80,88 +91,150 @@ impl PlayerPagination {
                 _ => None,
             };
 
-            players.push(Player {
+            let player = Player {
                 base: DatabasePlayer {
                     id: row.get("id"),
                     name: CiString(row.get("name")),
                     banned: row.get("banned"),
                 },
                 nationality,
-            })
+                youtube_channel: row.get("youtube_channel"),
+            };
+
+            players.push((parameters.cursor(&row)?, player))
         }
 
-        Ok(players)
+        drop(stream);
+
+        let mut page = Page::new(parameters, players);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                self.bind(parameters.bind_no_cursors(sqlx::query(&count_query)))
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
+        }
+
+        Ok(page)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.name.as_ref().map(|s| s.as_str()))
+            .bind(self.name_contains.as_ref().map(|s| s.as_str()))
+            .bind(self.banned)
+            .bind(&self.nation)
+            .bind(self.nation == Some(None))
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct RankingPagination {
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "before")]
-    pub before_index: Option<i64>,
+/// The order of the player rankings, which is always by the pseudo-field `index`
+///
+/// Unlike a player's rank, the index is unique and thus doubles as the ID field of the rankings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RankingSortColumn {
+    Index,
+}
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "after")]
-    pub after_index: Option<i64>,
+impl Default for RankingSortColumn {
+    fn default() -> Self {
+        RankingSortColumn::Index
+    }
+}
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub limit: Option<u8>,
+impl SortColumn for RankingSortColumn {
+    const ALL: &'static [Self] = &[RankingSortColumn::Index];
+
+    fn name(self) -> &'static str {
+        "index"
+    }
+
+    fn column(self) -> &'static str {
+        "index"
+    }
+
+    // The column is also used to break ties, and cursors store those as INTEGER
+    fn sql_type(self) -> &'static str {
+        "INTEGER"
+    }
+}
 
-    #[serde(default, deserialize_with = "nullable")]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RankingPagination {
+    #[serde(default, with = "nullable_query", skip_serializing_if = "Option::is_none")]
     nation: Option<Option<String>>,
     #[serde(default, deserialize_with = "non_nullable")]
     name_contains: Option<CiString>,
 }
 
 impl RankingPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<RankedPlayer>> {
-        if let Some(limit) = self.limit {
-            if limit < 1 || limit > 100 {
-                return Err(PointercrateError::InvalidPaginationLimit)
-            }
-        }
+    pub async fn page(
+        &self, parameters: &PaginationParameters<RankingSortColumn>, connection: &mut PgConnection,
+    ) -> Result<Page<RankedPlayer>> {
+        parameters.validate()?;
 
-        let order = if self.before_index.is_some() && self.after_index.is_none() {
-            "DESC"
-        } else {
-            "ASC"
-        };
-
-        let query = format!(include_str!("../../../../sql/paginate_player_ranking.sql"), order);
-
-        let mut stream = sqlx::query(&query)
-            .bind(self.before_index)
-            .bind(self.after_index)
-            .bind(self.name_contains.as_ref().map(|s| s.as_str()))
-            .bind(&self.nation)
-            .bind(self.nation == Some(None))
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
+        let query = include_str!("../../../../sql/paginate_player_ranking.sql");
+        let page_query = parameters.page_query(query);
 
         let mut players = Vec::new();
+        let mut stream = self.bind(parameters.bind_cursors(sqlx::query(&page_query))).fetch(&mut *connection);
 
         while let Some(row) = stream.next().await {
             let row = row?;
 
-            let nationality = match (row.get("nation"), row.get("iso_country_code")) {
-                (Some(nation), Some(country_code)) =>
-                    Some(Nationality {
-                        iso_country_code: country_code,
-                        nation: CiString(nation),
-                    }),
-                _ => None,
-            };
+            players.push((parameters.cursor(&row)?, RankedPlayer::from_row(&row)))
+        }
 
-            players.push(RankedPlayer {
-                id: row.get("id"),
-                name: CiString(row.get("name")),
-                rank: row.get("rank"),
-                nationality,
-                score: row.get("score"),
-                index: row.get("index"),
-            })
+        drop(stream);
+
+        let mut page = Page::new(parameters, players);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                self.bind(parameters.bind_no_cursors(sqlx::query(&count_query)))
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
         }
 
-        Ok(players)
+        Ok(page)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.name_contains.as_ref().map(|s| s.as_str()))
+            .bind(&self.nation)
+            .bind(self.nation == Some(None))
+    }
+}
+
+impl RankedPlayer {
+    /// Constructs a ranked player from a row of a query generated from
+    /// `paginate_player_ranking.sql` or `paginate_ranking_history.sql`
+    pub(super) fn from_row(row: &PgRow) -> RankedPlayer {
+        let nationality = match (row.get("nation"), row.get("iso_country_code")) {
+            (Some(nation), Some(country_code)) =>
+                Some(Nationality {
+                    iso_country_code: country_code,
+                    nation: CiString(nation),
+                }),
+            _ => None,
+        };
+
+        RankedPlayer {
+            id: row.get("id"),
+            name: CiString(row.get("name")),
+            rank: row.get("rank"),
+            nationality,
+            score: row.get("score"),
+            index: row.get::<i32, _>("index").into(),
+        }
     }
 }
@@ -3,10 +3,11 @@ use crate::{
     error::PointercrateError,
     model::{
         demonlist::{
-            player::{DatabasePlayer, FullPlayer, Player},
+            player::{DatabasePlayer, FullPlayer, Player, PlayerClaim},
             record::{approved_records_by, FullRecord},
         },
         nationality::Nationality,
+        webhook::{self, Event},
     },
     util::{non_nullable, nullable},
     Result,
@@ -25,10 +26,42 @@ pub struct PatchPlayer {
 
     #[serde(default, deserialize_with = "nullable")]
     nationality: Option<Option<CiString>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    youtube_channel: Option<Option<String>>,
+}
+
+impl PatchPlayer {
+    /// Ensures this patch only touches fields the user who claimed a player is allowed to change
+    /// themselves, which are `nationality` and `youtube_channel`
+    pub fn require_claimant_safe(&self) -> Result<()> {
+        // Deliberately not using `..`, so that every field added to this struct is forbidden for
+        // claimants until it is explicitly allowed here
+        let PatchPlayer {
+            nationality: _,
+            youtube_channel: _,
+            name,
+            banned,
+        } = self;
+
+        let forbidden = [("name", name.is_some()), ("banned", banned.is_some())];
+
+        match forbidden.iter().find(|(_, set)| *set) {
+            Some(&(field, _)) => Err(PointercrateError::ClaimantPatch { field }),
+            None => Ok(()),
+        }
+    }
 }
 
 impl FullPlayer {
     pub async fn apply_patch(mut self, patch: PatchPlayer, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(youtube_channel) = patch.youtube_channel {
+            match youtube_channel {
+                Some(youtube_channel) => self.player.set_youtube_channel(youtube_channel, connection).await?,
+                None => self.player.reset_youtube_channel(connection).await?,
+            }
+        }
+
         if let Some(nationality) = patch.nationality {
             match nationality {
                 Some(ident) =>
@@ -161,6 +194,21 @@ impl FullPlayer {
 
         info!("Moved {} records from {} to {}", updated.rows_affected(), with, self);
 
+        // Transfer over claims. If both players have been claimed, the claim on the second one is
+        // dropped (it gets deleted together with the player below)
+        let updated = sqlx::query!(
+            "UPDATE player_claims SET player = $1 WHERE player = $2 AND NOT (verified AND EXISTS (SELECT 1 FROM player_claims WHERE \
+             player = $1 AND verified))",
+            self.player.base.id,
+            with.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        info!("Transferred {} claims from {} to {}", updated.rows_affected(), with, self);
+
+        self.claimed = PlayerClaim::claimant_of(&self.player.base, &mut *connection).await?.is_some();
+
         // Delete the second player
         sqlx::query!("DELETE FROM players WHERE id = $1", with.id)
             .execute(connection)
@@ -194,6 +242,32 @@ impl Player {
 
         Ok(())
     }
+
+    pub async fn set_youtube_channel(&mut self, youtube_channel: String, connection: &mut PgConnection) -> Result<()> {
+        let youtube_channel = crate::video::validate_channel(&youtube_channel)?;
+
+        sqlx::query!(
+            "UPDATE players SET youtube_channel = $1::text WHERE id = $2",
+            youtube_channel,
+            self.base.id
+        )
+        .execute(connection)
+        .await?;
+
+        self.youtube_channel = Some(youtube_channel);
+
+        Ok(())
+    }
+
+    pub async fn reset_youtube_channel(&mut self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("UPDATE players SET youtube_channel = NULL WHERE id = $1", self.base.id)
+            .execute(connection)
+            .await?;
+
+        self.youtube_channel = None;
+
+        Ok(())
+    }
 }
 
 impl DatabasePlayer {
@@ -231,12 +305,12 @@ impl DatabasePlayer {
 
         // Actually ban the player
         sqlx::query!("UPDATE players SET banned = true WHERE id = $1", self.id)
-            .execute(connection)
+            .execute(&mut *connection)
             .await?;
 
         self.banned = true;
 
-        Ok(())
+        webhook::dispatch(Event::player_banned(self), connection).await
     }
 }
 
@@ -244,10 +318,12 @@ impl DatabasePlayer {
 mod tests {
     use crate::{
         cistring::{CiStr, CiString},
+        error::PointercrateError,
         model::demonlist::{
             player::{DatabasePlayer, PatchPlayer, Player},
             record::{RecordPagination, RecordStatus},
         },
+        pagination::PaginationParameters,
     };
 
     #[actix_rt::test]
@@ -273,6 +349,38 @@ mod tests {
         assert_eq!(player_after.unwrap(), player_before);
     }
 
+    #[test]
+    fn test_claimant_safe() {
+        let patch = PatchPlayer {
+            nationality: Some(Some(CiString("DE".to_owned()))),
+            youtube_channel: Some(None),
+            ..Default::default()
+        };
+
+        assert!(patch.require_claimant_safe().is_ok());
+
+        let patch = PatchPlayer {
+            banned: Some(false),
+            nationality: Some(None),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            patch.require_claimant_safe().err(),
+            Some(PointercrateError::ClaimantPatch { field: "banned" })
+        );
+
+        let patch = PatchPlayer {
+            name: Some(CiString("stardust1972".to_owned())),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            patch.require_claimant_safe().err(),
+            Some(PointercrateError::ClaimantPatch { field: "name" })
+        );
+    }
+
     #[actix_rt::test]
     async fn test_ban_player() {
         let mut connection = crate::test::test_setup().await;
@@ -287,7 +395,11 @@ mod tests {
         // single player
         let mut pagination = RecordPagination::default();
         pagination.player = Some(player_id);
-        let records_before = pagination.page(&mut connection).await.unwrap();
+        let records_before = pagination
+            .page(&PaginationParameters::default(), &mut connection)
+            .await
+            .unwrap()
+            .items;
 
         let patch = PatchPlayer {
             banned: Some(true),
@@ -311,7 +423,11 @@ mod tests {
         assert_eq!(player, patched_player);
 
         // Check if all records where properly updated
-        let records_after = pagination.page(&mut connection).await.unwrap();
+        let records_after = pagination
+            .page(&PaginationParameters::default(), &mut connection)
+            .await
+            .unwrap()
+            .items;
 
         assert_eq!(records_after.len(), 2);
 
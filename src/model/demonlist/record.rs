@@ -22,36 +22,38 @@
 //!   the 'under consideration' status makes. A record under consideration IS NOT UNIQUE!
 
 pub use self::{
-    get::{approved_records_by, approved_records_on},
-    paginate::RecordPagination,
+    bulk::{BulkPatchRecord, BulkPatchResult},
+    claim::RecordClaim,
+    get::{approved_records_by, approved_records_on, records_submitted_by},
+    paginate::{RecordPagination, RecordSortColumn},
     patch::PatchRecord,
     post::Submission,
+    raw_footage::RawFootage,
+    validation::{process_validation_jobs, ValidationStatus, VideoValidation},
 };
-use crate::{
-    model::{
-        demonlist::{demon::MinimalDemon, player::DatabasePlayer, record::note::Note, submitter::Submitter},
-        nationality::Nationality,
-    },
-    state::PointercrateState,
-    Result,
+use crate::model::{
+    demonlist::{demon::MinimalDemon, player::DatabasePlayer, record::note::Note, submitter::Submitter},
+    nationality::Nationality,
 };
 use derive_more::Display;
-use log::{debug, error, warn};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use serde_json::json;
-use sqlx::PgConnection;
 use std::{
     fmt::{Display, Formatter},
     hash::{Hash, Hasher},
 };
 
 pub mod audit;
+mod bulk;
+mod claim;
 mod delete;
 mod get;
 pub mod note;
 mod paginate;
 mod patch;
 mod post;
+mod raw_footage;
+mod revert;
+mod validation;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
 pub enum RecordStatus {
@@ -141,7 +143,14 @@ pub struct FullRecord {
     pub demon: MinimalDemon,
     pub submitter: Option<Submitter>,
     pub notes: Vec<Note>,
-    pub raw_footage: Option<String>
+    /// The raw footage of this record. Only visible to list helpers and above
+    pub raw_footage: Option<RawFootage>,
+
+    /// The state of the automatic check of this record's video, if one was ever queued
+    pub validation: Option<VideoValidation>,
+
+    /// The active claim on this record, if any
+    pub claim: Option<RecordClaim>,
 }
 
 impl Hash for FullRecord {
@@ -152,6 +161,7 @@ impl Hash for FullRecord {
         self.status.hash(state);
         self.player.id.hash(state);
         self.demon.id.hash(state);
+        self.raw_footage.hash(state);
         self.notes.hash(state)
         // submitter cannot be patched -> no hash
     }
@@ -166,6 +176,11 @@ pub struct MinimalRecordPD {
     pub status: RecordStatus,
     pub demon: MinimalDemon,
     pub player: DatabasePlayer,
+
+    /// The active claim on this record, if any. Only set for records retrieved via
+    /// [`RecordPagination`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claim: Option<RecordClaim>,
 }
 
 #[derive(Debug, Hash, Serialize, Display, PartialEq, Eq)]
@@ -178,6 +193,18 @@ pub struct MinimalRecordD {
     pub demon: MinimalDemon,
 }
 
+/// A record as seen by the user who submitted it
+#[derive(Debug, Hash, Serialize, Display, PartialEq, Eq)]
+#[display(fmt = "{} - {}% on {} (ID: {})", player, progress, demon, id)]
+pub struct SubmittedRecord {
+    pub id: i32,
+    pub progress: i16,
+    pub video: Option<String>,
+    pub status: RecordStatus,
+    pub player: DatabasePlayer,
+    pub demon: MinimalDemon,
+}
+
 #[derive(Debug, Hash, Serialize, Display, PartialEq, Eq)]
 #[display(fmt = "{} - {}% (ID: {})", player, progress, id)]
 pub struct MinimalRecordP {
@@ -188,145 +215,3 @@ pub struct MinimalRecordP {
     pub player: DatabasePlayer,
     pub nationality: Option<Nationality>,
 }
-
-impl FullRecord {
-    /// Gets the maximal and minimal submitter id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_record_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(id) AS "max_id!: i32", MIN(id) AS "min_id!: i32" FROM records"#)
-            .fetch_one(connection)
-            .await?; // FIXME: crashes on empty table
-        Ok((row.max_id, row.min_id))
-    }
-
-    pub async fn validate(self, state: PointercrateState) {
-        let mut connection = match state.connection().await {
-            Ok(connection) => connection,
-            Err(err) => return error!("INTERNAL SERVER ERROR: failed to acquire database connection: {:?}", err),
-        };
-
-        let video = match self.video {
-            Some(ref video) => video,
-            None => return,
-        };
-
-        debug!("Verifying that submission {} with video {} actually is valid", self, video);
-
-        match state.http_client.head(video).send().await {
-            Ok(response) => {
-                let status = response.status().as_u16();
-
-                if status == 401 || status == 403 || status == 405 {
-                    // Some websites (billibilli) respond unfavorably to HEAD requests. Retry with
-                    // GET
-                    match state.http_client.get(video).send().await {
-                        Ok(response) => {
-                            let status = response.status().as_u16();
-
-                            if status >= 200 && status < 400 {
-                                debug!("HEAD request yielded some sort of successful response, executing webhook");
-
-                                self.execute_webhook(&state).await;
-                            }
-                        },
-                        Err(err) => {
-                            error!(
-                                "INTERNAL SERVER ERROR: HEAD request to verify video failed: {:?}. Deleting submission",
-                                err
-                            );
-
-                            match self.delete(&mut connection).await {
-                                Ok(_) => (),
-                                Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-                            }
-                        },
-                    }
-                } else if status >= 200 && status < 400 {
-                    debug!("HEAD request yielded some sort of successful response, executing webhook");
-
-                    self.execute_webhook(&state).await;
-                } else {
-                    warn!("Server response to 'HEAD {}' was {:?}, deleting submission!", video, response);
-
-                    match self.delete(&mut connection).await {
-                        Ok(_) => (),
-                        Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-                    }
-                }
-            },
-            Err(error) => {
-                error!(
-                    "INTERNAL SERVER ERROR: HEAD request to verify video failed: {:?}. Deleting submission",
-                    error
-                );
-
-                match self.delete(&mut connection).await {
-                    Ok(_) => (),
-                    Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-                }
-            },
-        }
-    }
-
-    async fn execute_webhook(&self, state: &PointercrateState) {
-        if let Some(ref webhook_url) = state.webhook_url {
-            match state
-                .http_client
-                .post(&**webhook_url)
-                .header("Content-Type", "application/json")
-                .body(self.webhook_embed().to_string())
-                .send()
-                .await
-            {
-                Err(error) => error!("INTERNAL SERVER ERROR: Failure to execute discord webhook: {:?}", error),
-                Ok(_) => debug!("Successfully executed discord webhook"),
-            }
-        } else {
-            warn!("Trying to execute webhook, though no link was configured!");
-        }
-    }
-
-    fn webhook_embed(&self) -> serde_json::Value {
-        let mut payload = json!({
-            "content": "",
-            "embeds": [
-                {
-                    "type": "rich",
-                    "title": format!("New Record Submitted ({})", self.id),
-                    "description": "To accept the record, go to the [website](https://challengelist.gd/account/) and do so. (For now, at least.)",
-                    "footer": {
-                        "text": format!("This record has been submitted by submitter #{}", self.submitter.map(|s|s.id).unwrap_or(1))
-                    },
-                    "color": 0xf542f5
-                }
-            ]
-        });
-
-        if let Some(ref video) = self.video {
-            payload["embeds"][0]["fields"] = json! {
-                [{
-                    "name": "Player",
-                    "value": self.player.name,
-                    "inline": true
-                }, {
-                    "name": "Challenge",
-                    "value": self.demon.name,
-                    "inline": true
-                },
-                {
-                    "name": "Video Proof",
-                    "value": self.video,
-                    "inline": false
-                },
-                {
-                    "name": "Raw Footage",
-                    "value": self.raw_footage,
-                    "inline": false
-                }]
-            };
-        }
-
-        payload
-    }
-}
\ No newline at end of file
@@ -1,15 +1,12 @@
-use crate::{model::demonlist::record::RecordStatus, Result};
+use crate::{
+    model::{audit::NamedId, demonlist::record::RecordStatus},
+    Result,
+};
 use chrono::NaiveDateTime;
 use futures::StreamExt;
 use serde::Serialize;
 use sqlx::PgConnection;
 
-#[derive(Serialize)]
-pub struct NamedId {
-    id: i32,
-    name: Option<String>,
-}
-
 #[derive(Serialize)]
 pub struct RecordModificationData {
     progress: Option<i16>,
@@ -33,23 +30,27 @@ pub enum RecordEntryType {
     Addition,
     Modification(RecordModificationData),
     Deletion,
+    Claim { claimant: NamedId, expires_at: NaiveDateTime },
+    ClaimRelease { claimant: NamedId },
 }
 
 /// Gets all audit log entries for the given record, in chronological order
+///
+/// Note that restored records have multiple addition and deletion entries
 pub async fn entries_for_record(record_id: i32, connection: &mut PgConnection) -> Result<Vec<RecordEntry>> {
     let mut entries = Vec::new();
 
-    let addition_row = sqlx::query!(
+    let addition_rows = sqlx::query!(
         r#"SELECT time, audit_id, 
                   userid,
                   members.name AS "name?"
                   FROM record_additions LEFT OUTER JOIN members ON members.member_id = userid WHERE id = $1"#,
         record_id
     )
-    .fetch_optional(&mut *connection)
+    .fetch_all(&mut *connection)
     .await?;
 
-    if let Some(addition) = addition_row {
+    for addition in addition_rows {
         entries.push(RecordEntry {
             time: addition.time,
             audit_id: addition.audit_id,
@@ -121,17 +122,17 @@ pub async fn entries_for_record(record_id: i32, connection: &mut PgConnection) -
         }
     }
 
-    let deletion_row = sqlx::query!(
+    let deletion_rows = sqlx::query!(
         r#"SELECT time, audit_id, 
                   userid,
                   members.name AS "name?"
                   FROM record_deletions LEFT OUTER JOIN members ON members.member_id = userid WHERE id = $1"#,
         record_id
     )
-    .fetch_optional(&mut *connection)
+    .fetch_all(&mut *connection)
     .await?;
 
-    if let Some(deletion) = deletion_row {
+    for deletion in deletion_rows {
         entries.push(RecordEntry {
             time: deletion.time,
             audit_id: deletion.audit_id,
@@ -144,5 +145,66 @@ pub async fn entries_for_record(record_id: i32, connection: &mut PgConnection) -
         });
     }
 
+    let claim_rows = sqlx::query!(
+        r#"SELECT time, audit_id, userid, users.name AS "username?", member, claimants.name AS "claimant?", expires_at
+                  FROM record_claim_additions 
+                  LEFT OUTER JOIN members AS users ON users.member_id = userid 
+                  LEFT OUTER JOIN members AS claimants ON claimants.member_id = member 
+                  WHERE id = $1"#,
+        record_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for claim in claim_rows {
+        entries.push(RecordEntry {
+            time: claim.time,
+            audit_id: claim.audit_id,
+            record_id,
+            user: NamedId {
+                name: claim.username,
+                id: claim.userid,
+            },
+            r#type: RecordEntryType::Claim {
+                claimant: NamedId {
+                    name: claim.claimant,
+                    id: claim.member,
+                },
+                expires_at: claim.expires_at,
+            },
+        });
+    }
+
+    let release_rows = sqlx::query!(
+        r#"SELECT time, audit_id, userid, users.name AS "username?", member, claimants.name AS "claimant?"
+                  FROM record_claim_deletions 
+                  LEFT OUTER JOIN members AS users ON users.member_id = userid 
+                  LEFT OUTER JOIN members AS claimants ON claimants.member_id = member 
+                  WHERE id = $1"#,
+        record_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for release in release_rows {
+        entries.push(RecordEntry {
+            time: release.time,
+            audit_id: release.audit_id,
+            record_id,
+            user: NamedId {
+                name: release.username,
+                id: release.userid,
+            },
+            r#type: RecordEntryType::ClaimRelease {
+                claimant: NamedId {
+                    name: release.claimant,
+                    id: release.member,
+                },
+            },
+        });
+    }
+
+    entries.sort_by_key(|entry| entry.audit_id);
+
     Ok(entries)
 }
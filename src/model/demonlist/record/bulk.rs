@@ -0,0 +1,179 @@
+//! Module for applying the same change to many records at once
+//!
+//! Every record is patched via [`FullRecord::apply_patch`] inside its own savepoint, so the
+//! invariants described in the module documentation of [`crate::model::demonlist::record`] are
+//! upheld exactly like for single-record patches. A record that cannot be patched (because it
+//! doesn't exist, or because the change would be invalid for it) has its savepoint rolled back and
+//! the error is reported for that record only. All other changes are kept.
+//!
+//! Records claimed by someone other than the user making the change are skipped and reported as
+//! conflicts, just like they would be for single-record patches.
+//!
+//! Note that since records are processed in the order given, a change to one record might delete
+//! records that appear later in the batch (for example approving a record deletes all submissions
+//! with less progress for the same (player, demon) pair). These then show up as `404 NOT FOUND`
+//! in the results.
+
+use crate::{
+    error::PointercrateError,
+    model::{
+        demonlist::record::{FullRecord, PatchRecord},
+        user::User,
+    },
+    Result,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+/// The maximal number of records that can be changed in a single request
+pub const MAX_BULK_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct BulkPatchRecord {
+    pub records: Vec<i32>,
+    pub patch: PatchRecord,
+}
+
+/// The error a single record in a bulk patch failed with. Has the same format as the error
+/// responses of the API
+#[derive(Debug, Serialize)]
+pub struct BulkPatchError {
+    code: u16,
+    message: String,
+    data: PointercrateError,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkPatchResult {
+    pub id: i32,
+    pub status: u16,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<FullRecord>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BulkPatchError>,
+}
+
+impl BulkPatchResult {
+    fn success(record: FullRecord) -> Self {
+        BulkPatchResult {
+            id: record.id,
+            status: 200,
+            data: Some(record),
+            error: None,
+        }
+    }
+
+    fn failure(id: i32, error: PointercrateError) -> Self {
+        BulkPatchResult {
+            id,
+            status: error.status_code().as_u16(),
+            data: None,
+            error: Some(BulkPatchError {
+                code: error.error_code(),
+                message: error.to_string(),
+                data: error,
+            }),
+        }
+    }
+}
+
+impl BulkPatchRecord {
+    /// Must be called inside a transaction
+    pub async fn apply(self, user: &User, connection: &mut PgConnection) -> Result<Vec<BulkPatchResult>> {
+        if self.records.is_empty() || self.records.len() > MAX_BULK_SIZE {
+            return Err(PointercrateError::BadRequest {
+                message: format!("Bulk changes need to affect between 1 and {} records", MAX_BULK_SIZE),
+            })
+        }
+
+        info!("Applying patch {:?} to records {:?}", self.patch, self.records);
+
+        let mut seen = Vec::new();
+        let mut results = Vec::new();
+
+        for id in self.records {
+            if seen.contains(&id) {
+                continue
+            }
+
+            seen.push(id);
+
+            // We manage the savepoint by hand (instead of using a nested sqlx transaction) as we do not
+            // know how the surrounding transaction was started
+            sqlx::query!("SAVEPOINT bulk_patch").execute(&mut *connection).await?;
+
+            let result = match FullRecord::by_id(id, &mut *connection).await {
+                Ok(record) =>
+                    match record.require_claim_held_by(user) {
+                        Ok(()) => record.apply_patch(self.patch.clone(), &mut *connection).await,
+                        Err(err) => Err(err),
+                    },
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(record) => {
+                    sqlx::query!("RELEASE SAVEPOINT bulk_patch").execute(&mut *connection).await?;
+
+                    results.push(BulkPatchResult::success(record))
+                },
+                Err(err) => {
+                    sqlx::query!("ROLLBACK TO SAVEPOINT bulk_patch").execute(&mut *connection).await?;
+
+                    results.push(BulkPatchResult::failure(id, err))
+                },
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        model::{
+            demonlist::record::{bulk::BulkPatchRecord, PatchRecord, RecordStatus},
+            user::User,
+        },
+        permissions::Permissions,
+    };
+
+    #[actix_rt::test]
+    async fn test_bulk_patch_partial_failure() {
+        let mut connection = crate::test::test_setup().await;
+
+        let id = sqlx::query!("SELECT id FROM records WHERE status_ = 'SUBMITTED'")
+            .fetch_one(&mut connection)
+            .await
+            .unwrap()
+            .id;
+
+        let user = User {
+            id: 0,
+            name: "test".to_string(),
+            permissions: Permissions::ListHelper,
+            display_name: None,
+            youtube_channel: None,
+        };
+
+        let mut patch = PatchRecord::default();
+        patch.status = Some(RecordStatus::UnderConsideration);
+
+        let results = BulkPatchRecord {
+            records: vec![id, -1, id],
+            patch,
+        }
+        .apply(&user, &mut connection)
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].status, 200);
+        assert_eq!(results[0].data.as_ref().unwrap().status, RecordStatus::UnderConsideration);
+        assert_eq!(results[1].status, 404);
+    }
+}
@@ -0,0 +1,136 @@
+//! Module containing the claims list helpers can put on records they are reviewing
+//!
+//! A claim marks a record as "under review" by some list helper for a limited amount of time (see
+//! [`config::record_claim_duration`]). While a claim is active, nobody but its holder can claim or
+//! modify the record. Claims are never cleaned up actively. Once they expire they are simply
+//! ignored and overwritten by the next claim made on the record.
+//!
+//! Claiming a record and releasing a claim before it expired are recorded in the audit log.
+
+use crate::{
+    config,
+    error::PointercrateError,
+    model::{audit::NamedId, demonlist::record::FullRecord, user::User},
+    permissions::Capabilities,
+    Result,
+};
+use chrono::NaiveDateTime;
+use log::info;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, Hash, Clone)]
+pub struct RecordClaim {
+    pub claimed_by: NamedId,
+    pub claimed_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+impl RecordClaim {
+    /// Gets the active claim on the given record, if any
+    pub async fn of(record_id: i32, connection: &mut PgConnection) -> Result<Option<RecordClaim>> {
+        let row = sqlx::query!(
+            r#"SELECT member, members.name AS "name?", claimed_at, expires_at FROM record_claims LEFT OUTER JOIN members ON members.member_id
+             = member WHERE record = $1 AND expires_at > (NOW() AT TIME ZONE 'utc')"#,
+            record_id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        Ok(row.map(|row| {
+            RecordClaim {
+                claimed_by: NamedId {
+                    id: row.member,
+                    name: row.name,
+                },
+                claimed_at: row.claimed_at,
+                expires_at: row.expires_at,
+            }
+        }))
+    }
+
+    /// Claims the given record for the given user, or renews the user's existing claim
+    ///
+    /// Fails if someone else holds an active claim on the record
+    pub async fn claim(record_id: i32, user: &User, connection: &mut PgConnection) -> Result<RecordClaim> {
+        // make sure the record exists
+        sqlx::query!("SELECT id FROM records WHERE id = $1", record_id)
+            .fetch_optional(&mut *connection)
+            .await?
+            .ok_or_else(|| {
+                PointercrateError::ModelNotFound {
+                    model: "Record",
+                    identified_by: record_id.to_string(),
+                }
+            })?;
+
+        info!("{} is claiming record {}", user, record_id);
+
+        // The WHERE clause makes sure we never overwrite an active claim of someone else, even if
+        // two list helpers try to claim the same record at the same time. Renewals keep the original
+        // claim time.
+        let claimed = sqlx::query!(
+            "INSERT INTO record_claims (record, member, expires_at) VALUES ($1, $2, (NOW() AT TIME ZONE 'utc') + make_interval(mins => \
+             $3)) ON CONFLICT (record) DO UPDATE SET claimed_at = CASE WHEN record_claims.member = EXCLUDED.member AND \
+             record_claims.expires_at > (NOW() AT TIME ZONE 'utc') THEN record_claims.claimed_at ELSE EXCLUDED.claimed_at END, member = \
+             EXCLUDED.member, expires_at = EXCLUDED.expires_at WHERE record_claims.member = EXCLUDED.member OR record_claims.expires_at <= \
+             (NOW() AT TIME ZONE 'utc') RETURNING record",
+            record_id,
+            user.id,
+            config::record_claim_duration()
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        let claim = RecordClaim::of(record_id, connection)
+            .await?
+            .ok_or(PointercrateError::InternalServerError)?;
+
+        match claimed {
+            Some(_) => Ok(claim),
+            None => Err(claim.conflict(record_id)),
+        }
+    }
+
+    /// Releases the active claim on the given record
+    ///
+    /// Only the holder of a claim and list moderators can release it
+    pub async fn release(record_id: i32, user: &User, connection: &mut PgConnection) -> Result<()> {
+        let claim = RecordClaim::of(record_id, &mut *connection).await?.ok_or_else(|| {
+            PointercrateError::ModelNotFound {
+                model: "RecordClaim",
+                identified_by: record_id.to_string(),
+            }
+        })?;
+
+        if claim.claimed_by.id != user.id && !user.has_capability(Capabilities::OverrideClaims) {
+            return Err(claim.conflict(record_id))
+        }
+
+        info!("{} is releasing the claim of {:?} on record {}", user, claim.claimed_by, record_id);
+
+        sqlx::query!("DELETE FROM record_claims WHERE record = $1", record_id)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+
+    fn conflict(self, record_id: i32) -> PointercrateError {
+        PointercrateError::RecordClaimed {
+            record: record_id,
+            claimed_by: self.claimed_by.name.unwrap_or_else(|| self.claimed_by.id.to_string()),
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+impl FullRecord {
+    /// Ensures that no one but the given user holds an active claim on this record
+    pub fn require_claim_held_by(&self, user: &User) -> Result<()> {
+        match self.claim {
+            Some(ref claim) if claim.claimed_by.id != user.id => Err(claim.clone().conflict(self.id)),
+            _ => Ok(()),
+        }
+    }
+}
@@ -5,7 +5,10 @@ use crate::{
         demonlist::{
             demon::MinimalDemon,
             player::DatabasePlayer,
-            record::{note::notes_on, FullRecord, MinimalRecordD, MinimalRecordP, RecordStatus},
+            record::{
+                note::notes_on, FullRecord, MinimalRecordD, MinimalRecordP, RawFootage, RecordClaim, RecordStatus, SubmittedRecord,
+                VideoValidation,
+            },
             submitter::Submitter,
         },
         nationality::Nationality,
@@ -28,6 +31,7 @@ struct FetchedRecord {
     position: i16,
     submitter_id: i32,
     submitter_banned: bool,
+    submitter_member: Option<i32>,
     raw_footage: Option<String>
 }
 
@@ -57,9 +61,12 @@ impl FullRecord {
                     submitter: Some(Submitter {
                         id: row.submitter_id,
                         banned: row.submitter_banned,
+                        member: row.submitter_member,
                     }),
-                    raw_footage: row.raw_footage,
-                    notes: notes_on(id, connection).await?,
+                    raw_footage: row.raw_footage.map(RawFootage::from_stored),
+                    notes: notes_on(id, &mut *connection).await?,
+                    validation: VideoValidation::of(id, &mut *connection).await?,
+                    claim: RecordClaim::of(id, connection).await?,
                 }),
 
             Err(Error::RowNotFound) =>
@@ -70,6 +77,59 @@ impl FullRecord {
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Makes sure the given video isn't already used as proof by some other record, or as the
+    /// showcase video of a demon
+    ///
+    /// The video needs to have gone through [`crate::video::validate`] already, as we only compare
+    /// canonical URLs here (all videos stored in the database are canonical)
+    pub(super) async fn require_unused_video(video: &str, connection: &mut PgConnection) -> Result<()> {
+        let conflicting_record = sqlx::query!(
+            r#"SELECT records.id, progress, status_::text AS "status_!: String", players.id AS player_id, players.name AS "player_name: String", 
+             players.banned AS player_banned, demons.id AS demon_id, demons.name AS "demon_name: String", demons.position FROM records INNER JOIN 
+             players ON records.player = players.id INNER JOIN demons ON records.demon = demons.id WHERE records.video = $1"#,
+            video.to_string()
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        if let Some(row) = conflicting_record {
+            return Err(PointercrateError::DuplicateVideo {
+                id: row.id,
+                progress: row.progress,
+                status: RecordStatus::from_sql(&row.status_),
+                player: DatabasePlayer {
+                    id: row.player_id,
+                    name: CiString(row.player_name),
+                    banned: row.player_banned,
+                },
+                demon: MinimalDemon {
+                    id: row.demon_id,
+                    position: row.position,
+                    name: CiString(row.demon_name),
+                },
+            })
+        }
+
+        let showcased_on = sqlx::query!(
+            r#"SELECT id, name AS "name: String", position FROM demons WHERE video = $1 LIMIT 1"#,
+            video.to_string()
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        match showcased_on {
+            Some(row) =>
+                Err(PointercrateError::VideoIsShowcase {
+                    demon: MinimalDemon {
+                        id: row.id,
+                        position: row.position,
+                        name: CiString(row.name),
+                    },
+                }),
+            None => Ok(()),
+        }
+    }
 }
 
 pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalRecordD>> {
@@ -102,6 +162,44 @@ pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgCon
     Ok(records)
 }
 
+/// Gets all records the user with the given ID submitted while logged in, newest first
+pub async fn records_submitted_by(member: i32, connection: &mut PgConnection) -> Result<Vec<SubmittedRecord>> {
+    let mut stream = sqlx::query!(
+        r#"SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, status_::text AS
+         "status_!: String", players.id AS player_id, players.name AS "player_name: String", players.banned AS player_banned, demons.id AS
+         demon_id, demons.name AS "demon_name: String", demons.position FROM records INNER JOIN submitters ON records.submitter =
+         submitters.submitter_id INNER JOIN players ON records.player = players.id INNER JOIN demons ON records.demon = demons.id WHERE
+         submitters.member = $1 ORDER BY records.id DESC"#,
+        member
+    )
+    .fetch(connection);
+
+    let mut records = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        records.push(SubmittedRecord {
+            id: row.id,
+            progress: row.progress,
+            video: row.video,
+            status: RecordStatus::from_sql(&row.status_),
+            player: DatabasePlayer {
+                id: row.player_id,
+                name: CiString(row.player_name),
+                banned: row.player_banned,
+            },
+            demon: MinimalDemon {
+                id: row.demon_id,
+                position: row.position,
+                name: CiString(row.demon_name),
+            },
+        })
+    }
+
+    Ok(records)
+}
+
 pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnection) -> Result<Vec<MinimalRecordP>> {
     struct Fetched {
         id: i32,
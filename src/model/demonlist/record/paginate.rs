@@ -1,31 +1,67 @@
 use crate::{
     cistring::CiString,
-    error::PointercrateError,
-    model::demonlist::{
-        demon::MinimalDemon,
-        player::DatabasePlayer,
-        record::{MinimalRecordPD, RecordStatus},
+    model::{
+        audit::NamedId,
+        demonlist::{
+            demon::MinimalDemon,
+            player::DatabasePlayer,
+            record::{MinimalRecordPD, RecordClaim, RecordStatus},
+        },
     },
-    util::{non_nullable, nullable},
+    pagination::{Page, PaginationParameters, SortColumn},
+    util::{non_nullable, nullable_query},
     Result,
 };
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgRow, PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    PgConnection, Postgres, Row,
+};
 
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
-pub struct RecordPagination {
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "before")]
-    pub before_id: Option<i32>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordSortColumn {
+    Id,
+    Progress,
+    DemonPosition,
+}
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[serde(rename = "after")]
-    pub after_id: Option<i32>,
+impl Default for RecordSortColumn {
+    fn default() -> Self {
+        RecordSortColumn::Id
+    }
+}
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub limit: Option<u8>,
+impl SortColumn for RecordSortColumn {
+    const ALL: &'static [Self] = &[RecordSortColumn::Id, RecordSortColumn::Progress, RecordSortColumn::DemonPosition];
+
+    fn name(self) -> &'static str {
+        match self {
+            RecordSortColumn::Id => "id",
+            RecordSortColumn::Progress => "progress",
+            RecordSortColumn::DemonPosition => "demon_position",
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            RecordSortColumn::Id => "id",
+            RecordSortColumn::Progress => "progress",
+            RecordSortColumn::DemonPosition => "position",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            RecordSortColumn::Id => "INTEGER",
+            RecordSortColumn::Progress | RecordSortColumn::DemonPosition => "SMALLINT",
+        }
+    }
+}
 
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct RecordPagination {
     progress: Option<i16>,
 
     #[serde(default, deserialize_with = "non_nullable")]
@@ -58,70 +94,33 @@ pub struct RecordPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     demon_id: Option<i32>,
 
-    #[serde(default, deserialize_with = "nullable")]
+    #[serde(default, with = "nullable_query", skip_serializing_if = "Option::is_none")]
     video: Option<Option<String>>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub submitter: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    claimed: Option<bool>,
 }
 
 impl RecordPagination {
-    /// Retries the page of records matching the pagination data in here
-    ///
-    /// Note that this method returns _one more record than requested_. This is used as a quick and
-    /// dirty way to determine if further pages exist: If the additional record was returned, more
-    /// pages obviously exist. This additional object is the last in the returned vector.
-    ///
-    /// Additionally, if _before_ is set, but not _after_, the page is returned in reverse order
-    /// (the additional object stays the last)
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<MinimalRecordPD>> {
-        if let Some(limit) = self.limit {
-            if limit < 1 || limit > 100 {
-                return Err(PointercrateError::InvalidPaginationLimit)
-            }
-        }
+    /// Retrieves the page of records matching the filters in here
+    pub async fn page(
+        &self, parameters: &PaginationParameters<RecordSortColumn>, connection: &mut PgConnection,
+    ) -> Result<Page<MinimalRecordPD>> {
+        parameters.validate()?;
 
-        if let (Some(after), Some(before)) = (self.before_id, self.after_id) {
-            if after < before {
-                return Err(PointercrateError::AfterSmallerBefore)
-            }
-        }
-
-        let limit = self.limit.unwrap_or(50) as i32;
-
-        let order = if self.after_id.is_none() && self.before_id.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
-
-        let query = format!(include_str!("../../../../sql/paginate_records.sql"), order);
-
-        let mut stream = sqlx::query(&query)
-            .bind(self.before_id)
-            .bind(self.after_id)
-            .bind(self.progress)
-            .bind(self.progress_lt)
-            .bind(self.progress_gt)
-            .bind(self.demon_position)
-            .bind(self.demon_position_lt)
-            .bind(self.demon_position_gt)
-            .bind(self.status.map(|s| s.to_sql()))
-            .bind(self.demon.as_ref().map(|s| s.as_str()))
-            .bind(self.demon_id)
-            .bind(&self.video)
-            .bind(self.video == Some(None))
-            .bind(self.player)
-            .bind(self.submitter)
-            .bind(limit + 1)
-            .fetch(&mut *connection);
+        let query = include_str!("../../../../sql/paginate_records.sql");
+        let page_query = parameters.page_query(query);
 
         let mut records = Vec::new();
+        let mut stream = self.bind(parameters.bind_cursors(sqlx::query(&page_query))).fetch(&mut *connection);
 
         while let Some(row) = stream.next().await {
             let row: PgRow = row?;
 
-            records.push(MinimalRecordPD {
+            let record = MinimalRecordPD {
                 id: row.try_get("id")?,
                 progress: row.try_get("progress")?,
                 video: row.try_get("video")?,
@@ -136,9 +135,56 @@ impl RecordPagination {
                     position: row.try_get("position")?,
                     name: CiString(row.try_get("demon_name")?),
                 },
-            })
+                claim: match row.try_get("claim_member")? {
+                    Some(member) =>
+                        Some(RecordClaim {
+                            claimed_by: NamedId {
+                                id: member,
+                                name: row.try_get("claim_member_name")?,
+                            },
+                            claimed_at: row.try_get("claimed_at")?,
+                            expires_at: row.try_get("expires_at")?,
+                        }),
+                    None => None,
+                },
+            };
+
+            records.push((parameters.cursor(&row)?, record))
+        }
+
+        drop(stream);
+
+        let mut page = Page::new(parameters, records);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                self.bind(parameters.bind_no_cursors(sqlx::query(&count_query)))
+                    .fetch_one(connection)
+                    .await?
+                    .try_get(0)?,
+            );
         }
 
-        Ok(records)
+        Ok(page)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.progress)
+            .bind(self.progress_lt)
+            .bind(self.progress_gt)
+            .bind(self.demon_position)
+            .bind(self.demon_position_lt)
+            .bind(self.demon_position_gt)
+            .bind(self.status.map(|s| s.to_sql()))
+            .bind(self.demon.as_ref().map(|s| s.as_str()))
+            .bind(self.demon_id)
+            .bind(&self.video)
+            .bind(self.video == Some(None))
+            .bind(self.player)
+            .bind(self.submitter)
+            .bind(self.claimed)
     }
 }
@@ -1,10 +1,13 @@
 use crate::{
     cistring::CiString,
     error::PointercrateError,
-    model::demonlist::{
-        demon::MinimalDemon,
-        player::DatabasePlayer,
-        record::{FullRecord, RecordStatus},
+    model::{
+        demonlist::{
+            demon::MinimalDemon,
+            player::DatabasePlayer,
+            record::{FullRecord, RawFootage, RecordStatus, VideoValidation},
+        },
+        webhook::{self, Event, WebhookEvent},
     },
     util::{non_nullable, nullable},
     Result,
@@ -13,28 +16,28 @@ use log::{info, warn};
 use serde::Deserialize;
 use sqlx::{Done, PgConnection};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default, Clone)]
 pub struct PatchRecord {
     #[serde(default, deserialize_with = "non_nullable")]
-    progress: Option<i16>,
+    pub(super) progress: Option<i16>,
 
     #[serde(default, deserialize_with = "nullable")]
-    video: Option<Option<String>>,
+    pub(super) video: Option<Option<String>>,
 
     #[serde(default, deserialize_with = "non_nullable")]
-    status: Option<RecordStatus>,
+    pub(super) status: Option<RecordStatus>,
 
     #[serde(default, deserialize_with = "non_nullable")]
-    player: Option<CiString>,
+    pub(super) player: Option<CiString>,
 
     #[serde(default, deserialize_with = "non_nullable")]
-    demon: Option<CiString>,
+    pub(super) demon: Option<CiString>,
 
     #[serde(default, deserialize_with = "non_nullable")]
-    demon_id: Option<i32>,
+    pub(super) demon_id: Option<i32>,
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    raw_footage: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub(super) raw_footage: Option<Option<String>>,
 }
 
 impl FullRecord {
@@ -64,7 +67,10 @@ impl FullRecord {
         }
 
         if let Some(raw_footage) = data.raw_footage {
-            self.set_raw(raw_footage, connection).await?;
+            match raw_footage {
+                None => self.delete_raw(connection).await?,
+                Some(raw_footage) => self.set_raw(raw_footage, connection).await?,
+            }
         }
 
         match (data.demon, data.demon_id) {
@@ -193,10 +199,11 @@ impl FullRecord {
 
     pub async fn delete_video(&mut self, connection: &mut PgConnection) -> Result<()> {
         sqlx::query!("UPDATE records SET video = NULL WHERE id = $1", self.id)
-            .execute(connection)
+            .execute(&mut *connection)
             .await?;
 
         self.video = None;
+        self.validation = VideoValidation::skip(self.id, connection).await?;
 
         Ok(())
     }
@@ -208,28 +215,37 @@ impl FullRecord {
             return Ok(())
         }
 
-        if let Some(row) = sqlx::query!(r#"SELECT id FROM records WHERE video = $1"#, video.to_string())
-            .fetch_optional(&mut *connection)
-            .await?
-        {
-            return Err(PointercrateError::DuplicateVideo { id: row.id })
-        }
+        FullRecord::require_unused_video(&video, &mut *connection).await?;
 
         sqlx::query!("UPDATE records SET video = $1::text WHERE id = $2", video, self.id)
-            .execute(connection)
+            .execute(&mut *connection)
             .await?;
 
         self.video = Some(video);
+        // Whatever the previous check found out was about the old video
+        self.validation = Some(VideoValidation::enqueue(self.id, connection).await?);
+
+        Ok(())
+    }
+
+    pub async fn delete_raw(&mut self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("UPDATE records SET raw_footage = NULL WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        self.raw_footage = None;
 
         Ok(())
     }
 
     pub async fn set_raw(&mut self, raw: String, connection: &mut PgConnection) -> Result<()> {
+        let raw = RawFootage::validate(&raw)?;
+
         if Some(&raw) == self.raw_footage.as_ref() {
             return Ok(())
         }
 
-        sqlx::query!("UPDATE records SET raw_footage = $1::text WHERE id = $2", raw, self.id)
+        sqlx::query!("UPDATE records SET raw_footage = $1::text WHERE id = $2", raw.url, self.id)
             .execute(connection)
             .await?;
 
@@ -245,7 +261,7 @@ impl FullRecord {
             return Err(PointercrateError::InvalidProgress { requirement })
         }
 
-        self.ensure_invariants(self.player.id, self.demon.id, connection).await?;
+        self.ensure_invariants(self.player.id, demon.id, connection).await?;
 
         sqlx::query!("UPDATE records SET demon = $1 WHERE id = $2", demon.id, self.id)
             .execute(connection)
@@ -351,11 +367,21 @@ impl FullRecord {
             status.to_sql().to_string(),
             self.id
         )
-        .execute(connection)
+        .execute(&mut *connection)
         .await?;
 
+        let previous_status = self.status;
+
         self.status = status;
 
+        if previous_status != status {
+            match status {
+                RecordStatus::Approved => webhook::dispatch(Event::record(WebhookEvent::RecordApproved, self), connection).await?,
+                RecordStatus::Rejected => webhook::dispatch(Event::record(WebhookEvent::RecordRejected, self), connection).await?,
+                _ => (),
+            }
+        }
+
         Ok(())
     }
 
@@ -1,12 +1,12 @@
 use crate::{
     cistring::CiString,
-    config,
     error::PointercrateError,
     model::demonlist::{
         demon::MinimalDemon,
         player::DatabasePlayer,
-        record::{note::Note, FullRecord, RecordStatus},
-        submitter::Submitter,
+        record::{note::Note, FullRecord, RawFootage, RecordStatus, VideoValidation},
+        section::ListSection,
+        submitter::{Submitter, TrustLevel},
     },
     ratelimit::{PreparedRatelimits, RatelimitScope},
     Result,
@@ -24,7 +24,7 @@ pub struct Submission {
     pub demon: i32,
 
     #[serde(default)]
-    pub raw_footage: String,
+    pub raw_footage: Option<String>,
     #[serde(default)]
     pub video: Option<String>,
     #[serde(default)]
@@ -36,7 +36,7 @@ pub struct Submission {
 
 impl FullRecord {
     pub async fn create_from(
-        submitter: Submitter, submission: Submission, connection: &mut PgConnection, ratelimits: Option<PreparedRatelimits<'_>>,
+        submitter: Submitter, submission: Submission, connection: &mut PgConnection, ratelimits: Option<&PreparedRatelimits<'_>>,
     ) -> Result<FullRecord> {
         info!("Processing record addition '{}' by {}", submission, submitter);
 
@@ -45,10 +45,14 @@ impl FullRecord {
             return Err(PointercrateError::BannedFromSubmissions)
         }
 
-        // validate raw footage (later)
-        // todo: uuuuuuuu
-        let raw_footage = Some(submission.raw_footage);
-        
+        let trust = submitter.statistics(&mut *connection).await?.trust;
+
+        // validate raw footage
+        let raw_footage = match submission.raw_footage {
+            Some(ref raw_footage) => Some(RawFootage::validate(raw_footage)?),
+            None => None,
+        };
+
         // validate video
         let video = match submission.video {
             Some(ref video) => Some(crate::video::validate(video)?),
@@ -65,24 +69,31 @@ impl FullRecord {
             return Err(PointercrateError::PlayerBanned)
         }
 
-        // Cannot submit records for the legacy list (it is possible to directly add them for list mods)
-        if demon.position > config::extended_list_size() && submission.status == RecordStatus::Submitted {
-            // do nothing
-            // return Err(PointercrateError::SubmitLegacy)
-        }
+        // Check the submission rules of the section the demon is in (it is possible to directly add records
+        // that violate them for list mods)
+        if submission.status == RecordStatus::Submitted {
+            if let Some(section) = ListSection::of_position(demon.position, &mut *connection).await? {
+                if !section.submissions_allowed {
+                    return Err(PointercrateError::SubmissionsClosed { section: section.name })
+                }
 
-        // Can only submit 100% records for the extended list (it is possible to directly add them for list
-        // mods)
-        if demon.position > config::list_size() && submission.progress != 100 && submission.status == RecordStatus::Submitted {
-            return Err(PointercrateError::Non100Extended)
+                if let Some(min_progress) = section.min_progress {
+                    if submission.progress < min_progress {
+                        return Err(PointercrateError::SectionRequirement {
+                            section: section.name,
+                            requirement: min_progress,
+                        })
+                    }
+                }
+            }
         }
 
         let requirement = demon.requirement(&mut *connection).await?;
 
-        // Check if the record meets the record requirement for this demon
-        if submission.progress > 100 || submission.progress < 100 {
-            let new_req = 100;
-            return Err(PointercrateError::InvalidProgress { requirement: new_req })
+        // Check if the record meets the record requirement for this demon (100% for completion only
+        // demons)
+        if submission.progress > 100 || submission.progress < requirement {
+            return Err(PointercrateError::InvalidProgress { requirement })
         }
 
         debug!("Submission is valid, checking for duplicates!");
@@ -104,11 +115,21 @@ impl FullRecord {
             })
         }
 
+        // The same video cannot be proof for multiple records (this also catches videos used for
+        // a different player or demon, which the above doesn't)
+        if let Some(ref video) = video {
+            FullRecord::require_unused_video(video, &mut *connection).await?;
+        }
+
         // Check ratelimits before any change is made to the database so that the transaction rollback is
         // easier.
         if let Some(ratelimits) = ratelimits {
-            ratelimits.check(RatelimitScope::RecordSubmissionGlobal)?;
-            ratelimits.check(RatelimitScope::RecordSubmission)?;
+            if trust == TrustLevel::Trusted {
+                ratelimits.mark_trusted();
+            }
+
+            ratelimits.check(RatelimitScope::RecordSubmissionGlobal).await?;
+            ratelimits.check(RatelimitScope::RecordSubmission).await?;
         }
 
         let id = sqlx::query(
@@ -120,7 +141,7 @@ impl FullRecord {
         .bind(player.id)
         .bind(submitter.id)
         .bind(demon.id)
-        .bind(&raw_footage)
+        .bind(raw_footage.as_ref().map(|raw_footage| &raw_footage.url))
         .fetch_one(&mut *connection)
         .await?
         .get("id");
@@ -132,15 +153,27 @@ impl FullRecord {
             status: RecordStatus::Submitted,
             player,
             demon,
-            raw_footage: raw_footage,
+            raw_footage,
             submitter: Some(submitter),
             notes: Vec::new(),
+            validation: None,
+            claim: None,
         };
 
+        // Submissions get their video checked in the background. Records added directly by list
+        // helpers are trusted
+        if submission.status == RecordStatus::Submitted && record.video.is_some() {
+            record.validation = Some(VideoValidation::enqueue(record.id, &mut *connection).await?);
+        }
+
         // Dealing with different status and upholding their invariant is complicated, we should not
         // duplicate that code!
         if submission.status != RecordStatus::Submitted {
             record.set_status(submission.status, &mut *connection).await?;
+        } else if trust == TrustLevel::Flagged {
+            info!("{} is flagged, putting submission {} under consideration", submitter, record.id);
+
+            record.set_status(RecordStatus::UnderConsideration, &mut *connection).await?;
         }
 
         if let Some(note) = submission.note {
@@ -168,3 +201,88 @@ impl FullRecord {
         Ok(record)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cistring::CiString,
+        error::PointercrateError,
+        model::demonlist::{
+            record::{FullRecord, RecordStatus, Submission},
+            submitter::Submitter,
+        },
+    };
+
+    #[actix_rt::test]
+    async fn test_video_reused_for_other_player() {
+        let mut connection = crate::test::test_setup().await;
+
+        let row = sqlx::query!(r#"SELECT id, video AS "video!: String", demon, submitter FROM records WHERE video IS NOT NULL LIMIT 1"#)
+            .fetch_one(&mut connection)
+            .await
+            .unwrap();
+
+        let submitter = Submitter::by_id(row.submitter, &mut connection).await.unwrap();
+
+        let result = FullRecord::create_from(
+            submitter,
+            Submission {
+                progress: 100,
+                player: CiString("Someone else entirely".to_string()),
+                demon: row.demon,
+                raw_footage: None,
+                video: Some(row.video),
+                status: RecordStatus::Approved,
+                note: None,
+            },
+            &mut connection,
+            None,
+        )
+        .await;
+
+        assert!(matches!(result, Err(PointercrateError::DuplicateVideo { id, .. }) if id == row.id));
+    }
+
+    #[actix_rt::test]
+    async fn test_video_reused_as_youtube_short() {
+        let mut connection = crate::test::test_setup().await;
+
+        let row = sqlx::query!("SELECT demon, submitter FROM records LIMIT 1")
+            .fetch_one(&mut connection)
+            .await
+            .unwrap();
+
+        let submission = |player: &str, video: &str| {
+            Submission {
+                progress: 100,
+                player: CiString(player.to_string()),
+                demon: row.demon,
+                raw_footage: None,
+                video: Some(video.to_string()),
+                status: RecordStatus::Approved,
+                note: None,
+            }
+        };
+
+        let submitter = Submitter::by_id(row.submitter, &mut connection).await.unwrap();
+        let record = FullRecord::create_from(
+            submitter,
+            submission("Someone", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            &mut connection,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let submitter = Submitter::by_id(row.submitter, &mut connection).await.unwrap();
+        let result = FullRecord::create_from(
+            submitter,
+            submission("Someone else entirely", "https://youtube.com/shorts/dQw4w9WgXcQ"),
+            &mut connection,
+            None,
+        )
+        .await;
+
+        assert!(matches!(result, Err(PointercrateError::DuplicateVideo { id, .. }) if id == record.id));
+    }
+}
@@ -0,0 +1,171 @@
+//! Module containing the raw footage attached to records
+//!
+//! Raw footage is the unedited recording of a completion, which list helpers use to verify records
+//! whose video alone isn't conclusive. Since it might contain personal information (e.g. a player's
+//! desktop), it is only ever shown to members of the list team.
+//!
+//! Just like videos, raw footage links are validated and normalized before they are stored. Only
+//! links to file hosts we know how to deal with are accepted, see [`StorageHost`].
+
+use crate::{error::PointercrateError, Result};
+use serde::Serialize;
+use url::Url;
+
+const SCHEMES: [&str; 2] = ["http", "https"];
+const GOOGLE_DRIVE_FORMAT: &str = "https://drive.google.com/file/d/{file_id}' or \
+                                   'https://drive.google.com/open?id={file_id}' or \
+                                   'https://drive.google.com/drive/folders/{folder_id}";
+const MEGA_FORMAT: &str = "https://mega.nz/file/{file_id}#{key}' or 'https://mega.nz/folder/{folder_id}#{key}";
+const DROPBOX_FORMAT: &str = "https://www.dropbox.com/s/{file_id}/{file_name}' or \
+                              'https://www.dropbox.com/scl/fi/{file_id}/{file_name}?rlkey={key}' or \
+                              'https://www.dropbox.com/sh/{folder_id}/{key}";
+
+/// File extensions we accept for direct links to video files
+const VIDEO_EXTENSIONS: [&str; 7] = ["mp4", "mov", "mkv", "webm", "avi", "flv", "wmv"];
+
+/// The places raw footage can be stored at
+#[derive(Debug, Serialize, Hash, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageHost {
+    GoogleDrive,
+    Mega,
+    Dropbox,
+
+    /// A direct link to a video file, hosted anywhere
+    Direct,
+
+    /// Raw footage stored before validation was introduced, which doesn't match any of the above
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Hash, Eq, PartialEq, Clone)]
+pub struct RawFootage {
+    /// The normalized link to the raw footage
+    pub url: String,
+
+    /// Where the raw footage is stored. Derived from [`RawFootage::url`]
+    pub host: StorageHost,
+}
+
+impl RawFootage {
+    /// Validates the given raw footage link and brings it into a normalized form
+    pub fn validate(url: &str) -> Result<RawFootage> {
+        let url = Url::parse(url.trim()).map_err(|_| {
+            PointercrateError::BadRequest {
+                message: "Malformed raw footage URL".to_owned(),
+            }
+        })?;
+
+        if !SCHEMES.contains(&url.scheme()) {
+            return Err(PointercrateError::InvalidUrlScheme)
+        }
+
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err(PointercrateError::UrlAuthenticated)
+        }
+
+        let host = url.domain().ok_or(PointercrateError::UnprocessableEntity)?;
+        let segments = url.path_segments().map(|segments| segments.filter(|s| !s.is_empty()).collect::<Vec<_>>()).unwrap_or_default();
+        let query = |name: &str| url.query_pairs().find_map(|(key, value)| if key == name { Some(value.into_owned()) } else { None });
+
+        let (url, host) = match host {
+            "drive.google.com" =>
+                (
+                    match &segments[..] {
+                        ["file", "d", file_id, ..] => format!("https://drive.google.com/file/d/{}/view", file_id),
+                        ["drive", "folders", folder_id] | ["drive", "u", _, "folders", folder_id] =>
+                            format!("https://drive.google.com/drive/folders/{}", folder_id),
+                        ["open"] | ["uc"] =>
+                            match query("id") {
+                                Some(file_id) => format!("https://drive.google.com/file/d/{}/view", file_id),
+                                None => return Err(PointercrateError::InvalidUrlFormat { expected: GOOGLE_DRIVE_FORMAT }),
+                            },
+                        _ => return Err(PointercrateError::InvalidUrlFormat { expected: GOOGLE_DRIVE_FORMAT }),
+                    },
+                    StorageHost::GoogleDrive,
+                ),
+            "mega.nz" | "www.mega.nz" | "mega.co.nz" | "www.mega.co.nz" => {
+                let fragment = url.fragment().unwrap_or_default();
+                let fragment_parts = fragment.split('!').collect::<Vec<_>>();
+
+                (
+                    match (&segments[..], &fragment_parts[..]) {
+                        (["file", file_id], _) if !fragment.is_empty() => format!("https://mega.nz/file/{}#{}", file_id, fragment),
+                        (["folder", folder_id], _) if !fragment.is_empty() => format!("https://mega.nz/folder/{}#{}", folder_id, fragment),
+                        // Old style links, e.g. https://mega.nz/#!{file_id}!{key}
+                        ([], ["", file_id, key]) => format!("https://mega.nz/file/{}#{}", file_id, key),
+                        ([], ["F", folder_id, key]) => format!("https://mega.nz/folder/{}#{}", folder_id, key),
+                        _ => return Err(PointercrateError::InvalidUrlFormat { expected: MEGA_FORMAT }),
+                    },
+                    StorageHost::Mega,
+                )
+            },
+            "www.dropbox.com" | "dropbox.com" =>
+                (
+                    match &segments[..] {
+                        ["s", file_id, file_name] => format!("https://www.dropbox.com/s/{}/{}", file_id, file_name),
+                        ["sh", folder_id, key, ..] => format!("https://www.dropbox.com/sh/{}/{}", folder_id, key),
+                        ["scl", "fi", file_id, file_name] =>
+                            match query("rlkey") {
+                                Some(key) => format!("https://www.dropbox.com/scl/fi/{}/{}?rlkey={}", file_id, file_name, key),
+                                None => return Err(PointercrateError::InvalidUrlFormat { expected: DROPBOX_FORMAT }),
+                            },
+                        _ => return Err(PointercrateError::InvalidUrlFormat { expected: DROPBOX_FORMAT }),
+                    },
+                    StorageHost::Dropbox,
+                ),
+            _ if is_video_file(&segments) => (url.to_string(), StorageHost::Direct),
+            _ => return Err(PointercrateError::UnsupportedRawFootageHost),
+        };
+
+        Ok(RawFootage { url, host })
+    }
+
+    /// Wraps raw footage read from the database
+    ///
+    /// Raw footage stored before validation was introduced might not be in any of the formats we
+    /// accept nowadays, so this never fails and falls back to [`StorageHost::Unknown`]. The stored
+    /// URL is returned as-is.
+    pub fn from_stored(url: String) -> RawFootage {
+        let host = match RawFootage::validate(&url) {
+            Ok(raw_footage) => raw_footage.host,
+            Err(_) => StorageHost::Unknown,
+        };
+
+        RawFootage { url, host }
+    }
+}
+
+fn is_video_file(segments: &[&str]) -> bool {
+    match segments.last() {
+        Some(file_name) if file_name.contains('.') => {
+            let extension = file_name.rsplit('.').next().unwrap_or_default().to_lowercase();
+
+            VIDEO_EXTENSIONS.contains(&&extension[..])
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RawFootage, StorageHost};
+
+    #[test]
+    fn test_normalization() {
+        let raw = RawFootage::validate("https://drive.google.com/open?id=1SJxMqUuh0QgbilyrrDIFsQZQV3XhtXpK").unwrap();
+
+        assert_eq!(raw.host, StorageHost::GoogleDrive);
+        assert_eq!(raw.url, "https://drive.google.com/file/d/1SJxMqUuh0QgbilyrrDIFsQZQV3XhtXpK/view");
+
+        let raw = RawFootage::validate("https://mega.nz/#!abcdef!key123").unwrap();
+
+        assert_eq!(raw.host, StorageHost::Mega);
+        assert_eq!(raw.url, "https://mega.nz/file/abcdef#key123");
+
+        let raw = RawFootage::validate("https://example.com/raws/completion.MP4").unwrap();
+
+        assert_eq!(raw.host, StorageHost::Direct);
+        assert!(RawFootage::validate("https://example.com/raws/").is_err());
+    }
+}
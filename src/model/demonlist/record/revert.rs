@@ -0,0 +1,288 @@
+//! Module for undoing changes to records based on the audit log
+//!
+//! For every modification of a record, the `record_modifications` table stores the values the
+//! changed fields had _before_ the change (all other fields are `NULL`). The state of a record
+//! directly before some modification can therefore be reconstructed by walking the log forward from
+//! that modification and taking, for each field, the first value we encounter. Fields that were
+//! never changed afterwards keep their current value.
+//!
+//! Deleting a record adds a copy of the deleted record to `record_modifications` directly before
+//! the entry in `record_deletions`, which is what we use to restore deleted records.
+//!
+//! All changes are made via [`FullRecord::apply_patch`], so that the invariants described in the
+//! module documentation of [`crate::model::demonlist::record`] still hold afterwards. Note that
+//! this means that records deleted to uphold these invariants when the original change was made are
+//! not brought back by a revert.
+
+use crate::{
+    error::PointercrateError,
+    model::demonlist::{
+        demon::MinimalDemon,
+        player::DatabasePlayer,
+        record::{FullRecord, PatchRecord, RecordStatus},
+        submitter::Submitter,
+    },
+    ratelimit::PreparedRatelimits,
+    Result,
+};
+use log::info;
+use sqlx::PgConnection;
+use std::net::IpAddr;
+
+impl FullRecord {
+    /// Reverts this record to the state it had directly before the modification with the given
+    /// audit id
+    ///
+    /// Must be called inside a transaction
+    pub async fn revert_to(self, audit_id: i32, connection: &mut PgConnection) -> Result<FullRecord> {
+        let modifications = sqlx::query!(
+            "SELECT audit_id, progress, video::text, status_::text, player, demon FROM record_modifications WHERE id = $1 AND audit_id >= \
+             $2 ORDER BY audit_id",
+            self.id,
+            audit_id
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+
+        if modifications.first().map(|modification| modification.audit_id) != Some(audit_id) {
+            return Err(PointercrateError::ModelNotFound {
+                model: "RecordModification",
+                identified_by: audit_id.to_string(),
+            })
+        }
+
+        let mut progress = None;
+        let mut video = None;
+        let mut status = None;
+        let mut player = None;
+        let mut demon = None;
+
+        for modification in modifications {
+            progress = progress.or(modification.progress);
+            video = video.or(modification.video);
+            status = status.or(modification.status_);
+            player = player.or(modification.player);
+            demon = demon.or(modification.demon);
+        }
+
+        let mut patch = PatchRecord::default();
+
+        if let Some(progress) = progress {
+            if progress != self.progress {
+                patch.progress = Some(progress);
+            }
+        }
+
+        if let Some(video) = video {
+            if Some(&video) != self.video.as_ref() {
+                patch.video = Some(Some(video));
+            }
+        }
+
+        if let Some(status) = status {
+            let status = RecordStatus::from_sql(&status);
+
+            if status != self.status {
+                patch.status = Some(status);
+            }
+        }
+
+        if let Some(player) = player {
+            if player != self.player.id {
+                patch.player = Some(DatabasePlayer::by_id(player, &mut *connection).await?.name);
+            }
+        }
+
+        if let Some(demon) = demon {
+            if demon != self.demon.id {
+                patch.demon_id = Some(demon);
+            }
+        }
+
+        info!("Reverting record {} to its state before audit log entry {}: {:?}", self, audit_id, patch);
+
+        self.apply_patch(patch, connection).await
+    }
+
+    /// Restores the deleted record with the given id
+    ///
+    /// Records deleted before the submitter of deleted records was logged are attributed to
+    /// `fallback_submitter`.
+    ///
+    /// Must be called inside a transaction
+    pub async fn restore(
+        record_id: i32, fallback_ip: IpAddr, ratelimits: Option<&PreparedRatelimits<'_>>, connection: &mut PgConnection,
+    ) -> Result<FullRecord> {
+        let deletion = sqlx::query!(
+            "SELECT audit_id, submitter, raw_footage FROM record_deletions WHERE id = $1 ORDER BY audit_id DESC LIMIT 1",
+            record_id
+        )
+        .fetch_optional(&mut *connection)
+        .await?
+        .ok_or_else(|| {
+            PointercrateError::ModelNotFound {
+                model: "RecordDeletion",
+                identified_by: record_id.to_string(),
+            }
+        })?;
+
+        if sqlx::query!("SELECT id FROM records WHERE id = $1", record_id)
+            .fetch_optional(&mut *connection)
+            .await?
+            .is_some()
+        {
+            return Err(PointercrateError::RecordExists { id: record_id })
+        }
+
+        // The copy made by the deletion trigger is the last modification logged before the deletion
+        let copy = sqlx::query!(
+            r#"SELECT progress AS "progress!", video::text, status_::text AS "status_!", player AS "player!", demon AS "demon!" FROM
+             record_modifications WHERE id = $1 AND audit_id < $2 ORDER BY audit_id DESC LIMIT 1"#,
+            record_id,
+            deletion.audit_id
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+
+        let status = RecordStatus::from_sql(&copy.status_);
+        let player = DatabasePlayer::by_id(copy.player, &mut *connection).await?;
+        let demon = MinimalDemon::by_id(copy.demon, &mut *connection).await?;
+
+        if player.banned && status != RecordStatus::Rejected {
+            return Err(PointercrateError::PlayerBanned)
+        }
+
+        let submitter = match deletion.submitter {
+            Some(submitter) => Submitter::by_id(submitter, &mut *connection).await?,
+            // The deletion predates us logging the submitters of deleted records, so attribute the
+            // restored record to whoever restores it
+            None => Submitter::by_ip_or_create(fallback_ip, &mut *connection, ratelimits).await?,
+        };
+
+        info!("Restoring deleted record {} (deleted in audit log entry {})", record_id, deletion.audit_id);
+
+        // Like with new submissions, we insert the record as 'submitted' and let `apply_patch` deal with
+        // the status change. Same goes for the video, which might have been claimed by another record
+        // in the meantime
+        sqlx::query!(
+            "INSERT INTO records (id, progress, status_, player, submitter, demon, raw_footage) VALUES ($1, $2, 'SUBMITTED', $3, $4, $5, $6)",
+            record_id,
+            copy.progress,
+            player.id,
+            submitter.id,
+            demon.id,
+            deletion.raw_footage
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        let record = FullRecord::by_id(record_id, &mut *connection).await?;
+
+        let mut patch = PatchRecord::default();
+
+        patch.video = copy.video.map(Some);
+
+        if status != RecordStatus::Submitted {
+            patch.status = Some(status);
+        }
+
+        record.apply_patch(patch, connection).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::demonlist::{
+        demon::MinimalDemon,
+        record::{FullRecord, RecordStatus},
+    };
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[actix_rt::test]
+    async fn test_restore_deleted_record() {
+        let mut connection = crate::test::test_setup().await;
+
+        let id = sqlx::query!("SELECT id FROM records WHERE status_ = 'REJECTED'")
+            .fetch_one(&mut connection)
+            .await
+            .unwrap()
+            .id;
+
+        let record = FullRecord::by_id(id, &mut connection).await.unwrap();
+        let submitter = record.submitter.as_ref().unwrap().id;
+
+        record.delete(&mut connection).await.unwrap();
+
+        let result = FullRecord::restore(id, IpAddr::V4(Ipv4Addr::LOCALHOST), None, &mut connection).await;
+
+        assert!(result.is_ok(), "{:?}", result.unwrap_err());
+
+        let record = result.unwrap();
+
+        assert_eq!(record.status, RecordStatus::Rejected);
+        // The deletion logged the submitter, so the fallback must not have been used
+        assert_eq!(record.submitter.map(|submitter| submitter.id), Some(submitter));
+    }
+
+    #[actix_rt::test]
+    async fn test_revert_demon_change() {
+        let mut connection = crate::test::test_setup().await;
+
+        let row = sqlx::query!(
+            "SELECT records.id, records.player, records.demon, records.submitter FROM records INNER JOIN players ON records.player = \
+             players.id WHERE players.name = 'stardust1971' AND status_ = 'APPROVED' AND progress = 100"
+        )
+        .fetch_one(&mut connection)
+        .await
+        .unwrap();
+
+        let other_demon = sqlx::query!("SELECT id FROM demons WHERE name = 'terminal void'")
+            .fetch_one(&mut connection)
+            .await
+            .unwrap()
+            .id;
+
+        let mut record = FullRecord::by_id(row.id, &mut connection).await.unwrap();
+
+        record
+            .set_demon(MinimalDemon::by_id(other_demon, &mut connection).await.unwrap(), &mut connection)
+            .await
+            .unwrap();
+
+        let audit_id = sqlx::query!(
+            "SELECT audit_id FROM record_modifications WHERE id = $1 ORDER BY audit_id DESC LIMIT 1",
+            row.id
+        )
+        .fetch_one(&mut connection)
+        .await
+        .unwrap()
+        .audit_id;
+
+        // After the demon was changed, the player got another record on the original demon
+        sqlx::query!(
+            "INSERT INTO records (progress, status_, player, submitter, demon) VALUES (100, 'APPROVED', $1, $2, $3)",
+            row.player,
+            row.submitter,
+            row.demon
+        )
+        .execute(&mut connection)
+        .await
+        .unwrap();
+
+        let record = record.revert_to(audit_id, &mut connection).await.unwrap();
+
+        assert_eq!(record.demon.id, row.demon);
+
+        let records = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM records WHERE player = $1 AND demon = $2 AND status_ = 'APPROVED'"#,
+            row.player,
+            row.demon
+        )
+        .fetch_one(&mut connection)
+        .await
+        .unwrap()
+        .count;
+
+        assert_eq!(records, 1);
+    }
+}
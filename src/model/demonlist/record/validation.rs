@@ -0,0 +1,324 @@
+//! Module containing the job queue used to check that the videos of new submissions actually exist
+//!
+//! When a record is submitted, a job is queued in the same transaction. The jobs are processed by a
+//! background task in [`crate::scheduler`]. Submissions are never deleted because of a failed check:
+//! If the video host tells us the video does not exist, the job ends up as
+//! [`ValidationStatus::NeedsReview`]. If the check keeps failing for reasons that might be temporary
+//! (network errors, server errors, rate limits), it is retried with exponential backoff until it
+//! runs out of attempts, at which point it is moved to [`ValidationStatus::Dead`]. Both outcomes are
+//! visible to list helpers in the records manager, and both can be re-queued from there. Changing a
+//! record's video re-queues its job, and removing it marks the job as [`ValidationStatus::Skipped`].
+
+use crate::{
+    error::PointercrateError,
+    model::{
+        demonlist::record::FullRecord,
+        webhook::{self, Event, WebhookEvent},
+    },
+    state::PointercrateState,
+    Result,
+};
+use chrono::NaiveDateTime;
+use log::{debug, error, info, warn};
+use serde::{Serialize, Serializer};
+use sqlx::{Done, PgConnection};
+use std::time::Duration;
+
+/// How often we try to check a video before moving its job to the dead-letter state
+const MAX_ATTEMPTS: i16 = 6;
+
+/// The delay before the first retry. Doubles with every further attempt
+const BASE_RETRY_DELAY: u64 = 60;
+
+/// How many jobs are processed per tick of the validation task
+const VALIDATION_BATCH_SIZE: i64 = 10;
+
+/// How many seconds we wait for a video host to respond to a single request
+const CHECK_TIMEOUT: u64 = 15;
+
+/// How many seconds a batch of jobs stays claimed by the instance processing it. Needs to be larger
+/// than the time it takes to work through a batch whose checks all time out (each check makes up to
+/// two requests)
+const VALIDATION_LEASE: u64 = 2 * 2 * CHECK_TIMEOUT * VALIDATION_BATCH_SIZE as u64;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum ValidationStatus {
+    Pending,
+    Valid,
+    NeedsReview,
+    Dead,
+    Skipped,
+}
+
+impl ValidationStatus {
+    fn to_sql(&self) -> String {
+        match self {
+            ValidationStatus::Pending => "PENDING",
+            ValidationStatus::Valid => "VALID",
+            ValidationStatus::NeedsReview => "NEEDS_REVIEW",
+            ValidationStatus::Dead => "DEAD",
+            ValidationStatus::Skipped => "SKIPPED",
+        }
+        .to_owned()
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "PENDING" => ValidationStatus::Pending,
+            "VALID" => ValidationStatus::Valid,
+            "NEEDS_REVIEW" => ValidationStatus::NeedsReview,
+            "DEAD" => ValidationStatus::Dead,
+            "SKIPPED" => ValidationStatus::Skipped,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Serialize for ValidationStatus {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(match self {
+            ValidationStatus::Pending => "pending",
+            ValidationStatus::Valid => "valid",
+            ValidationStatus::NeedsReview => "needs review",
+            ValidationStatus::Dead => "dead",
+            ValidationStatus::Skipped => "skipped",
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VideoValidation {
+    pub status: ValidationStatus,
+    pub attempts: i16,
+    pub last_status: Option<i16>,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub next_attempt_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// The result of a single attempt at checking a video
+enum Outcome {
+    /// The host confirmed the video exists
+    Valid(i16),
+
+    /// The host claims the video does not exist
+    Missing(i16, String),
+
+    /// Something went wrong that might go away if we try again later
+    Transient(Option<i16>, String),
+
+    /// The record no longer has a video, so there is nothing to check
+    Skipped,
+}
+
+impl VideoValidation {
+    pub async fn of(record_id: i32, connection: &mut PgConnection) -> Result<Option<VideoValidation>> {
+        let row = sqlx::query!(
+            r#"SELECT status::text AS "status!: String", attempts, last_status, last_error, created_at, next_attempt_at, finished_at FROM
+             video_validation_jobs WHERE record = $1"#,
+            record_id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        Ok(row.map(|row| {
+            VideoValidation {
+                status: ValidationStatus::from_sql(&row.status),
+                attempts: row.attempts,
+                last_status: row.last_status,
+                last_error: row.last_error,
+                created_at: row.created_at,
+                next_attempt_at: row.next_attempt_at,
+                finished_at: row.finished_at,
+            }
+        }))
+    }
+
+    /// Queues a check of the given record's video, resetting any previous check
+    pub async fn enqueue(record_id: i32, connection: &mut PgConnection) -> Result<VideoValidation> {
+        sqlx::query!(
+            "INSERT INTO video_validation_jobs (record) VALUES ($1) ON CONFLICT (record) DO UPDATE SET status = 'PENDING', attempts = 0, \
+             last_status = NULL, last_error = NULL, next_attempt_at = (NOW() AT TIME ZONE 'utc'), finished_at = NULL",
+            record_id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        VideoValidation::of(record_id, connection)
+            .await?
+            .ok_or(PointercrateError::InternalServerError)
+    }
+
+    /// Marks the check of the given record's video as skipped, if one was ever queued. Used when the
+    /// video is removed from the record
+    pub async fn skip(record_id: i32, connection: &mut PgConnection) -> Result<Option<VideoValidation>> {
+        sqlx::query!(
+            "UPDATE video_validation_jobs SET status = 'SKIPPED', last_status = NULL, last_error = NULL, finished_at = (NOW() AT TIME ZONE \
+             'utc') WHERE record = $1",
+            record_id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        VideoValidation::of(record_id, connection).await
+    }
+}
+
+async fn check(state: &PointercrateState, video: &str) -> Outcome {
+    let response = match state.http_client.head(video).timeout(Duration::from_secs(CHECK_TIMEOUT)).send().await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+
+            if status == 401 || status == 403 || status == 405 {
+                // Some websites (billibilli) respond unfavorably to HEAD requests. Retry with GET
+                state.http_client.get(video).timeout(Duration::from_secs(CHECK_TIMEOUT)).send().await
+            } else {
+                Ok(response)
+            }
+        },
+        Err(err) => Err(err),
+    };
+
+    match response {
+        Ok(response) => {
+            let status = response.status().as_u16();
+
+            if status >= 200 && status < 400 {
+                Outcome::Valid(status as i16)
+            } else if status == 429 || status >= 500 {
+                Outcome::Transient(Some(status as i16), format!("Server responded with {}", response.status()))
+            } else {
+                Outcome::Missing(status as i16, format!("Server responded with {}", response.status()))
+            }
+        },
+        Err(err) => Outcome::Transient(None, err.to_string()),
+    }
+}
+
+/// Processes all validation jobs that are due. Called periodically by the scheduler
+///
+/// Due jobs are first claimed by pushing their next attempt back by `VALIDATION_LEASE` seconds, so
+/// that other instances running this task concurrently skip them. The database connection is
+/// released while the videos are being checked. Since re-queueing a job resets its next attempt,
+/// the results are only stored if the job still holds our lease, as otherwise they might be about a
+/// video the record no longer has.
+pub async fn process_validation_jobs(state: &PointercrateState) -> Result<()> {
+    let mut connection = state.connection().await?;
+
+    let due = sqlx::query!(
+        r#"UPDATE video_validation_jobs SET next_attempt_at = (NOW() AT TIME ZONE 'utc') + make_interval(secs => $2) FROM records WHERE
+         records.id = video_validation_jobs.record AND video_validation_jobs.record IN (SELECT record FROM video_validation_jobs WHERE
+         status = 'PENDING' AND next_attempt_at <= (NOW() AT TIME ZONE 'utc') ORDER BY next_attempt_at LIMIT $1 FOR UPDATE SKIP LOCKED)
+         RETURNING video_validation_jobs.record, video_validation_jobs.attempts, video_validation_jobs.next_attempt_at AS "lease!",
+         records.video::text"#,
+        VALIDATION_BATCH_SIZE,
+        VALIDATION_LEASE as f64
+    )
+    .fetch_all(&mut connection)
+    .await?;
+
+    drop(connection);
+
+    for job in due {
+        let attempts = job.attempts + 1;
+
+        let outcome = match job.video {
+            Some(ref video) => {
+                debug!("Verifying that video {} of record {} actually exists", video, job.record);
+
+                check(state, video).await
+            },
+            // The video was removed from the record after the job was queued. Nothing to check
+            None => Outcome::Skipped,
+        };
+
+        let mut connection = state.connection().await?;
+
+        let (status, last_status, last_error) = match outcome {
+            Outcome::Valid(status) => (ValidationStatus::Valid, Some(status), None),
+            Outcome::Skipped => (ValidationStatus::Skipped, None, None),
+            Outcome::Missing(status, error) => {
+                warn!("Video of record {} seems to be missing ({}), flagging it for review", job.record, error);
+
+                (ValidationStatus::NeedsReview, Some(status), Some(error))
+            },
+            Outcome::Transient(status, error) if attempts >= MAX_ATTEMPTS => {
+                error!(
+                    "Giving up on validating video of record {} after {} attempts: {}",
+                    job.record, attempts, error
+                );
+
+                (ValidationStatus::Dead, status, Some(error))
+            },
+            Outcome::Transient(status, error) => {
+                let delay = BASE_RETRY_DELAY << (attempts - 1) as u64;
+
+                info!(
+                    "Failed to validate video of record {} (attempt {}): {}. Retrying in {}s",
+                    job.record, attempts, error, delay
+                );
+
+                sqlx::query!(
+                    "UPDATE video_validation_jobs SET attempts = $2, last_status = $3, last_error = $4, next_attempt_at = (NOW() AT TIME \
+                     ZONE 'utc') + make_interval(secs => $5) WHERE record = $1 AND status = 'PENDING' AND next_attempt_at = $6",
+                    job.record,
+                    attempts,
+                    status,
+                    error,
+                    delay as f64,
+                    job.lease
+                )
+                .execute(&mut connection)
+                .await?;
+
+                continue
+            },
+        };
+
+        let updated = sqlx::query!(
+            "UPDATE video_validation_jobs SET status = cast($2::text as validation_status), attempts = $3, last_status = $4, last_error = \
+             $5, finished_at = (NOW() AT TIME ZONE 'utc') WHERE record = $1 AND status = 'PENDING' AND next_attempt_at = $6",
+            job.record,
+            status.to_sql(),
+            attempts,
+            last_status,
+            last_error,
+            job.lease
+        )
+        .execute(&mut connection)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            info!(
+                "Validation job of record {} was re-queued or removed while we were checking its video, discarding the result",
+                job.record
+            );
+
+            continue
+        }
+
+        if status == ValidationStatus::Valid {
+            // Only announce records that are still awaiting review, and only once. Jobs are also re-queued
+            // when the video of a long approved record changes
+            let announce = sqlx::query!(
+                "UPDATE video_validation_jobs SET announced = TRUE WHERE record = $1 AND NOT announced AND EXISTS(SELECT 1 FROM records \
+                 WHERE id = $1 AND status_ = 'SUBMITTED') RETURNING record",
+                job.record
+            )
+            .fetch_optional(&mut connection)
+            .await?;
+
+            if announce.is_some() {
+                let record = FullRecord::by_id(job.record, &mut connection).await?;
+
+                webhook::dispatch(Event::record(WebhookEvent::RecordSubmitted, &record), &mut connection).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Module containing the sections the list is divided into (main list, legacy list, ...)
+//!
+//! Each section covers a contiguous range of positions. The ranges of different sections never
+//! overlap, but there might be positions not covered by any section. Demons at such positions
+//! behave as if they were in a section with default settings (submissions allowed, no minimal
+//! progress apart from the demon's requirement, a scoring weight of 1 and not legacy).
+//!
+//! Sections decide
+//! * whether records can be submitted for a demon, and with how much progress
+//! * the factor the score given by the active [`ScoringFormula`](super::formula::ScoringFormula)
+//!   is multiplied with. This is applied in the `formula_score` SQL function
+//! * how demons are presented on the website (legacy demons aren't shown on the overview and
+//!   their positions aren't displayed)
+
+pub use self::{patch::PatchListSection, post::PostListSection};
+use crate::{error::PointercrateError, Result};
+use derive_more::Display;
+use serde::Serialize;
+use std::hash::{Hash, Hasher};
+
+mod delete;
+mod get;
+mod patch;
+mod post;
+
+#[derive(Debug, Serialize, Display, Clone)]
+#[display(fmt = "{} (ID: {})", name, id)]
+pub struct ListSection {
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+
+    /// The first position belonging to this section
+    pub first_position: i16,
+
+    /// The last position belonging to this section. If [`None`], the section extends to the end of
+    /// the list
+    pub last_position: Option<i16>,
+
+    /// The minimal progress submissions for demons in this section need to have. If [`None`], only
+    /// the demon's record requirement applies
+    pub min_progress: Option<i16>,
+
+    pub submissions_allowed: bool,
+
+    /// The factor scores awarded by demons in this section are multiplied with
+    pub scoring_weight: f64,
+
+    /// Whether this section is for demons that have "fallen off" the list
+    pub legacy: bool,
+}
+
+// f64 doesn't implement Hash
+impl Hash for ListSection {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.name.hash(state);
+        self.description.hash(state);
+        self.first_position.hash(state);
+        self.last_position.hash(state);
+        self.min_progress.hash(state);
+        self.submissions_allowed.hash(state);
+        self.scoring_weight.to_bits().hash(state);
+        self.legacy.hash(state);
+    }
+}
+
+impl ListSection {
+    pub fn contains(&self, position: i16) -> bool {
+        position >= self.first_position && self.last_position.map(|last| position <= last).unwrap_or(true)
+    }
+
+    /// Finds the section the given position belongs to
+    pub fn find(sections: &[ListSection], position: i16) -> Option<&ListSection> {
+        sections.iter().find(|section| section.contains(position))
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(PointercrateError::InvalidSectionParameter {
+                parameter: "name",
+                reason: "must not be empty",
+            })
+        }
+
+        if self.first_position < 1 {
+            return Err(PointercrateError::InvalidSectionParameter {
+                parameter: "first_position",
+                reason: "must be at least 1",
+            })
+        }
+
+        if let Some(last_position) = self.last_position {
+            if last_position < self.first_position {
+                return Err(PointercrateError::InvalidSectionParameter {
+                    parameter: "last_position",
+                    reason: "must not be smaller than first_position",
+                })
+            }
+        }
+
+        if let Some(min_progress) = self.min_progress {
+            if min_progress < 0 || min_progress > 100 {
+                return Err(PointercrateError::InvalidSectionParameter {
+                    parameter: "min_progress",
+                    reason: "must lie between 0 and 100",
+                })
+            }
+        }
+
+        if !self.scoring_weight.is_finite() || self.scoring_weight < 0.0 {
+            return Err(PointercrateError::InvalidSectionParameter {
+                parameter: "scoring_weight",
+                reason: "must be a non-negative number",
+            })
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use crate::{model::demonlist::section::ListSection, Result};
+use log::info;
+use sqlx::PgConnection;
+
+impl ListSection {
+    /// Deletes this section. The demons it covered afterwards behave like demons in a section with
+    /// default settings
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("DELETE FROM list_sections WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        info!("Deleted list section {}", self);
+
+        Ok(())
+    }
+}
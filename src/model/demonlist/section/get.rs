@@ -0,0 +1,82 @@
+use crate::{error::PointercrateError, model::demonlist::section::ListSection, Result};
+use sqlx::{Error, PgConnection};
+
+impl ListSection {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<ListSection> {
+        let result = sqlx::query_as!(
+            ListSection,
+            "SELECT id, name, description, first_position, last_position, min_progress, submissions_allowed, scoring_weight, legacy FROM \
+             list_sections WHERE id = $1",
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match result {
+            Ok(section) => Ok(section),
+            Err(Error::RowNotFound) =>
+                Err(PointercrateError::ModelNotFound {
+                    model: "ListSection",
+                    identified_by: id.to_string(),
+                }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets all sections, ordered by position
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<ListSection>> {
+        Ok(sqlx::query_as!(
+            ListSection,
+            "SELECT id, name, description, first_position, last_position, min_progress, submissions_allowed, scoring_weight, legacy FROM \
+             list_sections ORDER BY first_position"
+        )
+        .fetch_all(connection)
+        .await?)
+    }
+
+    /// Gets the section the given position belongs to, if any
+    pub async fn of_position(position: i16, connection: &mut PgConnection) -> Result<Option<ListSection>> {
+        Ok(sqlx::query_as!(
+            ListSection,
+            "SELECT id, name, description, first_position, last_position, min_progress, submissions_allowed, scoring_weight, legacy FROM \
+             list_sections WHERE first_position <= $1 AND (last_position IS NULL OR $1 <= last_position)",
+            position
+        )
+        .fetch_optional(connection)
+        .await?)
+    }
+
+    /// Makes sure the position range of this section doesn't overlap with that of any other section
+    pub(super) async fn require_no_overlap(&self, connection: &mut PgConnection) -> Result<()> {
+        let overlapping = sqlx::query!(
+            "SELECT name FROM list_sections WHERE id <> $1 AND int4range(first_position, last_position, '[]') && int4range($2, $3, '[]') \
+             LIMIT 1",
+            self.id,
+            self.first_position as i32,
+            self.last_position.map(i32::from)
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        match overlapping {
+            Some(row) => Err(PointercrateError::SectionOverlap { section: row.name }),
+            None => Ok(()),
+        }
+    }
+
+    /// Makes sure no other section has the name of this one
+    pub(super) async fn require_unique_name(&self, connection: &mut PgConnection) -> Result<()> {
+        let taken = sqlx::query!("SELECT id FROM list_sections WHERE id <> $1 AND name = $2", self.id, self.name)
+            .fetch_optional(connection)
+            .await?;
+
+        match taken {
+            Some(_) =>
+                Err(PointercrateError::InvalidSectionParameter {
+                    parameter: "name",
+                    reason: "is already used by another section",
+                }),
+            None => Ok(()),
+        }
+    }
+}
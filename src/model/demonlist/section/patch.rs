@@ -0,0 +1,99 @@
+use crate::{
+    model::demonlist::section::ListSection,
+    util::{non_nullable, nullable},
+    Result,
+};
+use log::info;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Deserialize, Debug)]
+pub struct PatchListSection {
+    #[serde(default, deserialize_with = "non_nullable")]
+    name: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    description: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    first_position: Option<i16>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    last_position: Option<Option<i16>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    min_progress: Option<Option<i16>>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    submissions_allowed: Option<bool>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    scoring_weight: Option<f64>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    legacy: Option<bool>,
+}
+
+impl ListSection {
+    /// Must be run within a transaction!
+    ///
+    /// Since the validity of the position range depends on both of its ends, all fields are
+    /// validated together and written in a single update
+    pub async fn apply_patch(mut self, patch: PatchListSection, connection: &mut PgConnection) -> Result<Self> {
+        info!("Patching list section {} with {:?}", self, patch);
+
+        if let Some(name) = patch.name {
+            self.name = name.trim().to_string();
+        }
+
+        if let Some(description) = patch.description {
+            self.description = description;
+        }
+
+        if let Some(first_position) = patch.first_position {
+            self.first_position = first_position;
+        }
+
+        if let Some(last_position) = patch.last_position {
+            self.last_position = last_position;
+        }
+
+        if let Some(min_progress) = patch.min_progress {
+            self.min_progress = min_progress;
+        }
+
+        if let Some(submissions_allowed) = patch.submissions_allowed {
+            self.submissions_allowed = submissions_allowed;
+        }
+
+        if let Some(scoring_weight) = patch.scoring_weight {
+            self.scoring_weight = scoring_weight;
+        }
+
+        if let Some(legacy) = patch.legacy {
+            self.legacy = legacy;
+        }
+
+        self.validate()?;
+        self.require_unique_name(&mut *connection).await?;
+        self.require_no_overlap(&mut *connection).await?;
+
+        sqlx::query!(
+            "UPDATE list_sections SET name = $2, description = $3, first_position = $4, last_position = $5, min_progress = $6, \
+             submissions_allowed = $7, scoring_weight = $8, legacy = $9 WHERE id = $1",
+            self.id,
+            self.name,
+            self.description,
+            self.first_position,
+            self.last_position,
+            self.min_progress,
+            self.submissions_allowed,
+            self.scoring_weight,
+            self.legacy
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(self)
+    }
+}
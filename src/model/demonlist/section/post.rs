@@ -0,0 +1,109 @@
+use crate::{model::demonlist::section::ListSection, Result};
+use log::info;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Deserialize, Debug)]
+pub struct PostListSection {
+    name: String,
+
+    #[serde(default)]
+    description: String,
+
+    first_position: i16,
+
+    #[serde(default)]
+    last_position: Option<i16>,
+
+    #[serde(default)]
+    min_progress: Option<i16>,
+
+    #[serde(default = "default_submissions_allowed")]
+    submissions_allowed: bool,
+
+    #[serde(default = "default_scoring_weight")]
+    scoring_weight: f64,
+
+    #[serde(default)]
+    legacy: bool,
+}
+
+fn default_submissions_allowed() -> bool {
+    true
+}
+
+fn default_scoring_weight() -> f64 {
+    1.0
+}
+
+impl ListSection {
+    /// Must be run within a transaction!
+    pub async fn create_from(data: PostListSection, connection: &mut PgConnection) -> Result<ListSection> {
+        info!("Creating new list section from {:?}", data);
+
+        let mut section = ListSection {
+            // not yet known, but no section has this id, so it doesn't interfere with the checks below
+            id: 0,
+            name: data.name.trim().to_string(),
+            description: data.description,
+            first_position: data.first_position,
+            last_position: data.last_position,
+            min_progress: data.min_progress,
+            submissions_allowed: data.submissions_allowed,
+            scoring_weight: data.scoring_weight,
+            legacy: data.legacy,
+        };
+
+        section.validate()?;
+        section.require_unique_name(&mut *connection).await?;
+        section.require_no_overlap(&mut *connection).await?;
+
+        section.id = sqlx::query!(
+            "INSERT INTO list_sections (name, description, first_position, last_position, min_progress, submissions_allowed, \
+             scoring_weight, legacy) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id",
+            section.name,
+            section.description,
+            section.first_position,
+            section.last_position,
+            section.min_progress,
+            section.submissions_allowed,
+            section.scoring_weight,
+            section.legacy
+        )
+        .fetch_one(connection)
+        .await?
+        .id;
+
+        Ok(section)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::PointercrateError,
+        model::demonlist::section::{ListSection, PostListSection},
+    };
+
+    #[actix_rt::test]
+    async fn test_overlapping_section() {
+        let mut connection = crate::test::test_setup().await;
+
+        let result = ListSection::create_from(
+            PostListSection {
+                name: "Overlapping".to_string(),
+                description: String::new(),
+                first_position: 70,
+                last_position: Some(80),
+                min_progress: None,
+                submissions_allowed: true,
+                scoring_weight: 1.0,
+                legacy: false,
+            },
+            &mut connection,
+        )
+        .await;
+
+        assert!(matches!(result, Err(PointercrateError::SectionOverlap { .. })));
+    }
+}
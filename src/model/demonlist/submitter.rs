@@ -1,28 +1,40 @@
-pub use self::{paginate::SubmitterPagination, patch::PatchSubmitter};
-use crate::Result;
+pub use self::{
+    paginate::{SubmitterPagination, SubmitterSortColumn},
+    patch::PatchSubmitter,
+    statistics::{SubmitterStatistics, TrustLevel},
+};
 use derive_more::Display;
 use serde::Serialize;
-use sqlx::PgConnection;
+use std::hash::{Hash, Hasher};
 
 mod get;
 mod paginate;
 mod patch;
+mod statistics;
 
 #[derive(Debug, Serialize, Hash, Display, Copy, Clone)]
 #[display(fmt = "{} (Banned: {})", id, banned)]
 pub struct Submitter {
     pub id: i32,
     pub banned: bool,
+
+    /// The ID of the user account this submitter belongs to, if the submissions were made while
+    /// logged in. Banning such a submitter bans the account from submitting records
+    pub member: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Display)]
+#[display(fmt = "{}", submitter)]
+pub struct FullSubmitter {
+    #[serde(flatten)]
+    pub submitter: Submitter,
+    pub statistics: SubmitterStatistics,
 }
 
-impl Submitter {
-    /// Gets the maximal and minimal submitter id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_submitter_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(submitter_id) AS "max_id!: i32", MIN(submitter_id) AS "min_id!: i32" FROM submitters"#)
-            .fetch_one(connection)
-            .await?; // FIXME: crashes on empty table
-        Ok((row.max_id, row.min_id))
+// Statistics change whenever one of the submitter's records does, which shouldn't cause
+// conditional requests modifying the submitter itself to fail
+impl Hash for FullSubmitter {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.submitter.hash(state)
     }
 }
@@ -1,20 +1,27 @@
 use super::Submitter;
 use crate::{
     error::PointercrateError,
+    model::user::User,
     ratelimit::{PreparedRatelimits, RatelimitScope},
     Result,
 };
+use log::{info, warn};
 use sqlx::{Error, PgConnection};
 use std::net::IpAddr;
 
 impl Submitter {
     pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Submitter> {
-        let result = sqlx::query!("SELECT submitter_id, banned FROM submitters WHERE submitter_id = $1", id)
+        let result = sqlx::query!("SELECT submitter_id, banned, member FROM submitters WHERE submitter_id = $1", id)
             .fetch_one(connection)
             .await;
 
         match result {
-            Ok(row) => Ok(Submitter { id, banned: row.banned }),
+            Ok(row) =>
+                Ok(Submitter {
+                    id,
+                    banned: row.banned,
+                    member: row.member,
+                }),
             Err(Error::RowNotFound) =>
                 Err(PointercrateError::ModelNotFound {
                     model: "Submitter",
@@ -24,11 +31,16 @@ impl Submitter {
         }
     }
 
+    /// Gets the submitter for the given IP address, creating it if nobody submitted a record from
+    /// there without being logged in before
+    ///
+    /// Submitters of (possibly deleted) accounts are never returned, even though they remember the
+    /// IP address they first submitted from.
     pub async fn by_ip_or_create(
-        ip: IpAddr, connection: &mut PgConnection, ratelimits: Option<PreparedRatelimits<'_>>,
+        ip: IpAddr, connection: &mut PgConnection, ratelimits: Option<&PreparedRatelimits<'_>>,
     ) -> Result<Submitter> {
         let optional_row = sqlx::query!(
-            "SELECT submitter_id, banned FROM submitters WHERE ip_address = cast($1::text as inet)",
+            "SELECT submitter_id, banned FROM submitters WHERE ip_address = cast($1::text as inet) AND NOT is_account",
             ip.to_string()
         )
         .fetch_optional(&mut *connection)
@@ -39,10 +51,11 @@ impl Submitter {
                 Ok(Submitter {
                     id: row.submitter_id,
                     banned: row.banned,
+                    member: None,
                 }),
             None => {
                 if let Some(ratelimits) = ratelimits {
-                    ratelimits.check(RatelimitScope::NewSubmitter)?;
+                    ratelimits.check(RatelimitScope::NewSubmitter).await?;
                 }
 
                 let id = sqlx::query!(
@@ -53,8 +66,122 @@ impl Submitter {
                 .await?
                 .submitter_id;
 
-                Ok(Submitter { id, banned: false })
+                Ok(Submitter {
+                    id,
+                    banned: false,
+                    member: None,
+                })
             },
         }
     }
+
+    /// Gets the submitter linked to the given user's account, creating it if the user never
+    /// submitted a record while logged in before
+    ///
+    /// Fails if the IP address the submission is made from is banned, so that banned submitters
+    /// cannot simply register an account to get around their ban.
+    pub async fn by_member_or_create(user: &User, ip: IpAddr, connection: &mut PgConnection) -> Result<Submitter> {
+        let ip_banned = sqlx::query!(
+            r#"SELECT EXISTS(SELECT 1 FROM submitters WHERE ip_address = cast($1::text as inet) AND NOT is_account AND banned)
+             AS "banned!""#,
+            ip.to_string()
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .banned;
+
+        if ip_banned {
+            warn!("{} attempted to submit a record from banned IP address {}", user, ip);
+
+            return Err(PointercrateError::BannedFromSubmissions)
+        }
+
+        let optional_row = sqlx::query!("SELECT submitter_id, banned FROM submitters WHERE member = $1", user.id)
+            .fetch_optional(&mut *connection)
+            .await?;
+
+        match optional_row {
+            Some(row) =>
+                Ok(Submitter {
+                    id: row.submitter_id,
+                    banned: row.banned,
+                    member: Some(user.id),
+                }),
+            None => {
+                // No need to ratelimit this, creating accounts is ratelimited already
+                let id = sqlx::query!(
+                    "INSERT INTO submitters (ip_address, member, is_account) VALUES (cast($1::text as inet), $2, TRUE) RETURNING \
+                     submitter_id",
+                    ip.to_string(),
+                    user.id
+                )
+                .fetch_one(connection)
+                .await?
+                .submitter_id;
+
+                info!("Created submitter {} for {}", id, user);
+
+                Ok(Submitter {
+                    id,
+                    banned: false,
+                    member: Some(user.id),
+                })
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::PointercrateError,
+        model::{demonlist::submitter::Submitter, user::User},
+    };
+    use std::net::{IpAddr, Ipv4Addr};
+
+    const IP: IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+
+    #[actix_rt::test]
+    async fn test_by_member_or_create() {
+        let mut connection = crate::test::test_setup().await;
+
+        let user = User::by_name("stadust_existing", &mut connection).await.unwrap();
+
+        let submitter = Submitter::by_member_or_create(&user, IP, &mut connection).await.unwrap();
+        let ip_submitter = Submitter::by_ip_or_create(IP, &mut connection, None).await.unwrap();
+
+        assert_eq!(submitter.member, Some(user.id));
+        assert_ne!(submitter.id, ip_submitter.id);
+        assert_eq!(
+            Submitter::by_member_or_create(&user, IP, &mut connection).await.unwrap().id,
+            submitter.id
+        );
+
+        // Logging in doesn't get you around an IP ban
+        let mut ip_submitter = ip_submitter;
+        ip_submitter.ban(&mut connection).await.unwrap();
+
+        assert_eq!(
+            Submitter::by_member_or_create(&user, IP, &mut connection).await.err(),
+            Some(PointercrateError::BannedFromSubmissions)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_deleted_account_submitter() {
+        let mut connection = crate::test::test_setup().await;
+
+        let user = User::by_name("stadust_existing", &mut connection).await.unwrap();
+
+        let mut submitter = Submitter::by_member_or_create(&user, IP, &mut connection).await.unwrap();
+        submitter.ban(&mut connection).await.unwrap();
+
+        user.delete(&mut connection).await.unwrap();
+
+        // The account's ban must not turn into a ban of the IP address it submitted from
+        let ip_submitter = Submitter::by_ip_or_create(IP, &mut connection, None).await.unwrap();
+
+        assert_ne!(ip_submitter.id, submitter.id);
+        assert!(!ip_submitter.banned);
+    }
 }
@@ -1,64 +1,129 @@
-use super::Submitter;
-use crate::{error::PointercrateError, util::non_nullable, Result};
+use super::{FullSubmitter, Submitter, SubmitterStatistics};
+use crate::{
+    pagination::{Page, PaginationParameters, SortColumn},
+    util::non_nullable,
+    Result,
+};
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgConnection, Row};
 
-#[derive(Deserialize, Debug, Clone, Serialize)]
-pub struct SubmitterPagination {
-    #[serde(rename = "before", default, deserialize_with = "non_nullable")]
-    pub before_id: Option<i32>,
+/// Submitters are anonymous, so other than by ID they can only be sorted by their statistics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubmitterSortColumn {
+    Id,
+    Submitted,
+    Approved,
+    Rejected,
+}
+
+impl Default for SubmitterSortColumn {
+    fn default() -> Self {
+        SubmitterSortColumn::Id
+    }
+}
 
-    #[serde(rename = "after", default, deserialize_with = "non_nullable")]
-    pub after_id: Option<i32>,
+impl SortColumn for SubmitterSortColumn {
+    const ALL: &'static [Self] = &[
+        SubmitterSortColumn::Id,
+        SubmitterSortColumn::Submitted,
+        SubmitterSortColumn::Approved,
+        SubmitterSortColumn::Rejected,
+    ];
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub limit: Option<u8>,
+    fn name(self) -> &'static str {
+        match self {
+            SubmitterSortColumn::Id => "id",
+            SubmitterSortColumn::Submitted => "submitted",
+            SubmitterSortColumn::Approved => "approved",
+            SubmitterSortColumn::Rejected => "rejected",
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            SubmitterSortColumn::Id => "submitter_id",
+            SubmitterSortColumn::Submitted => "submitted",
+            SubmitterSortColumn::Approved => "approved",
+            SubmitterSortColumn::Rejected => "rejected",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            SubmitterSortColumn::Id => "INTEGER",
+            _ => "BIGINT",
+        }
+    }
+}
 
+#[derive(Deserialize, Debug, Clone, Serialize)]
+pub struct SubmitterPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     banned: Option<bool>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    member: Option<i32>,
 }
 
 impl SubmitterPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<Submitter>> {
-        if let Some(limit) = self.limit {
-            if limit < 1 || limit > 100 {
-                return Err(PointercrateError::InvalidPaginationLimit)
-            }
-        }
-
-        if let (Some(after), Some(before)) = (self.before_id, self.after_id) {
-            if after < before {
-                return Err(PointercrateError::AfterSmallerBefore)
-            }
-        }
+    pub async fn page(
+        &self, parameters: &PaginationParameters<SubmitterSortColumn>, connection: &mut PgConnection,
+    ) -> Result<Page<FullSubmitter>> {
+        parameters.validate()?;
 
-        let query = if self.before_id.is_some() && self.after_id.is_none() {
-            "SELECT submitter_id, banned FROM submitters WHERE (submitter_id < $1 OR $1 IS NULL) AND (submitter_id > $2 OR $2 IS NULL) AND \
-             (banned = $3 OR $3 IS NULL) ORDER BY submitter_id DESC LIMIT $4 "
-        } else {
-            "SELECT submitter_id, banned FROM submitters WHERE (submitter_id < $1 OR $1 IS NULL) AND (submitter_id > $2 OR $2 IS NULL) AND \
-             (banned = $3 OR $3 IS NULL) ORDER BY submitter_id ASC LIMIT $4 "
-        };
-
-        let mut stream = sqlx::query(query)
-            .bind(self.before_id)
-            .bind(self.after_id)
-            .bind(self.banned)
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
+        let query = "SELECT submitter_id, banned, member, submitted, approved, rejected, under_consideration, first_seen, last_seen FROM \
+                     submitters INNER JOIN submitter_statistics USING (submitter_id) WHERE (banned = $5 OR $5 IS NULL) AND (member = $6 OR $6 IS \
+                     NULL)";
+        let page_query = parameters.page_query(query);
 
         let mut submitters = Vec::new();
+        let mut stream = parameters
+            .bind_cursors(sqlx::query(&page_query))
+            .bind(self.banned)
+            .bind(self.member)
+            .fetch(&mut *connection);
 
         while let Some(row) = stream.next().await {
             let row = row?;
 
-            submitters.push(Submitter {
-                id: row.get("submitter_id"),
-                banned: row.get("banned"),
-            })
+            let submitter = FullSubmitter {
+                submitter: Submitter {
+                    id: row.get("submitter_id"),
+                    banned: row.get("banned"),
+                    member: row.get("member"),
+                },
+                statistics: SubmitterStatistics::new(
+                    row.get("submitted"),
+                    row.get("approved"),
+                    row.get("rejected"),
+                    row.get("under_consideration"),
+                    row.get("first_seen"),
+                    row.get("last_seen"),
+                ),
+            };
+
+            submitters.push((parameters.cursor(&row)?, submitter))
+        }
+
+        drop(stream);
+
+        let mut page = Page::new(parameters, submitters);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                parameters
+                    .bind_no_cursors(sqlx::query(&count_query))
+                    .bind(self.banned)
+                    .bind(self.member)
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
         }
 
-        Ok(submitters)
+        Ok(page)
     }
 }
@@ -0,0 +1,134 @@
+use super::{FullSubmitter, Submitter};
+use crate::{config, Result};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgConnection;
+use std::hash::{Hash, Hasher};
+
+/// How much we trust a submitter based on their past submissions
+///
+/// The thresholds are configurable, see [`config::submitter_trusted_min_approved`] and friends.
+#[derive(Debug, Serialize, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustLevel {
+    /// Most of this submitter's records got rejected. Their submissions are automatically put
+    /// under consideration
+    Flagged,
+
+    Normal,
+
+    /// This submitter has a long history of legitimate submissions, and gets relaxed rate limits
+    Trusted,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmitterStatistics {
+    pub submitted: i64,
+    pub approved: i64,
+    pub rejected: i64,
+    pub under_consideration: i64,
+
+    /// The share of rejected records among all approved and rejected ones
+    pub rejection_ratio: f64,
+
+    /// When this submitter submitted their first record, if they submitted any
+    pub first_seen: Option<NaiveDateTime>,
+
+    /// When this submitter submitted their latest record, if they submitted any
+    pub last_seen: Option<NaiveDateTime>,
+
+    pub trust: TrustLevel,
+}
+
+// `rejection_ratio` is derived from the other counts, so there is no need to hash it (which we
+// couldn't do anyway, it being a float)
+impl Hash for SubmitterStatistics {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.submitted.hash(state);
+        self.approved.hash(state);
+        self.rejected.hash(state);
+        self.under_consideration.hash(state);
+        self.first_seen.hash(state);
+        self.last_seen.hash(state);
+    }
+}
+
+impl SubmitterStatistics {
+    pub(super) fn new(
+        submitted: i64, approved: i64, rejected: i64, under_consideration: i64, first_seen: Option<NaiveDateTime>,
+        last_seen: Option<NaiveDateTime>,
+    ) -> Self {
+        let rejection_ratio = if approved + rejected == 0 {
+            0.0
+        } else {
+            rejected as f64 / (approved + rejected) as f64
+        };
+
+        let flagged =
+            rejected >= config::submitter_flagged_min_rejected() && rejection_ratio >= config::submitter_flagged_min_rejection_ratio();
+        let trusted =
+            approved >= config::submitter_trusted_min_approved() && rejection_ratio <= config::submitter_trusted_max_rejection_ratio();
+
+        let trust = if flagged {
+            TrustLevel::Flagged
+        } else if trusted {
+            TrustLevel::Trusted
+        } else {
+            TrustLevel::Normal
+        };
+
+        SubmitterStatistics {
+            submitted,
+            approved,
+            rejected,
+            under_consideration,
+            rejection_ratio,
+            first_seen,
+            last_seen,
+            trust,
+        }
+    }
+}
+
+impl Submitter {
+    pub async fn statistics(&self, connection: &mut PgConnection) -> Result<SubmitterStatistics> {
+        let row = sqlx::query!(
+            r#"SELECT submitted AS "submitted!", approved AS "approved!", rejected AS "rejected!", under_consideration AS
+             "under_consideration!", first_seen, last_seen FROM submitter_statistics WHERE submitter_id = $1"#,
+            self.id
+        )
+        .fetch_one(connection)
+        .await?;
+
+        Ok(SubmitterStatistics::new(
+            row.submitted,
+            row.approved,
+            row.rejected,
+            row.under_consideration,
+            row.first_seen,
+            row.last_seen,
+        ))
+    }
+
+    pub async fn upgrade(self, connection: &mut PgConnection) -> Result<FullSubmitter> {
+        Ok(FullSubmitter {
+            statistics: self.statistics(connection).await?,
+            submitter: self,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SubmitterStatistics, TrustLevel};
+
+    #[test]
+    fn test_trust_level() {
+        assert_eq!(SubmitterStatistics::new(0, 0, 0, 0, None, None).trust, TrustLevel::Normal);
+        assert_eq!(SubmitterStatistics::new(30, 25, 1, 0, None, None).trust, TrustLevel::Trusted);
+        assert_eq!(SubmitterStatistics::new(30, 25, 5, 0, None, None).trust, TrustLevel::Normal);
+        assert_eq!(SubmitterStatistics::new(12, 2, 10, 0, None, None).trust, TrustLevel::Flagged);
+        // a handful of rejections alone doesn't get you flagged
+        assert_eq!(SubmitterStatistics::new(3, 0, 3, 0, None, None).trust, TrustLevel::Normal);
+    }
+}
@@ -4,15 +4,23 @@
 //! * Deleting other accounts
 //! * Modifying other people's accounts (assign permissions, change offensive names, etc)
 //! * Querying account information
+//! * Changing the account security policy
 
 pub use self::{
-    auth::{AuthenticatedUser, Authorization, PatchMe, Registration},
-    paginate::UserPagination,
+    auth::{
+        initialize_password_hashing, AuthenticatedUser, Authorization, ConfirmTwoFactor, PasswordResetToken, PatchMe, PersonalAccessToken,
+        PostAccessToken, RefreshSession, Registration, RequestPasswordReset, ResetPassword, Session, SessionTokens, TwoFactorEnrollment,
+    },
+    paginate::{UserPagination, UserSortColumn},
     patch::PatchUser,
+    policy::{PatchSecurityPolicy, SecurityPolicy},
+};
+use crate::{
+    error::PointercrateError,
+    permissions::{Capabilities, Permissions},
+    Result,
 };
-use crate::{error::PointercrateError, permissions::Permissions, Result};
 use serde::Serialize;
-use sqlx::PgConnection;
 use std::{
     fmt::{Display, Formatter},
     hash::Hash,
@@ -23,6 +31,7 @@ mod delete;
 mod get;
 mod paginate;
 mod patch;
+mod policy;
 
 // TODO: impl the nationality stuff already in the database
 /// Model representing a user in the database
@@ -61,9 +70,15 @@ impl User {
         self.permissions.implied().contains(perm)
     }
 
-    pub fn require_permissions(&self, perm: Permissions) -> Result<()> {
-        if !self.has_permission(perm) {
-            return Err(PointercrateError::MissingPermissions { required: perm })
+    pub fn has_capability(&self, capability: Capabilities) -> bool {
+        self.permissions.capabilities().contains(capability)
+    }
+
+    pub fn require_capability(&self, capability: Capabilities) -> Result<()> {
+        if !self.has_capability(capability) {
+            return Err(PointercrateError::MissingPermissions {
+                required: capability.granted_by(),
+            })
         }
 
         Ok(())
@@ -91,14 +106,4 @@ impl User {
     pub fn extended_list_access(&self) -> bool {
         self.permissions.implied().contains(Permissions::ExtendedAccess)
     }
-
-    /// Gets the maximal and minimal member id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_member_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(member_id) AS "max_id!: i32", MIN(member_id) AS "min_id!: i32" FROM members"#)
-            .fetch_one(connection)
-            .await?; // FIXME: crashes on empty table
-        Ok((row.max_id, row.min_id))
-    }
 }
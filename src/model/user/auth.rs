@@ -4,26 +4,67 @@
 //! * Registration
 //! * Deletion of own account
 //! * Modification of own account
-
-pub use self::{get::Authorization, patch::PatchMe, post::Registration};
+//! * Management of personal access tokens
+//! * Login sessions
+//! * Account recovery via password reset tokens
+//! * Two-factor authentication
+
+pub use self::{
+    access_token::{PersonalAccessToken, PostAccessToken},
+    get::Authorization,
+    password::initialize as initialize_password_hashing,
+    patch::PatchMe,
+    post::Registration,
+    recovery::{PasswordResetToken, RequestPasswordReset, ResetPassword},
+    session::{RefreshSession, Session, SessionTokens},
+    two_factor::{ConfirmTwoFactor, TwoFactorEnrollment},
+};
 use crate::{error::PointercrateError, model::user::User, Result};
 use jsonwebtoken::{DecodingKey, EncodingKey};
-use log::{debug, warn};
+use log::warn;
 use serde::{Deserialize, Serialize};
 
+mod access_token;
 mod delete;
 mod get;
+mod password;
 mod patch;
 mod post;
+mod recovery;
+mod session;
+mod two_factor;
 
 pub struct AuthenticatedUser {
     user: User,
     password_hash: String,
+
+    /// The id of the personal access token this user authenticated with, if any
+    personal_access_token: Option<i32>,
+
+    /// The id of the session this user authenticated with, if any
+    session: Option<i32>,
+
+    two_factor_enabled: bool,
+
+    /// Whether this user's list team permissions have been suspended because the security policy
+    /// requires two-factor authentication for the list team, but this user hasn't enabled it
+    permissions_suspended: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Copy, Clone)]
 pub struct Claims {
     pub id: i32,
+
+    /// The id of the personal access token, if these are the claims of one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<i32>,
+
+    /// The id of the session, if these are the claims of a (short-lived) access token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Copy, Clone)]
@@ -42,12 +83,15 @@ impl AuthenticatedUser {
         &self.user
     }
 
-    pub fn validate_password(password: &str) -> Result<()> {
-        if password.len() < 10 {
-            return Err(PointercrateError::InvalidPassword)
+    /// Makes sure this user did not authenticate using a personal access token
+    ///
+    /// Used for endpoints that manage account credentials, so that a leaked personal access token
+    /// cannot be used to create more of them
+    pub fn forbid_personal_access_token(&self) -> Result<()> {
+        match self.personal_access_token {
+            Some(_) => Err(PointercrateError::PersonalAccessTokenForbidden),
+            None => Ok(()),
         }
-
-        Ok(())
     }
 
     fn jwt_secret(&self, application_secret: &[u8]) -> Vec<u8> {
@@ -56,29 +100,6 @@ impl AuthenticatedUser {
         key
     }
 
-    pub fn generate_token(&self, application_secret: &[u8]) -> String {
-        jsonwebtoken::encode(
-            &jsonwebtoken::Header::default(),
-            &Claims { id: self.user.id },
-            &EncodingKey::from_secret(&self.jwt_secret(application_secret)),
-        )
-        .unwrap()
-    }
-
-    pub fn validate_token(self, token: &str, application_secret: &[u8]) -> Result<Self> {
-        // TODO: maybe one day do something with this
-        let mut validation = jsonwebtoken::Validation::default();
-        validation.validate_exp = false;
-
-        jsonwebtoken::decode::<Claims>(token, &DecodingKey::from_secret(&self.jwt_secret(application_secret)), &validation)
-            .map_err(|err| {
-                warn!("Token validation FAILED for account {}: {}", self.user, err);
-
-                PointercrateError::Unauthorized
-            })
-            .map(move |_| self)
-    }
-
     pub fn generate_csrf_token(&self, application_secret: &[u8]) -> String {
         use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -119,30 +140,13 @@ impl AuthenticatedUser {
         let raw_parts: Vec<_> = self.password_hash.split('$').filter(|s| !s.is_empty()).collect();
 
         match &raw_parts[..] {
+            // bcrypt: $<version>$<cost>$<salt><hash>
             [_, _, hash] => b64::decode(&hash[..22]),
+            // argon2: $<variant>$v=<version>$<parameters>$<salt>$<hash>
+            [_, _, _, salt, _] => base64::decode_config(salt, base64::STANDARD_NO_PAD).unwrap(),
             _ => unreachable!(),
         }
     }
-
-    pub fn verify_password(self, password: &str) -> Result<Self> {
-        debug!("Verifying a password!");
-
-        let valid = bcrypt::verify(&password, &self.password_hash).map_err(|err| {
-            warn!("Password verification FAILED for account {}: {}", self.user, err);
-
-            PointercrateError::Unauthorized
-        })?;
-
-        if valid {
-            debug!("Password correct, proceeding");
-
-            Ok(self)
-        } else {
-            warn!("Potentially malicious log-in attempt to account {}", self.user);
-
-            Err(PointercrateError::Unauthorized)
-        }
-    }
 }
 
 // This code is copied from https://github.com/Keats/rust-bcrypt/blob/master/src/b64.rs
@@ -0,0 +1,331 @@
+//! Module containing personal access tokens
+//!
+//! Personal access tokens are named, long-lived tokens meant for bots and other programs acting on
+//! a user's behalf. Unlike the access token returned by `POST /auth/`, each of them can be limited
+//! to a subset of its owner's permissions (its "scopes"), can expire, and can be revoked without
+//! affecting any other token.
+//!
+//! The tokens themselves are never stored. They are JWTs referencing a row in the
+//! `personal_access_tokens` table, signed with a key derived from the application secret. This
+//! means they survive password changes, and that deleting the row is the only (and sufficient) way
+//! to revoke them.
+
+use crate::{
+    error::PointercrateError,
+    model::user::auth::{AuthenticatedUser, Claims},
+    permissions::Permissions,
+    Result,
+};
+use chrono::{NaiveDateTime, Utc};
+use jsonwebtoken::{DecodingKey, EncodingKey};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sqlx::{Done, PgConnection};
+
+#[derive(Debug, Serialize, Hash, Eq, PartialEq)]
+pub struct PersonalAccessToken {
+    pub id: i32,
+    pub name: String,
+
+    /// The permissions requests authenticated with this token can make use of. Only permissions
+    /// the token's owner actually has are taken into account
+    pub scopes: Permissions,
+
+    pub created_at: NaiveDateTime,
+
+    /// The point in time after which this token stops working. [`None`] if it never expires
+    pub expires_at: Option<NaiveDateTime>,
+
+    /// The last time this token was used to authenticate a request
+    pub last_used: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PostAccessToken {
+    name: String,
+
+    #[serde(default = "Permissions::empty")]
+    scopes: Permissions,
+
+    #[serde(default)]
+    expires_at: Option<NaiveDateTime>,
+}
+
+struct FetchedAccessToken {
+    id: i32,
+    name: String,
+    scopes: i32,
+    created_at: NaiveDateTime,
+    expires_at: Option<NaiveDateTime>,
+    last_used: Option<NaiveDateTime>,
+}
+
+impl From<FetchedAccessToken> for PersonalAccessToken {
+    fn from(row: FetchedAccessToken) -> Self {
+        PersonalAccessToken {
+            id: row.id,
+            name: row.name,
+            scopes: Permissions::from_bits_truncate(row.scopes as u16),
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used: row.last_used,
+        }
+    }
+}
+
+impl PersonalAccessToken {
+    /// Deletes all personal access tokens that have expired, returning how many there were
+    pub async fn prune_expired(connection: &mut PgConnection) -> Result<u64> {
+        let deleted = sqlx::query!("DELETE FROM personal_access_tokens WHERE expires_at <= (NOW() AT TIME ZONE 'utc')")
+            .execute(connection)
+            .await?;
+
+        Ok(deleted.rows_affected())
+    }
+}
+
+fn access_token_secret(application_secret: &[u8]) -> Vec<u8> {
+    // Use a different key than for CSRF tokens, so that one can never be used in place of the other
+    let mut key: Vec<u8> = application_secret.into();
+    key.extend(b"personal-access-token");
+    key
+}
+
+impl AuthenticatedUser {
+    /// Gets all personal access tokens of this user, including expired ones that haven't been pruned
+    /// yet
+    pub async fn access_tokens(&self, connection: &mut PgConnection) -> Result<Vec<PersonalAccessToken>> {
+        self.forbid_personal_access_token()?;
+
+        let tokens = sqlx::query_as!(
+            FetchedAccessToken,
+            r#"SELECT id, name, scopes::integer AS "scopes!: i32", created_at, expires_at, last_used FROM personal_access_tokens WHERE
+             member = $1 ORDER BY id"#,
+            self.user.id
+        )
+        .fetch_all(connection)
+        .await?;
+
+        Ok(tokens.into_iter().map(Into::into).collect())
+    }
+
+    /// Creates a new personal access token for this user
+    ///
+    /// Returns the newly created token along with the JWT to use for authentication. Since the
+    /// latter is never stored, this is the only time it can be retrieved
+    pub async fn create_access_token(
+        &self, data: PostAccessToken, application_secret: &[u8], connection: &mut PgConnection,
+    ) -> Result<(PersonalAccessToken, String)> {
+        info!("Creating personal access token for {} from {:?}", self.user, data);
+
+        self.forbid_personal_access_token()?;
+
+        let name = data.name.trim().to_string();
+
+        if name.is_empty() || name.len() > 64 {
+            return Err(PointercrateError::InvalidAccessTokenParameter {
+                parameter: "name",
+                reason: "must be between 1 and 64 characters long",
+            })
+        }
+
+        if !self.user.has_permission(data.scopes) {
+            return Err(PointercrateError::MissingPermissions { required: data.scopes })
+        }
+
+        if let Some(expires_at) = data.expires_at {
+            if expires_at <= Utc::now().naive_utc() {
+                return Err(PointercrateError::InvalidAccessTokenParameter {
+                    parameter: "expires_at",
+                    reason: "must lie in the future",
+                })
+            }
+        }
+
+        let taken = sqlx::query!(
+            "SELECT id FROM personal_access_tokens WHERE member = $1 AND name = $2",
+            self.user.id,
+            name
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        if taken.is_some() {
+            return Err(PointercrateError::InvalidAccessTokenParameter {
+                parameter: "name",
+                reason: "is already used by another of your tokens",
+            })
+        }
+
+        let row = sqlx::query!(
+            "INSERT INTO personal_access_tokens (member, name, scopes, expires_at) VALUES ($1, $2, CAST($3::integer AS BIT(16)), $4) \
+             RETURNING id, created_at",
+            self.user.id,
+            name,
+            data.scopes.bits() as i32,
+            data.expires_at
+        )
+        .fetch_one(connection)
+        .await?;
+
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &Claims {
+                id: self.user.id,
+                token: Some(row.id),
+                session: None,
+                exp: None,
+            },
+            &EncodingKey::from_secret(&access_token_secret(application_secret)),
+        )
+        .unwrap();
+
+        Ok((
+            PersonalAccessToken {
+                id: row.id,
+                name,
+                scopes: data.scopes,
+                created_at: row.created_at,
+                expires_at: data.expires_at,
+                last_used: None,
+            },
+            token,
+        ))
+    }
+
+    /// Revokes the personal access token with the given id, if it belongs to this user
+    pub async fn revoke_access_token(&self, token_id: i32, connection: &mut PgConnection) -> Result<()> {
+        self.forbid_personal_access_token()?;
+
+        let deleted = sqlx::query!(
+            "DELETE FROM personal_access_tokens WHERE id = $1 AND member = $2 RETURNING id",
+            token_id,
+            self.user.id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        match deleted {
+            Some(_) => {
+                info!("{} revoked their personal access token {}", self.user, token_id);
+
+                Ok(())
+            },
+            None =>
+                Err(PointercrateError::ModelNotFound {
+                    model: "PersonalAccessToken",
+                    identified_by: token_id.to_string(),
+                }),
+        }
+    }
+
+    /// Validates the given personal access token and restricts this user's permissions to the
+    /// token's scopes
+    ///
+    /// Also updates the time the token was last used.
+    pub(super) async fn validate_access_token(
+        mut self, token_id: i32, token: &str, application_secret: &[u8], connection: &mut PgConnection,
+    ) -> Result<Self> {
+        // expiry is tracked in the database, the token itself doesn't have an `exp` claim
+        let mut validation = jsonwebtoken::Validation::default();
+        validation.validate_exp = false;
+
+        jsonwebtoken::decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(&access_token_secret(application_secret)),
+            &validation,
+        )
+        .map_err(|err| {
+            warn!("Personal access token validation FAILED for account {}: {}", self.user, err);
+
+            PointercrateError::Unauthorized
+        })?;
+
+        // Revoked and expired tokens simply don't match here
+        let row = sqlx::query!(
+            r#"UPDATE personal_access_tokens SET last_used = (NOW() AT TIME ZONE 'utc') WHERE id = $1 AND member = $2 AND (expires_at IS
+             NULL OR expires_at > (NOW() AT TIME ZONE 'utc')) RETURNING scopes::integer AS "scopes!: i32""#,
+            token_id,
+            self.user.id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or_else(|| {
+            warn!("Personal access token {} of account {} was revoked or has expired", token_id, self.user);
+
+            PointercrateError::Unauthorized
+        })?;
+
+        self.user.permissions = self.user.permissions.implied() & Permissions::from_bits_truncate(row.scopes as u16);
+        self.personal_access_token = Some(token_id);
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::PointercrateError,
+        model::user::{AuthenticatedUser, Authorization, PostAccessToken},
+        permissions::Permissions,
+    };
+
+    const SECRET: &[u8] = b"test secret";
+
+    #[actix_rt::test]
+    async fn test_access_token_lifecycle() {
+        let mut connection = crate::test::test_setup().await;
+
+        let user = AuthenticatedUser::basic_auth(
+            &Authorization::Basic {
+                username: "stadust_existing".to_owned(),
+                password: "password1234567890".to_string(),
+                second_factor: None,
+            },
+            &mut connection,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let data = PostAccessToken {
+            name: "Discord bot".to_string(),
+            scopes: Permissions::ListHelper,
+            expires_at: None,
+        };
+
+        // stadust_existing doesn't have any permissions
+        assert!(matches!(
+            user.create_access_token(data, SECRET, &mut connection).await,
+            Err(PointercrateError::MissingPermissions { .. })
+        ));
+
+        let data = PostAccessToken {
+            name: "Discord bot".to_string(),
+            scopes: Permissions::empty(),
+            expires_at: None,
+        };
+
+        let (token, access_token) = user.create_access_token(data, SECRET, &mut connection).await.unwrap();
+
+        let authorization = Authorization::Token {
+            access_token,
+            csrf_token: None,
+        };
+        let token_user = AuthenticatedUser::token_auth(&authorization, SECRET, &mut connection).await.unwrap();
+
+        assert_eq!(token_user.inner().name, "stadust_existing");
+        assert_eq!(
+            token_user.access_tokens(&mut connection).await.err(),
+            Some(PointercrateError::PersonalAccessTokenForbidden)
+        );
+
+        user.revoke_access_token(token.id, &mut connection).await.unwrap();
+
+        assert_eq!(
+            AuthenticatedUser::token_auth(&authorization, SECRET, &mut connection).await.err(),
+            Some(PointercrateError::Unauthorized)
+        );
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
         User,
     },
     permissions::Permissions,
+    ratelimit::PreparedRatelimits,
     Result,
 };
 use log::{debug, info, warn};
@@ -17,6 +18,7 @@ struct FetchedUser {
     display_name: Option<String>,
     youtube_channel: Option<String>,
     password_hash: String,
+    two_factor_enabled: bool,
 }
 
 /// Enum representing a parsed `Authorization` header
@@ -26,21 +28,31 @@ pub enum Authorization {
     Unauthorized,
 
     /// The chosen authorization method was `Basic`
-    Basic { username: String, password: String },
+    ///
+    /// The second factor is taken from the `X-TOTP-Code` header, and only required for accounts
+    /// with two-factor authentication enabled
+    Basic {
+        username: String,
+        password: String,
+        second_factor: Option<String>,
+    },
 
     /// The chosen authorization method was `Bearer`
     Token { access_token: String, csrf_token: Option<String> },
 }
 
 impl AuthenticatedUser {
-    pub async fn invalidate_all_tokens(authorization: Authorization, connection: &mut PgConnection) -> Result<()> {
-        let user = Self::basic_auth(&authorization, connection).await?;
+    pub async fn invalidate_all_tokens(
+        authorization: Authorization, connection: &mut PgConnection, ratelimits: Option<&PreparedRatelimits<'_>>,
+    ) -> Result<()> {
+        let user = Self::basic_auth(&authorization, connection, ratelimits).await?;
 
         if let Authorization::Basic { password, .. } = authorization {
             let patch = PatchMe {
                 password: Some(password),
                 display_name: None,
                 youtube_channel: None,
+                email: None,
             };
 
             warn!("Invalidating all access tokens for user {}", user.inner());
@@ -54,15 +66,31 @@ impl AuthenticatedUser {
         }
     }
 
-    pub async fn basic_auth(auth: &Authorization, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
+    /// Invalid second factors are counted against the limit on them for both the account and the
+    /// requesting IP address, if `ratelimits` are given
+    pub async fn basic_auth(
+        auth: &Authorization, connection: &mut PgConnection, ratelimits: Option<&PreparedRatelimits<'_>>,
+    ) -> Result<AuthenticatedUser> {
         info!("We are expected to perform basic authentication");
 
-        // TODO: ratelimiting here (what did I mean by this?)
-
-        if let Authorization::Basic { username, password } = auth {
+        if let Authorization::Basic {
+            username,
+            password,
+            second_factor,
+        } = auth
+        {
             debug!("Trying to authorize user {}", username);
 
-            Self::by_name(username, connection).await?.verify_password(password)
+            let mut user = Self::by_name(username, connection)
+                .await?
+                .verify_password(password)?
+                .verify_second_factor(second_factor.as_deref(), connection, ratelimits)
+                .await?;
+
+            user.upgrade_password_hash(password, connection).await?;
+            user.enforce_two_factor_policy(connection).await?;
+
+            Ok(user)
         } else {
             warn!("No basic authentication found");
 
@@ -76,23 +104,41 @@ impl AuthenticatedUser {
         if let Authorization::Token { access_token, csrf_token } = auth {
             // Well this is reassuring. Also we directly deconstruct it and only save the ID
             // so we don't accidentally use unsafe values later on
-            let Claims { id, .. } = jsonwebtoken::dangerous_insecure_decode::<Claims>(&access_token)
+            let Claims { id, token, session, .. } = jsonwebtoken::dangerous_insecure_decode::<Claims>(&access_token)
                 .map_err(|_| PointercrateError::Unauthorized)?
                 .claims;
 
             debug!("The token identified the user with id {}, validating...", id);
 
-            // Note that at this point we haven't validated the access token OR the csrf token yet.
-            // However, the key they are signed with encompasses the password salt for the user they supposedly
-            // identify, so we need to retrieve that.
-            let user = Self::by_id(id, connection)
-                .await?
-                .validate_token(&access_token, application_secret)?;
+            // Both kinds of tokens are validated against the database, since they can be revoked individually
+            let mut user = match (token, session) {
+                (Some(token_id), _) =>
+                    Self::by_id(id, connection)
+                        .await?
+                        .validate_access_token(token_id, &access_token, application_secret, connection)
+                        .await?,
+                // Note that at this point we haven't validated the access token OR the csrf token yet.
+                // However, the key they are signed with encompasses the password salt for the user they supposedly
+                // identify, so we need to retrieve that.
+                (None, Some(session_id)) =>
+                    Self::by_id(id, connection)
+                        .await?
+                        .validate_session(session_id, &access_token, application_secret, connection)
+                        .await?,
+                // Access tokens predating sessions never expire, so we no longer accept them
+                (None, None) => {
+                    warn!("Access token of account {} is not associated with a session", id);
+
+                    return Err(PointercrateError::Unauthorized)
+                },
+            };
 
             if let Some(ref csrf_token) = csrf_token {
                 user.validate_csrf_token(csrf_token, application_secret)?
             }
 
+            user.enforce_two_factor_policy(connection).await?;
+
             Ok(user)
         } else {
             warn!("No token authentication found");
@@ -101,10 +147,11 @@ impl AuthenticatedUser {
         }
     }
 
-    async fn by_id(id: i32, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
+    pub(super) async fn by_id(id: i32, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
         let row = sqlx::query_as!(
             FetchedUser,
-            r#"SELECT member_id, name, permissions::integer as "permissions!: i32", display_name, youtube_channel::text, password_hash FROM members WHERE 
+            r#"SELECT member_id, name, permissions::integer as "permissions!: i32", display_name, youtube_channel::text, password_hash, 
+             EXISTS(SELECT 1 FROM two_factor_secrets WHERE member = member_id AND confirmed) AS "two_factor_enabled!" FROM members WHERE 
              member_id = $1"#,
             id
         )
@@ -124,14 +171,20 @@ impl AuthenticatedUser {
                         youtube_channel: row.youtube_channel,
                     },
                     password_hash: row.password_hash,
+                    personal_access_token: None,
+                    session: None,
+                    two_factor_enabled: row.two_factor_enabled,
+                    permissions_suspended: false,
                 }),
         }
     }
 
-    async fn by_name(name: &str, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
+    pub(super) async fn by_name(name: &str, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
         let row = sqlx::query_as!(
             FetchedUser,
-            r#"SELECT member_id, name, permissions::integer as "permissions!: i32", display_name, youtube_channel::text, password_hash FROM members WHERE name = $1"#,
+            r#"SELECT member_id, name, permissions::integer as "permissions!: i32", display_name, youtube_channel::text, password_hash, 
+             EXISTS(SELECT 1 FROM two_factor_secrets WHERE member = member_id AND confirmed) AS "two_factor_enabled!" FROM members WHERE 
+             name = $1"#,
             name.to_string()
         )
         .fetch_one(connection)
@@ -150,6 +203,10 @@ impl AuthenticatedUser {
                         youtube_channel: row.youtube_channel,
                     },
                     password_hash: row.password_hash,
+                    personal_access_token: None,
+                    session: None,
+                    two_factor_enabled: row.two_factor_enabled,
+                    permissions_suspended: false,
                 }),
         }
     }
@@ -170,8 +227,10 @@ mod tests {
             &Authorization::Basic {
                 username: "stadust_existing".to_owned(),
                 password: "password1234567890".to_string(),
+                second_factor: None,
             },
             &mut connection,
+            None,
         )
         .await;
 
@@ -187,8 +246,10 @@ mod tests {
             &Authorization::Basic {
                 username: "stadust_nonexisting".to_owned(),
                 password: "password1234567890".to_string(),
+                second_factor: None,
             },
             &mut connection,
+            None,
         )
         .await;
 
@@ -204,8 +265,10 @@ mod tests {
             &Authorization::Basic {
                 username: "stadust_existing".to_owned(),
                 password: "wrong password".to_string(),
+                second_factor: None,
             },
             &mut connection,
+            None,
         )
         .await;
 
@@ -0,0 +1,220 @@
+//! Module containing the password policy and password hashing
+//!
+//! New password hashes are always computed using the algorithm (and parameters) configured via
+//! [`config::password_hash_algorithm`], which is parsed once at startup (see [`initialize`]).
+//! Hashes computed with an outdated algorithm or outdated parameters are transparently upgraded
+//! the next time the user logs in, since that's the only time we have access to the plaintext
+//! password.
+//!
+//! When upgrading, the hash's salt is kept. Since access tokens are signed with a key derived from
+//! the salt (see [`AuthenticatedUser::jwt_secret`]), this means that upgrading a hash doesn't log
+//! the user out of all their devices.
+
+use crate::{config, error::PointercrateError, model::user::auth::AuthenticatedUser, Result};
+use lazy_static::lazy_static;
+use log::{debug, info, warn};
+use rand::Rng;
+use sqlx::PgConnection;
+use std::{collections::HashSet, str::FromStr};
+
+lazy_static! {
+    static ref COMMON_PASSWORDS: HashSet<&'static str> = include_str!("common_passwords.txt").lines().collect();
+    static ref HASH_ALGORITHM: HashAlgorithm = config::password_hash_algorithm()
+        .parse()
+        .expect("Invalid PASSWORD_HASH_ALGORITHM, expected 'bcrypt' or 'argon2'");
+    static ref BCRYPT_COST: u32 = config::bcrypt_cost();
+    static ref ARGON2_CONFIG: argon2::Config<'static> = argon2::Config {
+        variant: argon2::Variant::Argon2id,
+        mem_cost: config::argon2_memory_cost(),
+        time_cost: config::argon2_time_cost(),
+        ..argon2::Config::default()
+    };
+}
+
+/// Parses the password hashing configuration, so that a misconfiguration is noticed at startup
+/// instead of whenever a password is hashed
+pub fn initialize() {
+    lazy_static::initialize(&HASH_ALGORITHM);
+    lazy_static::initialize(&BCRYPT_COST);
+    lazy_static::initialize(&ARGON2_CONFIG);
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum HashAlgorithm {
+    Bcrypt,
+    Argon2,
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(algorithm: &str) -> std::result::Result<Self, Self::Err> {
+        match algorithm {
+            "bcrypt" => Ok(HashAlgorithm::Bcrypt),
+            "argon2" => Ok(HashAlgorithm::Argon2),
+            _ => Err(algorithm.to_string()),
+        }
+    }
+}
+
+impl HashAlgorithm {
+    fn of(hash: &str) -> HashAlgorithm {
+        if hash.starts_with("$argon2") {
+            HashAlgorithm::Argon2
+        } else {
+            HashAlgorithm::Bcrypt
+        }
+    }
+}
+
+/// Hashes the given password with a newly generated salt
+pub(super) fn hash(password: &str) -> String {
+    hash_with_salt(password, &rand::thread_rng().gen::<[u8; 16]>())
+}
+
+/// Hashes the given password using the configured algorithm
+fn hash_with_salt(password: &str, salt: &[u8]) -> String {
+    // it is safe to unwrap here because the only errors that can happen are invalid parameters
+    // (which would be a configuration error, so crashing loudly is the best we can do) or errors
+    // that happen during internally encoding the hash. Obviously, an error there is a bug in the
+    // library, so we definitely wanna panic since we're dealing with passwords
+    match *HASH_ALGORITHM {
+        HashAlgorithm::Bcrypt => bcrypt::hash_with_salt(password, *BCRYPT_COST, salt).unwrap().to_string(),
+        HashAlgorithm::Argon2 => argon2::hash_encoded(password.as_bytes(), salt, &ARGON2_CONFIG).unwrap(),
+    }
+}
+
+/// Checks whether the given hash was computed using the configured algorithm and parameters
+fn is_outdated(hash: &str) -> bool {
+    let parts: Vec<_> = hash.split('$').filter(|s| !s.is_empty()).collect();
+
+    match (*HASH_ALGORITHM, &parts[..]) {
+        (HashAlgorithm::Bcrypt, [version, cost, _]) if version.starts_with('2') => cost.parse::<u32>().ok() != Some(*BCRYPT_COST),
+        (HashAlgorithm::Argon2, ["argon2id", _, parameters, ..]) =>
+            *parameters != format!("m={},t={},p={}", ARGON2_CONFIG.mem_cost, ARGON2_CONFIG.time_cost, ARGON2_CONFIG.lanes),
+        _ => true,
+    }
+}
+
+impl AuthenticatedUser {
+    /// Checks the given password against the configured password policy
+    pub fn validate_password(password: &str, username: &str) -> Result<()> {
+        let min_length = config::password_min_length();
+
+        if password.chars().count() < min_length {
+            return Err(PointercrateError::InvalidPassword { min_length })
+        }
+
+        let lowercase = password.to_lowercase();
+
+        if config::password_forbid_common() && COMMON_PASSWORDS.contains(lowercase.as_str()) {
+            return Err(PointercrateError::WeakPassword {
+                reason: "This password is too commonly used",
+            })
+        }
+
+        if config::password_forbid_username() && lowercase.contains(&username.to_lowercase()) {
+            return Err(PointercrateError::WeakPassword {
+                reason: "The password must not contain your username",
+            })
+        }
+
+        Ok(())
+    }
+
+    pub fn verify_password(self, password: &str) -> Result<Self> {
+        debug!("Verifying a password!");
+
+        let valid = match HashAlgorithm::of(&self.password_hash) {
+            HashAlgorithm::Bcrypt => bcrypt::verify(&password, &self.password_hash).map_err(|err| err.to_string()),
+            HashAlgorithm::Argon2 => argon2::verify_encoded(&self.password_hash, password.as_bytes()).map_err(|err| err.to_string()),
+        }
+        .map_err(|err| {
+            warn!("Password verification FAILED for account {}: {}", self.user, err);
+
+            PointercrateError::Unauthorized
+        })?;
+
+        if valid {
+            debug!("Password correct, proceeding");
+
+            Ok(self)
+        } else {
+            warn!("Potentially malicious log-in attempt to account {}", self.user);
+
+            Err(PointercrateError::Unauthorized)
+        }
+    }
+
+    /// Rehashes the given password (which must have already been verified) if the stored hash is
+    /// outdated
+    pub(super) async fn upgrade_password_hash(&mut self, password: &str, connection: &mut PgConnection) -> Result<()> {
+        if !is_outdated(&self.password_hash) {
+            return Ok(())
+        }
+
+        info!("Upgrading outdated password hash of {}", self.user);
+
+        self.password_hash = hash_with_salt(password, &self.password_salt());
+
+        sqlx::query!(
+            "UPDATE members SET password_hash = $1 WHERE member_id = $2",
+            self.password_hash,
+            self.user.id
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_outdated;
+    use crate::{
+        error::PointercrateError,
+        model::user::{AuthenticatedUser, Authorization},
+    };
+
+    #[test]
+    fn test_validate_password() {
+        assert!(AuthenticatedUser::validate_password("correct horse battery staple", "stadust").is_ok());
+        assert_eq!(
+            AuthenticatedUser::validate_password("short", "stadust"),
+            Err(PointercrateError::InvalidPassword { min_length: 10 })
+        );
+        assert!(AuthenticatedUser::validate_password("QwertyUiop", "stadust").is_err());
+        assert!(AuthenticatedUser::validate_password("i am Stadust and this is my password", "stadust").is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_upgrade_password_hash() {
+        let mut connection = crate::test::test_setup().await;
+
+        let outdated = bcrypt::hash_with_salt("password1234567890", 4, &[0; 16]).unwrap().to_string();
+
+        sqlx::query!(
+            "UPDATE members SET password_hash = $1 WHERE name = 'stadust_existing'",
+            outdated
+        )
+        .execute(&mut connection)
+        .await
+        .unwrap();
+
+        let authorization = Authorization::Basic {
+            username: "stadust_existing".to_owned(),
+            password: "password1234567890".to_string(),
+            second_factor: None,
+        };
+
+        let user = AuthenticatedUser::basic_auth(&authorization, &mut connection, None).await.unwrap();
+
+        assert!(is_outdated(&outdated));
+        assert!(!is_outdated(&user.password_hash));
+        assert_eq!(user.password_salt(), vec![0; 16]);
+
+        // Logging in again works with the upgraded hash
+        assert!(AuthenticatedUser::basic_auth(&authorization, &mut connection, None).await.is_ok());
+    }
+}
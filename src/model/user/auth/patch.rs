@@ -1,5 +1,8 @@
 use crate::{
-    model::user::{auth::AuthenticatedUser, patch::PatchUser},
+    model::user::{
+        auth::{password, AuthenticatedUser},
+        patch::PatchUser,
+    },
     util::{non_nullable, nullable},
     Result,
 };
@@ -17,6 +20,9 @@ pub struct PatchMe {
 
     #[serde(default, deserialize_with = "nullable")]
     pub(super) youtube_channel: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub(super) email: Option<Option<String>>,
 }
 
 impl PatchMe {
@@ -41,6 +47,10 @@ impl AuthenticatedUser {
             self.set_password(password, connection).await?;
         }
 
+        if let Some(email) = patch.email {
+            self.set_email(email, connection).await?;
+        }
+
         self.user = self
             .user
             .apply_patch(
@@ -57,23 +67,23 @@ impl AuthenticatedUser {
     }
 
     pub async fn set_password(&mut self, password: String, connection: &mut PgConnection) -> Result<()> {
-        Self::validate_password(&password)?;
+        Self::validate_password(&password, &self.user.name)?;
 
-        // it is safe to unwrap here because the only errors that can happen are
-        // 'BcryptError::CostNotAllowed' (won't happen because DEFAULT_COST is obviously allowed)
-        // or errors that happen during internally parsing the hash the library itself just
-        // generated. Obviously, an error there is a bug in the library, so we definitely wanna panic since
-        // we're dealing with passwords
-        self.password_hash = bcrypt::hash(password, bcrypt::DEFAULT_COST).unwrap();
+        self.password_hash = password::hash(&password);
 
         sqlx::query!(
             "UPDATE members SET password_hash = $1 WHERE member_id = $2",
             self.password_hash,
             self.user.id
         )
-        .execute(connection)
+        .execute(&mut *connection)
         .await?;
 
+        // The new password salt invalidates the tokens of all sessions, so we might as well get rid of them
+        sqlx::query!("DELETE FROM sessions WHERE member = $1", self.user.id)
+            .execute(connection)
+            .await?;
+
         Ok(())
     }
 }
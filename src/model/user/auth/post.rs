@@ -1,4 +1,4 @@
-use super::AuthenticatedUser;
+use super::{password, AuthenticatedUser};
 use crate::{
     error::PointercrateError,
     model::user::User,
@@ -18,15 +18,15 @@ pub struct Registration {
 
 impl AuthenticatedUser {
     pub async fn register(
-        registration: Registration, connection: &mut PgConnection, ratelimits: Option<PreparedRatelimits<'_>>,
+        registration: Registration, connection: &mut PgConnection, ratelimits: Option<&PreparedRatelimits<'_>>,
     ) -> Result<AuthenticatedUser> {
         info!("Attempting registration of new user under name {}", registration.name);
 
-        Self::validate_password(&registration.password)?;
+        Self::validate_password(&registration.password, &registration.name)?;
         User::validate_name(&registration.name)?;
 
         if let Some(ratelimits) = ratelimits {
-            ratelimits.check(RatelimitScope::SoftRegistration)?;
+            ratelimits.check(RatelimitScope::SoftRegistration).await?;
         }
 
         trace!("Registration request is formally correct");
@@ -34,7 +34,7 @@ impl AuthenticatedUser {
         match User::by_name(&registration.name, connection).await {
             Ok(_) => Err(PointercrateError::NameTaken),
             Err(PointercrateError::ModelNotFound { .. }) => {
-                let hash = bcrypt::hash(&registration.password, bcrypt::DEFAULT_COST).unwrap();
+                let hash = password::hash(&registration.password);
 
                 let id = sqlx::query!(
                     "INSERT INTO members (name, password_hash) VALUES ($1, $2) RETURNING member_id",
@@ -48,7 +48,7 @@ impl AuthenticatedUser {
                 info!("Newly registered user with name {} has been assigned ID {}", registration.name, id);
 
                 if let Some(ratelimits) = ratelimits {
-                    ratelimits.check(RatelimitScope::Registration)?;
+                    ratelimits.check(RatelimitScope::Registration).await?;
                 }
 
                 Ok(AuthenticatedUser {
@@ -60,6 +60,10 @@ impl AuthenticatedUser {
                         youtube_channel: None,
                     },
                     password_hash: hash,
+                    personal_access_token: None,
+                    session: None,
+                    two_factor_enabled: false,
+                    permissions_suspended: false,
                 })
             },
             Err(err) => Err(err),
@@ -0,0 +1,313 @@
+//! Module containing account recovery via password reset tokens
+//!
+//! Since pointercrate has no way to verify a user's identity other than their password, resetting
+//! it requires a one-time token. It can either be issued by an administrator (and handed to the
+//! user out of band), or, if a [`MailTransport`] is configured and the user set a recovery email
+//! address, be sent to the user via email.
+//!
+//! Only a hash of the token is stored, and each user has at most one outstanding token at a time.
+
+use crate::{
+    config,
+    error::PointercrateError,
+    mail::{Mail, MailTransport},
+    model::user::{auth::AuthenticatedUser, User},
+    Result,
+};
+use chrono::NaiveDateTime;
+use log::{info, warn};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::{Done, PgConnection};
+use std::fmt::{Debug, Formatter};
+
+const TOKEN_LENGTH: usize = 32;
+
+/// Characters reset tokens are made of. Leaves out some that are easily confused with each other,
+/// since tokens might be typed off by hand
+const TOKEN_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz23456789";
+
+/// Reset tokens are long and random, so unlike passwords, their hashes don't need to be slow to
+/// compute
+const TOKEN_COST: u32 = 4;
+
+#[derive(Debug, Serialize)]
+pub struct PasswordResetToken {
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RequestPasswordReset {
+    pub username: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResetPassword {
+    username: String,
+    token: String,
+    password: String,
+}
+
+// manual debug impl to ensure that neither the token nor the new password are ever printed anywhere
+impl Debug for ResetPassword {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResetPassword").field("username", &self.username).finish()
+    }
+}
+
+impl PasswordResetToken {
+    /// Deletes all password reset tokens that have expired, returning how many there were
+    pub async fn prune_expired(connection: &mut PgConnection) -> Result<u64> {
+        let deleted = sqlx::query!("DELETE FROM password_resets WHERE expires_at <= (NOW() AT TIME ZONE 'utc')")
+            .execute(connection)
+            .await?;
+
+        Ok(deleted.rows_affected())
+    }
+}
+
+impl User {
+    /// Issues a new password reset token for this user, replacing any outstanding one
+    ///
+    /// `issued_by` is the administrator issuing the token, or [`None`] if the user requested it
+    /// themselves
+    pub async fn issue_password_reset(&self, issued_by: Option<&User>, connection: &mut PgConnection) -> Result<PasswordResetToken> {
+        let token = {
+            let mut rng = rand::thread_rng();
+
+            (0..TOKEN_LENGTH)
+                .map(|_| TOKEN_ALPHABET[rng.gen_range(0, TOKEN_ALPHABET.len())] as char)
+                .collect::<String>()
+        };
+
+        // The only possible error is an invalid cost, and ours is obviously valid
+        let token_hash = bcrypt::hash(&token, TOKEN_COST).unwrap();
+
+        let expires_at = sqlx::query!(
+            "INSERT INTO password_resets (member, token_hash, issued_by, expires_at) VALUES ($1, $2, $3, (NOW() AT TIME ZONE 'utc') + \
+             make_interval(hours => $4)) ON CONFLICT (member) DO UPDATE SET token_hash = EXCLUDED.token_hash, issued_by = \
+             EXCLUDED.issued_by, created_at = EXCLUDED.created_at, expires_at = EXCLUDED.expires_at RETURNING expires_at",
+            self.id,
+            token_hash,
+            issued_by.map(|user| user.id),
+            config::password_reset_duration()
+        )
+        .fetch_one(connection)
+        .await?
+        .expires_at;
+
+        match issued_by {
+            Some(issuer) => warn!("{} issued a password reset token for {}", issuer, self),
+            None => info!("Issued a password reset token for {}", self),
+        }
+
+        Ok(PasswordResetToken { token, expires_at })
+    }
+}
+
+impl AuthenticatedUser {
+    /// Gets the email address password reset tokens for this user are sent to, if they set one
+    pub async fn email(&self, connection: &mut PgConnection) -> Result<Option<String>> {
+        Ok(sqlx::query!("SELECT email FROM member_emails WHERE member = $1", self.user.id)
+            .fetch_optional(connection)
+            .await?
+            .map(|row| row.email))
+    }
+
+    /// Sets (or, if [`None`] is given, removes) the email address password reset tokens for this
+    /// user are sent to
+    pub async fn set_email(&mut self, email: Option<String>, connection: &mut PgConnection) -> Result<()> {
+        match email {
+            Some(email) => {
+                let email = email.trim();
+
+                validate_email(email)?;
+
+                sqlx::query!(
+                    "INSERT INTO member_emails (member, email) VALUES ($1, $2) ON CONFLICT (member) DO UPDATE SET email = EXCLUDED.email",
+                    self.user.id,
+                    email
+                )
+                .execute(connection)
+                .await?;
+            },
+            None => {
+                sqlx::query!("DELETE FROM member_emails WHERE member = $1", self.user.id)
+                    .execute(connection)
+                    .await?;
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Sends a password reset token to the email address of the user with the given name
+    ///
+    /// Does nothing if no such user exists, or if they didn't set an email address. Callers must
+    /// not reveal to the requester which of the cases happened.
+    pub async fn request_password_reset(username: &str, mailer: &dyn MailTransport, connection: &mut PgConnection) -> Result<()> {
+        let row = sqlx::query!(
+            "SELECT member_id, email FROM members INNER JOIN member_emails ON member = member_id WHERE name = $1",
+            username
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => {
+                info!("Password reset requested for '{}', which has no email address set", username);
+
+                return Ok(())
+            },
+        };
+
+        let user = User::by_id(row.member_id, connection).await?;
+        let token = user.issue_password_reset(None, connection).await?;
+
+        mailer.send(&Mail {
+            to: row.email,
+            subject: "Pointercrate password reset".to_string(),
+            body: format!(
+                "Someone (hopefully you) requested to reset the password of your pointercrate account '{}'.\n\nTo choose a new password, \
+                 click 'Forgot your password?' on the login page and enter the following reset token:\n\n{}\n\nThe token is valid until \
+                 {} (UTC). If you didn't request a password reset, you can ignore this email.",
+                user.name, token.token, token.expires_at
+            ),
+        })
+    }
+
+    /// Sets a new password for the user the given reset token was issued for
+    ///
+    /// The token is consumed in the process, and all of the user's sessions are revoked.
+    pub async fn reset_password(data: ResetPassword, connection: &mut PgConnection) -> Result<()> {
+        let mut user = match Self::by_name(&data.username, connection).await {
+            Err(PointercrateError::Unauthorized) => return Err(PointercrateError::InvalidResetToken),
+            result => result?,
+        };
+
+        let row = sqlx::query!(
+            "SELECT token_hash FROM password_resets WHERE member = $1 AND expires_at > (NOW() AT TIME ZONE 'utc') FOR UPDATE",
+            user.user.id
+        )
+        .fetch_optional(&mut *connection)
+        .await?
+        .ok_or(PointercrateError::InvalidResetToken)?;
+
+        if !bcrypt::verify(data.token.trim(), &row.token_hash).unwrap_or(false) {
+            warn!("Invalid password reset token provided for {}", user.user);
+
+            return Err(PointercrateError::InvalidResetToken)
+        }
+
+        Self::validate_password(&data.password, &user.user.name)?;
+
+        sqlx::query!("DELETE FROM password_resets WHERE member = $1", user.user.id)
+            .execute(&mut *connection)
+            .await?;
+
+        // Personal access tokens survive password changes. A reset however means that someone else
+        // might have had access to the account, and might have created tokens to keep that access
+        sqlx::query!("DELETE FROM personal_access_tokens WHERE member = $1", user.user.id)
+            .execute(&mut *connection)
+            .await?;
+
+        warn!("Resetting password of {} and revoking all their personal access tokens", user.user);
+
+        user.set_password(data.password, connection).await
+    }
+}
+
+fn validate_email(email: &str) -> Result<()> {
+    // Deliberately lax, the only way to really validate an email address is to send a mail to it
+    let valid = match &email.split('@').collect::<Vec<_>>()[..] {
+        [local, domain] => !local.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.'),
+        _ => false,
+    };
+
+    if !valid || email.len() > 254 || email.contains(char::is_whitespace) {
+        return Err(PointercrateError::InvalidEmail)
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_email, ResetPassword};
+    use crate::{
+        error::PointercrateError,
+        model::user::{AuthenticatedUser, Authorization, User},
+    };
+
+    #[test]
+    fn test_validate_email() {
+        assert!(validate_email("stadust@pointercrate.com").is_ok());
+        assert!(validate_email("stadust.pointercrate.com").is_err());
+        assert!(validate_email("stadust@pointercrate").is_err());
+        assert!(validate_email("stadust@@pointercrate.com").is_err());
+        assert!(validate_email("stad ust@pointercrate.com").is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_password_reset() {
+        let mut connection = crate::test::test_setup().await;
+
+        let user = User::by_name("stadust_existing", &mut connection).await.unwrap();
+        let token = user.issue_password_reset(None, &mut connection).await.unwrap();
+
+        sqlx::query!(
+            "INSERT INTO personal_access_tokens (member, name) VALUES ($1, 'Discord bot')",
+            user.id
+        )
+        .execute(&mut connection)
+        .await
+        .unwrap();
+
+        let wrong_token = ResetPassword {
+            username: "stadust_existing".to_string(),
+            token: "not the token".to_string(),
+            password: "new password 1234".to_string(),
+        };
+
+        assert_eq!(
+            AuthenticatedUser::reset_password(wrong_token, &mut connection).await.err(),
+            Some(PointercrateError::InvalidResetToken)
+        );
+
+        let reset = ResetPassword {
+            username: "stadust_existing".to_string(),
+            token: token.token.clone(),
+            password: "new password 1234".to_string(),
+        };
+
+        AuthenticatedUser::reset_password(reset, &mut connection).await.unwrap();
+
+        let result = AuthenticatedUser::basic_auth(
+            &Authorization::Basic {
+                username: "stadust_existing".to_owned(),
+                password: "new password 1234".to_string(),
+                second_factor: None,
+            },
+            &mut connection,
+            None,
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert!(result.unwrap().access_tokens(&mut connection).await.unwrap().is_empty());
+
+        // tokens can only be used once
+        let reuse = ResetPassword {
+            username: "stadust_existing".to_string(),
+            token: token.token,
+            password: "another password 1234".to_string(),
+        };
+
+        assert_eq!(
+            AuthenticatedUser::reset_password(reuse, &mut connection).await.err(),
+            Some(PointercrateError::InvalidResetToken)
+        );
+    }
+}
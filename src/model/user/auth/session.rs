@@ -0,0 +1,439 @@
+//! Module containing login sessions
+//!
+//! Every login creates a session, representing one device the user is logged in on. Access tokens
+//! handed out for a session are only valid for a short amount of time, after which a new one has
+//! to be requested using the session's refresh token.
+//!
+//! Refresh tokens are rotated on every use: They carry the session's current "generation", which
+//! is incremented each time a refresh token is redeemed. An outdated refresh token being presented
+//! means that either it, or the copy held by the legitimate client, was stolen, so the whole session
+//! is revoked in that case. The only exception are refresh tokens that were rotated just a few
+//! seconds ago (see [`REFRESH_GRACE_PERIOD`]): clients with several requests in flight when their
+//! access token expires (or with several open tabs) will try to refresh for each of them, and only
+//! the first of these refreshes can actually rotate the token.
+//!
+//! Both kinds of tokens are signed with a key derived from the user's password salt, meaning that
+//! changing the password still logs out every device at once.
+
+use crate::{
+    error::PointercrateError,
+    model::user::auth::{AuthenticatedUser, Claims},
+    Result,
+};
+use chrono::NaiveDateTime;
+use jsonwebtoken::{DecodingKey, EncodingKey};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sqlx::{Done, PgConnection};
+use std::{
+    net::IpAddr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of seconds an access token is valid for
+pub const ACCESS_TOKEN_LIFETIME: u64 = 15 * 60;
+
+/// The number of days a session can go unused before it expires
+const SESSION_LIFETIME_DAYS: i32 = 30;
+
+/// The number of seconds a refresh token can still be redeemed for after it has been rotated
+const REFRESH_GRACE_PERIOD: f64 = 30.0;
+
+#[derive(Debug, Serialize, Hash, Eq, PartialEq)]
+pub struct Session {
+    pub id: i32,
+
+    /// The IP address this session was last refreshed from
+    pub ip_address: String,
+
+    /// The user agent of the device this session was last refreshed from, if it sent one
+    pub user_agent: Option<String>,
+
+    pub created_at: NaiveDateTime,
+
+    /// The last time an access token was issued for this session
+    pub last_used: NaiveDateTime,
+
+    /// Whether the current request was authenticated using this session
+    pub current: bool,
+}
+
+/// The tokens handed out when logging in or refreshing a session
+#[derive(Debug, Serialize)]
+pub struct SessionTokens {
+    pub token: String,
+    pub refresh_token: String,
+
+    /// The number of seconds until `token` expires
+    pub expires_in: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RefreshSession {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone)]
+struct RefreshClaims {
+    id: i32,
+    session: i32,
+    generation: i32,
+}
+
+impl Session {
+    /// Deletes all sessions that have expired, returning how many there were
+    pub async fn prune_expired(connection: &mut PgConnection) -> Result<u64> {
+        let deleted = sqlx::query!(
+            "DELETE FROM sessions WHERE last_used <= (NOW() AT TIME ZONE 'utc') - make_interval(days => $1)",
+            SESSION_LIFETIME_DAYS
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(deleted.rows_affected())
+    }
+}
+
+impl AuthenticatedUser {
+    fn refresh_token_secret(&self, application_secret: &[u8]) -> Vec<u8> {
+        // Use a different key than for access tokens, so that one can never be used in place of the other
+        let mut key = self.jwt_secret(application_secret);
+        key.extend(b"refresh-token");
+        key
+    }
+
+    fn session_tokens(&self, session: i32, generation: i32, application_secret: &[u8]) -> SessionTokens {
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).expect("time went backwards");
+
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &Claims {
+                id: self.user.id,
+                token: None,
+                session: Some(session),
+                exp: Some(since_epoch.as_secs() + ACCESS_TOKEN_LIFETIME),
+            },
+            &EncodingKey::from_secret(&self.jwt_secret(application_secret)),
+        )
+        .unwrap();
+
+        let refresh_token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &RefreshClaims {
+                id: self.user.id,
+                session,
+                generation,
+            },
+            &EncodingKey::from_secret(&self.refresh_token_secret(application_secret)),
+        )
+        .unwrap();
+
+        SessionTokens {
+            token,
+            refresh_token,
+            expires_in: ACCESS_TOKEN_LIFETIME,
+        }
+    }
+
+    /// Starts a new session for this user, logging in the device the request was made from
+    pub async fn create_session(
+        &mut self, ip: IpAddr, user_agent: Option<&str>, application_secret: &[u8], connection: &mut PgConnection,
+    ) -> Result<SessionTokens> {
+        self.forbid_personal_access_token()?;
+
+        let session = sqlx::query!(
+            "INSERT INTO sessions (member, ip_address, user_agent) VALUES ($1, CAST($2::TEXT AS INET), $3) RETURNING id",
+            self.user.id,
+            ip.to_string(),
+            user_agent
+        )
+        .fetch_one(connection)
+        .await?
+        .id;
+
+        info!("Started session {} for {} from {}", session, self.user, ip);
+
+        self.session = Some(session);
+
+        Ok(self.session_tokens(session, 0, application_secret))
+    }
+
+    /// Redeems the given refresh token for a new access and refresh token
+    ///
+    /// The refresh token can not be used again afterwards.
+    pub async fn refresh_session(
+        refresh_token: &str, ip: IpAddr, user_agent: Option<&str>, application_secret: &[u8], connection: &mut PgConnection,
+    ) -> Result<(AuthenticatedUser, SessionTokens)> {
+        // Same deal as in `token_auth`: we need to know whose password salt the token was signed with
+        let RefreshClaims { id, .. } = jsonwebtoken::dangerous_insecure_decode::<RefreshClaims>(refresh_token)
+            .map_err(|_| PointercrateError::Unauthorized)?
+            .claims;
+
+        let mut user = Self::by_id(id, connection).await?;
+
+        let mut validation = jsonwebtoken::Validation::default();
+        validation.validate_exp = false;
+
+        let RefreshClaims { session, generation, .. } = jsonwebtoken::decode::<RefreshClaims>(
+            refresh_token,
+            &DecodingKey::from_secret(&user.refresh_token_secret(application_secret)),
+            &validation,
+        )
+        .map_err(|err| {
+            warn!("Refresh token validation FAILED for account {}: {}", user.user, err);
+
+            PointercrateError::Unauthorized
+        })?
+        .claims;
+
+        let rotated = sqlx::query!(
+            "UPDATE sessions SET generation = generation + 1, ip_address = CAST($4::TEXT AS INET), user_agent = $5, last_used = (NOW() AT \
+             TIME ZONE 'utc') WHERE id = $1 AND member = $2 AND generation = $3 AND last_used > (NOW() AT TIME ZONE 'utc') - \
+             make_interval(days => $6) RETURNING generation",
+            session,
+            id,
+            generation,
+            ip.to_string(),
+            user_agent,
+            SESSION_LIFETIME_DAYS
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        let generation = match rotated {
+            Some(row) => row.generation,
+            None => {
+                // Someone else might have rotated this token a moment ago, in which case we hand out
+                // tokens for the generation they rotated to
+                let raced = sqlx::query!(
+                    "SELECT generation FROM sessions WHERE id = $1 AND member = $2 AND generation = $3 + 1 AND last_used > (NOW() AT \
+                     TIME ZONE 'utc') - make_interval(secs => $4)",
+                    session,
+                    id,
+                    generation,
+                    REFRESH_GRACE_PERIOD
+                )
+                .fetch_optional(&mut *connection)
+                .await?;
+
+                match raced {
+                    Some(row) => row.generation,
+                    None => {
+                        // Either the session has expired or has been revoked, or the refresh token was already used.
+                        // In all cases, make sure the session is gone for good.
+                        warn!(
+                            "Outdated or unknown refresh token for session {} of account {} presented, revoking session",
+                            session, user.user
+                        );
+
+                        sqlx::query!("DELETE FROM sessions WHERE id = $1 AND member = $2", session, id)
+                            .execute(connection)
+                            .await?;
+
+                        return Err(PointercrateError::Unauthorized)
+                    },
+                }
+            },
+        };
+
+        user.session = Some(session);
+        user.enforce_two_factor_policy(connection).await?;
+
+        let tokens = user.session_tokens(session, generation, application_secret);
+
+        Ok((user, tokens))
+    }
+
+    /// Gets all active sessions of this user
+    pub async fn sessions(&self, connection: &mut PgConnection) -> Result<Vec<Session>> {
+        self.forbid_personal_access_token()?;
+
+        let rows = sqlx::query!(
+            r#"SELECT id, host(ip_address) AS "ip_address!", user_agent, created_at, last_used FROM sessions WHERE member = $1 AND
+             last_used > (NOW() AT TIME ZONE 'utc') - make_interval(days => $2) ORDER BY last_used DESC"#,
+            self.user.id,
+            SESSION_LIFETIME_DAYS
+        )
+        .fetch_all(connection)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Session {
+                id: row.id,
+                ip_address: row.ip_address,
+                user_agent: row.user_agent,
+                created_at: row.created_at,
+                last_used: row.last_used,
+                current: self.session == Some(row.id),
+            })
+            .collect())
+    }
+
+    /// Revokes the session with the given id, if it belongs to this user
+    ///
+    /// This immediately logs out the device the session belongs to.
+    pub async fn revoke_session(&self, session_id: i32, connection: &mut PgConnection) -> Result<()> {
+        self.forbid_personal_access_token()?;
+
+        let deleted = sqlx::query!(
+            "DELETE FROM sessions WHERE id = $1 AND member = $2 RETURNING id",
+            session_id,
+            self.user.id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        match deleted {
+            Some(_) => {
+                info!("{} revoked their session {}", self.user, session_id);
+
+                Ok(())
+            },
+            None =>
+                Err(PointercrateError::ModelNotFound {
+                    model: "Session",
+                    identified_by: session_id.to_string(),
+                }),
+        }
+    }
+
+    /// Validates the given access token, making sure that it hasn't expired and that the session
+    /// it was issued for still exists
+    pub(super) async fn validate_session(
+        mut self, session_id: i32, token: &str, application_secret: &[u8], connection: &mut PgConnection,
+    ) -> Result<Self> {
+        jsonwebtoken::decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(&self.jwt_secret(application_secret)),
+            &jsonwebtoken::Validation::default(),
+        )
+        .map_err(|err| {
+            warn!("Token validation FAILED for account {}: {}", self.user, err);
+
+            PointercrateError::Unauthorized
+        })?;
+
+        let row = sqlx::query!(
+            "SELECT id FROM sessions WHERE id = $1 AND member = $2",
+            session_id,
+            self.user.id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        if row.is_none() {
+            warn!("Session {} of account {} was revoked", session_id, self.user);
+
+            return Err(PointercrateError::Unauthorized)
+        }
+
+        self.session = Some(session_id);
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::PointercrateError,
+        model::user::{AuthenticatedUser, Authorization, Session},
+    };
+    use std::net::{IpAddr, Ipv4Addr};
+
+    const SECRET: &[u8] = b"test secret";
+    const IP: IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+
+    #[actix_rt::test]
+    async fn test_session_lifecycle() {
+        let mut connection = crate::test::test_setup().await;
+
+        let mut user = AuthenticatedUser::basic_auth(
+            &Authorization::Basic {
+                username: "stadust_existing".to_owned(),
+                password: "password1234567890".to_string(),
+                second_factor: None,
+            },
+            &mut connection,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let tokens = user.create_session(IP, Some("test"), SECRET, &mut connection).await.unwrap();
+
+        let authorization = Authorization::Token {
+            access_token: tokens.token,
+            csrf_token: None,
+        };
+        let token_user = AuthenticatedUser::token_auth(&authorization, SECRET, &mut connection).await.unwrap();
+
+        let sessions = token_user.sessions(&mut connection).await.unwrap();
+
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].current);
+
+        let (_, refreshed) = AuthenticatedUser::refresh_session(&tokens.refresh_token, IP, None, SECRET, &mut connection)
+            .await
+            .unwrap();
+
+        // Concurrent refreshes with the same token all succeed, without rotating the token any further
+        let (_, raced) = AuthenticatedUser::refresh_session(&tokens.refresh_token, IP, None, SECRET, &mut connection)
+            .await
+            .unwrap();
+
+        assert_eq!(raced.refresh_token, refreshed.refresh_token);
+
+        sqlx::query!("UPDATE sessions SET last_used = last_used - INTERVAL '1 minute'")
+            .execute(&mut connection)
+            .await
+            .unwrap();
+
+        // Using an already redeemed refresh token outside the grace period revokes the session
+        assert_eq!(
+            AuthenticatedUser::refresh_session(&tokens.refresh_token, IP, None, SECRET, &mut connection)
+                .await
+                .err(),
+            Some(PointercrateError::Unauthorized)
+        );
+        assert_eq!(
+            AuthenticatedUser::refresh_session(&refreshed.refresh_token, IP, None, SECRET, &mut connection)
+                .await
+                .err(),
+            Some(PointercrateError::Unauthorized)
+        );
+        assert_eq!(
+            AuthenticatedUser::token_auth(&authorization, SECRET, &mut connection).await.err(),
+            Some(PointercrateError::Unauthorized)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_prune_expired_sessions() {
+        let mut connection = crate::test::test_setup().await;
+
+        let mut user = AuthenticatedUser::basic_auth(
+            &Authorization::Basic {
+                username: "stadust_existing".to_owned(),
+                password: "password1234567890".to_string(),
+                second_factor: None,
+            },
+            &mut connection,
+            None,
+        )
+        .await
+        .unwrap();
+
+        user.create_session(IP, None, SECRET, &mut connection).await.unwrap();
+        user.create_session(IP, None, SECRET, &mut connection).await.unwrap();
+
+        sqlx::query!("UPDATE sessions SET last_used = last_used - INTERVAL '31 days' WHERE id = (SELECT MIN(id) FROM sessions)")
+            .execute(&mut connection)
+            .await
+            .unwrap();
+
+        assert_eq!(Session::prune_expired(&mut connection).await.unwrap(), 1);
+        assert_eq!(user.sessions(&mut connection).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,439 @@
+//! Module containing two-factor authentication via time-based one-time passwords (TOTP, see
+//! [RFC 6238](https://tools.ietf.org/html/rfc6238))
+//!
+//! Enrollment happens in two steps: First, a new secret is generated and handed to the user, who
+//! adds it to their authenticator app. Then the user proves they did so by providing a valid code,
+//! at which point two-factor authentication is enabled and a set of single use recovery codes is
+//! generated. From then on, basic authentication additionally requires either a TOTP code or one of
+//! the recovery codes.
+
+use crate::{
+    error::PointercrateError,
+    model::user::{auth::AuthenticatedUser, SecurityPolicy},
+    permissions::Permissions,
+    ratelimit::{PreparedRatelimits, RatelimitScope},
+    Result,
+};
+use hmac::{Hmac, Mac, NewMac};
+use log::{info, warn};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sqlx::PgConnection;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// The length of a TOTP time step, in seconds
+const STEP: u64 = 30;
+
+/// The number of digits in a TOTP code
+const DIGITS: u32 = 6;
+
+/// The number of time steps a code is still (or already) accepted for before (or after) the time
+/// step it belongs to, to account for clock drift
+const ALLOWED_DRIFT: i64 = 1;
+
+const SECRET_LENGTH: usize = 20;
+
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// The characters recovery codes are made of. Leaves out characters that are easily confused
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// Recovery codes are random and long enough that they don't need an expensive hash
+const RECOVERY_CODE_COST: u32 = 4;
+
+#[derive(Debug, Serialize)]
+pub struct TwoFactorEnrollment {
+    /// The base32 encoded secret to add to an authenticator app
+    pub secret: String,
+
+    /// An `otpauth://` URI containing the secret, which most authenticator apps can import
+    pub uri: String,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmTwoFactor {
+    code: String,
+}
+
+impl AuthenticatedUser {
+    pub fn two_factor_enabled(&self) -> bool {
+        self.two_factor_enabled
+    }
+
+    pub fn permissions_suspended(&self) -> bool {
+        self.permissions_suspended
+    }
+
+    /// Generates a new TOTP secret for this user
+    ///
+    /// Two-factor authentication only gets enabled once the enrollment has been confirmed via
+    /// [`AuthenticatedUser::confirm_two_factor_enrollment`]. Starting a new enrollment discards any
+    /// unconfirmed previous one.
+    pub async fn begin_two_factor_enrollment(&self, connection: &mut PgConnection) -> Result<TwoFactorEnrollment> {
+        if self.two_factor_enabled {
+            return Err(PointercrateError::TwoFactorEnabled)
+        }
+
+        let secret: [u8; SECRET_LENGTH] = rand::thread_rng().gen();
+
+        sqlx::query!(
+            "INSERT INTO two_factor_secrets (member, secret) VALUES ($1, $2) ON CONFLICT (member) DO UPDATE SET secret = EXCLUDED.secret, \
+             confirmed = FALSE, last_step = NULL",
+            self.user.id,
+            &secret[..]
+        )
+        .execute(connection)
+        .await?;
+
+        info!("{} started enrolling in two-factor authentication", self.user);
+
+        let secret = base32(&secret);
+
+        let mut uri = Url::parse("otpauth://totp/").unwrap();
+        uri.path_segments_mut()
+            .unwrap()
+            .pop_if_empty()
+            .push(&format!("pointercrate:{}", self.user.name));
+        uri.query_pairs_mut().append_pair("secret", &secret).append_pair("issuer", "pointercrate");
+
+        Ok(TwoFactorEnrollment {
+            secret,
+            uri: uri.to_string(),
+        })
+    }
+
+    /// Enables two-factor authentication if the given code is valid for the secret generated by
+    /// [`AuthenticatedUser::begin_two_factor_enrollment`]
+    ///
+    /// Returns the newly generated recovery codes
+    pub async fn confirm_two_factor_enrollment(&mut self, data: ConfirmTwoFactor, connection: &mut PgConnection) -> Result<Vec<String>> {
+        if self.two_factor_enabled {
+            return Err(PointercrateError::TwoFactorEnabled)
+        }
+
+        let row = sqlx::query!("SELECT secret FROM two_factor_secrets WHERE member = $1", self.user.id)
+            .fetch_optional(&mut *connection)
+            .await?
+            .ok_or_else(|| {
+                PointercrateError::BadRequest {
+                    message: "There is no two-factor authentication enrollment in progress for this account".to_string(),
+                }
+            })?;
+
+        let step = matching_step(&row.secret, data.code.trim(), None).ok_or(PointercrateError::InvalidTwoFactorCode)?;
+
+        sqlx::query!(
+            "UPDATE two_factor_secrets SET confirmed = TRUE, last_step = $2 WHERE member = $1",
+            self.user.id,
+            step
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        info!("{} enabled two-factor authentication", self.user);
+
+        self.two_factor_enabled = true;
+        self.generate_recovery_codes(connection).await
+    }
+
+    /// Replaces all recovery codes of this user with newly generated ones
+    pub async fn generate_recovery_codes(&self, connection: &mut PgConnection) -> Result<Vec<String>> {
+        if !self.two_factor_enabled {
+            return Err(PointercrateError::BadRequest {
+                message: "Two-factor authentication is not enabled for this account".to_string(),
+            })
+        }
+
+        let codes = {
+            let mut rng = rand::thread_rng();
+
+            (0..RECOVERY_CODE_COUNT)
+                .map(|_| {
+                    let code = (0..10)
+                        .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0, RECOVERY_CODE_ALPHABET.len())] as char)
+                        .collect::<String>();
+
+                    format!("{}-{}", &code[..5], &code[5..])
+                })
+                .collect::<Vec<_>>()
+        };
+
+        sqlx::query!("DELETE FROM two_factor_recovery_codes WHERE member = $1", self.user.id)
+            .execute(&mut *connection)
+            .await?;
+
+        for code in &codes {
+            // see `set_password` for why unwrapping is fine here
+            let hash = bcrypt::hash(code, RECOVERY_CODE_COST).unwrap();
+
+            sqlx::query!(
+                "INSERT INTO two_factor_recovery_codes (member, code_hash) VALUES ($1, $2)",
+                self.user.id,
+                hash
+            )
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        info!("Generated new recovery codes for {}", self.user);
+
+        Ok(codes)
+    }
+
+    /// Disables two-factor authentication for this user, deleting their secret and all recovery
+    /// codes
+    pub async fn disable_two_factor(&mut self, connection: &mut PgConnection) -> Result<()> {
+        warn!("Disabling two-factor authentication for {}", self.user);
+
+        sqlx::query!("DELETE FROM two_factor_secrets WHERE member = $1", self.user.id)
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query!("DELETE FROM two_factor_recovery_codes WHERE member = $1", self.user.id)
+            .execute(connection)
+            .await?;
+
+        self.two_factor_enabled = false;
+
+        Ok(())
+    }
+
+    /// Checks the given TOTP or recovery code, if this user has two-factor authentication enabled
+    ///
+    /// Every code can only be used once: Used recovery codes are deleted, and TOTP codes from time
+    /// steps up to the one of the last used code are rejected.
+    ///
+    /// Invalid codes are counted against [`RatelimitScope::SecondFactor`]. Once that limit is
+    /// exhausted, all codes are rejected, valid or not, since otherwise guessing could continue.
+    pub(super) async fn verify_second_factor(
+        self, code: Option<&str>, connection: &mut PgConnection, ratelimits: Option<&PreparedRatelimits<'_>>,
+    ) -> Result<Self> {
+        if !self.two_factor_enabled {
+            return Ok(self)
+        }
+
+        let code = match code {
+            Some(code) => code.trim().to_lowercase(),
+            None => return Err(PointercrateError::TwoFactorRequired),
+        };
+
+        if let Some(ratelimits) = ratelimits {
+            ratelimits.peek_account(RatelimitScope::SecondFactor, self.user.id).await?;
+        }
+
+        if code.len() == DIGITS as usize && code.bytes().all(|byte| byte.is_ascii_digit()) {
+            let row = sqlx::query!(
+                "SELECT secret, last_step FROM two_factor_secrets WHERE member = $1 AND confirmed",
+                self.user.id
+            )
+            .fetch_one(&mut *connection)
+            .await?;
+
+            if let Some(step) = matching_step(&row.secret, &code, row.last_step) {
+                // Checking `last_step` again guards against the same code being used by two concurrent requests
+                let updated = sqlx::query!(
+                    "UPDATE two_factor_secrets SET last_step = $2 WHERE member = $1 AND (last_step IS NULL OR last_step < $2) RETURNING \
+                     member",
+                    self.user.id,
+                    step
+                )
+                .fetch_optional(connection)
+                .await?;
+
+                if updated.is_some() {
+                    return Ok(self)
+                }
+            }
+        } else {
+            // Allow recovery codes to be entered without the dash
+            let code = code.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>();
+            let code = if code.len() == 10 { format!("{}-{}", &code[..5], &code[5..]) } else { code };
+
+            let rows = sqlx::query!("SELECT id, code_hash FROM two_factor_recovery_codes WHERE member = $1", self.user.id)
+                .fetch_all(&mut *connection)
+                .await?;
+
+            for row in rows {
+                if bcrypt::verify(&code, &row.code_hash).unwrap_or(false) {
+                    let deleted = sqlx::query!("DELETE FROM two_factor_recovery_codes WHERE id = $1 RETURNING id", row.id)
+                        .fetch_optional(&mut *connection)
+                        .await?;
+
+                    if deleted.is_some() {
+                        warn!("{} used a recovery code to log in", self.user);
+
+                        return Ok(self)
+                    }
+
+                    break
+                }
+            }
+        }
+
+        warn!("Invalid two-factor authentication code provided for account {}", self.user);
+
+        if let Some(ratelimits) = ratelimits {
+            ratelimits.check_account(RatelimitScope::SecondFactor, self.user.id).await?;
+        }
+
+        Err(PointercrateError::InvalidTwoFactorCode)
+    }
+
+    /// Suspends this user's list team permissions if the security policy requires two-factor
+    /// authentication for the list team, but this user hasn't enabled it
+    ///
+    /// Suspended permissions aren't removed from the database. They can be used again as soon as
+    /// two-factor authentication is enabled.
+    pub(super) async fn enforce_two_factor_policy(&mut self, connection: &mut PgConnection) -> Result<()> {
+        if self.two_factor_enabled || !self.user.list_team_member() {
+            return Ok(())
+        }
+
+        if SecurityPolicy::load(connection).await?.list_team_requires_2fa {
+            warn!(
+                "Suspending list team permissions of {} since they haven't enabled two-factor authentication",
+                self.user
+            );
+
+            self.user
+                .permissions
+                .remove(Permissions::ListHelper | Permissions::ListModerator | Permissions::ListAdministrator);
+            self.permissions_suspended = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the time step around the current time the given code is valid for, ignoring steps up to
+/// and including `last_step`
+fn matching_step(secret: &[u8], code: &str, last_step: Option<i64>) -> Option<i64> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time went backwards")
+        .as_secs()
+        / STEP;
+    let now = now as i64;
+
+    (now - ALLOWED_DRIFT..=now + ALLOWED_DRIFT)
+        .filter(|&step| last_step.map(|last_step| step > last_step).unwrap_or(true))
+        .find(|&step| hotp(secret, step as u64) == code)
+}
+
+/// Computes the HOTP code (see [RFC 4226](https://tools.ietf.org/html/rfc4226)) for the given
+/// counter. TOTP codes are HOTP codes whose counter is the current time step
+fn hotp(secret: &[u8], counter: u64) -> String {
+    let mut mac = Hmac::<Sha1>::new_varkey(secret).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // "dynamic truncation"
+    let offset = (hash[hash.len() - 1] & 0xf) as usize;
+    let value = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+
+    format!("{:0width$}", value % 10u32.pow(DIGITS), width = DIGITS as usize)
+}
+
+/// Base32 encoding (see [RFC 4648](https://tools.ietf.org/html/rfc4648)) without padding, which is
+/// what authenticator apps expect secrets to be in
+fn base32(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+    let mut encoded = String::with_capacity((data.len() * 8 + 4) / 5);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u16;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+
+        buffer &= (1 << bits) - 1;
+    }
+
+    if bits > 0 {
+        encoded.push(ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{base32, hotp, STEP};
+    use crate::{
+        error::PointercrateError,
+        model::user::{AuthenticatedUser, Authorization},
+        ratelimit::Ratelimits,
+    };
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn test_hotp() {
+        // test vectors from RFC 4226, appendix D
+        let secret = b"12345678901234567890";
+
+        assert_eq!(hotp(secret, 0), "755224");
+        assert_eq!(hotp(secret, 1), "287082");
+        assert_eq!(hotp(secret, 9), "520489");
+    }
+
+    #[test]
+    fn test_base32() {
+        // test vectors from RFC 4648, section 10
+        assert_eq!(base32(b"f"), "MY");
+        assert_eq!(base32(b"fo"), "MZXQ");
+        assert_eq!(base32(b"foobar"), "MZXW6YTBOI");
+    }
+
+    #[actix_rt::test]
+    async fn test_second_factor_ratelimit() {
+        let mut connection = crate::test::test_setup().await;
+
+        let secret = b"12345678901234567890";
+
+        sqlx::query!(
+            "INSERT INTO two_factor_secrets (member, secret, confirmed) SELECT member_id, $1, TRUE FROM members WHERE name = \
+             'stadust_existing'",
+            &secret[..]
+        )
+        .execute(&mut connection)
+        .await
+        .unwrap();
+
+        let ratelimits = Ratelimits::in_memory();
+        let ratelimits = ratelimits.prepare(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+
+        let step = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() / STEP;
+        let authorization = |code: String| {
+            Authorization::Basic {
+                username: "stadust_existing".to_owned(),
+                password: "password1234567890".to_string(),
+                second_factor: Some(code),
+            }
+        };
+
+        for _ in 0..3 {
+            assert_eq!(
+                AuthenticatedUser::basic_auth(&authorization("000000".to_string()), &mut connection, Some(&ratelimits))
+                    .await
+                    .err(),
+                Some(PointercrateError::InvalidTwoFactorCode)
+            );
+        }
+
+        // Once the limit is exhausted, not even a valid code gets through
+        match AuthenticatedUser::basic_auth(&authorization(hotp(secret, step)), &mut connection, Some(&ratelimits)).await {
+            Err(PointercrateError::Ratelimited { .. }) => (),
+            result => panic!("Expected ratelimit, got {:?}", result.map(|user| user.inner().id)),
+        }
+    }
+}
@@ -1,32 +1,64 @@
 use crate::{
-    error::PointercrateError,
     model::user::User,
+    pagination::{Page, PaginationParameters, SortColumn},
     permissions::Permissions,
-    util::{non_nullable, nullable},
+    util::{non_nullable, nullable_query},
     Result,
 };
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgRow, PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    PgConnection, Postgres, Row,
+};
 
-#[derive(Deserialize, Debug, Clone, Serialize)]
-pub struct UserPagination {
-    #[serde(rename = "before", default, deserialize_with = "non_nullable")]
-    pub before_id: Option<i32>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UserSortColumn {
+    Id,
+    Name,
+}
 
-    #[serde(rename = "after", default, deserialize_with = "non_nullable")]
-    pub after_id: Option<i32>,
+impl Default for UserSortColumn {
+    fn default() -> Self {
+        UserSortColumn::Id
+    }
+}
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub limit: Option<u8>,
+impl SortColumn for UserSortColumn {
+    const ALL: &'static [Self] = &[UserSortColumn::Id, UserSortColumn::Name];
+
+    fn name(self) -> &'static str {
+        match self {
+            UserSortColumn::Id => "id",
+            UserSortColumn::Name => "name",
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            UserSortColumn::Id => "member_id",
+            UserSortColumn::Name => "name",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            UserSortColumn::Id => "INTEGER",
+            UserSortColumn::Name => "TEXT",
+        }
+    }
+}
 
+#[derive(Deserialize, Debug, Clone, Serialize)]
+pub struct UserPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     pub name: Option<String>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub name_contains: Option<String>,
 
-    #[serde(default, deserialize_with = "nullable")]
+    #[serde(default, with = "nullable_query", skip_serializing_if = "Option::is_none")]
     pub display_name: Option<Option<String>>,
 
     #[serde(default, deserialize_with = "non_nullable")]
@@ -37,55 +69,56 @@ pub struct UserPagination {
 }
 
 impl UserPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<User>> {
-        if let Some(limit) = self.limit {
-            if limit < 1 || limit > 100 {
-                return Err(PointercrateError::InvalidPaginationLimit)
-            }
-        }
-
-        if let (Some(after), Some(before)) = (self.before_id, self.after_id) {
-            if after < before {
-                return Err(PointercrateError::AfterSmallerBefore)
-            }
-        }
+    pub async fn page(&self, parameters: &PaginationParameters<UserSortColumn>, connection: &mut PgConnection) -> Result<Page<User>> {
+        parameters.validate()?;
 
-        let order = if self.after_id.is_none() && self.before_id.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
-
-        let query = format!(include_str!("../../../sql/paginate_users.sql"), order);
-
-        let mut stream = sqlx::query(&query)
-            .bind(self.before_id)
-            .bind(self.after_id)
-            .bind(self.name.as_ref())
-            .bind(self.display_name.as_ref())
-            .bind(self.display_name == Some(None))
-            .bind(self.has_permissions.map(|p| p.bits() as i32))
-            .bind(self.any_permissions.map(|p| p.bits() as i32))
-            .bind(self.name_contains.as_ref())
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
+        let query = include_str!("../../../sql/paginate_users.sql");
+        let page_query = parameters.page_query(query);
 
         let mut users = Vec::new();
+        let mut stream = self.bind(parameters.bind_cursors(sqlx::query(&page_query))).fetch(&mut *connection);
 
         while let Some(row) = stream.next().await {
             let row: PgRow = row?;
 
             let perms_as_i32: i32 = row.get("permissions");
 
-            users.push(User {
+            let user = User {
                 id: row.get("member_id"),
                 name: row.get("name"),
                 permissions: Permissions::from_bits_truncate(perms_as_i32 as u16),
                 display_name: row.get("display_name"),
                 youtube_channel: row.get("youtube_channel"),
-            })
+            };
+
+            users.push((parameters.cursor(&row)?, user))
+        }
+
+        drop(stream);
+
+        let mut page = Page::new(parameters, users);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                self.bind(parameters.bind_no_cursors(sqlx::query(&count_query)))
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
         }
 
-        Ok(users)
+        Ok(page)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.name.as_ref())
+            .bind(self.display_name.as_ref())
+            .bind(self.display_name == Some(None))
+            .bind(self.has_permissions.map(|p| p.bits() as i32))
+            .bind(self.any_permissions.map(|p| p.bits() as i32))
+            .bind(self.name_contains.as_ref())
     }
 }
@@ -0,0 +1,44 @@
+//! Module containing the account security policy, which administrators can change at runtime
+
+use crate::{util::non_nullable, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, Hash, Eq, PartialEq, Clone, Copy)]
+pub struct SecurityPolicy {
+    /// Whether members of the list team need to enable two-factor authentication to use their list
+    /// team permissions
+    pub list_team_requires_2fa: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PatchSecurityPolicy {
+    #[serde(default, deserialize_with = "non_nullable")]
+    list_team_requires_2fa: Option<bool>,
+}
+
+impl SecurityPolicy {
+    pub async fn load(connection: &mut PgConnection) -> Result<SecurityPolicy> {
+        Ok(sqlx::query_as!(SecurityPolicy, "SELECT list_team_requires_2fa FROM security_policy")
+            .fetch_one(connection)
+            .await?)
+    }
+
+    pub async fn apply_patch(mut self, patch: PatchSecurityPolicy, connection: &mut PgConnection) -> Result<Self> {
+        info!("Patching security policy {:?} with {:?}", self, patch);
+
+        if let Some(list_team_requires_2fa) = patch.list_team_requires_2fa {
+            self.list_team_requires_2fa = list_team_requires_2fa;
+        }
+
+        sqlx::query!(
+            "UPDATE security_policy SET list_team_requires_2fa = $1",
+            self.list_team_requires_2fa
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(self)
+    }
+}
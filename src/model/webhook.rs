@@ -0,0 +1,165 @@
+//! Module containing the webhook registry
+//!
+//! Webhooks are managed by list administrators via the API. Whenever an event a webhook is
+//! subscribed to happens, a [`WebhookDelivery`] is queued in the same transaction as the change
+//! that caused the event (so that rolled back changes do not trigger webhooks). The queued
+//! deliveries are then sent out by a background task in [`crate::scheduler`], which retries failed
+//! deliveries with exponential backoff.
+
+pub use self::{
+    delivery::{deliver_pending, dispatch, DeliveryPagination, DeliverySortColumn, DeliveryStatus, WebhookDelivery},
+    event::{Event, WebhookEvent},
+    patch::PatchWebhook,
+    post::PostWebhook,
+};
+use crate::{error::PointercrateError, Result};
+use chrono::NaiveDateTime;
+use derive_more::Display;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
+use url::Url;
+
+mod delete;
+mod delivery;
+mod event;
+mod get;
+mod patch;
+mod post;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum WebhookFormat {
+    /// A Discord message with a single rich embed
+    Discord,
+
+    /// A generic JSON object containing the event name and the affected objects
+    Json,
+
+    /// A Slack incoming webhook message with a single attachment
+    Slack,
+}
+
+impl WebhookFormat {
+    fn to_sql(&self) -> String {
+        match self {
+            WebhookFormat::Discord => "DISCORD",
+            WebhookFormat::Json => "JSON",
+            WebhookFormat::Slack => "SLACK",
+        }
+        .to_owned()
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "DISCORD" => WebhookFormat::Discord,
+            "JSON" => WebhookFormat::Json,
+            "SLACK" => WebhookFormat::Slack,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Renders the request body this webhook format uses for the given event
+    pub fn render(&self, event: &Event) -> serde_json::Value {
+        match self {
+            WebhookFormat::Discord => {
+                let fields = event
+                    .fields
+                    .iter()
+                    .map(|(name, value)| json!({"name": name, "value": value, "inline": value.len() < 40}))
+                    .collect::<Vec<_>>();
+
+                json!({
+                    "content": "",
+                    "embeds": [{
+                        "type": "rich",
+                        "title": event.title,
+                        "description": event.description,
+                        "fields": fields,
+                        "color": event.kind.color()
+                    }]
+                })
+            },
+            WebhookFormat::Slack => {
+                let fields = event
+                    .fields
+                    .iter()
+                    .map(|(name, value)| json!({"title": name, "value": value, "short": value.len() < 40}))
+                    .collect::<Vec<_>>();
+
+                json!({
+                    "text": event.title,
+                    "attachments": [{
+                        "color": format!("#{:06x}", event.kind.color()),
+                        "text": event.description,
+                        "fields": fields
+                    }]
+                })
+            },
+            WebhookFormat::Json =>
+                json!({
+                    "event": event.kind,
+                    "data": event.data
+                }),
+        }
+    }
+}
+
+impl Serialize for WebhookFormat {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_sql().to_lowercase())
+    }
+}
+
+impl<'de> Deserialize<'de> for WebhookFormat {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?.to_lowercase();
+
+        match &string[..] {
+            "discord" => Ok(WebhookFormat::Discord),
+            "json" => Ok(WebhookFormat::Json),
+            "slack" => Ok(WebhookFormat::Slack),
+            _ =>
+                Err(serde::de::Error::invalid_value(
+                    serde::de::Unexpected::Str(&string),
+                    &"'discord', 'json' or 'slack'",
+                )),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Hash, Display, Clone, PartialEq, Eq)]
+#[display(fmt = "webhook {} ({})", id, url)]
+pub struct Webhook {
+    pub id: i32,
+    pub url: String,
+    pub format: WebhookFormat,
+    pub events: Vec<WebhookEvent>,
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+}
+
+impl Webhook {
+    /// Validates that the given URL is something we can send webhooks to
+    pub fn validate_url(url: &str) -> Result<String> {
+        let url = Url::parse(url).map_err(|_| {
+            PointercrateError::BadRequest {
+                message: "Malformed webhook URL".to_owned(),
+            }
+        })?;
+
+        if url.scheme() != "https" && url.scheme() != "http" {
+            return Err(PointercrateError::InvalidUrlScheme)
+        }
+
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err(PointercrateError::UrlAuthenticated)
+        }
+
+        Ok(url.to_string())
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{model::webhook::Webhook, Result};
+use log::info;
+use sqlx::PgConnection;
+
+impl Webhook {
+    /// Deletes this webhook, together with its delivery log
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("DELETE FROM webhooks WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        info!("Deleted {}", self);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,350 @@
+use crate::{
+    error::PointercrateError,
+    model::webhook::{Event, Webhook},
+    pagination::{Page, PaginationParameters, SortColumn},
+    state::PointercrateState,
+    util::non_nullable,
+    Result,
+};
+use chrono::NaiveDateTime;
+use futures::StreamExt;
+use log::{debug, error, warn};
+use serde::{Deserialize, Serialize, Serializer};
+use sqlx::{PgConnection, Row};
+use std::time::Duration;
+
+/// How often we try to deliver a payload before giving up on it
+const MAX_ATTEMPTS: i16 = 8;
+
+/// The delay before the first retry. Doubles with every further attempt, so the last retry happens
+/// roughly an hour after the first attempt
+const BASE_RETRY_DELAY: u64 = 30;
+
+/// How many pending deliveries are sent out per tick of the delivery task
+const DELIVERY_BATCH_SIZE: i64 = 25;
+
+/// How many seconds we wait for a webhook server to respond to a single delivery
+const DELIVERY_TIMEOUT: u64 = 10;
+
+/// How many seconds a batch of deliveries stays claimed by the instance sending it out. Needs to be
+/// larger than the time it takes to work through a batch whose requests all time out
+const DELIVERY_LEASE: u64 = 2 * DELIVERY_TIMEOUT * DELIVERY_BATCH_SIZE as u64;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl DeliveryStatus {
+    fn to_sql(&self) -> String {
+        match self {
+            DeliveryStatus::Pending => "PENDING",
+            DeliveryStatus::Delivered => "DELIVERED",
+            DeliveryStatus::Failed => "FAILED",
+        }
+        .to_owned()
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "PENDING" => DeliveryStatus::Pending,
+            "DELIVERED" => DeliveryStatus::Delivered,
+            "FAILED" => DeliveryStatus::Failed,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Serialize for DeliveryStatus {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_sql().to_lowercase())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook: i32,
+    pub event: String,
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: i16,
+    pub last_status: Option<i16>,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub next_attempt_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+/// Queues deliveries of the given event for all webhooks subscribed to it
+///
+/// Should be called with the connection the change that caused the event was made on, so that the
+/// deliveries are only queued if that change is committed.
+pub async fn dispatch(event: Event, connection: &mut PgConnection) -> Result<()> {
+    let webhooks = Webhook::subscribed_to(event.kind, connection).await?;
+
+    for webhook in webhooks {
+        debug!("Queueing delivery of {:?} to {}", event.kind, webhook);
+
+        sqlx::query!(
+            "INSERT INTO webhook_deliveries (webhook, event, payload) VALUES ($1, $2, $3)",
+            webhook.id,
+            event.kind.name(),
+            webhook.format.render(&event).to_string()
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(())
+}
+
+impl WebhookDelivery {
+    pub async fn by_id(webhook: &Webhook, id: i32, connection: &mut PgConnection) -> Result<WebhookDelivery> {
+        let row = sqlx::query!(
+            r#"SELECT id, webhook, event, payload, status::text AS "status!: String", attempts, last_status, last_error, created_at,
+             next_attempt_at, delivered_at FROM webhook_deliveries WHERE id = $1 AND webhook = $2"#,
+            id,
+            webhook.id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        match row {
+            Some(row) =>
+                Ok(WebhookDelivery {
+                    id: row.id,
+                    webhook: row.webhook,
+                    event: row.event,
+                    payload: row.payload,
+                    status: DeliveryStatus::from_sql(&row.status),
+                    attempts: row.attempts,
+                    last_status: row.last_status,
+                    last_error: row.last_error,
+                    created_at: row.created_at,
+                    next_attempt_at: row.next_attempt_at,
+                    delivered_at: row.delivered_at,
+                }),
+            None =>
+                Err(PointercrateError::ModelNotFound {
+                    model: "WebhookDelivery",
+                    identified_by: id.to_string(),
+                }),
+        }
+    }
+
+    /// Schedules a failed delivery for another round of attempts
+    pub async fn retry(mut self, connection: &mut PgConnection) -> Result<WebhookDelivery> {
+        if self.status != DeliveryStatus::Failed {
+            return Ok(self)
+        }
+
+        let next_attempt_at = sqlx::query!(
+            "UPDATE webhook_deliveries SET status = 'PENDING', attempts = 0, next_attempt_at = (NOW() AT TIME ZONE 'utc') WHERE id = $1 \
+             RETURNING next_attempt_at",
+            self.id
+        )
+        .fetch_one(connection)
+        .await?
+        .next_attempt_at;
+
+        self.status = DeliveryStatus::Pending;
+        self.attempts = 0;
+        self.next_attempt_at = next_attempt_at;
+
+        Ok(self)
+    }
+}
+
+/// Sends out all deliveries that are due. Called periodically by the scheduler
+///
+/// Due deliveries are first claimed by pushing their next attempt back by `DELIVERY_LEASE`
+/// seconds, so that other instances running this task concurrently skip them. The database
+/// connection is released while the payloads are being sent.
+pub async fn deliver_pending(state: &PointercrateState) -> Result<()> {
+    let mut connection = state.connection().await?;
+
+    let due = sqlx::query!(
+        r#"UPDATE webhook_deliveries SET next_attempt_at = (NOW() AT TIME ZONE 'utc') + make_interval(secs => $2) FROM webhooks
+         WHERE webhooks.id = webhook_deliveries.webhook AND webhook_deliveries.id IN (SELECT webhook_deliveries.id FROM
+         webhook_deliveries INNER JOIN webhooks ON webhooks.id = webhook_deliveries.webhook WHERE status = 'PENDING' AND webhooks.active
+         AND next_attempt_at <= (NOW() AT TIME ZONE 'utc') ORDER BY webhook_deliveries.id LIMIT $1 FOR UPDATE OF webhook_deliveries
+         SKIP LOCKED) RETURNING webhook_deliveries.id, webhook_deliveries.payload, webhook_deliveries.attempts, webhooks.url AS "url!""#,
+        DELIVERY_BATCH_SIZE,
+        DELIVERY_LEASE as f64
+    )
+    .fetch_all(&mut connection)
+    .await?;
+
+    drop(connection);
+
+    for delivery in due {
+        let attempts = delivery.attempts + 1;
+
+        let result = state
+            .http_client
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .timeout(Duration::from_secs(DELIVERY_TIMEOUT))
+            .body(delivery.payload)
+            .send()
+            .await;
+
+        let mut connection = state.connection().await?;
+
+        let (last_status, last_error) = match result {
+            Ok(response) if response.status().is_success() => {
+                debug!("Successfully delivered webhook payload {}", delivery.id);
+
+                sqlx::query!(
+                    "UPDATE webhook_deliveries SET status = 'DELIVERED', attempts = $2, last_status = $3, last_error = NULL, delivered_at = \
+                     (NOW() AT TIME ZONE 'utc') WHERE id = $1",
+                    delivery.id,
+                    attempts,
+                    response.status().as_u16() as i16
+                )
+                .execute(&mut connection)
+                .await?;
+
+                continue
+            },
+            Ok(response) => (Some(response.status().as_u16() as i16), format!("Server responded with {}", response.status())),
+            Err(err) => (None, err.to_string()),
+        };
+
+        if attempts >= MAX_ATTEMPTS {
+            error!(
+                "Giving up on delivering webhook payload {} to {} after {} attempts: {}",
+                delivery.id, delivery.url, attempts, last_error
+            );
+
+            sqlx::query!(
+                "UPDATE webhook_deliveries SET status = 'FAILED', attempts = $2, last_status = $3, last_error = $4 WHERE id = $1",
+                delivery.id,
+                attempts,
+                last_status,
+                last_error
+            )
+            .execute(&mut connection)
+            .await?;
+        } else {
+            let delay = BASE_RETRY_DELAY << (attempts - 1) as u64;
+
+            warn!(
+                "Failed to deliver webhook payload {} to {} (attempt {}): {}. Retrying in {}s",
+                delivery.id, delivery.url, attempts, last_error, delay
+            );
+
+            sqlx::query!(
+                "UPDATE webhook_deliveries SET attempts = $2, last_status = $3, last_error = $4, next_attempt_at = (NOW() AT TIME ZONE \
+                 'utc') + make_interval(secs => $5) WHERE id = $1",
+                delivery.id,
+                attempts,
+                last_status,
+                last_error,
+                delay as f64
+            )
+            .execute(&mut connection)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeliverySortColumn {
+    Id,
+}
+
+impl Default for DeliverySortColumn {
+    fn default() -> Self {
+        DeliverySortColumn::Id
+    }
+}
+
+impl SortColumn for DeliverySortColumn {
+    const ALL: &'static [Self] = &[DeliverySortColumn::Id];
+
+    fn name(self) -> &'static str {
+        "id"
+    }
+
+    fn column(self) -> &'static str {
+        "id"
+    }
+
+    fn sql_type(self) -> &'static str {
+        "INTEGER"
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+pub struct DeliveryPagination {
+    #[serde(default, deserialize_with = "non_nullable")]
+    status: Option<String>,
+}
+
+impl DeliveryPagination {
+    pub async fn page(
+        &self, webhook: &Webhook, parameters: &PaginationParameters<DeliverySortColumn>, connection: &mut PgConnection,
+    ) -> Result<Page<WebhookDelivery>> {
+        parameters.validate()?;
+
+        let query = "SELECT id, webhook, event, payload, status::text AS status, attempts, last_status, last_error, created_at, \
+                     next_attempt_at, delivered_at FROM webhook_deliveries WHERE webhook = $5 AND (status::text = UPPER($6) OR $6 IS NULL)";
+        let page_query = parameters.page_query(query);
+
+        let mut deliveries = Vec::new();
+        let mut stream = parameters
+            .bind_cursors(sqlx::query(&page_query))
+            .bind(webhook.id)
+            .bind(&self.status)
+            .fetch(&mut *connection);
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            let delivery = WebhookDelivery {
+                id: row.get("id"),
+                webhook: row.get("webhook"),
+                event: row.get("event"),
+                payload: row.get("payload"),
+                status: DeliveryStatus::from_sql(row.get("status")),
+                attempts: row.get("attempts"),
+                last_status: row.get("last_status"),
+                last_error: row.get("last_error"),
+                created_at: row.get("created_at"),
+                next_attempt_at: row.get("next_attempt_at"),
+                delivered_at: row.get("delivered_at"),
+            };
+
+            deliveries.push((parameters.cursor(&row)?, delivery))
+        }
+
+        drop(stream);
+
+        let mut page = Page::new(parameters, deliveries);
+
+        if parameters.count {
+            let count_query = parameters.count_query(query);
+
+            page.total = Some(
+                parameters
+                    .bind_no_cursors(sqlx::query(&count_query))
+                    .bind(webhook.id)
+                    .bind(&self.status)
+                    .fetch_one(connection)
+                    .await?
+                    .get(0),
+            );
+        }
+
+        Ok(page)
+    }
+}
@@ -0,0 +1,159 @@
+use crate::model::demonlist::{demon::MinimalDemon, player::DatabasePlayer, record::FullRecord};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum WebhookEvent {
+    RecordSubmitted,
+    RecordApproved,
+    RecordRejected,
+    DemonMoved,
+    PlayerBanned,
+}
+
+impl WebhookEvent {
+    /// The name under which this event is stored in the `webhooks.events` column
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::RecordSubmitted => "record_submitted",
+            WebhookEvent::RecordApproved => "record_approved",
+            WebhookEvent::RecordRejected => "record_rejected",
+            WebhookEvent::DemonMoved => "demon_moved",
+            WebhookEvent::PlayerBanned => "player_banned",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "record_submitted" => Some(WebhookEvent::RecordSubmitted),
+            "record_approved" => Some(WebhookEvent::RecordApproved),
+            "record_rejected" => Some(WebhookEvent::RecordRejected),
+            "demon_moved" => Some(WebhookEvent::DemonMoved),
+            "player_banned" => Some(WebhookEvent::PlayerBanned),
+            _ => None,
+        }
+    }
+
+    /// The embed/attachment color used by the Discord and Slack formats
+    pub fn color(&self) -> u32 {
+        match self {
+            WebhookEvent::RecordSubmitted => 0xf542f5,
+            WebhookEvent::RecordApproved => 0x2ecc71,
+            WebhookEvent::RecordRejected => 0xe74c3c,
+            WebhookEvent::DemonMoved => 0x3498db,
+            WebhookEvent::PlayerBanned => 0x992d22,
+        }
+    }
+}
+
+impl Serialize for WebhookEvent {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for WebhookEvent {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?.to_lowercase();
+
+        WebhookEvent::from_name(&string).ok_or_else(|| {
+            serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(&string),
+                &"'record_submitted', 'record_approved', 'record_rejected', 'demon_moved' or 'player_banned'",
+            )
+        })
+    }
+}
+
+/// Something that happened on the list that webhooks can be notified about
+///
+/// Contains everything any [`WebhookFormat`](super::WebhookFormat) needs to render a payload:
+/// a human readable title, description and list of fields for the chat formats, and a machine
+/// readable representation of the affected objects for the JSON format.
+#[derive(Debug)]
+pub struct Event {
+    pub kind: WebhookEvent,
+    pub title: String,
+    pub description: String,
+    pub fields: Vec<(&'static str, String)>,
+    pub data: serde_json::Value,
+}
+
+impl Event {
+    /// Constructs an event of the given kind about the given record
+    pub fn record(kind: WebhookEvent, record: &FullRecord) -> Event {
+        let (title, description) = match kind {
+            WebhookEvent::RecordSubmitted =>
+                (
+                    format!("New Record Submitted ({})", record.id),
+                    "To accept the record, go to the [website](https://challengelist.gd/account/) and do so.".to_string(),
+                ),
+            WebhookEvent::RecordApproved => (format!("Record Approved ({})", record.id), String::new()),
+            _ => (format!("Record Rejected ({})", record.id), String::new()),
+        };
+
+        let mut fields = vec![
+            ("Player", record.player.name.to_string()),
+            ("Challenge", record.demon.name.to_string()),
+            ("Progress", format!("{}%", record.progress)),
+        ];
+
+        if let Some(ref video) = record.video {
+            fields.push(("Video Proof", video.clone()))
+        }
+
+        if let Some(ref raw_footage) = record.raw_footage {
+            fields.push(("Raw Footage", raw_footage.url.clone()))
+        }
+
+        if let Some(ref submitter) = record.submitter {
+            fields.push(("Submitter", format!("#{}", submitter.id)))
+        }
+
+        Event {
+            kind,
+            title,
+            description,
+            fields,
+            data: json!({
+                "id": record.id,
+                "progress": record.progress,
+                "video": record.video,
+                "status": record.status,
+                "player": record.player,
+                "demon": record.demon,
+                "submitter": record.submitter.as_ref().map(|submitter| submitter.id),
+            }),
+        }
+    }
+
+    pub fn demon_moved(demon: &MinimalDemon, from: i16) -> Event {
+        Event {
+            kind: WebhookEvent::DemonMoved,
+            title: format!("{} Moved", demon.name),
+            description: format!("{} was moved from #{} to #{}", demon.name, from, demon.position),
+            fields: vec![("From", format!("#{}", from)), ("To", format!("#{}", demon.position))],
+            data: json!({
+                "demon": demon,
+                "from": from,
+                "to": demon.position,
+            }),
+        }
+    }
+
+    pub fn player_banned(player: &DatabasePlayer) -> Event {
+        Event {
+            kind: WebhookEvent::PlayerBanned,
+            title: format!("Player Banned ({})", player.id),
+            description: format!("{} was banned. All their records have been rejected.", player.name),
+            fields: vec![("Player", player.name.to_string())],
+            data: json!({ "player": player }),
+        }
+    }
+}
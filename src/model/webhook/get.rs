@@ -0,0 +1,94 @@
+use crate::{
+    error::PointercrateError,
+    model::webhook::{Webhook, WebhookEvent, WebhookFormat},
+    Result,
+};
+use futures::StreamExt;
+use sqlx::{Error, PgConnection};
+
+fn parse_events(events: Vec<String>) -> Vec<WebhookEvent> {
+    // Unknown event names can only appear if an event type was removed from the code. Those are
+    // simply ignored
+    events.iter().filter_map(|name| WebhookEvent::from_name(name)).collect()
+}
+
+impl Webhook {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Webhook> {
+        let result = sqlx::query!(
+            r#"SELECT id, url, format::text AS "format!: String", events, active, created_at FROM webhooks WHERE id = $1"#,
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match result {
+            Ok(row) =>
+                Ok(Webhook {
+                    id: row.id,
+                    url: row.url,
+                    format: WebhookFormat::from_sql(&row.format),
+                    events: parse_events(row.events),
+                    active: row.active,
+                    created_at: row.created_at,
+                }),
+            Err(Error::RowNotFound) =>
+                Err(PointercrateError::ModelNotFound {
+                    model: "Webhook",
+                    identified_by: id.to_string(),
+                }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<Webhook>> {
+        let mut stream =
+            sqlx::query!(r#"SELECT id, url, format::text AS "format!: String", events, active, created_at FROM webhooks ORDER BY id"#)
+                .fetch(connection);
+
+        let mut webhooks = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            webhooks.push(Webhook {
+                id: row.id,
+                url: row.url,
+                format: WebhookFormat::from_sql(&row.format),
+                events: parse_events(row.events),
+                active: row.active,
+                created_at: row.created_at,
+            })
+        }
+
+        Ok(webhooks)
+    }
+
+    /// Gets all webhooks subscribed to the given event
+    ///
+    /// Includes inactive webhooks, as events happening while a webhook is paused are still queued
+    /// for it. Whether a webhook is active is only checked once its deliveries are sent out.
+    pub async fn subscribed_to(event: WebhookEvent, connection: &mut PgConnection) -> Result<Vec<Webhook>> {
+        let mut stream = sqlx::query!(
+            r#"SELECT id, url, format::text AS "format!: String", events, active, created_at FROM webhooks WHERE $1 = ANY(events)"#,
+            event.name()
+        )
+        .fetch(connection);
+
+        let mut webhooks = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            webhooks.push(Webhook {
+                id: row.id,
+                url: row.url,
+                format: WebhookFormat::from_sql(&row.format),
+                events: parse_events(row.events),
+                active: row.active,
+                created_at: row.created_at,
+            })
+        }
+
+        Ok(webhooks)
+    }
+}
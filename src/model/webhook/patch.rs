@@ -0,0 +1,95 @@
+use crate::{
+    model::webhook::{Webhook, WebhookEvent, WebhookFormat},
+    util::non_nullable,
+    Result,
+};
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Deserialize, Debug)]
+pub struct PatchWebhook {
+    #[serde(default, deserialize_with = "non_nullable")]
+    url: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    format: Option<WebhookFormat>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    events: Option<Vec<WebhookEvent>>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    active: Option<bool>,
+}
+
+impl Webhook {
+    /// Must be run within a transaction!
+    pub async fn apply_patch(mut self, patch: PatchWebhook, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(url) = patch.url {
+            self.set_url(url, connection).await?;
+        }
+
+        if let Some(format) = patch.format {
+            self.set_format(format, connection).await?;
+        }
+
+        if let Some(events) = patch.events {
+            self.set_events(events, connection).await?;
+        }
+
+        if let Some(active) = patch.active {
+            self.set_active(active, connection).await?;
+        }
+
+        Ok(self)
+    }
+
+    pub async fn set_url(&mut self, url: String, connection: &mut PgConnection) -> Result<()> {
+        let url = Webhook::validate_url(&url)?;
+
+        sqlx::query!("UPDATE webhooks SET url = $1 WHERE id = $2", url, self.id)
+            .execute(connection)
+            .await?;
+
+        self.url = url;
+
+        Ok(())
+    }
+
+    pub async fn set_format(&mut self, format: WebhookFormat, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE webhooks SET format = cast($1::text as webhook_format) WHERE id = $2",
+            format.to_sql(),
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        self.format = format;
+
+        Ok(())
+    }
+
+    pub async fn set_events(&mut self, events: Vec<WebhookEvent>, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE webhooks SET events = $1 WHERE id = $2",
+            &events.iter().map(|event| event.name().to_string()).collect::<Vec<_>>(),
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        self.events = events;
+
+        Ok(())
+    }
+
+    pub async fn set_active(&mut self, active: bool, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("UPDATE webhooks SET active = $1 WHERE id = $2", active, self.id)
+            .execute(connection)
+            .await?;
+
+        self.active = active;
+
+        Ok(())
+    }
+}
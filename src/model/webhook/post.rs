@@ -0,0 +1,66 @@
+use crate::{
+    model::webhook::{Webhook, WebhookEvent, WebhookFormat},
+    Result,
+};
+use log::info;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Deserialize, Debug)]
+pub struct PostWebhook {
+    url: String,
+    format: WebhookFormat,
+
+    #[serde(default)]
+    events: Vec<WebhookEvent>,
+}
+
+impl Webhook {
+    pub async fn create_from(data: PostWebhook, connection: &mut PgConnection) -> Result<Webhook> {
+        info!("Creating new webhook from {:?}", data);
+
+        let url = Webhook::validate_url(&data.url)?;
+
+        let events = data.events;
+
+        let row = sqlx::query!(
+            "INSERT INTO webhooks (url, format, events) VALUES ($1, cast($2::text as webhook_format), $3) RETURNING id, created_at",
+            url,
+            data.format.to_sql(),
+            &events.iter().map(|event| event.name().to_string()).collect::<Vec<_>>()
+        )
+        .fetch_one(connection)
+        .await?;
+
+        Ok(Webhook {
+            id: row.id,
+            url,
+            format: data.format,
+            events,
+            active: true,
+            created_at: row.created_at,
+        })
+    }
+
+    /// Registers the webhook configured via the `DISCORD_WEBHOOK` environment variable (which
+    /// predates the webhook registry) as a Discord webhook for new submissions
+    ///
+    /// This only ever happens once. Afterwards, the webhook is managed via the registry like any other,
+    /// and deleting it there is permanent.
+    pub async fn import_legacy(url: &str, connection: &mut PgConnection) -> Result<()> {
+        let imported = sqlx::query!(
+            "WITH recorded AS (INSERT INTO legacy_imports (name) VALUES ('DISCORD_WEBHOOK') ON CONFLICT DO NOTHING RETURNING name) INSERT \
+             INTO webhooks (url, format, events) SELECT $1, 'DISCORD', ARRAY['record_submitted'] FROM recorded WHERE NOT EXISTS (SELECT \
+             FROM webhooks WHERE url = $1)",
+            url
+        )
+        .execute(connection)
+        .await?;
+
+        if imported.rows_affected() > 0 {
+            info!("Imported DISCORD_WEBHOOK into the webhook registry");
+        }
+
+        Ok(())
+    }
+}
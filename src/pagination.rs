@@ -0,0 +1,481 @@
+//! Module containing the cursor based pagination used by the API's listing endpoints
+//!
+//! Paginated listings are ordered by one of a whitelisted set of [`SortColumn`]s, with ties broken
+//! by the objects' IDs. Pages are delimited by opaque [`Cursor`]s pointing at a specific object in
+//! that order, which are handed out to clients via the `Link` header. Since a cursor contains the
+//! sort key of the object it points at, pages stay consistent even if objects are added or removed
+//! between requests.
+//!
+//! Endpoints only have to provide a query selecting all objects matching the request's filters,
+//! which is then wrapped into the SQL doing the actual pagination (see
+//! [`PaginationParameters::page_query`]). The first four query parameters are reserved for the
+//! cursors, so the endpoint's own parameters have to start at `$5`.
+
+use crate::{error::PointercrateError, util::non_nullable, Result};
+use actix_web::HttpResponse;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    Postgres, Row,
+};
+use std::fmt::Debug;
+
+/// The page size used if a request doesn't specify one
+const DEFAULT_LIMIT: u8 = 50;
+
+/// A column a paginated listing can be ordered by
+///
+/// The [`Default`] value has to be the unique ID of the objects being paginated, as it is used to
+/// break ties between objects with the same sort key.
+pub trait SortColumn: Copy + Default + PartialEq + Debug + 'static {
+    /// All columns the listing can be ordered by
+    const ALL: &'static [Self];
+
+    /// The name of this column in the `sort` query parameter
+    fn name(self) -> &'static str;
+
+    /// The column of the paginated query's result set holding the sort key
+    fn column(self) -> &'static str;
+
+    /// The SQL type of [`SortColumn::column`], used to turn the sort keys stored in cursors back into
+    /// values
+    fn sql_type(self) -> &'static str;
+}
+
+/// The order of a paginated listing, given as `sort=column` or `sort=-column` (for descending
+/// order) in the query string
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Sort<C> {
+    pub column: C,
+    pub descending: bool,
+}
+
+impl<C: SortColumn> Serialize for Sort<C> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.descending {
+            serializer.serialize_str(&format!("-{}", self.column.name()))
+        } else {
+            serializer.serialize_str(self.column.name())
+        }
+    }
+}
+
+impl<'de, C: SortColumn> Deserialize<'de> for Sort<C> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+
+        let (descending, name) = if value.starts_with('-') {
+            (true, &value[1..])
+        } else {
+            (false, &value[..])
+        };
+
+        match C::ALL.iter().find(|column| column.name() == name) {
+            Some(&column) => Ok(Sort { column, descending }),
+            None =>
+                Err(D::Error::custom(format!(
+                    "cannot sort by '{}', expected one of {}",
+                    name,
+                    C::ALL.iter().map(|column| column.name()).collect::<Vec<_>>().join(", ")
+                ))),
+        }
+    }
+}
+
+/// A position in a paginated listing
+///
+/// Cursors are handed out to clients base64 encoded and should be treated as opaque by them. For
+/// backwards compatibility, plain integers are accepted as well and refer to the object with that
+/// ID (in the default order). For listings not ordered by an actual ID, this is the value of the
+/// default [`SortColumn`], for example a demon's position.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cursor {
+    /// Points at the object with the given ID, which has the given sort key
+    At {
+        /// The [`SortColumn::name`] of the column the cursor was generated for
+        column: String,
+        key: String,
+        id: i32,
+    },
+
+    /// Points past the last object of the listing
+    End,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        let json = match self {
+            Cursor::At { column, key, id } => serde_json::json!([column, key, id]),
+            Cursor::End => serde_json::json!("end"),
+        };
+
+        base64::encode_config(&json.to_string(), base64::URL_SAFE_NO_PAD)
+    }
+
+    fn decode(encoded: &str) -> Option<Cursor> {
+        if let Ok(id) = encoded.parse::<i32>() {
+            return Some(Cursor::At {
+                column: "id".to_string(),
+                key: id.to_string(),
+                id,
+            })
+        }
+
+        let json = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD).ok()?;
+
+        if let Ok((column, key, id)) = serde_json::from_slice::<(String, String, i32)>(&json) {
+            return Some(Cursor::At { column, key, id })
+        }
+
+        match serde_json::from_slice::<String>(&json) {
+            Ok(end) if end == "end" => Some(Cursor::End),
+            _ => None,
+        }
+    }
+
+    fn key_and_id(cursor: &Option<Cursor>) -> (Option<String>, Option<i32>) {
+        match cursor {
+            Some(Cursor::At { key, id, .. }) => (Some(key.clone()), Some(*id)),
+            _ => (None, None),
+        }
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+impl<'de> Deserialize<'de> for Cursor {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let encoded = String::deserialize(deserializer)?;
+
+        Cursor::decode(&encoded).ok_or_else(|| D::Error::custom("malformed pagination cursor"))
+    }
+}
+
+/// The query parameters controlling which page of a listing is returned. Filters are handled by
+/// endpoint specific structs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(bound(serialize = "C: SortColumn", deserialize = "C: SortColumn"))]
+pub struct PaginationParameters<C> {
+    /// The cursor pointing at the first object on the next page
+    #[serde(default, deserialize_with = "non_nullable", skip_serializing_if = "Option::is_none")]
+    pub before: Option<Cursor>,
+
+    /// The cursor pointing at the last object on the previous page
+    #[serde(default, deserialize_with = "non_nullable", skip_serializing_if = "Option::is_none")]
+    pub after: Option<Cursor>,
+
+    #[serde(default, deserialize_with = "non_nullable", skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u8>,
+
+    #[serde(default, skip_serializing_if = "is_default_sort")]
+    pub sort: Sort<C>,
+
+    /// Whether the total amount of objects matching the request's filters should be returned in the
+    /// `X-Total-Count` header. Off by default, since it requires an additional query
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub count: bool,
+}
+
+fn is_default_sort<C: SortColumn>(sort: &Sort<C>) -> bool {
+    *sort == Sort::default()
+}
+
+impl<C: SortColumn> PaginationParameters<C> {
+    fn limit(&self) -> u8 {
+        self.limit.unwrap_or(DEFAULT_LIMIT)
+    }
+
+    /// Whether the page has to be retrieved in reverse order, which is the case if it is only bounded
+    /// from above
+    fn backwards(&self) -> bool {
+        self.before.is_some() && self.after.is_none()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let limit = self.limit();
+
+        if limit < 1 || limit > 100 {
+            return Err(PointercrateError::InvalidPaginationLimit)
+        }
+
+        if self.after == Some(Cursor::End) {
+            return Err(PointercrateError::BadRequest {
+                message: "'after' cannot point past the end of the listing".to_string(),
+            })
+        }
+
+        for cursor in self.before.iter().chain(self.after.iter()) {
+            if let Cursor::At { column, .. } = cursor {
+                // Plain integer cursors are decoded as cursors for the "id" column, but always refer to
+                // the default order, even for listings whose ID field is called differently
+                let legacy = column == "id" && self.sort.column == C::default();
+
+                if column != self.sort.column.name() && !legacy {
+                    return Err(PointercrateError::BadRequest {
+                        message: format!(
+                            "Cursor for sorting by '{}' used while sorting by '{}'",
+                            column,
+                            self.sort.column.name()
+                        ),
+                    })
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The condition restricting a listing to the objects between the two cursors, which are bound
+    /// to the parameters `$1` to `$4`
+    fn condition(&self) -> String {
+        let column = self.sort.column.column();
+        let id = C::default().column();
+        let sql_type = self.sort.column.sql_type();
+
+        // Comparing the (key, id) tuples makes the order total even if sort keys aren't unique
+        let (before, after) = if self.sort.descending { (">", "<") } else { ("<", ">") };
+
+        format!(
+            "($1::TEXT IS NULL OR (page.{column}, page.{id}) {before} (CAST($1::TEXT AS {ty}), $2::INTEGER)) AND ($3::TEXT IS NULL OR \
+             (page.{column}, page.{id}) {after} (CAST($3::TEXT AS {ty}), $4::INTEGER))",
+            column = column,
+            id = id,
+            ty = sql_type,
+            before = before,
+            after = after
+        )
+    }
+
+    /// Wraps the given query, which selects all objects matching a request's filters, into SQL
+    /// retrieving the page described by these parameters
+    ///
+    /// Note that the resulting query returns _one more object than requested_, which [`Page::new`]
+    /// uses to determine whether further pages exist. If the page is only bounded from above, the
+    /// objects are returned in reverse order.
+    pub fn page_query(&self, query: &str) -> String {
+        let column = self.sort.column.column();
+        let id = C::default().column();
+        let direction = if self.sort.descending != self.backwards() { "DESC" } else { "ASC" };
+
+        format!(
+            "SELECT page.*, CAST(page.{column} AS TEXT) AS sort_key FROM ({query}) AS page WHERE {condition} ORDER BY page.{column} \
+             {direction}, page.{id} {direction} LIMIT {limit}",
+            column = column,
+            query = query,
+            condition = self.condition(),
+            direction = direction,
+            id = id,
+            limit = self.limit() as i32 + 1
+        )
+    }
+
+    /// Wraps the given query, which selects all objects matching a request's filters, into SQL
+    /// counting them. The cursors have to be bound via [`PaginationParameters::bind_no_cursors`]
+    pub fn count_query(&self, query: &str) -> String {
+        format!("SELECT COUNT(*) FROM ({}) AS page WHERE {}", query, self.condition())
+    }
+
+    /// Binds the cursors to a query generated by [`PaginationParameters::page_query`]. Must be
+    /// called before any other parameters are bound
+    pub fn bind_cursors<'q>(&self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        let (before_key, before_id) = Cursor::key_and_id(&self.before);
+        let (after_key, after_id) = Cursor::key_and_id(&self.after);
+
+        query.bind(before_key).bind(before_id).bind(after_key).bind(after_id)
+    }
+
+    /// Binds empty cursors to a query generated by [`PaginationParameters::count_query`], since the
+    /// total count shouldn't depend on the current page. Must be called before any other parameters
+    /// are bound
+    pub fn bind_no_cursors<'q>(&self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query.bind(None::<String>).bind(None::<i32>).bind(None::<String>).bind(None::<i32>)
+    }
+
+    /// Constructs the cursor pointing at the given row of the result of a query generated by
+    /// [`PaginationParameters::page_query`]
+    pub fn cursor(&self, row: &PgRow) -> Result<Cursor> {
+        Ok(Cursor::At {
+            column: self.sort.column.name().to_string(),
+            key: row.try_get("sort_key")?,
+            id: row.try_get(C::default().column())?,
+        })
+    }
+}
+
+/// A page of objects, together with the information needed to link to the neighbouring pages
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+
+    first: Option<Cursor>,
+    last: Option<Cursor>,
+
+    /// Whether there are further objects beyond this page in the direction it was retrieved in
+    more: bool,
+
+    /// The total amount of objects matching the request's filters. Only set if requested via the
+    /// `count` parameter
+    pub total: Option<i64>,
+}
+
+impl<T> Page<T> {
+    /// Constructs a page from the rows returned by a query generated by
+    /// [`PaginationParameters::page_query`], each paired with the cursor pointing at it
+    pub fn new<C: SortColumn>(parameters: &PaginationParameters<C>, mut rows: Vec<(Cursor, T)>) -> Page<T> {
+        let more = rows.len() > parameters.limit() as usize;
+
+        if more {
+            // remove the object from the next page
+            rows.pop();
+        }
+
+        if parameters.backwards() {
+            rows.reverse();
+        }
+
+        Page {
+            first: rows.first().map(|(cursor, _)| cursor.clone()),
+            last: rows.last().map(|(cursor, _)| cursor.clone()),
+            items: rows.into_iter().map(|(_, item)| item).collect(),
+            more,
+            total: None,
+        }
+    }
+
+    /// Generates the value of the `Link` header for this page
+    ///
+    /// `filters` are the endpoint specific query parameters of the request, which are carried over
+    /// into all links
+    fn links<C: SortColumn, F: Serialize>(&self, endpoint: &str, filters: &F, parameters: &PaginationParameters<C>) -> String {
+        let filters = serde_urlencoded::to_string(filters).unwrap();
+
+        let link = |before: Option<Cursor>, after: Option<Cursor>, rel: &str| {
+            let parameters = PaginationParameters {
+                before,
+                after,
+                ..parameters.clone()
+            };
+            let parameters = serde_urlencoded::to_string(&parameters).unwrap();
+            let query = [&filters[..], &parameters[..]]
+                .iter()
+                .filter(|part| !part.is_empty())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("&");
+
+            format!("<{}?{}>; rel=\"{}\"", endpoint, query, rel)
+        };
+
+        let mut links = vec![link(None, None, "first"), link(Some(Cursor::End), None, "last")];
+
+        if let (Some(first), Some(last)) = (&self.first, &self.last) {
+            if parameters.backwards() {
+                if self.more {
+                    links.push(link(Some(first.clone()), None, "prev"));
+                }
+
+                if parameters.before != Some(Cursor::End) {
+                    links.push(link(None, Some(last.clone()), "next"));
+                }
+            } else {
+                // If both cursors are set, only the objects up to 'before' are supposed to be paginated
+                if self.more {
+                    links.push(link(parameters.before.clone(), Some(last.clone()), "next"));
+                }
+
+                if parameters.after.is_some() {
+                    links.push(link(Some(first.clone()), None, "prev"));
+                }
+            }
+        }
+
+        links.join(", ")
+    }
+
+    /// Generates the response to a pagination request, with the `Link` and (if requested)
+    /// `X-Total-Count` headers set
+    pub fn response<C: SortColumn, F: Serialize>(&self, endpoint: &str, filters: &F, parameters: &PaginationParameters<C>) -> HttpResponse
+    where
+        T: Serialize,
+    {
+        let links = self.links(endpoint, filters, parameters);
+
+        log::debug!("Link header has value '{}'", links);
+
+        let mut response = HttpResponse::Ok();
+
+        response.header("Link", links);
+
+        if let Some(total) = self.total {
+            response.header("X-Total-Count", total.to_string());
+        }
+
+        response.json(&self.items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cursor, Page, PaginationParameters};
+    use crate::model::user::{UserPagination, UserSortColumn};
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = Cursor::At {
+            column: "progress".to_string(),
+            key: "100".to_string(),
+            id: 42,
+        };
+
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(Cursor::decode(&Cursor::End.encode()), Some(Cursor::End));
+        assert_eq!(Cursor::decode("not a cursor"), None);
+    }
+
+    #[test]
+    fn test_integer_cursor() {
+        assert_eq!(
+            Cursor::decode("123"),
+            Some(Cursor::At {
+                column: "id".to_string(),
+                key: "123".to_string(),
+                id: 123
+            })
+        );
+    }
+
+    #[test]
+    fn test_null_filter_in_links() {
+        let filters: UserPagination = serde_urlencoded::from_str("display_name=").unwrap();
+
+        assert_eq!(filters.display_name, Some(None));
+
+        let parameters = PaginationParameters::<UserSortColumn>::default();
+        let page = Page::<()>::new(&parameters, Vec::new());
+        let links = page.links("/api/v1/users/", &filters, &parameters);
+
+        for link in links.split(", ") {
+            let query = &link[link.find('?').unwrap() + 1..link.find('>').unwrap()];
+            let filters: UserPagination = serde_urlencoded::from_str(query).unwrap();
+
+            assert_eq!(filters.display_name, Some(None), "{}", link);
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use bitflags::bitflags;
+use lazy_static::lazy_static;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{Display, Formatter};
 
 bitflags! {
     /// Permissions bitmask used for authorisation.
     ///
+    /// Each bit represents a role, see [`ROLES`] for what each of them allows.
+    ///
     /// A `Permissions` object can be see as a 16-ary boolean function that evaluate to true if,
     /// and only if, **all** bits that are set in the [`Permissions`] object are also set in the input.
     ///
@@ -30,33 +33,167 @@ bitflags! {
     }
 }
 
-impl Display for Permissions {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        let mut perms = Vec::new();
+bitflags! {
+    /// The individual actions [`Permissions`] can allow a user to perform.
+    ///
+    /// Capabilities are never assigned directly, they are always granted by a role (see [`ROLES`]).
+    pub struct Capabilities: u32 {
+        /// See records that haven't been approved (yet)
+        const ViewUnapprovedRecords = 1 << 0;
 
-        if *self & Permissions::ExtendedAccess == Permissions::ExtendedAccess {
-            perms.push("Extended Access")
-        }
+        /// Claim records, add notes to them, see their audit logs and raw footage
+        const ReviewRecords = 1 << 1;
 
-        if *self & Permissions::ListHelper == Permissions::ListHelper {
-            perms.push("List Helper")
-        }
+        /// Change the status of records, submit records without a video and delete unprocessed
+        /// submissions
+        const ApproveRecords = 1 << 2;
 
-        if *self & Permissions::ListModerator == Permissions::ListModerator {
-            perms.push("List Moderator")
-        }
+        /// Delete already processed records, and revert or restore records
+        const DeleteRecords = 1 << 3;
 
-        if *self & Permissions::ListAdministrator == Permissions::ListAdministrator {
-            perms.push("List Administrator")
-        }
+        /// Release claims on records held by other users
+        const OverrideClaims = 1 << 4;
 
-        if *self & Permissions::Moderator == Permissions::Moderator {
-            perms.push("Moderator")
-        }
+        /// Add demons, change their position and manage their creators
+        const ManageDemons = 1 << 5;
 
-        if *self & Permissions::Administrator == Permissions::Administrator {
-            perms.push("Administrator")
-        }
+        /// Edit and ban players
+        const ManagePlayers = 1 << 6;
+
+        /// View and ban individual submitters
+        const ManageSubmitters = 1 << 7;
+
+        /// List all submitters
+        const ListSubmitters = 1 << 8;
+
+        /// Edit and delete record notes written by other users
+        const ManageNotes = 1 << 9;
+
+        /// Manage scoring formulas, list sections and webhooks
+        const ConfigureList = 1 << 10;
+
+        /// View the audit log
+        const ViewAuditLog = 1 << 11;
+
+        /// See all users and change their display names and YouTube channels
+        const ModerateUsers = 1 << 12;
+
+        /// Delete users, issue password resets for them and change the security policy
+        const ManageUsers = 1 << 13;
+    }
+}
+
+/// A named role, represented by a single bit in a [`Permissions`] bitmask
+#[derive(Debug, Serialize)]
+pub struct Role {
+    pub permission: Permissions,
+    pub name: &'static str,
+
+    /// Other roles every holder of this role automatically has
+    pub implies: Permissions,
+
+    /// The roles that allow assigning (or revoking) this role. Having any of them suffices.
+    pub assigned_by: Permissions,
+
+    pub capabilities: Capabilities,
+}
+
+lazy_static! {
+    /// The names of all capabilities, as used in the API
+    pub static ref CAPABILITIES: Vec<(Capabilities, &'static str)> = vec![
+        (Capabilities::ViewUnapprovedRecords, "view_unapproved_records"),
+        (Capabilities::ReviewRecords, "review_records"),
+        (Capabilities::ApproveRecords, "approve_records"),
+        (Capabilities::DeleteRecords, "delete_records"),
+        (Capabilities::OverrideClaims, "override_claims"),
+        (Capabilities::ManageDemons, "manage_demons"),
+        (Capabilities::ManagePlayers, "manage_players"),
+        (Capabilities::ManageSubmitters, "manage_submitters"),
+        (Capabilities::ListSubmitters, "list_submitters"),
+        (Capabilities::ManageNotes, "manage_notes"),
+        (Capabilities::ConfigureList, "configure_list"),
+        (Capabilities::ViewAuditLog, "view_audit_log"),
+        (Capabilities::ModerateUsers, "moderate_users"),
+        (Capabilities::ManageUsers, "manage_users"),
+    ];
+
+    /// All roles that exist on pointercrate, in ascending order of their bits.
+    ///
+    /// Everything about what a role allows (and who can give it out) is defined here.
+    pub static ref ROLES: Vec<Role> = vec![
+        Role {
+            permission: Permissions::ExtendedAccess,
+            name: "Extended Access",
+            implies: Permissions::empty(),
+            assigned_by: Permissions::Administrator,
+            capabilities: Capabilities::ViewUnapprovedRecords,
+        },
+        Role {
+            permission: Permissions::ListHelper,
+            name: "List Helper",
+            implies: Permissions::ExtendedAccess,
+            assigned_by: Permissions::ListAdministrator | Permissions::Administrator,
+            capabilities: Capabilities::ReviewRecords | Capabilities::ApproveRecords,
+        },
+        Role {
+            permission: Permissions::ListModerator,
+            name: "List Moderator",
+            implies: Permissions::ListHelper,
+            assigned_by: Permissions::ListAdministrator | Permissions::Administrator,
+            capabilities: Capabilities::DeleteRecords
+                | Capabilities::OverrideClaims
+                | Capabilities::ManageDemons
+                | Capabilities::ManagePlayers
+                | Capabilities::ManageSubmitters,
+        },
+        Role {
+            permission: Permissions::ListAdministrator,
+            name: "List Administrator",
+            implies: Permissions::ListModerator,
+            assigned_by: Permissions::Administrator,
+            capabilities: Capabilities::ListSubmitters
+                | Capabilities::ManageNotes
+                | Capabilities::ConfigureList
+                | Capabilities::ViewAuditLog,
+        },
+        Role {
+            permission: Permissions::LeaderboardModerator,
+            name: "Leaderboard Moderator",
+            implies: Permissions::empty(),
+            assigned_by: Permissions::LeaderboardAdministrator | Permissions::Administrator,
+            capabilities: Capabilities::empty(),
+        },
+        Role {
+            permission: Permissions::LeaderboardAdministrator,
+            name: "Leaderboard Administrator",
+            implies: Permissions::LeaderboardModerator,
+            assigned_by: Permissions::Administrator,
+            capabilities: Capabilities::empty(),
+        },
+        Role {
+            permission: Permissions::Moderator,
+            name: "Moderator",
+            implies: Permissions::empty(),
+            assigned_by: Permissions::Administrator,
+            capabilities: Capabilities::ModerateUsers,
+        },
+        Role {
+            permission: Permissions::Administrator,
+            name: "Administrator",
+            implies: Permissions::Moderator,
+            assigned_by: Permissions::Administrator,
+            capabilities: Capabilities::ManageUsers,
+        },
+    ];
+}
+
+impl Display for Permissions {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let perms: Vec<_> = ROLES
+            .iter()
+            .filter(|role| self.contains(role.permission))
+            .map(|role| role.name)
+            .collect();
 
         if perms.is_empty() {
             write!(f, "None")
@@ -67,29 +204,19 @@ impl Display for Permissions {
 }
 
 impl Permissions {
+    fn roles(self) -> impl Iterator<Item = &'static Role> {
+        ROLES.iter().filter(move |role| self.contains(role.permission))
+    }
+
     /// Gets a [`Permissions`] object containing all the permissions you can assign if you have the
     /// permissions stored in this object.
     pub fn assigns(self) -> Permissions {
-        let mut perms = Permissions::empty();
+        let held = self.implied();
 
-        if Permissions::ListAdministrator & self != Permissions::empty() {
-            perms.insert(Permissions::ListHelper | Permissions::ListModerator)
-        }
-
-        if Permissions::Administrator & self != Permissions::empty() {
-            perms.insert(
-                Permissions::Moderator
-                    | Permissions::ListAdministrator
-                    | Permissions::LeaderboardAdministrator
-                    | Permissions::ExtendedAccess,
-            )
-        }
-
-        if Permissions::LeaderboardAdministrator & self != Permissions::empty() {
-            perms.insert(Permissions::LeaderboardModerator)
-        }
-
-        perms
+        ROLES
+            .iter()
+            .filter(|role| role.assigned_by.intersects(held))
+            .fold(Permissions::empty(), |perms, role| perms | role.permission)
     }
 
     /// Gets a [`Permissions`] object additionally containing all the permissions implied by the
@@ -97,28 +224,24 @@ impl Permissions {
     pub fn implied(self) -> Permissions {
         let mut perms = self;
 
-        // pseudo dynamic programming
-        if perms.contains(Permissions::Administrator) {
-            perms.insert(Permissions::Moderator)
-        }
-
-        if perms.contains(Permissions::ListAdministrator) {
-            perms.insert(Permissions::ListModerator)
-        }
+        // Implications can be chained, so keep going until nothing changes anymore
+        loop {
+            let next = perms.roles().fold(perms, |perms, role| perms | role.implies);
 
-        if perms.contains(Permissions::ListModerator) {
-            perms.insert(Permissions::ListHelper)
-        }
+            if next == perms {
+                return perms
+            }
 
-        if perms.contains(Permissions::ListHelper) {
-            perms.insert(Permissions::ExtendedAccess)
-        }
-
-        if perms.contains(Permissions::LeaderboardAdministrator) {
-            perms.insert(Permissions::LeaderboardModerator)
+            perms = next;
         }
+    }
 
-        perms
+    /// Gets all capabilities granted by the permissions stored in this object (including implied
+    /// ones)
+    pub fn capabilities(self) -> Capabilities {
+        self.implied()
+            .roles()
+            .fold(Capabilities::empty(), |capabilities, role| capabilities | role.capabilities)
     }
 
     /// Checks whether a user with the current permission set can assign `permissions` to another
@@ -129,12 +252,40 @@ impl Permissions {
 
     /// Returns the most specific permission required to assign all permissions in this object
     pub fn required_for_assignment(self) -> Permissions {
-        if self & (Permissions::ListHelper | Permissions::ListModerator) == self {
-            // only list helper and moderator perms,
-            Permissions::ListAdministrator
-        } else {
-            Permissions::Administrator
-        }
+        // Roles are sorted by their bits, which coincides with how privileged they are
+        ROLES
+            .iter()
+            .map(|role| role.permission)
+            .find(|permission| permission.can_assign(self))
+            .unwrap_or(Permissions::ItIsImpossibleToGainThisPermission)
+    }
+}
+
+impl Capabilities {
+    /// Gets the permissions that directly grant any of the capabilities in this object
+    pub fn granted_by(self) -> Permissions {
+        ROLES
+            .iter()
+            .filter(|role| role.capabilities.intersects(self))
+            .fold(Permissions::empty(), |perms, role| perms | role.permission)
+    }
+
+    /// Gets the API names of all capabilities in this object
+    pub fn names(self) -> Vec<&'static str> {
+        CAPABILITIES
+            .iter()
+            .filter(|(capability, _)| self.contains(*capability))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl Serialize for Capabilities {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.names().serialize(serializer)
     }
 }
 
@@ -159,3 +310,40 @@ impl<'de> Deserialize<'de> for Permissions {
         Ok(perms)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Capabilities, Permissions};
+
+    #[test]
+    fn test_implied() {
+        assert_eq!(
+            Permissions::ListAdministrator.implied(),
+            Permissions::ListAdministrator | Permissions::ListModerator | Permissions::ListHelper | Permissions::ExtendedAccess
+        );
+        assert_eq!(
+            Permissions::Administrator.implied(),
+            Permissions::Administrator | Permissions::Moderator
+        );
+        assert!(Permissions::ListModerator.capabilities().contains(Capabilities::ApproveRecords));
+        assert!(!Permissions::Administrator.capabilities().contains(Capabilities::ApproveRecords));
+    }
+
+    #[test]
+    fn test_assignment() {
+        assert!(Permissions::ListAdministrator.can_assign(Permissions::ListHelper | Permissions::ListModerator));
+        assert!(!Permissions::ListAdministrator.can_assign(Permissions::ListAdministrator));
+        assert!(!Permissions::ListModerator.can_assign(Permissions::ListHelper));
+        assert!(Permissions::Administrator.can_assign(Permissions::ListAdministrator | Permissions::Moderator));
+
+        assert_eq!(
+            (Permissions::ListHelper | Permissions::ListModerator).required_for_assignment(),
+            Permissions::ListAdministrator
+        );
+        assert_eq!(Permissions::Moderator.required_for_assignment(), Permissions::Administrator);
+        assert_eq!(
+            Permissions::ItIsImpossibleToGainThisPermission.required_for_assignment(),
+            Permissions::ItIsImpossibleToGainThisPermission
+        );
+    }
+}
@@ -1,13 +1,37 @@
-use crate::{Result, error::PointercrateError};
+//! Module containing pointercrate's rate limiting
+//!
+//! Every [`RatelimitScope`] has its own limit of the form "`n` requests per `period`", which can be
+//! overridden via the `RATELIMIT_<SCOPE>` environment variable (e.g. `RATELIMIT_LOGIN=3/1800` for
+//! 3 login attempts per 30 minutes, or `RATELIMIT_LOGIN=off` to disable the limit). Users holding
+//! any of the roles in `RATELIMIT_<SCOPE>_EXEMPT` (a permission bitmask) are not subject to the
+//! limit at all, while [trusted submitters](crate::model::demonlist::submitter::TrustLevel) are
+//! subject to the (usually more relaxed) limit in `RATELIMIT_<SCOPE>_TRUSTED` instead.
+//!
+//! Limits are enforced using the generic cell rate algorithm (GCRA), which only needs to remember a
+//! single point in time (the "theoretical arrival time" of the next request) per limited key. By
+//! default, these are kept in memory, but setting `RATELIMIT_STORE=postgres` stores them in the
+//! database instead, so that limits survive restarts and are shared between instances.
+//!
+//! Most limits are counted per IP address. Some, such as the one on invalid two-factor
+//! authentication codes, are additionally counted per account, so that an attacker cannot get
+//! around them by spreading the requests over many addresses.
+
+use crate::{config, error::PointercrateError, permissions::Permissions, Result};
+use actix_web::dev::HttpResponseBuilder;
 use derive_more::Display;
-use nonzero_ext::nonzero;
-use ratelimit_meter::{DirectRateLimiter, KeyedRateLimiter, NonConformance};
+use log::{debug, info};
+use rand::Rng;
+use sqlx::{Pool, Postgres};
 use std::{
+    cell::Cell,
+    collections::HashMap,
     net::IpAddr,
+    str::FromStr,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
-#[derive(Debug, Display, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RatelimitScope {
     #[display(fmt = "You're submitting too many records too fast!")]
     RecordSubmission,
@@ -26,68 +50,584 @@ pub enum RatelimitScope {
 
     #[display(fmt = "Too many login attempts!")]
     Login,
+
+    #[display(fmt = "Too many password reset attempts!")]
+    PasswordReset,
+
+    #[display(fmt = "Too many invalid two-factor authentication codes!")]
+    SecondFactor,
+}
+
+impl RatelimitScope {
+    const ALL: [RatelimitScope; 8] = [
+        RatelimitScope::RecordSubmission,
+        RatelimitScope::RecordSubmissionGlobal,
+        RatelimitScope::NewSubmitter,
+        RatelimitScope::Registration,
+        RatelimitScope::SoftRegistration,
+        RatelimitScope::Login,
+        RatelimitScope::PasswordReset,
+        RatelimitScope::SecondFactor,
+    ];
+
+    /// The name of this scope, used for configuration and as part of the key limits are stored
+    /// under
+    fn name(self) -> &'static str {
+        match self {
+            RatelimitScope::RecordSubmission => "record_submission",
+            RatelimitScope::RecordSubmissionGlobal => "record_submission_global",
+            RatelimitScope::NewSubmitter => "new_submitter",
+            RatelimitScope::Registration => "registration",
+            RatelimitScope::SoftRegistration => "soft_registration",
+            RatelimitScope::Login => "login",
+            RatelimitScope::PasswordReset => "password_reset",
+            RatelimitScope::SecondFactor => "second_factor",
+        }
+    }
+
+    /// Whether this scope is limited per IP address (as opposed to globally)
+    fn per_ip(self) -> bool {
+        !matches!(self, RatelimitScope::RecordSubmissionGlobal | RatelimitScope::NewSubmitter)
+    }
+
+    fn default_limit(self) -> Option<Limit> {
+        let (requests, seconds) = match self {
+            // can only submit 5 records per 30 seconds
+            RatelimitScope::RecordSubmission => (5, 30),
+            // can only submit 20 records  per hour
+            RatelimitScope::RecordSubmissionGlobal => (20, 3600),
+            // 5 per hour
+            RatelimitScope::NewSubmitter => (5, 3600),
+            // 1 per day
+            RatelimitScope::Registration => (1, 3600 * 24),
+            // 5 per 6 hours
+            RatelimitScope::SoftRegistration => (5, 3600 * 6),
+            // 3 per 30 minutes
+            RatelimitScope::Login => (3, 1800),
+            // 5 per hour
+            RatelimitScope::PasswordReset => (5, 3600),
+            // 3 invalid codes per 30 minutes
+            RatelimitScope::SecondFactor => (3, 1800),
+        };
+
+        Some(Limit {
+            requests,
+            period: Duration::from_secs(seconds),
+        })
+    }
+
+    fn default_trusted_limit(self) -> Option<Limit> {
+        match self {
+            // trusted submitters can submit 20 records per 30 seconds
+            RatelimitScope::RecordSubmission =>
+                Some(Limit {
+                    requests: 20,
+                    period: Duration::from_secs(30),
+                }),
+            _ => None,
+        }
+    }
+
+    fn default_exempt(self) -> Permissions {
+        match self {
+            // The list team needs to be able to add records in bulk
+            RatelimitScope::RecordSubmission | RatelimitScope::RecordSubmissionGlobal => Permissions::ListHelper,
+            _ => Permissions::empty(),
+        }
+    }
+}
+
+/// A limit of the form "`requests` requests per `period`"
+#[derive(Debug, Clone, Copy)]
+struct Limit {
+    requests: u32,
+    period: Duration,
+}
+
+impl Limit {
+    /// The amount of time each request "uses up"
+    fn emission_interval(&self) -> Duration {
+        self.period / self.requests
+    }
+}
+
+/// Parses limits of the form `<requests>/<seconds>`
+impl FromStr for Limit {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+
+        let requests = parts.next().and_then(|requests| requests.trim().parse::<u32>().ok());
+        let seconds = parts.next().and_then(|seconds| seconds.trim().parse::<u64>().ok());
+
+        match (requests, seconds) {
+            (Some(requests), Some(seconds)) if requests > 0 && seconds > 0 =>
+                Ok(Limit {
+                    requests,
+                    period: Duration::from_secs(seconds),
+                }),
+            _ => Err(format!("Invalid rate limit '{}', expected '<requests>/<seconds>' or 'off'", s)),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
+struct ScopeConfig {
+    /// [`None`] if this scope isn't limited
+    limit: Option<Limit>,
+
+    /// The limit applying to trusted submitters. [`None`] if they are subject to the normal limit
+    trusted_limit: Option<Limit>,
+
+    /// Users with any of these permissions are not subject to the limit
+    exempt: Permissions,
+}
+
+/// The state of a limit after a request has been counted against it
+#[derive(Debug, Clone, Copy)]
+pub struct RatelimitStatus {
+    limit: u32,
+    remaining: u32,
+
+    /// The time until the limit has been fully replenished
+    reset: Duration,
+}
+
+impl RatelimitStatus {
+    /// Adds the `RateLimit-*` headers describing this status to the given response
+    pub fn headers<'a>(&self, response: &'a mut HttpResponseBuilder) -> &'a mut HttpResponseBuilder {
+        response
+            .header("RateLimit-Limit", self.limit.to_string())
+            .header("RateLimit-Remaining", self.remaining.to_string())
+            .header("RateLimit-Reset", ceil_seconds(self.reset).to_string())
+    }
+}
+
+/// Where the theoretical arrival times of all limited keys are kept
+#[derive(Clone)]
+enum RatelimitStore {
+    /// Keeps them in memory, meaning they are lost on restart and not shared between instances
+    Memory(Arc<Mutex<HashMap<(RatelimitScope, String), Instant>>>),
+
+    /// Keeps them in the `ratelimits` table
+    Postgres(Pool<Postgres>),
+}
+
+#[derive(Clone)]
 pub struct Ratelimits {
-    record_submission: KeyedRateLimiter<IpAddr>,
-    record_submission_global: DirectRateLimiter,
-    new_submitters: DirectRateLimiter,
-    registrations: KeyedRateLimiter<IpAddr>,
-    soft_registrations: KeyedRateLimiter<IpAddr>,
-    login_attempts: KeyedRateLimiter<IpAddr>,
+    scopes: HashMap<RatelimitScope, ScopeConfig>,
+    store: RatelimitStore,
 }
 
-#[derive(Copy, Clone)]
+/// Rate limits prepared for checking on behalf of a specific request
+///
+/// Keeps track of the most restrictive limit checked, so that it can be reported back to the
+/// client via [`PreparedRatelimits::headers`].
 pub struct PreparedRatelimits<'a> {
     ratelimits: &'a Ratelimits,
     ip: IpAddr,
+    permissions: Permissions,
+    trusted: Cell<bool>,
+    status: Cell<Option<RatelimitStatus>>,
 }
 
 impl PreparedRatelimits<'_> {
-    pub fn check(&self, scope: RatelimitScope) -> Result<()> {
-        self.ratelimits.check(scope, self.ip)
+    /// Marks the request as being made by a user with the given permissions, making it exempt from
+    /// all limits configured to not apply to them
+    pub fn authenticated_as(mut self, permissions: Permissions) -> Self {
+        self.permissions = permissions.implied();
+        self
+    }
+
+    /// Marks the request as being made by a trusted submitter, subjecting it to the relaxed limits
+    /// for trusted submitters in all further checks
+    ///
+    /// Whether a submitter is trusted is only known after the submitter has been looked up, which
+    /// itself might have to be rate limited, hence why this doesn't consume `self`.
+    pub fn mark_trusted(&self) {
+        self.trusted.set(true)
+    }
+
+    pub async fn check(&self, scope: RatelimitScope) -> Result<()> {
+        let key = if scope.per_ip() { self.ip.to_string() } else { String::new() };
+
+        self.check_key(scope, key, true).await
+    }
+
+    /// Counts the request against the given scope's limit for both the requesting IP address and
+    /// the given account
+    pub async fn check_account(&self, scope: RatelimitScope, member_id: i32) -> Result<()> {
+        self.check_key(scope, self.ip.to_string(), true).await?;
+        self.check_key(scope, account_key(member_id), true).await
+    }
+
+    /// Fails if [`PreparedRatelimits::check_account`] would fail, but doesn't count the request
+    ///
+    /// Useful for limits that only count failed attempts: An attempt made after the limit has been
+    /// exhausted has to be rejected even if it would have succeeded.
+    pub async fn peek_account(&self, scope: RatelimitScope, member_id: i32) -> Result<()> {
+        self.check_key(scope, self.ip.to_string(), false).await?;
+        self.check_key(scope, account_key(member_id), false).await
+    }
+
+    async fn check_key(&self, scope: RatelimitScope, key: String, count: bool) -> Result<()> {
+        let config = self.ratelimits.scopes[&scope];
+
+        if config.exempt.intersects(self.permissions) {
+            debug!("Request from {} is exempt from ratelimit {:?}", self.ip, scope);
+
+            return Ok(())
+        }
+
+        let limit = match config.trusted_limit {
+            Some(trusted_limit) if self.trusted.get() => Some(trusted_limit),
+            _ => config.limit,
+        };
+
+        if let Some(status) = self.ratelimits.check_limit(scope, limit, key, count).await? {
+            if count {
+                match self.status.get() {
+                    Some(current) if current.remaining <= status.remaining => (),
+                    _ => self.status.set(Some(status)),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the `RateLimit-*` headers for the most restrictive limit checked so far to the given
+    /// response
+    pub fn headers<'a>(&self, response: &'a mut HttpResponseBuilder) -> &'a mut HttpResponseBuilder {
+        match self.status.get() {
+            Some(status) => status.headers(response),
+            None => response,
+        }
     }
 }
 
 impl Ratelimits {
-    pub fn initialize() -> Self {
+    pub fn initialize(connection_pool: &Pool<Postgres>) -> Self {
+        let scopes = RatelimitScope::ALL
+            .iter()
+            .map(|&scope| {
+                let key = scope.name().to_uppercase();
+
+                let limit = match config::ratelimit(&key).as_deref() {
+                    None => scope.default_limit(),
+                    Some("off") => None,
+                    Some(limit) => Some(limit.parse().unwrap()),
+                };
+
+                let trusted_limit = match config::ratelimit(&format!("{}_TRUSTED", key)).as_deref() {
+                    None => scope.default_trusted_limit(),
+                    Some("off") => None,
+                    Some(limit) => Some(limit.parse().unwrap()),
+                };
+
+                let exempt = config::ratelimit(&format!("{}_EXEMPT", key))
+                    .map(|bits| Permissions::from_bits_truncate(bits.parse().unwrap()))
+                    .unwrap_or_else(|| scope.default_exempt());
+
+                info!(
+                    "Ratelimit {:?}: {:?}, trusted: {:?}, exempt: {}",
+                    scope, limit, trusted_limit, exempt
+                );
+
+                (scope, ScopeConfig {
+                    limit,
+                    trusted_limit,
+                    exempt,
+                })
+            })
+            .collect();
+
+        let store = match config::ratelimit_store().as_str() {
+            "memory" => RatelimitStore::Memory(Arc::new(Mutex::new(HashMap::new()))),
+            "postgres" => RatelimitStore::Postgres(connection_pool.clone()),
+            store => panic!("Unknown ratelimit store '{}'", store),
+        };
+
+        Ratelimits { scopes, store }
+    }
+
+    /// Ratelimits at their default limits, kept in memory
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        let scopes = RatelimitScope::ALL
+            .iter()
+            .map(|&scope| {
+                (scope, ScopeConfig {
+                    limit: scope.default_limit(),
+                    trusted_limit: scope.default_trusted_limit(),
+                    exempt: scope.default_exempt(),
+                })
+            })
+            .collect();
+
         Ratelimits {
-            // can only submit 5 records per 30 seconds
-            record_submission: KeyedRateLimiter::new(nonzero!(5u32), Duration::from_secs(30)),
-            // can only submit 20 records  per hour
-            record_submission_global: DirectRateLimiter::new(nonzero!(20u32), Duration::from_secs(3600)),
-            // 5 per hour
-            new_submitters: DirectRateLimiter::new(nonzero!(5u32), Duration::from_secs(3600)),
-            // 1 per day
-            registrations: KeyedRateLimiter::new(nonzero!(1u32), Duration::from_secs(3600 * 24)),
-            // 5 per 6 hours
-            soft_registrations: KeyedRateLimiter::new(nonzero!(5u32), Duration::from_secs(3600 * 6)),
-            // 3 per 30 minutes
-            login_attempts: KeyedRateLimiter::new(nonzero!(3u32), Duration::from_secs(1800)),
+            scopes,
+            store: RatelimitStore::Memory(Arc::new(Mutex::new(HashMap::new()))),
         }
     }
 
     pub fn prepare(&self, ip: IpAddr) -> PreparedRatelimits {
-        PreparedRatelimits { ratelimits: self, ip }
+        PreparedRatelimits {
+            ratelimits: self,
+            ip,
+            permissions: Permissions::empty(),
+            trusted: Cell::new(false),
+            status: Cell::new(None),
+        }
     }
 
-    pub fn check(&self, scope: RatelimitScope, ip: IpAddr) -> Result<()> {
-        let now = Instant::now();
+    /// Counts a request against the given limit of the given scope for the given key
+    ///
+    /// The key is an IP address, an [account key](account_key), or the empty string for global
+    /// limits. If `count` is false, the request is only checked against the limit, but not counted.
+    ///
+    /// Returns the state of the limit after counting the request, or [`None`] if the scope isn't
+    /// limited.
+    async fn check_limit(&self, scope: RatelimitScope, limit: Option<Limit>, key: String, count: bool) -> Result<Option<RatelimitStatus>> {
+        let limit = match limit {
+            Some(limit) => limit,
+            None => return Ok(None),
+        };
 
-        match scope {
-            RatelimitScope::RecordSubmission => self.record_submission.clone().check_at(ip, now),
-            RatelimitScope::RecordSubmissionGlobal => self.record_submission_global.clone().check_at(now),
-            RatelimitScope::NewSubmitter => self.new_submitters.clone().check_at(now),
-            RatelimitScope::Registration => self.registrations.clone().check_at(ip, now),
-            RatelimitScope::SoftRegistration => self.soft_registrations.clone().check_at(ip, now),
-            RatelimitScope::Login => self.login_attempts.clone().check_at(ip, now),
-        }
-        .map_err(|too_early| {
-            PointercrateError::Ratelimited {
+        // How far the theoretical arrival time of the next request lies in the future, after counting
+        // this request. If that's more than the limit's period, the request is rejected
+        let ahead = match self.store {
+            RatelimitStore::Memory(ref tats) => {
+                let now = Instant::now();
+                let mut tats = tats.lock().unwrap();
+
+                let key = (scope, key);
+                let tat = tats.get(&key).copied().unwrap_or(now).max(now) + limit.emission_interval();
+                let ahead = tat - now;
+
+                if count && ahead <= limit.period {
+                    tats.insert(key, tat);
+                }
+
+                ahead
+            },
+            RatelimitStore::Postgres(ref pool) => {
+                let interval = limit.emission_interval().as_secs_f64();
+
+                let updated = if count {
+                    sqlx::query!(
+                        r#"INSERT INTO ratelimits (scope, key, tat)
+                        VALUES ($1, $2, (NOW() AT TIME ZONE 'utc') + make_interval(secs => $3))
+                        ON CONFLICT (scope, key) DO UPDATE
+                        SET tat = GREATEST(ratelimits.tat, NOW() AT TIME ZONE 'utc') + make_interval(secs => $3)
+                        WHERE GREATEST(ratelimits.tat, NOW() AT TIME ZONE 'utc') + make_interval(secs => $3)
+                            <= (NOW() AT TIME ZONE 'utc') + make_interval(secs => $4)
+                        RETURNING EXTRACT(EPOCH FROM tat - (NOW() AT TIME ZONE 'utc'))::FLOAT8 AS "ahead!""#,
+                        scope.name(),
+                        key,
+                        interval,
+                        limit.period.as_secs_f64()
+                    )
+                    .fetch_optional(pool)
+                    .await?
+                } else {
+                    None
+                };
+
+                // If the guard above prevented the update (or we didn't even try), find out where the
+                // limit would be after this request
+                let ahead = match updated {
+                    Some(row) => row.ahead,
+                    None => {
+                        let current = sqlx::query!(
+                            r#"SELECT EXTRACT(EPOCH FROM GREATEST(tat, NOW() AT TIME ZONE 'utc') - (NOW() AT TIME ZONE 'utc'))::FLOAT8
+                            AS "ahead!" FROM ratelimits WHERE scope = $1 AND key = $2"#,
+                            scope.name(),
+                            key
+                        )
+                        .fetch_optional(pool)
+                        .await?
+                        .map(|row| row.ahead)
+                        .unwrap_or(0.0);
+
+                        current + interval
+                    },
+                };
+
+                Duration::from_secs_f64(ahead.max(0.0))
+            },
+        };
+
+        if ahead > limit.period {
+            // Spread out retries a bit so that not everyone who got limited at the same time comes back at
+            // the same time
+            let jitter = Duration::from_millis(rand::thread_rng().gen_range(0, 1000));
+
+            return Err(PointercrateError::Ratelimited {
                 scope,
-                remaining: too_early.earliest_possible() - now,
-            }
-        }) // TODO: add jitter
+                limit: limit.requests,
+                remaining: ahead - limit.period + jitter,
+            })
+        }
+
+        let remaining = (limit.period - ahead).as_secs_f64() / limit.emission_interval().as_secs_f64();
+
+        Ok(Some(RatelimitStatus {
+            limit: limit.requests,
+            remaining: remaining as u32,
+            reset: ahead,
+        }))
+    }
+
+    /// Forgets about all keys whose limits have been fully replenished
+    pub async fn prune(&self) -> Result<()> {
+        match self.store {
+            RatelimitStore::Memory(ref tats) => {
+                let now = Instant::now();
+
+                tats.lock().unwrap().retain(|_, tat| *tat > now);
+            },
+            RatelimitStore::Postgres(ref pool) => {
+                sqlx::query!("DELETE FROM ratelimits WHERE tat < NOW() AT TIME ZONE 'utc'")
+                    .execute(pool)
+                    .await?;
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// The key under which the limits of the given account are stored. Cannot clash with an IP address
+fn account_key(member_id: i32) -> String {
+    format!("member:{}", member_id)
+}
+
+/// Rounds the given duration up to full seconds, as required by the `RateLimit-Reset` and
+/// `Retry-After` headers
+pub fn ceil_seconds(duration: Duration) -> u64 {
+    duration.as_secs() + if duration.subsec_nanos() > 0 { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Limit, RatelimitScope, RatelimitStore, Ratelimits, ScopeConfig};
+    use crate::{error::PointercrateError, permissions::Permissions};
+    use std::{
+        collections::HashMap,
+        net::{IpAddr, Ipv4Addr},
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    const IP: IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+
+    fn ratelimits() -> Ratelimits {
+        let mut scopes = HashMap::new();
+
+        scopes.insert(RatelimitScope::Login, ScopeConfig {
+            limit: Some(Limit {
+                requests: 3,
+                period: Duration::from_secs(1800),
+            }),
+            trusted_limit: None,
+            exempt: Permissions::empty(),
+        });
+        scopes.insert(RatelimitScope::RecordSubmission, ScopeConfig {
+            limit: Some(Limit {
+                requests: 1,
+                period: Duration::from_secs(1800),
+            }),
+            trusted_limit: Some(Limit {
+                requests: 3,
+                period: Duration::from_secs(1800),
+            }),
+            exempt: Permissions::ListHelper,
+        });
+
+        Ratelimits {
+            scopes,
+            store: RatelimitStore::Memory(Arc::new(Mutex::new(HashMap::new()))),
+        }
+    }
+
+    #[test]
+    fn test_parse_limit() {
+        let limit: Limit = "5/30".parse().unwrap();
+
+        assert_eq!(limit.requests, 5);
+        assert_eq!(limit.period, Duration::from_secs(30));
+        assert!("5".parse::<Limit>().is_err());
+        assert!("0/30".parse::<Limit>().is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_limit() {
+        let ratelimits = ratelimits();
+        let limit = ratelimits.scopes[&RatelimitScope::Login].limit;
+
+        for remaining in (0..3).rev() {
+            let status = ratelimits
+                .check_limit(RatelimitScope::Login, limit, IP.to_string(), true)
+                .await
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(status.remaining, remaining);
+        }
+
+        match ratelimits.check_limit(RatelimitScope::Login, limit, IP.to_string(), true).await {
+            Err(PointercrateError::Ratelimited { remaining, .. }) => assert!(remaining > Duration::from_secs(590)),
+            result => panic!("Expected ratelimit, got {:?}", result),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_exempt() {
+        let ratelimits = ratelimits();
+
+        let prepared = ratelimits.prepare(IP).authenticated_as(Permissions::ListModerator);
+
+        prepared.check(RatelimitScope::RecordSubmission).await.unwrap();
+        prepared.check(RatelimitScope::RecordSubmission).await.unwrap();
+
+        let prepared = ratelimits.prepare(IP);
+
+        prepared.check(RatelimitScope::RecordSubmission).await.unwrap();
+        assert!(prepared.check(RatelimitScope::RecordSubmission).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_trusted() {
+        let ratelimits = ratelimits();
+
+        let prepared = ratelimits.prepare(IP);
+
+        prepared.check(RatelimitScope::RecordSubmission).await.unwrap();
+        prepared.mark_trusted();
+        prepared.check(RatelimitScope::RecordSubmission).await.unwrap();
+        prepared.check(RatelimitScope::RecordSubmission).await.unwrap();
+        assert!(prepared.check(RatelimitScope::RecordSubmission).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_account() {
+        let ratelimits = ratelimits();
+
+        let prepared = ratelimits.prepare(IP);
+
+        for _ in 0..3 {
+            prepared.peek_account(RatelimitScope::Login, 1).await.unwrap();
+            prepared.check_account(RatelimitScope::Login, 1).await.unwrap();
+        }
+
+        assert!(prepared.peek_account(RatelimitScope::Login, 1).await.is_err());
+
+        // The account's limit applies no matter where the requests come from
+        let elsewhere = ratelimits.prepare(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)));
+
+        assert!(elsewhere.peek_account(RatelimitScope::Login, 1).await.is_err());
+        elsewhere.peek_account(RatelimitScope::Login, 2).await.unwrap();
     }
 }
@@ -0,0 +1,124 @@
+//! Module containing the periodic background tasks pointercrate runs
+//!
+//! Tasks are spawned onto the actix runtime once during startup and run until the server shuts down.
+//! Each tick acquires its own database connection, so a failing tick (e.g. because the database was
+//! briefly unreachable) only gets logged and is retried on the next one.
+
+use crate::{
+    config,
+    model::{
+        demonlist::{player::RankingSnapshot, record},
+        user::{PasswordResetToken, PersonalAccessToken, Session},
+        webhook,
+    },
+    state::PointercrateState,
+    Result,
+};
+use log::{error, info};
+use std::time::Duration;
+
+/// Spawns all periodic tasks
+pub fn start(state: &PointercrateState) {
+    let snapshot_interval = config::ranking_snapshot_interval();
+
+    if snapshot_interval > 0 {
+        actix_rt::spawn(snapshot_ranking(state.clone(), Duration::from_secs(snapshot_interval)));
+    } else {
+        info!("Ranking snapshots are disabled");
+    }
+
+    actix_rt::spawn(deliver_webhooks(
+        state.clone(),
+        Duration::from_secs(config::webhook_delivery_interval()),
+    ));
+
+    actix_rt::spawn(validate_videos(
+        state.clone(),
+        Duration::from_secs(config::video_validation_interval()),
+    ));
+
+    actix_rt::spawn(prune_ratelimits(state.clone(), Duration::from_secs(3600)));
+    actix_rt::spawn(prune_credentials(state.clone(), Duration::from_secs(3600)));
+}
+
+async fn snapshot_ranking(state: PointercrateState, period: Duration) {
+    let mut interval = actix_rt::time::interval(period);
+
+    loop {
+        interval.tick().await;
+
+        let result = match state.connection().await {
+            Ok(mut connection) => RankingSnapshot::take(&mut connection).await,
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(Some(snapshot)) => info!("Took ranking snapshot {} at {}", snapshot.id, snapshot.taken_at),
+            Ok(None) => info!("Ranking unchanged since last snapshot, not taking a new one"),
+            Err(err) => error!("Failed to take ranking snapshot: {:?}", err),
+        }
+    }
+}
+
+async fn deliver_webhooks(state: PointercrateState, period: Duration) {
+    let mut interval = actix_rt::time::interval(period);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = webhook::deliver_pending(&state).await {
+            error!("Failed to process webhook delivery queue: {:?}", err)
+        }
+    }
+}
+
+async fn validate_videos(state: PointercrateState, period: Duration) {
+    let mut interval = actix_rt::time::interval(period);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = record::process_validation_jobs(&state).await {
+            error!("Failed to process video validation queue: {:?}", err)
+        }
+    }
+}
+
+async fn prune_ratelimits(state: PointercrateState, period: Duration) {
+    let mut interval = actix_rt::time::interval(period);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = state.ratelimits.prune().await {
+            error!("Failed to prune expired ratelimits: {:?}", err)
+        }
+    }
+}
+
+async fn prune_credentials(state: PointercrateState, period: Duration) {
+    let mut interval = actix_rt::time::interval(period);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = prune_expired_credentials(&state).await {
+            error!("Failed to prune expired credentials: {:?}", err)
+        }
+    }
+}
+
+async fn prune_expired_credentials(state: &PointercrateState) -> Result<()> {
+    let mut connection = state.connection().await?;
+
+    let sessions = Session::prune_expired(&mut connection).await?;
+    let resets = PasswordResetToken::prune_expired(&mut connection).await?;
+    let tokens = PersonalAccessToken::prune_expired(&mut connection).await?;
+
+    info!(
+        "Pruned {} expired sessions, {} expired password reset tokens and {} expired personal access tokens",
+        sessions, resets, tokens
+    );
+
+    Ok(())
+}
@@ -1,4 +1,10 @@
-use crate::{config, documentation, model::user::AuthenticatedUser, ratelimit::Ratelimits, Result};
+use crate::{
+    config, documentation,
+    mail::{self, MailTransport},
+    model::{user::AuthenticatedUser, webhook::Webhook},
+    ratelimit::Ratelimits,
+    Result,
+};
 use log::{info, trace};
 use reqwest::Client;
 use sqlx::{pool::PoolConnection, postgres::PgPoolOptions, PgConnection, Pool, Postgres, Transaction};
@@ -17,7 +23,9 @@ pub struct PointercrateState {
     pub ratelimits: Ratelimits,
 
     pub http_client: Client,
-    pub webhook_url: Option<Arc<String>>,
+
+    /// How to deliver emails. [`None`] if no mail transport is configured
+    pub mailer: Option<Arc<dyn MailTransport>>,
 }
 
 impl PointercrateState {
@@ -28,6 +36,9 @@ impl PointercrateState {
     pub async fn initialize() -> PointercrateState {
         info!("Initializing pointercrate state!");
 
+        crate::extractor::ip::initialize();
+        crate::model::user::initialize_password_hashing();
+
         let documentation_toc = Arc::new(documentation::read_table_of_contents(&config::documentation_location()).unwrap());
         let documentation_topics = Arc::new(documentation::read_topics(&config::documentation_location()).unwrap());
 
@@ -42,6 +53,16 @@ impl PointercrateState {
             .await
             .expect("Failed to connect to pointercrate database");
 
+        if let Ok(webhook_url) = std::env::var("DISCORD_WEBHOOK") {
+            let mut connection = connection_pool.acquire().await.expect("Failed to acquire database connection");
+
+            Webhook::import_legacy(&webhook_url, &mut connection)
+                .await
+                .expect("Failed to import DISCORD_WEBHOOK into the webhook registry");
+        }
+
+        let ratelimits = Ratelimits::initialize(&connection_pool);
+
         PointercrateState {
             documentation_toc,
             documentation_topics,
@@ -49,9 +70,9 @@ impl PointercrateState {
             guidelines_topics,
             connection_pool,
             secret: Arc::new(config::secret()),
-            ratelimits: Ratelimits::initialize(),
+            ratelimits,
             http_client: Client::builder().build().expect("Failed to create reqwest client"),
-            webhook_url: std::env::var("DISCORD_WEBHOOK").ok().map(Arc::new),
+            mailer: mail::from_config(),
         }
     }
 
@@ -1,4 +1,6 @@
-//! Some utils for pagination and patch
+//! Some utils for patch and header handling
+//!
+//! Pagination lives in [`crate::pagination`].
 
 use crate::error::PointercrateError;
 use actix_web::{dev::HttpResponseBuilder, http::HeaderMap, HttpResponse};
@@ -11,109 +13,6 @@ use std::{
     str::FromStr,
 };
 
-macro_rules! pagination_response {
-    ($endpoint: expr, $objects:expr, $pagination:expr, $min_id:expr, $max_id:expr, $before_field:ident, $after_field:ident, $($id_field:tt)*) => {{
-        log::debug!("Received pagination request {:?}", $pagination);
-
-        let mut rel = String::new();
-
-        let limit = $pagination.limit.unwrap_or(50) as usize;
-        let next_page_exists = $objects.len() > limit;
-
-        if !$objects.is_empty() {
-            if next_page_exists {
-                log::debug!("A new page exists!");
-
-                $objects.pop();  // remove the things from then next page
-            }
-
-            let last = $objects.last().unwrap().$($id_field)*;
-            let first = $objects.first().unwrap().$($id_field)*;
-
-            match ($pagination.$before_field, $pagination.$after_field) {
-                (None, after) => {
-                    log::debug!("No before value set, assuming result is correctly ordered!");
-
-                    // no 'before' value set.
-                    // if 'after' is none, we're on the first page, otherwise we have ot generate a 'prev' link
-
-                    if next_page_exists {
-
-                        $pagination.$after_field = Some(last);
-                        $pagination.$before_field = None;
-
-                        rel.push_str(&format!(
-                            ",<{}?{}>; rel=next",
-                            $endpoint, serde_urlencoded::to_string(&$pagination.0).unwrap()
-                        ));
-                    }
-
-                    if after.is_some() {
-                        $pagination.$after_field = None;
-                        $pagination.$before_field = Some(first);
-
-                        rel.push_str(&format!(
-                            ",<{}?{}>; rel=prev",
-                            $endpoint, serde_urlencoded::to_string(&$pagination.0).unwrap()
-                        ));
-                    }
-                }
-                (Some(_), None) => {
-                    log::debug!("Before value set, assuming result is reverse ordered!");
-
-                    // A previous page exists. This means "first" and "last" are actually to opposite of what the variables are named.
-                    $pagination.$before_field = Some(last);
-                    $pagination.$after_field = None;
-
-                    // In this case, the page was retrieved using 'ORDER BY ... DESC' so we need to reverse list order!
-                    $objects.reverse();
-
-                    if next_page_exists {
-                        rel.push_str(&format!(
-                            ",<{}?{}>; rel=prev",
-                            $endpoint, serde_urlencoded::to_string(&$pagination.0).unwrap()
-                        ));
-                    }
-                    $pagination.$after_field = Some(first);
-                    $pagination.$before_field = None;
-
-                    rel.push_str(&format!(
-                        ",<{}?{}>; rel=next",
-                        $endpoint, serde_urlencoded::to_string(&$pagination.0).unwrap()
-                    ));
-                }
-                (Some(_before), Some(_after)) => {
-                    // We interpret this as that all objects _up to 'before'_ are supposed to be paginated.
-                    // This means we keep the 'before' value and handle the 'after' value just as above.
-                    // tODO: implement
-                }
-            }
-        }
-
-        $pagination.$after_field = Some($min_id - 1);
-        $pagination.$before_field = None;
-
-        let mut links = format!(
-            "<{}?{}>; rel=first",
-            $endpoint, serde_urlencoded::to_string(&$pagination.0).unwrap()
-        );
-
-        $pagination.$after_field = None;
-        $pagination.$before_field = Some($max_id + 1);
-
-        links.push_str(&format!(
-            ",<{}?{}>; rel=last",
-            $endpoint, serde_urlencoded::to_string(&$pagination.0).unwrap()
-        ));
-
-        links.push_str(&rel);
-
-        log::debug!("Links headers has value '{}'", links);
-
-        Ok(HttpResponse::Ok().header("Links", links).json(&$objects))
-    }};
-}
-
 pub fn header<'a>(request: &'a HeaderMap, header: &'static str) -> Result<Option<&'a str>, PointercrateError> {
     match request.get(header) {
         Some(value) =>
@@ -197,6 +96,36 @@ where
     Ok(Some(Option::deserialize(deserializer)?))
 }
 
+/// (De)serialization of nullable filters in query strings, to be used via `#[serde(with =
+/// "nullable_query")]`
+///
+/// Query strings have no notion of `null`, so an empty value (`?nation=`) is used to filter for
+/// objects where the field is `null`. Omitting the parameter means not filtering by the field at all.
+pub mod nullable_query {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[allow(clippy::option_option)]
+    pub fn serialize<S>(value: &Option<Option<String>>, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(Some(value)) => serializer.serialize_str(value),
+            _ => serializer.serialize_str(""),
+        }
+    }
+
+    #[allow(clippy::option_option)]
+    pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<Option<Option<String>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+
+        Ok(Some(if value.is_empty() { None } else { Some(value) }))
+    }
+}
+
 pub fn non_nullable<'de, T, D>(deseralizer: D) -> std::result::Result<Option<T>, D::Error>
 where
     D: Deserializer<'de>,
@@ -1,22 +1,110 @@
+//! Module containing the video hosts we accept for record and verification videos
+//!
+//! Each supported host is modelled by a [`VideoProvider`], which knows how to turn the various URL
+//! formats of that host into a single canonical form, and how to embed and preview videos from it.
+//! All providers are registered in [`PROVIDERS`]. To support a new host, implement
+//! [`VideoProvider`] for it and add it to that table.
+//!
+//! Video URLs are stored in their canonical form, so everything but [`validate`] may assume that
+//! the URL it is given was produced by [`VideoProvider::canonicalize`] of some provider.
+
+pub use self::{
+    bilibili::Bilibili,
+    everyplay::Everyplay,
+    google_drive::GoogleDrive,
+    medal::Medal,
+    streamable::Streamable,
+    twitch::Twitch,
+    vimeo::Vimeo,
+    youtube::YouTube,
+};
 use crate::{error::PointercrateError, Result};
 use url::Url;
 
+mod bilibili;
+mod everyplay;
+mod google_drive;
+mod medal;
+mod streamable;
+mod twitch;
+mod vimeo;
+mod youtube;
+
 const SCHEMES: [&str; 2] = ["http", "https"];
-const YOUTUBE_FORMAT: &str = "https://www.youtube.com/watch?v={video_id}' or \
-                              'https://m.youtube.com/watch?v={video_id}' or \
-                              'https://youtube.com/watch?v={video_id}' or \
-                              'https://youtu.be/{video_id}";
-const TWITCH_FORMAT: &str = "https://www.twitch.tv/videos/{video_id}' or \
-                             'https://twitch.tv/videos/{video_id}' or\
-                             'https://www.twitch.tv/{channel_name}/v/{video_id}' or\
-                             'https://twitch.tv/{channel_name}/v/{video_id}";
-const EVERYPLAY_FORMAT: &str = "https://everyplay.com/videos/{video_id}' or'https://www.everyplay.com/videos/{video_id}";
-const VIMEO_FORMAT: &str = "https://vimeo.com/{video_id}' or'https://www.vimeo.com/{video_id}";
-const BILIBILI_FORMAT: &str = "'https://www.bilibili.com/video/{video_id}' or'https://bilibili.com/video/{video_id}";
 
 const YOUTUBE_CHANNEL_FORMAT: &str =
     "'youtube.com/channel/{channel_id}' or'youtube.com/c/{custom_channel_id}/' or'youtube.com/user/{username}/";
 
+/// Trait describing a video host we accept videos from
+pub trait VideoProvider: Sync {
+    /// The human readable name of this provider, e.g. "YouTube"
+    fn name(&self) -> &'static str;
+
+    /// The domains this provider serves videos from
+    fn domains(&self) -> &'static [&'static str];
+
+    /// A description of the URL formats accepted for this provider, shown to the user if they
+    /// provide a malformed URL
+    fn format(&self) -> &'static str;
+
+    /// Turns the given URL into its canonical form, or returns [`None`] if it isn't in any of the
+    /// formats accepted for this provider
+    ///
+    /// The domain of the URL has already been checked to be one of [`VideoProvider::domains`].
+    /// Canonicalizing a canonical URL must return it unchanged.
+    fn canonicalize(&self, url: &Url) -> Option<String>;
+
+    /// Generates a URL for embedding the given (canonical) video into a website via an `iframe`
+    fn embed(&self, _video: &Url) -> Option<String> {
+        None
+    }
+
+    /// Generates a URL to a preview image of the given (canonical) video
+    fn thumbnail(&self, _video: &Url) -> Option<String> {
+        None
+    }
+}
+
+/// All video providers we support
+///
+/// Providers sharing a domain are tried in the order they appear in here.
+pub static PROVIDERS: &[&dyn VideoProvider] = &[
+    &YouTube,
+    &Twitch,
+    &Everyplay,
+    &Vimeo,
+    &Bilibili,
+    &Medal,
+    &Streamable,
+    &GoogleDrive,
+];
+
+/// Returns the (non-empty) path segments of the given URL
+///
+/// Empty segments are dropped so that trailing slashes don't matter
+fn segments(url: &Url) -> Vec<&str> {
+    url.path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Returns the value of the given query parameter of the given URL, if it's set
+fn query_parameter(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find_map(|(key, value)| if key == name { Some(value.into_owned()) } else { None })
+}
+
+/// Finds the provider the given canonical video URL belongs to
+fn provider_of(video: &Url) -> Option<&'static dyn VideoProvider> {
+    let domain = video.domain()?;
+
+    PROVIDERS
+        .iter()
+        .copied()
+        .filter(|provider| provider.domains().contains(&domain))
+        .find(|provider| provider.canonicalize(video).is_some())
+}
+
 pub fn validate_channel(url: &str) -> Result<String> {
     let url = Url::parse(url).map_err(|_| {
         PointercrateError::BadRequest {
@@ -70,127 +158,62 @@ pub fn validate(url: &str) -> Result<String> {
         return Err(PointercrateError::UrlAuthenticated)
     }
 
-    if let Some(host) = url.domain() {
-        match host {
-            "www.youtube.com" | "m.youtube.com" | "youtube.com" => {
-                if url.path() == "/watch" {
-                    if let Some(video_id) = url
-                        .query_pairs()
-                        .find_map(|(key, value)| if key == "v" { Some(value) } else { None })
-                    {
-                        return Ok(format!(
-                            "https://www.youtube.com/watch?v={}",
-                            video_id.chars().take(11).collect::<String>()
-                        ))
-                    }
-                }
+    let domain = url.domain().ok_or(PointercrateError::UnprocessableEntity)?;
 
-                Err(PointercrateError::InvalidUrlFormat { expected: YOUTUBE_FORMAT })
-            },
-            "youtu.be" =>
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        [video_id] =>
-                            Ok(format!(
-                                "https://www.youtube.com/watch?v={}",
-                                video_id.chars().take(11).collect::<String>()
-                            )),
-                        _ => Err(PointercrateError::InvalidUrlFormat { expected: YOUTUBE_FORMAT }),
-                    }
-                } else {
-                    Err(PointercrateError::InvalidUrlFormat { expected: YOUTUBE_FORMAT })
-                },
-            "www.twitch.tv" | "twitch.tv" =>
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        ["videos", video_id] => Ok(format!("https://www.twitch.tv/videos/{}", video_id)),
-                        [_, "v", video_id] => Ok(format!("https://www.twitch.tv/videos/{}", video_id)),
-                        _ => Err(PointercrateError::InvalidUrlFormat { expected: TWITCH_FORMAT }),
-                    }
-                } else {
-                    Err(PointercrateError::InvalidUrlFormat { expected: TWITCH_FORMAT })
-                },
-            "everyplay.com" | "www.everyplay.com" =>
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        ["videos", video_id] => Ok(format!("https://everyplay.com/videos/{}", video_id)),
-                        _ =>
-                            Err(PointercrateError::InvalidUrlFormat {
-                                expected: EVERYPLAY_FORMAT,
-                            }),
-                    }
-                } else {
-                    Err(PointercrateError::InvalidUrlFormat {
-                        expected: EVERYPLAY_FORMAT,
-                    })
-                },
-            "www.bilibili.com" | "bilibili.com" =>
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        ["video", video_id] => Ok(format!("https://www.bilibili.com/video/{}", video_id)),
-                        _ => Err(PointercrateError::InvalidUrlFormat { expected: BILIBILI_FORMAT }),
-                    }
-                } else {
-                    Err(PointercrateError::InvalidUrlFormat { expected: BILIBILI_FORMAT })
-                },
-            "vimeo.com" | "www.vimeo.com" =>
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        [video_id] => Ok(format!("https://vimeo.com/{}", video_id)),
-                        _ => Err(PointercrateError::InvalidUrlFormat { expected: VIMEO_FORMAT }),
-                    }
-                } else {
-                    Err(PointercrateError::InvalidUrlFormat { expected: VIMEO_FORMAT })
-                },
-            _ => Err(PointercrateError::UnsupportedVideoHost),
-        }
-    } else {
-        Err(PointercrateError::UnprocessableEntity)
-    }
+    let mut candidates = PROVIDERS.iter().filter(|provider| provider.domains().contains(&domain)).peekable();
+
+    let expected = match candidates.peek() {
+        Some(provider) => provider.format(),
+        None => return Err(PointercrateError::UnsupportedVideoHost),
+    };
+
+    candidates
+        .find_map(|provider| provider.canonicalize(&url))
+        .ok_or(PointercrateError::InvalidUrlFormat { expected })
 }
 
 pub fn embed(video: &str) -> Option<String> {
     // Video URLs need to be wellformed once we get here!
-    let url = Url::parse(video).unwrap();
-
-    match url.domain()? {
-        "www.youtube.com" => {
-            let video_id = url
-                .query_pairs()
-                .find_map(|(key, value)| if key == "v" { Some(value) } else { None })?;
-
-            Some(format!("https://www.youtube.com/embed/{}", video_id))
-        },
-        "www.twitch.tv" => {
-            // per validation always of the form 'https://www.twitch.tv/videos/[video id]/'
-            let mut url_segment = url.path_segments()?;
-            url_segment.next()?;
-            let video_id = url_segment.next()?;
-
-            Some(format!("https://player.twitch.tv/?video={}&autoplay=false", video_id))
-        },
-        _ => None,
-    }
+    let url = Url::parse(video).ok()?;
+
+    provider_of(&url)?.embed(&url)
 }
 
-pub fn thumbnail(video: &str) -> String {
+pub fn thumbnail(video: &str) -> Option<String> {
     // Videos need to be well formed once we get here!
-    let url = Url::parse(video).unwrap();
-    let video_id = url
-        .query_pairs()
-        .find_map(|(key, value)| if key == "v" { Some(value) } else { None })
-        .unwrap();
+    let url = Url::parse(video).ok()?;
 
-    format!("https://i.ytimg.com/vi/{}/mqdefault.jpg", video_id)
+    provider_of(&url)?.thumbnail(&url)
 }
 
-pub fn host(video: &str) -> &str {
-    match Url::parse(video).unwrap().domain().unwrap() {
-        "www.youtube.com" => "YouTube",
-        "www.twitch.tv" => "Twitch",
-        "everyplay.com" => "Everyplay",
-        "www.bilibili.com" => "Bilibili",
-        "vimeo.com" => "Vimeo",
-        host => panic!("{}", host),
+pub fn host(video: &str) -> &'static str {
+    Url::parse(video)
+        .ok()
+        .and_then(|url| provider_of(&url))
+        .map(|provider| provider.name())
+        .unwrap_or("Video")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{embed, host, validate};
+
+    #[test]
+    fn test_canonical_urls_are_stable() {
+        let videos = [
+            ("https://youtu.be/cHEGAqOgddA", "https://www.youtube.com/watch?v=cHEGAqOgddA", "YouTube"),
+            ("https://youtube.com/shorts/cHEGAqOgddA/", "https://www.youtube.com/watch?v=cHEGAqOgddA", "YouTube"),
+            ("https://twitch.tv/someone/v/123456", "https://www.twitch.tv/videos/123456", "Twitch"),
+            ("https://medal.tv/games/geometry-dash/clips/abc123/some-title", "https://medal.tv/clips/abc123", "Medal"),
+            ("https://streamable.com/e/abc123", "https://streamable.com/abc123", "Streamable"),
+            ("https://drive.google.com/open?id=abc123", "https://drive.google.com/file/d/abc123/view", "Google Drive"),
+        ];
+
+        for (input, canonical, provider) in videos.iter() {
+            assert_eq!(validate(input).unwrap(), *canonical);
+            assert_eq!(validate(canonical).unwrap(), *canonical);
+            assert_eq!(host(canonical), *provider);
+            assert!(embed(canonical).is_some());
+        }
     }
 }
@@ -0,0 +1,37 @@
+use super::{segments, VideoProvider};
+use url::Url;
+
+pub struct Bilibili;
+
+impl VideoProvider for Bilibili {
+    fn name(&self) -> &'static str {
+        "Bilibili"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["www.bilibili.com", "bilibili.com"]
+    }
+
+    fn format(&self) -> &'static str {
+        "https://www.bilibili.com/video/{video_id}' or 'https://bilibili.com/video/{video_id}"
+    }
+
+    fn canonicalize(&self, url: &Url) -> Option<String> {
+        match &segments(url)[..] {
+            ["video", video_id] => Some(format!("https://www.bilibili.com/video/{}", video_id)),
+            _ => None,
+        }
+    }
+
+    fn embed(&self, video: &Url) -> Option<String> {
+        match &segments(video)[..] {
+            // The player only understands the newer "BV" IDs by that name. Old "av" IDs need to be passed
+            // as 'aid' without their prefix
+            ["video", video_id] if video_id.starts_with("BV") =>
+                Some(format!("https://player.bilibili.com/player.html?bvid={}&autoplay=0", video_id)),
+            ["video", video_id] if video_id.starts_with("av") =>
+                Some(format!("https://player.bilibili.com/player.html?aid={}&autoplay=0", &video_id[2..])),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use super::{segments, VideoProvider};
+use url::Url;
+
+/// Everyplay shut down in 2018, but old records still link to it
+pub struct Everyplay;
+
+impl VideoProvider for Everyplay {
+    fn name(&self) -> &'static str {
+        "Everyplay"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["everyplay.com", "www.everyplay.com"]
+    }
+
+    fn format(&self) -> &'static str {
+        "https://everyplay.com/videos/{video_id}' or 'https://www.everyplay.com/videos/{video_id}"
+    }
+
+    fn canonicalize(&self, url: &Url) -> Option<String> {
+        match &segments(url)[..] {
+            ["videos", video_id] => Some(format!("https://everyplay.com/videos/{}", video_id)),
+            _ => None,
+        }
+    }
+}
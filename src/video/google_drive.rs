@@ -0,0 +1,43 @@
+use super::{query_parameter, segments, VideoProvider};
+use url::Url;
+
+/// Videos uploaded to Google Drive. Only links to single files are accepted, not folders
+pub struct GoogleDrive;
+
+impl VideoProvider for GoogleDrive {
+    fn name(&self) -> &'static str {
+        "Google Drive"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["drive.google.com"]
+    }
+
+    fn format(&self) -> &'static str {
+        "https://drive.google.com/file/d/{file_id}' or 'https://drive.google.com/open?id={file_id}"
+    }
+
+    fn canonicalize(&self, url: &Url) -> Option<String> {
+        let file_id = match &segments(url)[..] {
+            ["file", "d", file_id, ..] => file_id.to_string(),
+            ["open"] | ["uc"] => query_parameter(url, "id")?,
+            _ => return None,
+        };
+
+        Some(format!("https://drive.google.com/file/d/{}/view", file_id))
+    }
+
+    fn embed(&self, video: &Url) -> Option<String> {
+        match &segments(video)[..] {
+            ["file", "d", file_id, "view"] => Some(format!("https://drive.google.com/file/d/{}/preview", file_id)),
+            _ => None,
+        }
+    }
+
+    fn thumbnail(&self, video: &Url) -> Option<String> {
+        match &segments(video)[..] {
+            ["file", "d", file_id, "view"] => Some(format!("https://drive.google.com/thumbnail?id={}", file_id)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use super::{segments, VideoProvider};
+use url::Url;
+
+pub struct Medal;
+
+impl VideoProvider for Medal {
+    fn name(&self) -> &'static str {
+        "Medal"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["medal.tv", "www.medal.tv"]
+    }
+
+    fn format(&self) -> &'static str {
+        "https://medal.tv/clips/{clip_id}' or 'https://medal.tv/games/{game}/clips/{clip_id}"
+    }
+
+    fn canonicalize(&self, url: &Url) -> Option<String> {
+        match &segments(url)[..] {
+            ["clips", clip_id, ..] | ["games", _, "clips", clip_id, ..] | ["clip", clip_id, ..] =>
+                Some(format!("https://medal.tv/clips/{}", clip_id)),
+            _ => None,
+        }
+    }
+
+    fn embed(&self, video: &Url) -> Option<String> {
+        match &segments(video)[..] {
+            ["clips", clip_id] => Some(format!("https://medal.tv/clip/{}", clip_id)),
+            _ => None,
+        }
+    }
+}
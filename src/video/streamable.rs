@@ -0,0 +1,32 @@
+use super::{segments, VideoProvider};
+use url::Url;
+
+pub struct Streamable;
+
+impl VideoProvider for Streamable {
+    fn name(&self) -> &'static str {
+        "Streamable"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["streamable.com", "www.streamable.com"]
+    }
+
+    fn format(&self) -> &'static str {
+        "https://streamable.com/{video_id}"
+    }
+
+    fn canonicalize(&self, url: &Url) -> Option<String> {
+        match &segments(url)[..] {
+            [video_id] | ["e", video_id] => Some(format!("https://streamable.com/{}", video_id)),
+            _ => None,
+        }
+    }
+
+    fn embed(&self, video: &Url) -> Option<String> {
+        match &segments(video)[..] {
+            [video_id] => Some(format!("https://streamable.com/e/{}", video_id)),
+            _ => None,
+        }
+    }
+}
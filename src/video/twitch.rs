@@ -0,0 +1,35 @@
+use super::{segments, VideoProvider};
+use url::Url;
+
+pub struct Twitch;
+
+impl VideoProvider for Twitch {
+    fn name(&self) -> &'static str {
+        "Twitch"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["www.twitch.tv", "twitch.tv"]
+    }
+
+    fn format(&self) -> &'static str {
+        "https://www.twitch.tv/videos/{video_id}' or \
+         'https://twitch.tv/videos/{video_id}' or \
+         'https://www.twitch.tv/{channel_name}/v/{video_id}' or \
+         'https://twitch.tv/{channel_name}/v/{video_id}"
+    }
+
+    fn canonicalize(&self, url: &Url) -> Option<String> {
+        match &segments(url)[..] {
+            ["videos", video_id] | [_, "v", video_id] => Some(format!("https://www.twitch.tv/videos/{}", video_id)),
+            _ => None,
+        }
+    }
+
+    fn embed(&self, video: &Url) -> Option<String> {
+        match &segments(video)[..] {
+            ["videos", video_id] => Some(format!("https://player.twitch.tv/?video={}&autoplay=false", video_id)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use super::{segments, VideoProvider};
+use url::Url;
+
+pub struct Vimeo;
+
+impl VideoProvider for Vimeo {
+    fn name(&self) -> &'static str {
+        "Vimeo"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["vimeo.com", "www.vimeo.com"]
+    }
+
+    fn format(&self) -> &'static str {
+        "https://vimeo.com/{video_id}' or 'https://www.vimeo.com/{video_id}"
+    }
+
+    fn canonicalize(&self, url: &Url) -> Option<String> {
+        match &segments(url)[..] {
+            [video_id] => Some(format!("https://vimeo.com/{}", video_id)),
+            _ => None,
+        }
+    }
+
+    fn embed(&self, video: &Url) -> Option<String> {
+        match &segments(video)[..] {
+            [video_id] => Some(format!("https://player.vimeo.com/video/{}", video_id)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::{query_parameter, segments, VideoProvider};
+use url::Url;
+
+/// YouTube video IDs are always 11 characters long. We truncate longer IDs since people sometimes
+/// paste them with garbage attached
+fn video_id(id: &str) -> String {
+    id.chars().take(11).collect()
+}
+
+pub struct YouTube;
+
+/// Extracts the video ID from the given YouTube URL. Shorts are regular YouTube videos, so
+/// `/shorts/{video_id}` URLs are simply another format for the same video
+fn id_of(url: &Url) -> Option<String> {
+    let video_id = match (url.domain()?, &segments(url)[..]) {
+        ("youtu.be", [video_id]) => video_id.to_string(),
+        (_, ["watch"]) => query_parameter(url, "v")?,
+        (domain, ["shorts", video_id]) if domain != "youtu.be" => video_id.to_string(),
+        _ => return None,
+    };
+
+    Some(self::video_id(&video_id))
+}
+
+impl VideoProvider for YouTube {
+    fn name(&self) -> &'static str {
+        "YouTube"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["www.youtube.com", "m.youtube.com", "youtube.com", "youtu.be"]
+    }
+
+    fn format(&self) -> &'static str {
+        "https://www.youtube.com/watch?v={video_id}' or \
+         'https://m.youtube.com/watch?v={video_id}' or \
+         'https://youtube.com/watch?v={video_id}' or \
+         'https://youtu.be/{video_id}' or \
+         'https://www.youtube.com/shorts/{video_id}"
+    }
+
+    fn canonicalize(&self, url: &Url) -> Option<String> {
+        Some(format!("https://www.youtube.com/watch?v={}", id_of(url)?))
+    }
+
+    fn embed(&self, video: &Url) -> Option<String> {
+        Some(format!("https://www.youtube.com/embed/{}", id_of(video)?))
+    }
+
+    fn thumbnail(&self, video: &Url) -> Option<String> {
+        Some(format!("https://i.ytimg.com/vi/{}/mqdefault.jpg", id_of(video)?))
+    }
+}
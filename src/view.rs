@@ -1,7 +1,6 @@
 #![allow(unused_variables)]
 // currently, all the request parameters are unused, but they will be required in the future
 
-use crate::config;
 use maud::{html, Markup, PreEscaped, DOCTYPE};
 
 pub mod account;
@@ -126,9 +125,6 @@ pub fn nav_bar() -> Markup {
 }
 
 pub fn footer() -> Markup {
-    let first_extended = config::list_size() + 1;
-    let first_legacy = config::extended_list_size() + 1;
-
     html! {
         footer.center {
             span.overline.pad style="text-align:center" {
@@ -163,7 +159,7 @@ pub fn footer() -> Markup {
                         "Hardest Challenge"
                     }   
                     br;
-                    a.link href = {"/challenges/" (first_legacy) "/"} title="Legacy List" {
+                    a.link href = "/challenges/legacy/" title="Legacy List" {
                         "Legacy List"
                     }
                 }
@@ -1,13 +1,16 @@
-use super::Page;
+use super::{login::set_session_cookies, Page};
 use crate::{
-    extractor::auth::TokenAuth,
-    model::{nationality::Nationality, user::User},
-    permissions::Permissions,
+    extractor::{auth::TokenAuth, ip::Ip, user_agent::UserAgent},
+    model::{
+        nationality::Nationality,
+        user::{AuthenticatedUser, User},
+    },
+    permissions::Capabilities,
     state::PointercrateState,
     view::demonlist::{overview_demons, OverviewDemon},
     ApiResult, ViewResult,
 };
-use actix_web::HttpResponse;
+use actix_web::{HttpMessage, HttpRequest, HttpResponse};
 use actix_web_codegen::get;
 use maud::{html, Markup, PreEscaped};
 use std::{
@@ -15,6 +18,7 @@ use std::{
     hash::{Hash, Hasher},
 };
 
+mod audit;
 mod demons;
 mod players;
 mod profile;
@@ -25,22 +29,62 @@ mod users;
 #[derive(Debug)]
 pub struct AccountPage {
     user: User,
+    two_factor_enabled: bool,
+    permissions_suspended: bool,
+
+    /// The user's recovery email address, only shown if password resets via email are enabled
+    email: Option<String>,
+    email_reset: bool,
     csrf_token: String,
     demons: Vec<OverviewDemon>,
     pub nations: Vec<Nationality>,
 }
 
 #[get("/account/")]
-pub async fn index(user: ApiResult<TokenAuth>, state: PointercrateState) -> ViewResult<HttpResponse> {
+pub async fn index(
+    request: HttpRequest, Ip(ip): Ip, UserAgent(user_agent): UserAgent, user: ApiResult<TokenAuth>, state: PointercrateState,
+) -> ViewResult<HttpResponse> {
+    let mut response = HttpResponse::Ok();
+
+    let user = match user {
+        Ok(TokenAuth(user)) => Some(user),
+        // The access token cookie is short-lived, so if it expired, try to get a new one via the refresh token
+        Err(_) =>
+            match request.cookie("refresh_token") {
+                Some(refresh_token) =>
+                    AuthenticatedUser::refresh_session(
+                        refresh_token.value(),
+                        ip,
+                        user_agent.as_deref(),
+                        &state.secret,
+                        &mut *state.connection().await?,
+                    )
+                    .await
+                    .ok()
+                    .map(|(user, tokens)| {
+                        set_session_cookies(&mut response, tokens);
+
+                        user
+                    }),
+                None => None,
+            },
+    };
+
     Ok(match user {
-        Ok(TokenAuth(user)) => {
+        Some(user) => {
             let csrf_token = user.generate_csrf_token(&state.secret);
+            let email_reset = state.mailer.is_some();
+            let email = if email_reset {
+                user.email(&mut *state.connection().await?).await?
+            } else {
+                None
+            };
 
-            let (demons, nations) = if user.inner().has_permission(Permissions::ListHelper) {
+            let (demons, nations) = if user.inner().has_capability(Capabilities::ReviewRecords) {
                 let mut connection = state.connection().await?;
                 (
                     overview_demons(&mut connection).await?,
-                    if user.inner().has_permission(Permissions::ListModerator) {
+                    if user.inner().has_capability(Capabilities::ManagePlayers) {
                         Nationality::all(&mut connection).await?
                     } else {
                         Vec::new()
@@ -50,8 +94,12 @@ pub async fn index(user: ApiResult<TokenAuth>, state: PointercrateState) -> View
                 (Vec::new(), Vec::new())
             };
 
-            HttpResponse::Ok().content_type("text/html; charset=utf-8").body(
+            response.content_type("text/html; charset=utf-8").body(
                 AccountPage {
+                    two_factor_enabled: user.two_factor_enabled(),
+                    permissions_suspended: user.permissions_suspended(),
+                    email,
+                    email_reset,
                     user: user.into_inner(),
                     csrf_token,
                     demons,
@@ -61,7 +109,7 @@ pub async fn index(user: ApiResult<TokenAuth>, state: PointercrateState) -> View
                 .0,
             )
         },
-        Err(_) =>
+        None =>
             actix_web::HttpResponse::Found()
                 .header(actix_web::http::header::LOCATION, "/login/")
                 .finish(),
@@ -87,6 +135,7 @@ impl Page for AccountPage {
             "js/account/demon.js",
             "js/account/player.js",
             "js/account/submitter.js",
+            "js/account/audit.js",
             "js/staff.js",
         ]
     }
@@ -107,7 +156,7 @@ impl Page for AccountPage {
                         (PreEscaped("&nbsp;&nbsp;"))
                         i class = "fa fa-user fa-2x" aria-hidden="true" {}
                     }
-                    @if self.user.has_permission(Permissions::Administrator) || self.user.has_permission(Permissions::ListAdministrator) {
+                    @if !self.user.permissions.assigns().is_empty() {
                         div.tab.button.white.hover.no-shadow data-tab-id="2" {
                             b {
                                 "Users"
@@ -116,7 +165,7 @@ impl Page for AccountPage {
                             i class = "fa fa-users fa-2x" aria-hidden="true" {}
                         }
                     }
-                    @if self.user.has_permission(Permissions::ListHelper) {
+                    @if self.user.has_capability(Capabilities::ReviewRecords) {
                         div.tab.button.white.hover.no-shadow data-tab-id="3" {
                             b {
                                 "Records"
@@ -125,7 +174,7 @@ impl Page for AccountPage {
                             i class = "fa fa-trophy fa-2x" aria-hidden="true" {}
                         }
                     }
-                    @if self.user.has_permission(Permissions::ListModerator) {
+                    @if self.user.has_capability(Capabilities::ManagePlayers) {
                         div.tab.button.white.hover.no-shadow data-tab-id="4" {
                             b {
                                 "Players"
@@ -133,6 +182,8 @@ impl Page for AccountPage {
                             (PreEscaped("&nbsp;&nbsp;"))
                             i class = "fa fa-beer fa-2x" aria-hidden="true" {}
                         }
+                    }
+                    @if self.user.has_capability(Capabilities::ManageDemons) {
                         div.tab.button.white.hover.no-shadow data-tab-id="5" {
                             i class = "fa fa-shower fa-2x" aria-hidden="true" {}
                             (PreEscaped("&nbsp;&nbsp;"))
@@ -140,6 +191,8 @@ impl Page for AccountPage {
                                 "Challenges"
                             }
                         }
+                    }
+                    @if self.user.has_capability(Capabilities::ManageSubmitters) {
                         div.tab.button.white.hover.no-shadow data-tab-id="6" {
                             b {
                                 "Submitters"
@@ -148,20 +201,36 @@ impl Page for AccountPage {
                             i class = "fa fa-eye fa-2x" aria-hidden="true" {}
                         }
                     }
+                    @if self.user.has_capability(Capabilities::ViewAuditLog) {
+                        div.tab.button.white.hover.no-shadow data-tab-id="7" {
+                            b {
+                                "Audit Log"
+                            }
+                            (PreEscaped("&nbsp;&nbsp;"))
+                            i class = "fa fa-history fa-2x" aria-hidden="true" {}
+                        }
+                    }
                 }
 
-                (profile::page(&self.user))
-                @if self.user.has_permission(Permissions::Administrator) || self.user.has_permission(Permissions::ListAdministrator) {
-                    (users::page(self.user.has_permission(Permissions::Administrator)))
+                (profile::page(&self.user, self.two_factor_enabled, self.permissions_suspended, self.email_reset, self.email.as_deref()))
+                @if !self.user.permissions.assigns().is_empty() {
+                    (users::page(self.user.has_capability(Capabilities::ManageUsers)))
                 }
-                @if self.user.has_permission(Permissions::ListHelper) {
+                @if self.user.has_capability(Capabilities::ReviewRecords) {
                     (records::page(&self.demons))
                 }
-                @if self.user.has_permission(Permissions::ListModerator) {
+                @if self.user.has_capability(Capabilities::ManagePlayers) {
                     (players::page(&self.nations))
+                }
+                @if self.user.has_capability(Capabilities::ManageDemons) {
                     (demons::page())
+                }
+                @if self.user.has_capability(Capabilities::ManageSubmitters) {
                     (submitters::page())
                 }
+                @if self.user.has_capability(Capabilities::ViewAuditLog) {
+                    (audit::page())
+                }
             }
         }
     }
@@ -173,7 +242,7 @@ impl Page for AccountPage {
         vec![html! {
             (PreEscaped(
                 format!(r#"
-                <link href="https://cdnjs.cloudflare.com/ajax/libs/flag-icon-css/3.4.3/css/flag-icon.min.css" rel="stylesheet"><script>window.username='{}'; window.etag='{}'; window.permissions='{}'</script>"#, self.user.name, hasher.finish().to_string(), self.user.permissions.bits())
+                <link href="https://cdnjs.cloudflare.com/ajax/libs/flag-icon-css/3.4.3/css/flag-icon.min.css" rel="stylesheet"><script>window.username='{}'; window.etag='{}'; window.permissions='{}'; window.capabilities='{}'</script>"#, self.user.name, hasher.finish().to_string(), self.user.permissions.bits(), self.user.permissions.capabilities().bits())
             ))
         }]
     }
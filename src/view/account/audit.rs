@@ -0,0 +1,135 @@
+use crate::view::{dropdown, paginator};
+use maud::{html, Markup};
+
+pub(super) fn page() -> Markup {
+    html! {
+        div.m-center.flex.tab-content.container data-tab-id = "7" {
+            div.left {
+                div.panel.fade {
+                    h2.underlined.pad {
+                        "Audit Log"
+                    }
+                    div.flex.viewer {
+                        (paginator("audit-pagination", "/api/v1/audit/"))
+                        p.viewer-welcome {
+                            "Click on an entry on the left to see what was changed!"
+                        }
+                        div.viewer-content {
+                            div.flex.col {
+                                h3 style = "font-size:1.1em; margin: 10px 0" {
+                                    "Audit Log Entry #"
+                                    i#audit-entry-id {}
+                                }
+                                div.stats-container.flex.space {
+                                    span {
+                                        b {
+                                            "Object:"
+                                        }
+                                        br;
+                                        span#audit-entry-object {}
+                                    }
+                                    span {
+                                        b {
+                                            "Action:"
+                                        }
+                                        br;
+                                        span#audit-entry-action {}
+                                    }
+                                }
+                                div.stats-container.flex.space {
+                                    span {
+                                        b {
+                                            "User:"
+                                        }
+                                        br;
+                                        span#audit-entry-user {}
+                                    }
+                                    span {
+                                        b {
+                                            "Time:"
+                                        }
+                                        br;
+                                        span#audit-entry-time {}
+                                    }
+                                }
+                                div.stats-container.flex.space {
+                                    span {
+                                        b {
+                                            "Details:"
+                                        }
+                                        br;
+                                        i {
+                                            "For modifications, these are the values the changed fields had before the change."
+                                        }
+                                        ul#audit-entry-details style = "text-align: left" {}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                div style="height: 50px" {} // to make sure that the footer doesnt float. if it floats, the user page is the only one without a scrollbar at the right, which causes jumpyness when switching tabs.
+            }
+            div.right {
+                (model_selector())
+                (filter_form())
+            }
+        }
+    }
+}
+
+fn model_selector() -> Markup {
+    let dropdown_items = ["record", "record_note", "record_claim", "demon", "creator", "player", "submitter", "user"]
+        .iter()
+        .map(|model| {
+            html! {
+                li.white.hover data-value = (model) {(model.replace('_', " "))}
+            }
+        });
+
+    html! {
+        div.panel.fade#audit-model-filter-panel style = "overflow: visible" {
+            h2.underlined.pad {
+                "Filter by object"
+            }
+            p {
+                "Only show changes to the selected kind of object"
+            }
+            (dropdown("All", html! {
+                li.white.hover.underlined data-value = "All" {"All"}
+            }, dropdown_items))
+        }
+    }
+}
+
+fn filter_form() -> Markup {
+    html! {
+        div.panel.fade {
+            h2.underlined.pad {
+                "Filter by user and time"
+            }
+            p {
+                "Only show changes made by the user with the given ID, and/or changes made in the given time frame. Times are in UTC. Leave a field empty to not filter by it."
+            }
+            form.flex.col#audit-filter-form novalidate = "" {
+                p.info-red.output {}
+                span.form-input#audit-filter-user {
+                    label for = "user" {"User ID:"}
+                    input type = "number" name = "user" min = "0" style="width:93%";
+                    p.error {}
+                }
+                span.form-input#audit-filter-after {
+                    label for = "after_time" {"Changes made after:"}
+                    input type = "datetime-local" name = "after_time" style="width:93%";
+                    p.error {}
+                }
+                span.form-input#audit-filter-before {
+                    label for = "before_time" {"Changes made before:"}
+                    input type = "datetime-local" name = "before_time" style="width:93%";
+                    p.error {}
+                }
+                input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Filter";
+            }
+        }
+    }
+}
@@ -94,6 +94,30 @@ pub(super) fn page() -> Markup {
                                         }
                                     }
                                 }
+                                div.stats-container.flex.space  {
+                                    span {
+                                        b {
+                                            i.fa.fa-pencil.clickable#demon-requirement-pen aria-hidden = "true" {} " Requirement:"
+                                        }
+                                        br;
+                                        span#demon-requirement {}
+                                    }
+                                    span {
+                                        b {
+                                            "Completion only:"
+                                        }
+                                        br;
+                                        div.dropdown-menu.js-search#edit-demon-completion-only style = "max-width: 50px"{
+                                            input type="text" style = "color: #444446; font-weight: bold;";
+                                            div.menu {
+                                                ul {
+                                                    li.white.hover data-value="true" {"yes"}
+                                                    li.white.hover data-value="false" {"no"}
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
                             }
                         }
                     }
@@ -106,6 +130,7 @@ pub(super) fn page() -> Markup {
             (change_name_dialog())
             (change_position_dialog())
             (change_fps_dialog())
+            (change_requirement_dialog())
             (change_video_dialog())
             (change_verifier_dialog())
             (change_publisher_dialog())
@@ -181,6 +206,32 @@ fn change_position_dialog() -> Markup {
     }
 }
 
+fn change_requirement_dialog() -> Markup {
+    html! {
+        div.overlay.closable {
+            div.dialog#demon-requirement-dialog {
+                span.plus.cross.hover {}
+                h2.underlined.pad {
+                    "Change record requirement:"
+                }
+                p style = "max-width: 400px"{
+                    "Change the minimal progress records on this demon need to have. Existing records with less progress are deleted! Has no effect on submissions while the demon is set to completion only."
+                }
+                form.flex.col novalidate = "" {
+                    p.info-red.output {}
+                    p.info-green.output {}
+                    span.form-input#demon-requirement-edit {
+                        label for = "requirement" {"Requirement:"}
+                        input name = "requirement" type = "number" min = "0" max = "100" required = "";
+                        p.error {}
+                    }
+                    input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value = "Edit";
+                }
+            }
+        }
+    }
+}
+
 fn change_fps_dialog() -> Markup {
     html! {
         div.overlay.closable {
@@ -43,6 +43,13 @@ pub(super) fn page(nationalities: &[Nationality]) -> Markup {
                                             }
                                         }
                                     }
+                                    span {
+                                        b {
+                                            "Claimed:"
+                                        }
+                                        br;
+                                        span#player-claimed {}
+                                    }
                                     span {
                                         b {
                                             "Nationality:"
@@ -76,6 +83,7 @@ pub(super) fn page(nationalities: &[Nationality]) -> Markup {
             }
             div.right {
                 (player_selector())
+                (pending_claims())
             }
             (change_name_dialog())
         }
@@ -104,6 +112,21 @@ fn player_selector() -> Markup {
     }
 }
 
+fn pending_claims() -> Markup {
+    html! {
+        div.panel.fade#player-claims-panel {
+            h2.underlined.pad {
+                "Pending claims"
+            }
+            p {
+                "Users can claim the player they are listed as on the demonlist. Verify a claim only if you are sure the user actually is that player (e.g. because they contacted you from the YouTube channel their records were uploaded to). Verified claims allow the user to change the player's nationality and YouTube channel."
+            }
+            p.info-red.output {}
+            ul#player-claims style = "list-style: none; padding: 0" {}
+        }
+    }
+}
+
 fn change_name_dialog() -> Markup {
     html! {
         div.overlay.closable {
@@ -1,7 +1,17 @@
-use crate::model::user::User;
+use crate::{model::user::User, permissions::Permissions};
 use maud::{html, Markup};
 
-pub(super) fn page(user: &User) -> Markup {
+/// The permissions that can be granted to personal access tokens, along with a human readable name
+const SCOPES: [(Permissions, &str); 6] = [
+    (Permissions::ExtendedAccess, "Extended Access"),
+    (Permissions::ListHelper, "List Helper"),
+    (Permissions::ListModerator, "List Moderator"),
+    (Permissions::ListAdministrator, "List Administrator"),
+    (Permissions::Moderator, "Moderator"),
+    (Permissions::Administrator, "Administrator"),
+];
+
+pub(super) fn page(user: &User, two_factor_enabled: bool, permissions_suspended: bool, email_reset: bool, email: Option<&str>) -> Markup {
     html! {
         div.m-center.flex.tab-content.tab-content-active.container data-tab-id = "1"{
             div.left {
@@ -9,6 +19,11 @@ pub(super) fn page(user: &User) -> Markup {
                     h1.underlined.pad {
                         "Profile - " (user.name())
                     }
+                    @if permissions_suspended {
+                        p.info-red style = "margin: 10px" {
+                            "Your list team permissions are currently suspended, since pointercrate requires members of the list team to use two-factor authentication. Enable it below to regain access to them."
+                        }
+                    }
                     div.flex.space.wrap#things {
                         p.info-red.output style = "margin: 10px" {}
                         p.info-green.output style = "margin: 10px" {}
@@ -49,6 +64,22 @@ pub(super) fn page(user: &User) -> Markup {
                                 "A link to your YouTube channel, if you have one. If set, all mentions of your name will turn into links to it."
                             }
                         }
+                        @if email_reset {
+                            span {
+                                b {
+                                    i.fa.fa-pencil.clickable#email-pen aria-hidden = "true" {} " Recovery email: "
+                                }
+                                i#profile-email {
+                                    @match email {
+                                        Some(email) => (email),
+                                        None => "-"
+                                    }
+                                }
+                                p {
+                                    "If you forget your password, a password reset token can be sent to this address. It is never shown publicly."
+                                }
+                            }
+                        }
                         span {
                             b {
                                 "Permissions: "
@@ -71,7 +102,7 @@ pub(super) fn page(user: &User) -> Markup {
                         "Get access token"
                     }
                     p {
-                        "Your pointercrate access token allows you, or programs authorized by you, to make API calls on your behalf. Anyone with access to your pointercrate access token has nearly full control over your account. The only thing that's not possible with only an access token is to change your password. Proceed with care!"
+                        "Your pointercrate access token allows you, or programs authorized by you, to make API calls on your behalf. Anyone with access to your pointercrate access token has nearly full control over your account. The only thing that's not possible with only an access token is to change your password. Proceed with care! Access tokens expire 15 minutes after being retrieved, so for anything running longer, use a personal access token instead."
                     }
                     form.flex.col.overlined.pad#login-form novalidate = "" style="display: none" {
                         p style = "text-align: center" {
@@ -83,6 +114,7 @@ pub(super) fn page(user: &User) -> Markup {
                             input required = "" type = "password" name = "password" minlength = "10";
                             p.error {}
                         }
+                        (second_factor_input("login-2fa", two_factor_enabled))
                         input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Log in";
                     }
                     div.overlined.pad#token-area style = "display: none" {
@@ -93,12 +125,202 @@ pub(super) fn page(user: &User) -> Markup {
                         "Get access token"
                     }
                 }
+                div.panel.fade#access-tokens-panel {
+                    h2.underlined.pad {
+                        "Personal access tokens"
+                    }
+                    p {
+                        "Personal access tokens are meant for bots and other programs making API calls on your behalf. Unlike your access token, each of them can be restricted to only some of your permissions, can be set to expire, and can be revoked individually. They are not affected by changing your password or invalidating your access tokens."
+                    }
+                    p.info-red.output {}
+                    ul#access-tokens style = "list-style: none; padding: 0" {}
+                    form.flex.col.overlined.pad#access-token-form novalidate = "" style="display: none" {
+                        p.info-red.output {}
+                        span.form-input#access-token-name {
+                            label for = "name" {"Name:"}
+                            input required = "" type = "text" name = "name" maxlength = "64";
+                            p.error {}
+                        }
+                        @if SCOPES.iter().any(|(scope, _)| user.has_permission(*scope)) {
+                            p {
+                                b {"Scopes:"}
+                                " Requests authenticated with the token can only make use of the permissions selected here."
+                            }
+                            div.stats-container.flex.space.col style = "align-items: center" {
+                                @for (scope, name) in &SCOPES {
+                                    @if user.has_permission(*scope) {
+                                        label.cb-container {
+                                            i {(name)}
+                                            input type = "checkbox" data-scope = (scope.bits());
+                                            span.checkmark {}
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        span.form-input#access-token-expiry {
+                            label for = "expires_at" {"Expires at (optional):"}
+                            input type = "date" name = "expires_at";
+                            p.error {}
+                        }
+                        span.overlined.pad.form-input#access-token-password {
+                            label {"Authenticate:"}
+                            input required = "" type = "password" minlength = "10";
+                            p.error {}
+                        }
+                        (second_factor_input("access-token-2fa", two_factor_enabled))
+                        input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Create";
+                    }
+                    div.overlined.pad#new-access-token-area style = "display: none" {
+                        b {"Your new personal access token is:"}
+                        textarea#new-access-token readonly="" style = "resize: none; width: 100%; margin-top: 8px; min-height:75px" {}
+                        p {
+                            "Make sure to copy it now, you won't be able to see it again!"
+                        }
+                    }
+                    a.blue.hover.button#create-access-token {
+                        "Create personal access token"
+                    }
+                }
+                div.panel.fade#two-factor-panel {
+                    h2.underlined.pad {
+                        "Two-factor authentication"
+                    }
+                    p {
+                        "Two-factor authentication protects your account even if your password gets leaked: Once enabled, logging in and making changes to your account additionally requires a code generated by an authenticator app on your phone."
+                    }
+                    p.info-red.output {}
+                    p.info-green.output {}
+                    @if two_factor_enabled {
+                        p {
+                            b {"Two-factor authentication is enabled."}
+                            " If you lose access to your authenticator app, you can use one of your recovery codes instead of a code from the app. Each recovery code works only once."
+                        }
+                        form.flex.col.overlined.pad#two-factor-manage-form novalidate = "" style="display: none" {
+                            p style = "text-align: center" {
+                                "For security reasons, this requires you to reenter your password"
+                            }
+                            p.info-red.output {}
+                            span.form-input#two-factor-manage-password {
+                                label {"Password:"}
+                                input required = "" type = "password" minlength = "10";
+                                p.error {}
+                            }
+                            (second_factor_input("two-factor-manage-2fa", true))
+                            input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Confirm";
+                        }
+                        div.flex.no-stretch {
+                            a.blue.hover.button#regenerate-recovery-codes style = "margin: 15px auto 0px;" {
+                                "Regenerate recovery codes"
+                            }
+                            a.red.hover.button#disable-two-factor style = "margin: 15px auto 0px;" {
+                                "Disable"
+                            }
+                        }
+                    }
+                    @else {
+                        form.flex.col.overlined.pad#two-factor-enroll-form novalidate = "" style="display: none" {
+                            p style = "text-align: center" {
+                                "For security reasons, enabling two-factor authentication requires you to reenter your password"
+                            }
+                            p.info-red.output {}
+                            span.form-input#two-factor-enroll-password {
+                                label {"Password:"}
+                                input required = "" type = "password" minlength = "10";
+                                p.error {}
+                            }
+                            input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Continue";
+                        }
+                        form.flex.col.overlined.pad#two-factor-confirm-form novalidate = "" style="display: none" {
+                            p {
+                                "Add the following secret to your authenticator app (or open the link below on your phone), then enter the code it generates to finish enabling two-factor authentication:"
+                            }
+                            textarea#two-factor-secret readonly="" style = "resize: none; width: 100%; margin-top: 8px; min-height:30px" {}
+                            a.link#two-factor-uri {"Open in authenticator app"}
+                            p.info-red.output {}
+                            span.form-input#two-factor-confirm-code {
+                                label {"Code:"}
+                                input required = "" type = "text" autocomplete = "one-time-code" inputmode = "numeric" maxlength = "6";
+                                p.error {}
+                            }
+                            input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Enable";
+                        }
+                        a.blue.hover.button#enable-two-factor {
+                            "Enable two-factor authentication"
+                        }
+                    }
+                    div.overlined.pad#recovery-codes-area style = "display: none" {
+                        b {"Your recovery codes are:"}
+                        textarea#recovery-codes readonly="" style = "resize: none; width: 100%; margin-top: 8px; min-height:200px" {}
+                        p {
+                            "Store them somewhere safe, you won't be able to see them again! Any previously generated recovery codes no longer work."
+                        }
+                    }
+                }
+                div.panel.fade#sessions-panel {
+                    h2.underlined.pad {
+                        "Sessions"
+                    }
+                    p {
+                        "These are the devices you are currently logged in on. If you don't recognize one of them, or lost access to it, you can log it out by revoking its session."
+                    }
+                    p.info-red.output {}
+                    ul#sessions style = "list-style: none; padding: 0" {}
+                }
+                div.panel.fade#claim-panel {
+                    h2.underlined.pad {
+                        "My player"
+                    }
+                    p {
+                        "If you have records on the demonlist, you can claim the player they are listed under. Once a list moderator verified your claim, you can set your nationality and link your YouTube channel yourself, and your player gets a badge in the stats viewer."
+                    }
+                    p.info-red.output {}
+                    p.info-green.output {}
+                    p#claim-status {}
+                    form.flex.col#claim-form novalidate = "" style = "display: none" {
+                        p.info-red.output {}
+                        span.form-input#claim-player-name {
+                            label for = "player" {"Player name:"}
+                            input required = "" type = "text" name = "player";
+                            p.error {}
+                        }
+                        input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value = "Claim";
+                    }
+                    form.flex.col#claimed-player-form novalidate = "" style = "display: none" {
+                        p.info-red.output {}
+                        p.info-green.output {}
+                        span.form-input#claimed-player-nationality {
+                            label for = "nationality" {"Nationality (country name or code):"}
+                            input type = "text" name = "nationality";
+                            p.error {}
+                        }
+                        span.form-input#claimed-player-youtube {
+                            label for = "youtube_channel" {"YouTube channel:"}
+                            input type = "url" name = "youtube_channel";
+                            p.error {}
+                        }
+                        input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value = "Save";
+                    }
+                    a.red.hover.button#claim-withdraw style = "display: none" {
+                        "Withdraw claim"
+                    }
+                }
+                div.panel.fade#submissions-panel {
+                    h2.underlined.pad {
+                        "My submissions"
+                    }
+                    p {
+                        "These are the records you submitted while logged in, together with their current status. Records you submitted while logged out are not listed here."
+                    }
+                    p.info-red.output {}
+                    ul#submissions style = "list-style: none; padding: 0" {}
+                }
                 div.panel.fade {
                     h2.underlined.pad {
                         "Invalidate tokens"
                     }
                     p {
-                        "If one of your access tokens ever got leaked, you can invalidate them here. Invalidating will cause all access tokens to your account to stop functioning. This includes the one stored inside the browser currently, meaning you'll have to log in again after this action"
+                        "If one of your access tokens ever got leaked, you can invalidate them here. Invalidating will cause all access tokens to your account to stop functioning and log you out on all devices. This includes the one stored inside the browser currently, meaning you'll have to log in again after this action"
                     }
                     form.flex.col.overlined.pad#invalidate-form novalidate = "" style="display: none" {
                         p style = "text-align: center" {
@@ -110,6 +332,7 @@ pub(super) fn page(user: &User) -> Markup {
                             input required = "" type = "password" name = "password" minlength = "10";
                             p.error {}
                         }
+                        (second_factor_input("invalidate-auth-2fa", two_factor_enabled))
                         input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Invalidate";
                     }
                     a.blue.hover.button#invalidate-token {
@@ -118,14 +341,31 @@ pub(super) fn page(user: &User) -> Markup {
                 }
             }
         }
-        (edit_display_name_dialog())
-        (edit_youtube_link_dialog())
-        (change_password_dialog())
-        (delete_account_dialog())
+        (edit_display_name_dialog(two_factor_enabled))
+        (edit_youtube_link_dialog(two_factor_enabled))
+        @if email_reset {
+            (edit_email_dialog(two_factor_enabled))
+        }
+        (change_password_dialog(two_factor_enabled))
+        (delete_account_dialog(two_factor_enabled))
+    }
+}
+
+/// Renders an input for the code from the user's authenticator app, if they have two-factor
+/// authentication enabled
+fn second_factor_input(id: &str, two_factor_enabled: bool) -> Markup {
+    html! {
+        @if two_factor_enabled {
+            span.form-input id = (id) {
+                label {"Two-factor code:"}
+                input required = "" type = "text" autocomplete = "one-time-code";
+                p.error {}
+            }
+        }
     }
 }
 
-fn edit_display_name_dialog() -> Markup {
+fn edit_display_name_dialog(two_factor_enabled: bool) -> Markup {
     html! {
         div.overlay.closable {
             div.dialog#edit-dn-dialog {
@@ -149,6 +389,7 @@ fn edit_display_name_dialog() -> Markup {
                         input type = "password" minlength = "10" required = "";
                         p.error {}
                     }
+                    (second_factor_input("auth-dn-2fa", two_factor_enabled))
                     input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Edit";
                 }
             }
@@ -156,7 +397,7 @@ fn edit_display_name_dialog() -> Markup {
     }
 }
 
-fn edit_youtube_link_dialog() -> Markup {
+fn edit_youtube_link_dialog(two_factor_enabled: bool) -> Markup {
     html! {
         div.overlay.closable {
             div.dialog#edit-yt-dialog {
@@ -180,6 +421,39 @@ fn edit_youtube_link_dialog() -> Markup {
                         input type = "password" minlength = "10" required = "";
                         p.error {}
                     }
+                    (second_factor_input("auth-yt-2fa", two_factor_enabled))
+                    input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Edit";
+                }
+            }
+        }
+    }
+}
+
+fn edit_email_dialog(two_factor_enabled: bool) -> Markup {
+    html! {
+        div.overlay.closable {
+            div.dialog#edit-email-dialog {
+                span.plus.cross.hover {}
+                h2.underlined.pad {
+                    "Edit Recovery Email:"
+                }
+                p {
+                    "To make profile related edits, re-entering your password below is required. Leave the address empty to remove it."
+                }
+                form.flex.col novalidate = "" {
+                    p.info-red.output {}
+                    p.info-green.output {}
+                    span.form-input#edit-email {
+                        label for = "email" {"New recovery email:"}
+                        input type = "email" name = "email";
+                        p.error {}
+                    }
+                    span.overlined.pad.form-input#auth-email {
+                        label {"Authenticate:"}
+                        input type = "password" minlength = "10" required = "";
+                        p.error {}
+                    }
+                    (second_factor_input("auth-email-2fa", two_factor_enabled))
                     input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Edit";
                 }
             }
@@ -187,7 +461,7 @@ fn edit_youtube_link_dialog() -> Markup {
     }
 }
 
-fn change_password_dialog() -> Markup {
+fn change_password_dialog(two_factor_enabled: bool) -> Markup {
     html! {
         div.overlay.closable {
             div.dialog#edit-pw-dialog {
@@ -216,6 +490,7 @@ fn change_password_dialog() -> Markup {
                         input type = "password" minlength = "10" required = "";
                         p.error {}
                     }
+                    (second_factor_input("auth-pw-2fa", two_factor_enabled))
                     input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Edit";
                 }
             }
@@ -223,7 +498,7 @@ fn change_password_dialog() -> Markup {
     }
 }
 
-fn delete_account_dialog() -> Markup {
+fn delete_account_dialog(two_factor_enabled: bool) -> Markup {
     html! {
         div.overlay.closable {
             div.dialog#delete-acc-dialog {
@@ -242,6 +517,7 @@ fn delete_account_dialog() -> Markup {
                         input type = "password" minlength = "10" required = "";
                         p.error {}
                     }
+                    (second_factor_input("auth-delete-2fa", two_factor_enabled))
                     input.button.red.hover type = "submit" style = "margin: 15px auto 0px;" value="Delete";
                 }
             }
@@ -84,6 +84,22 @@ fn record_manager(demons: &[OverviewDemon]) -> Markup {
                                 br;
                                 span#record-submitter {}
                             }
+                            span {
+                                b {
+                                    i.fa.fa-refresh.clickable#record-validation-refresh aria-hidden = "true" {} " Video Check:"
+                                }
+                                br;
+                                span#record-validation {}
+                            }
+                        }
+                        div.stats-container.flex.space {
+                            span {
+                                b {
+                                    i.fa.fa-lock.clickable#record-claim-toggle aria-hidden = "true" {} " Claimed By:"
+                                }
+                                br;
+                                span#record-claim {}
+                            }
                         }
                         span.button.red.hover#record-delete style = "margin: 15px auto 0px" {"Delete Record"};
                     }
@@ -128,6 +144,10 @@ fn manager_help() -> Markup {
                 b { "Note: " }
                 "If a player is banned, they cannot have accepted/submitted records on the list. All records marked as 'submitted' are deleted, all others are changed to 'rejected'"
             }
+            p {
+                b { "Note: " }
+                "Click the lock icon next to 'Claimed By' to claim a record while you are reviewing it. As long as your claim is active, nobody else can modify the record. Claims expire automatically, but please release them (by clicking the lock icon again) once you are done."
+            }
             p {
                 b { "Note: " }
                 "Banning a submitter will delete all their submissions that still have the status 'Submitted'. Records submitted by them that were already accepted/rejected will not be affected"
@@ -325,7 +345,7 @@ fn change_raw_dialog() -> Markup {
                     "Change raw footage:"
                 }
                 p style = "max-width: 400px"{
-                    "Change the raw footage for this record."
+                    "Change the raw footage for this record. Leave empty to remove the raw footage. Has to be a Google Drive, Mega or Dropbox link, or a direct link to a video file."
                 }
                 form.flex.col novalidate = "" {
                     p.info-red.output {}
@@ -26,8 +26,64 @@ pub(super) fn page() -> Markup {
                                 p {
                                     "Banning a submitter will delete all records they have submitted and which are still in the 'submitted' state. All submissions of their which are approved, rejected or under consideration are untouched. "
                                 }
+                                p {
+                                    "Submitters linked to an account belong to a user who submitted while logged in. Banning them bans the account from submitting records, no matter which IP address they use."
+                                }
                                 p.info-red.output style = "margin: 10px" {}
                                 p.info-green.output style = "margin: 10px" {}
+                                div.stats-container.flex.space {
+                                    span {
+                                        b {
+                                            "Submitted:"
+                                        }
+                                        br;
+                                        span#submitter-submitted {}
+                                    }
+                                    span {
+                                        b {
+                                            "Approved:"
+                                        }
+                                        br;
+                                        span#submitter-approved {}
+                                    }
+                                    span {
+                                        b {
+                                            "Rejected:"
+                                        }
+                                        br;
+                                        span#submitter-rejected {}
+                                    }
+                                    span {
+                                        b {
+                                            "Trust level:"
+                                        }
+                                        br;
+                                        span#submitter-trust {}
+                                    }
+                                }
+                                div.stats-container.flex.space {
+                                    span {
+                                        b {
+                                            "First seen:"
+                                        }
+                                        br;
+                                        span#submitter-first-seen {}
+                                    }
+                                    span {
+                                        b {
+                                            "Last seen:"
+                                        }
+                                        br;
+                                        span#submitter-last-seen {}
+                                    }
+                                    span {
+                                        b {
+                                            "Account:"
+                                        }
+                                        br;
+                                        span#submitter-member {}
+                                    }
+                                }
                                 div.stats-container.flex.space {
                                     span {
                                         b {
@@ -118,6 +118,7 @@ pub(super) fn page(is_admin: bool) -> Markup {
                                 div.flex.no-stretch {
                                     @if is_admin {
                                         input.button.red.hover#delete-user type = "button" style = "margin: 15px auto 0px;" value="Delete user";
+                                        input.button.blue.hover#issue-password-reset type = "button" style = "margin: 15px auto 0px;" value="Issue password reset";
                                     }
                                     input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Edit user";
                                 }
@@ -145,6 +146,26 @@ pub(super) fn page(is_admin: bool) -> Markup {
                         input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Find by ID";
                     }
                 }
+                @if is_admin {
+                    div.panel.fade {
+                        h2.underlined.pad {
+                            "Security policy"
+                        }
+                        p {
+                            "If enabled, members of the list team can only make use of their list team permissions once they've set up two-factor authentication for their account."
+                        }
+                        form.flex.col.pad#security-policy-form novalidate = "" {
+                            p.info-red.output {}
+                            p.info-green.output {}
+                            label.form-input.cb-container#policy-list-team-2fa for = "list_team_requires_2fa" {
+                                i {"Require two-factor authentication for the list team"}
+                                input type = "checkbox" name = "list_team_requires_2fa";
+                                span.checkmark {}
+                            }
+                            input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Save";
+                        }
+                    }
+                }
             }
         }
     }
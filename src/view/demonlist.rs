@@ -1,80 +1,32 @@
 pub use self::{
     demon_page::page,
-    overview::{index, overview_demons, OverviewDemon},
+    overview::{index, legacy, overview_demons, OverviewDemon},
 };
-use crate::{
-    config,
-    model::{demonlist::demon::Demon, nationality::Nationality},
+use crate::model::{
+    demonlist::{demon::Demon, section::ListSection},
+    nationality::Nationality,
 };
 use maud::{html, Markup, PreEscaped, Render};
 
 mod demon_page;
 mod overview;
 
-struct ListSection {
-    name: &'static str,
-    description: &'static str,
-    id: &'static str,
-    numbered: bool,
-}
-
-static MAIN_SECTION: ListSection = ListSection {
-    name: "Main List",
-    description: "The main list section, holding the top hardest challenges. Records here are given a large amounts of points.",
-    id: "mainlist",
-    numbered: true,
-};
-
-// UNUSED
-static EXTENDED_SECTION: ListSection = ListSection {
-    name: "Extended List",
-    description: "These are Challenges that dont qualify for the main section of the list, but are still of high relevance. Only 100% records \
-                  are accepted for these Challenges! Note that non-100% that were submitted/approved before a demon fell off the main list \
-                  will be retained",
-    id: "extended",
-    numbered: true,
-};
-
-static LEGACY_SECTION: ListSection = ListSection {
-    name: "Legacy List",
-    description: "These are the challenges that have fallen out of grace, and no longer are eligible to be given points.",
-    id: "legacy",
-    numbered: false,
-};
-
-fn dropdowns(all_demons: &[OverviewDemon], current: Option<&Demon>) -> Markup {
-    let (main, extended, legacy) = if all_demons.len() < config::list_size() as usize {;
-        (&all_demons[..], Default::default(), Default::default())
-    } else {
-        let (extended, legacy) = if all_demons.len() < config::extended_list_size() as usize {
-            (&all_demons[config::list_size() as usize..], Default::default())
-        } else {
-            (
-                &all_demons[config::list_size() as usize..config::extended_list_size() as usize],
-                &all_demons[config::extended_list_size() as usize..],
-            )
-        };
-
-        (&all_demons[..config::list_size() as usize], extended, legacy)
-    };
-
+fn dropdowns(sections: &[ListSection], all_demons: &[OverviewDemon], current: Option<&Demon>) -> Markup {
     html! {
         nav.flex.wrap.m-center.fade#lists style="text-align: center;" {
-            // The drop down for the main list:
-            (dropdown(&MAIN_SECTION, main, current))
-            // The drop down for the extended list:
-            /*(dropdown(&EXTENDED_SECTION, extended, current))*/ // later
-            // The drop down for the legacy list:
-            (dropdown(&LEGACY_SECTION, legacy, current))
+            @for section in sections {
+                (dropdown(section, all_demons.iter().filter(|demon| section.contains(demon.position)), current))
+            }
         }
     }
 }
 
-fn dropdown(section: &ListSection, demons: &[OverviewDemon], current: Option<&Demon>) -> Markup {
+fn dropdown<'a>(section: &ListSection, demons: impl Iterator<Item = &'a OverviewDemon>, current: Option<&Demon>) -> Markup {
+    let dropdown_id = format!("section-{}", section.id);
     let format = |demon: &OverviewDemon| -> Markup {
         html! {
             a href = {"/challenges/" (demon.position)} {
-                @if section.numbered {
+                @if !section.legacy {
                     {"#" (demon.position) " - " (demon.name)}
                     br ;
                     i {
@@ -94,11 +46,11 @@ fn dropdown(section: &ListSection, demons: &[OverviewDemon], current: Option<&De
 
     html! {
         div {
-            div.button.white.hover.no-shadow.js-toggle data-toggle-group="0" onclick={"javascript:void(DropDown.toggleDropDown('" (section.id) "'))"} {
+            div.button.white.hover.no-shadow.js-toggle data-toggle-group="0" onclick={"javascript:void(DropDown.toggleDropDown('" (dropdown_id) "'))"} {
                 (section.name)
             }
 
-            div.see-through.fade.dropdown#(section.id) {
+            div.see-through.fade.dropdown#(dropdown_id) {
                 div.search.js-search.seperated style = "margin: 10px" {
                     input placeholder = "Filter..." type = "text" {}
                 }
@@ -207,6 +159,16 @@ pub(super) fn submission_panel(demons: &[OverviewDemon]) -> Markup {
                         }
                         p.error {}
                     }
+                    h3 {
+                        "Progress:"
+                    }
+                    p {
+                        "The progress made as percentage. Has to meet the challenge's record requirement, which is 100% for most challenges."
+                    }
+                    span.form-input.flex.col#id_progress {
+                        input type = "number" name = "progress" required = "" min = "0" max = "100" value = "100";
+                        p.error {}
+                    }
                     h3 {
                         "Video: "
                     }
@@ -230,7 +192,7 @@ pub(super) fn submission_panel(demons: &[OverviewDemon]) -> Markup {
                         br {}
 
                         i { "Note: " }
-                        "Please pay attention to only submit well-formed URLs! Ideally, we would prefer for you to submit Google Drive, Mediafire, or Mega URLs."
+                        "Please upload your raw footage to Google Drive, Mega or Dropbox, or provide a direct link to the video file."
                     }
                     span.form-input.flex.col#id_raw_footage {
                         input type = "url" name = "raw_footage" required = "" placeholder = "e.g. 'https://drive.google.com/file/d/1SJxMqUuh0QgbilyrrDIFsQZQV3XhtXpK/view?usp=sharing'" ;
@@ -1,6 +1,5 @@
 use crate::{
-    config,
-    model::demonlist::demon::FullDemon,
+    model::demonlist::{demon::FullDemon, formula::ScoringFormula, section::ListSection},
     state::PointercrateState,
     video,
     view::{demonlist::overview::DemonlistOverview, Page},
@@ -24,6 +23,7 @@ pub struct Demonlist {
     data: FullDemon,
     movements: Vec<DemonMovement>,
     link_banned: bool,
+    formula: ScoringFormula,
 }
 
 #[get("/challenges/{position}/")]
@@ -31,6 +31,7 @@ pub async fn page(state: PointercrateState, position: Path<i16>) -> ViewResult<H
     let mut connection = state.connection().await?;
     let overview = DemonlistOverview::load(&mut connection).await?;
     let demon = FullDemon::by_position(position.into_inner(), &mut connection).await?;
+    let formula = ScoringFormula::active(&mut connection).await?;
     let link_banned = sqlx::query!(
         r#"SELECT link_banned AS "link_banned!: bool" FROM players WHERE id = $1"#,
         demon.demon.verifier.id
@@ -72,6 +73,7 @@ pub async fn page(state: PointercrateState, position: Path<i16>) -> ViewResult<H
             data: demon,
             movements,
             link_banned,
+            formula,
         }
         .render()
         .0,
@@ -79,14 +81,15 @@ pub async fn page(state: PointercrateState, position: Path<i16>) -> ViewResult<H
 }
 
 impl Demonlist {
+    fn section(&self) -> Option<&ListSection> {
+        ListSection::find(&self.overview.sections, self.data.demon.base.position)
+    }
+
     fn demon_panel(&self) -> Markup {
         let position = self.data.demon.base.position;
         let name = &self.data.demon.base.name;
 
-        //let score100 = self.data.demon.score(100);
-        let score_requirement = self.data.demon.score(position);
-        log::info!("{}", score_requirement);
-        log::info!("{:.2}", score_requirement);
+        let score100 = self.data.demon.score(&self.formula, 100) * self.section().map(|section| section.scoring_weight).unwrap_or(1.0);
 
         html! {
             section.panel.fade.js-scroll-anim data-anim = "fade" {
@@ -233,7 +236,7 @@ impl Demonlist {
                             }
                         }
                     }*/
-                    /*@if position <= config::extended_list_size() {
+                    /*@if !legacy {
                         span {
                             b {
                                 "Demonlist score (100%): "
@@ -242,13 +245,13 @@ impl Demonlist {
                             (format!("{:.2}", score100))
                         }
                     }*/
-                    @if position <= config::list_size(){
+                    @if score100 > 0f64 {
                         span {
                             b { 
                                 "Points Awarded"
                             }
                             br;
-                                (format!("{:.2}", score_requirement))
+                                (format!("{:.2}", score100))
                         }
                     }
 
@@ -276,24 +279,29 @@ impl Demonlist {
     }
 
     fn records_panel(&self) -> Markup {
-        let position = self.data.demon.base.position;
         let name = &self.data.demon.base.name;
+        let section = self.section();
+        let legacy = section.map(|section| section.legacy).unwrap_or(false);
 
         html! {
-            @if !self.data.records.is_empty() || position <= config::extended_list_size() {
+            @if !self.data.records.is_empty() || !legacy {
                 section.records.panel.fade.js-scroll-anim data-anim = "fade" {
                     div.underlined.pad {
                         h2 {
                             "Records"
                         }
-                        @if position <= config::list_size() {
-                            h3 {
-                                (self.data.demon.requirement) "% or better required to qualify"
-                            }
-                        }
-                        @else if position <= config::extended_list_size() {
-                            h3 {
-                                "100% required to qualify"
+                        @if !legacy {
+                            @let requirement = self.data.demon.effective_requirement();
+                            @match section.and_then(|section| section.min_progress) {
+                                Some(min_progress) if min_progress > requirement => h3 {
+                                    (min_progress) "% required to qualify"
+                                },
+                                _ if requirement == 100 => h3 {
+                                    "100% required to qualify"
+                                },
+                                _ => h3 {
+                                    (requirement) "% or better required to qualify"
+                                }
                             }
                         }
                         @if !self.data.records.is_empty() {
@@ -306,7 +314,7 @@ impl Demonlist {
                     }
                     @if self.data.records.is_empty() {
                         h3 {
-                            @if position > config::extended_list_size() {
+                            @if legacy {
                                 "No records!"
                             }
                             @else {
@@ -383,7 +391,7 @@ impl Page for Demonlist {
     }
 
     fn body(&self) -> Markup {
-        let dropdowns = super::dropdowns(&self.overview.demon_overview, Some(&self.data.demon));
+        let dropdowns = super::dropdowns(&self.overview.sections, &self.overview.demon_overview, Some(&self.data.demon));
 
         let mut labels = Vec::new();
 
@@ -447,9 +455,8 @@ impl Page for Demonlist {
             html! {
                 (PreEscaped(format!("
                     <script>
-                        window.list_length = {0};
-                        window.extended_list_length = {1}
-                    </script>", config::list_size(), config::extended_list_size()
+                        window.list_sections = {0};
+                    </script>", serde_json::to_string(&self.overview.sections).unwrap_or_else(|_| "[]".to_string())
                 )))
             },
         ]
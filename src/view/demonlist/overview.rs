@@ -1,6 +1,5 @@
 use crate::{
-    config,
-    model::{nationality::Nationality, user::User},
+    model::{demonlist::section::ListSection, nationality::Nationality, user::User},
     permissions::Permissions,
     state::PointercrateState,
     video,
@@ -30,6 +29,7 @@ pub struct DemonlistOverview {
     pub mods: Vec<User>,
     pub helpers: Vec<User>,
     pub nations: Vec<Nationality>,
+    pub sections: Vec<ListSection>,
 }
 
 pub async fn overview_demons(connection: &mut PgConnection) -> Result<Vec<OverviewDemon>> {
@@ -107,6 +107,7 @@ impl DemonlistOverview {
 
         let nations = Nationality::all(connection).await?;
         let demon_overview = overview_demons(connection).await?;
+        let sections = ListSection::all(connection).await?;
 
         Ok(DemonlistOverview {
             admins,
@@ -114,6 +115,7 @@ impl DemonlistOverview {
             helpers,
             nations,
             demon_overview,
+            sections,
         })
     }
 }
@@ -127,6 +129,20 @@ pub async fn index(state: PointercrateState) -> ViewResult<HttpResponse> {
         .body(DemonlistOverview::load(&mut connection).await?.render().0))
 }
 
+/// Redirects to the first demon of the legacy section, so that links to the legacy list don't need
+/// to know where it starts
+#[get("/challenges/legacy/")]
+pub async fn legacy(state: PointercrateState) -> ViewResult<HttpResponse> {
+    let mut connection = state.connection().await?;
+
+    let location = match ListSection::all(&mut connection).await?.into_iter().find(|section| section.legacy) {
+        Some(section) => format!("/challenges/{}/", section.first_position),
+        None => "/challenges/".to_string(),
+    };
+
+    Ok(HttpResponse::Found().header("Location", location).finish())
+}
+
 impl Page for DemonlistOverview {
     fn title(&self) -> String {
         "The Challenge List".to_string()
@@ -145,7 +161,7 @@ impl Page for DemonlistOverview {
     }
 
     fn body(&self) -> Markup {
-        let dropdowns = super::dropdowns(&self.demon_overview, None);
+        let dropdowns = super::dropdowns(&self.sections, &self.demon_overview, None);
 
         html! {
             (dropdowns)
@@ -157,11 +173,11 @@ impl Page for DemonlistOverview {
                     (super::stats_viewer(&self.nations))
 
                     @for demon in &self.demon_overview {
-                        @if demon.position <= config::extended_list_size() {
+                        @if !ListSection::find(&self.sections, demon.position).map(|section| section.legacy).unwrap_or(false) {
                             section.panel.fade style="overflow:hidden" {
                                 div.flex style = "align-items: center" {
-                                    @if let Some(ref video) = demon.video {
-                                        div.thumb."ratio-16-9"."js-delay-css" style = "position: relative" data-property = "background-image" data-property-value = {"url('" (video::thumbnail(video)) "')"} {
+                                    @if let (Some(video), Some(thumbnail)) = (&demon.video, demon.video.as_deref().and_then(video::thumbnail)) {
+                                        div.thumb."ratio-16-9"."js-delay-css" style = "position: relative" data-property = "background-image" data-property-value = {"url('" (thumbnail) "')"} {
                                             a.play href = (video) {}
                                         }
                                         div style = "padding-left: 15px" {
@@ -250,9 +266,8 @@ impl Page for DemonlistOverview {
             html! {
                 (PreEscaped(format!("
                     <script>
-                        window.list_length = {0};
-                        window.extended_list_length = {1}
-                    </script>", config::list_size(), config::extended_list_size())
+                        window.list_sections = {0};
+                    </script>", serde_json::to_string(&self.sections).unwrap_or_else(|_| "[]".to_string()))
                 ))
             },
         ]
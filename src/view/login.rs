@@ -1,75 +1,129 @@
 use super::Page;
 use crate::{
+    error::{JsonError, PointercrateError},
     extractor::{
         auth::{BasicAuth, TokenAuth},
         ip::Ip,
+        user_agent::UserAgent,
     },
-    model::user::{AuthenticatedUser, Registration},
+    model::user::{AuthenticatedUser, Registration, SessionTokens},
     ratelimit::RatelimitScope,
     state::PointercrateState,
     ApiResult,
 };
-use actix_web::{cookie::SameSite, http::Cookie, web::Json, HttpResponse};
+use actix_web::{cookie::SameSite, dev::HttpResponseBuilder, http::Cookie, web::Json, HttpMessage, HttpRequest, HttpResponse};
 use actix_web_codegen::{get, post};
 use maud::{html, Markup};
 
 #[derive(Debug, Copy, Clone)]
-pub struct LoginPage;
+pub struct LoginPage {
+    /// Whether users can request password reset tokens via email
+    email_reset: bool,
+}
 
 #[get("/login/")]
-pub fn index(user: ApiResult<TokenAuth>) -> HttpResponse {
+pub fn index(user: ApiResult<TokenAuth>, state: PointercrateState) -> HttpResponse {
     match user {
         Ok(user) => HttpResponse::Found().header("Location", "/account/").finish(),
         _ =>
-            HttpResponse::Ok()
-                .content_type("text/html; charset=utf-8")
-                .body(LoginPage.render().0),
+            HttpResponse::Ok().content_type("text/html; charset=utf-8").body(
+                LoginPage {
+                    email_reset: state.mailer.is_some(),
+                }
+                .render()
+                .0,
+            ),
     }
 }
 
-/// Alternate login handler for the web interface. Unlike the one in the api, it doesn't return your
-/// token, but puts it into a secure, http-only cookie
-#[post("/login/")]
-pub async fn post(Ip(ip): Ip, auth: ApiResult<BasicAuth>, state: PointercrateState) -> ApiResult<HttpResponse> {
-    state.ratelimits.check(RatelimitScope::Login, ip)?;
-
-    // we have to explicitly take the Result here and transform it into a ViewResult so that we get a
-    // Html error page >.>
-    let BasicAuth(user) = auth?;
-
-    let mut cookie = Cookie::build("access_token", user.generate_token(&state.secret))
+/// Puts the given session tokens into secure, http-only cookies
+pub(super) fn set_session_cookies(response: &mut HttpResponseBuilder, tokens: SessionTokens) -> &mut HttpResponseBuilder {
+    let mut access_token = Cookie::build("access_token", tokens.token)
         .http_only(true)
         .same_site(SameSite::Strict)
         .path("/");
 
+    // The server decides when the session expires, the cookie is only needed for as long as it lives
+    let mut refresh_token = Cookie::build("refresh_token", tokens.refresh_token)
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .path("/")
+        .permanent();
+
     // allow cookies of HTTP if we're in a debug build, because I don't have a ssl cert for
     // 127.0.0.1 on my laptop smh
     if !cfg!(debug_assertions) {
-        cookie = cookie.secure(true)
+        access_token = access_token.secure(true);
+        refresh_token = refresh_token.secure(true);
     }
 
-    Ok(HttpResponse::NoContent().cookie(cookie.finish()).finish())
+    response.cookie(access_token.finish()).cookie(refresh_token.finish())
+}
+
+/// Alternate login handler for the web interface. Unlike the one in the api, it doesn't return your
+/// tokens, but puts them into secure, http-only cookies
+#[post("/login/")]
+pub async fn post(
+    Ip(ip): Ip, UserAgent(user_agent): UserAgent, auth: ApiResult<BasicAuth>, state: PointercrateState,
+) -> ApiResult<HttpResponse> {
+    // Being asked for a second factor means that the password was correct. This shouldn't count
+    // towards the ratelimit, as otherwise logging in with two-factor authentication enabled would
+    // use up two attempts
+    if let Err(JsonError(PointercrateError::TwoFactorRequired)) = auth {
+        return Err(PointercrateError::TwoFactorRequired.into())
+    }
+
+    let ratelimits = state.ratelimits.prepare(ip);
+
+    ratelimits.check(RatelimitScope::Login).await?;
+
+    // we have to explicitly take the Result here and transform it into a ViewResult so that we get a
+    // Html error page >.>
+    let BasicAuth(mut user) = auth?;
+
+    let tokens = user
+        .create_session(ip, user_agent.as_deref(), &state.secret, &mut *state.connection().await?)
+        .await?;
+
+    Ok(set_session_cookies(ratelimits.headers(&mut HttpResponse::NoContent()), tokens).finish())
 }
 
-/// Alternate register handler for the web interface. Unlike the one in the api, it doesn't return
-/// your token, but puts it into a secure, http-only cookie
+/// Alternate refresh handler for the web interface, which takes the refresh token from, and puts
+/// the new tokens into, cookies
+#[post("/login/refresh/")]
+pub async fn refresh(
+    request: HttpRequest, Ip(ip): Ip, UserAgent(user_agent): UserAgent, state: PointercrateState,
+) -> ApiResult<HttpResponse> {
+    let refresh_token = request.cookie("refresh_token").ok_or(PointercrateError::Unauthorized)?;
+
+    let (_, tokens) = AuthenticatedUser::refresh_session(
+        refresh_token.value(),
+        ip,
+        user_agent.as_deref(),
+        &state.secret,
+        &mut *state.connection().await?,
+    )
+    .await?;
+
+    Ok(set_session_cookies(&mut HttpResponse::NoContent(), tokens).finish())
+}
+
+/// Alternate register handler for the web interface. Unlike the one in the api, it logs you in
+/// right away, putting the tokens of your new session into secure, http-only cookies
 #[post("/register/")]
-pub async fn register(Ip(ip): Ip, body: Json<Registration>, state: PointercrateState) -> ApiResult<HttpResponse> {
-    let mut connection = state.connection().await?;
-    let user = AuthenticatedUser::register(body.into_inner(), &mut connection, Some(state.ratelimits.prepare(ip))).await?;
+pub async fn register(
+    Ip(ip): Ip, UserAgent(user_agent): UserAgent, body: Json<Registration>, state: PointercrateState,
+) -> ApiResult<HttpResponse> {
+    let ratelimits = state.ratelimits.prepare(ip);
 
-    let mut cookie = Cookie::build("access_token", user.generate_token(&state.secret))
-        .http_only(true)
-        .same_site(SameSite::Strict)
-        .path("/");
+    let mut connection = state.connection().await?;
+    let mut user = AuthenticatedUser::register(body.into_inner(), &mut connection, Some(&ratelimits)).await?;
 
-    // allow cookies of HTTP if we're in a debug build, because I don't have a ssl cert for
-    // 127.0.0.1 on my laptop smh
-    if !cfg!(debug_assertions) {
-        cookie = cookie.secure(true)
-    }
+    let tokens = user
+        .create_session(ip, user_agent.as_deref(), &state.secret, &mut connection)
+        .await?;
 
-    Ok(HttpResponse::Created().cookie(cookie.finish()).finish())
+    Ok(set_session_cookies(ratelimits.headers(&mut HttpResponse::Created()), tokens).finish())
 }
 
 impl Page for LoginPage {
@@ -116,8 +170,14 @@ impl Page for LoginPage {
                                 input required = "" type = "password" name = "password" minlength = "10";
                                 p.error {}
                             }
+                            span.form-input#login-totp style = "display: none" {
+                                label for = "totp" {"Two-factor code:"}
+                                input type = "text" name = "totp" autocomplete = "one-time-code";
+                                p.error {}
+                            }
                             div.grow {}
                             input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value="Log in";
+                            a.link#forgot-password style = "margin: 10px auto 0px;" {"Forgot your password?"}
                         }
                     }
                     div.flex.col {
@@ -147,6 +207,43 @@ impl Page for LoginPage {
                         }
                     }
                 }
+                div.flex.col#reset-password style = "display: none" {
+                    h2 {"Reset password"}
+                    p {
+                        "If you forgot your password, you need a password reset token to choose a new one. Ask a pointercrate administrator to issue one for you."
+                        @if self.email_reset {
+                            " If you set a recovery email address for your account, you can also have a token sent to it by entering your username below and clicking 'Email me a reset token'."
+                        }
+                    }
+                    form.flex.col#reset-form novalidate = "" {
+                        p.info-red.output {}
+                        p.info-green.output {}
+                        span.form-input#reset-username {
+                            label for = "username" {"Username:"}
+                            input required = "" type = "text" name = "username" minlength = "3";
+                            p.error {}
+                        }
+                        @if self.email_reset {
+                            input.button.blue.hover#request-reset type = "button" style = "margin: 0px auto 15px;" value = "Email me a reset token";
+                        }
+                        span.form-input#reset-token {
+                            label for = "token" {"Reset token:"}
+                            input required = "" type = "text" name = "token" autocomplete = "off";
+                            p.error {}
+                        }
+                        span.form-input#reset-new-password {
+                            label for = "password" {"New password:"}
+                            input required = "" type = "password" name = "password" minlength = "10";
+                            p.error {}
+                        }
+                        span.form-input#reset-new-password-repeat {
+                            label for = "password2" {"Repeat new password:"}
+                            input required = "" type = "password" minlength = "10";
+                            p.error {}
+                        }
+                        input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value = "Reset password";
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,142 @@
+import {
+  Dropdown,
+  Form,
+  Paginator,
+  Viewer,
+} from "../modules/form.mjs";
+
+export let auditLogViewer;
+
+function generateEntry(entry) {
+  let li = document.createElement("li");
+  let b = document.createElement("b");
+  let i = document.createElement("i");
+
+  li.className = "white";
+  li.dataset.id = entry.audit_id;
+
+  switch (entry.action) {
+    case "addition":
+      li.style.backgroundColor = "rgba( 198, 255, 161, .3)";
+      break;
+    case "deletion":
+      li.style.backgroundColor = "rgba(255, 161, 174, .3)";
+      break;
+    default:
+      break;
+  }
+
+  b.innerText = entry.model.replace("_", " ") + " #" + entry.target + " " + entry.action;
+  i.innerText = "by " + (entry.user.name || "user #" + entry.user.id) + " at " + entry.time;
+
+  li.appendChild(b);
+  li.appendChild(document.createElement("br"));
+  li.appendChild(i);
+
+  // Audit log entries have no endpoint of their own, so we remember the data to display on selection
+  li.entry = entry;
+
+  return li;
+}
+
+class AuditLogViewer extends Paginator {
+  constructor() {
+    super("audit-pagination", {}, generateEntry);
+
+    this.output = new Viewer(
+      this.html.parentNode.getElementsByClassName("viewer-content")[0],
+      this
+    );
+
+    this._id = document.getElementById("audit-entry-id");
+    this._object = document.getElementById("audit-entry-object");
+    this._action = document.getElementById("audit-entry-action");
+    this._user = document.getElementById("audit-entry-user");
+    this._time = document.getElementById("audit-entry-time");
+    this._details = document.getElementById("audit-entry-details");
+  }
+
+  onSelect(selected) {
+    this.currentlySelected = selected;
+    this.currentObject = selected.entry;
+
+    for (let listener of this.selectionListeners) {
+      listener(this.currentObject);
+    }
+
+    this._id.innerText = this.currentObject.audit_id;
+    this._object.innerText = this.currentObject.model.replace("_", " ") + " #" + this.currentObject.target;
+    this._action.innerText = this.currentObject.action;
+    this._user.innerText = (this.currentObject.user.name || "Unknown") + " (ID: " + this.currentObject.user.id + ")";
+    this._time.innerText = this.currentObject.time;
+
+    while (this._details.firstChild) {
+      this._details.removeChild(this._details.firstChild);
+    }
+
+    let details = this.currentObject.details || {};
+
+    if (Object.keys(details).length === 0) {
+      let li = document.createElement("li");
+      li.innerText = "-";
+      this._details.appendChild(li);
+    }
+
+    for (let field of Object.keys(details)) {
+      let li = document.createElement("li");
+      let b = document.createElement("b");
+
+      b.innerText = field + ": ";
+
+      li.appendChild(b);
+      li.appendChild(document.createTextNode(JSON.stringify(details[field])));
+
+      this._details.appendChild(li);
+    }
+  }
+}
+
+// <input type="datetime-local"> omits the seconds, which the API requires
+function toApiTime(value) {
+  if (value === null) return undefined;
+  if (value.length === 16) return value + ":00";
+  return value;
+}
+
+function setupFilterForm() {
+  let filterForm = new Form(document.getElementById("audit-filter-form"));
+  let user = filterForm.input("audit-filter-user");
+  let after = filterForm.input("audit-filter-after");
+  let before = filterForm.input("audit-filter-before");
+
+  filterForm.onSubmit(function () {
+    let queryData = Object.assign({}, auditLogViewer.queryData);
+
+    queryData.user = user.value === null ? undefined : user.value;
+    queryData.after_time = toApiTime(after.value);
+    queryData.before_time = toApiTime(before.value);
+
+    for (let key of Object.keys(queryData)) {
+      if (queryData[key] === undefined) delete queryData[key];
+    }
+
+    auditLogViewer.setQueryData(queryData);
+  });
+}
+
+export function initialize() {
+  auditLogViewer = new AuditLogViewer();
+
+  new Dropdown(
+    document
+      .getElementById("audit-model-filter-panel")
+      .getElementsByClassName("dropdown-menu")[0]
+  ).addEventListener((selected) => {
+    if (selected === "All") auditLogViewer.updateQueryData("model", undefined);
+    else auditLogViewer.updateQueryData("model", selected);
+  });
+
+  setupFilterForm();
+
+  auditLogViewer.initialize();
+}
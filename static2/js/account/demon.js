@@ -44,6 +44,7 @@ export class DemonManager extends FilteredPaginator {
 
     this._position = document.getElementById("demon-position");
     this._fps = document.getElementById("demon-fps");
+    this._requirement = document.getElementById("demon-requirement");
 
     this._verifier = document.getElementById("demon-verifier");
     this._publisher = document.getElementById("demon-publisher");
@@ -91,6 +92,25 @@ export class DemonManager extends FilteredPaginator {
 
     positionForm.addErrorOverride(42213, "demon-position-edit");
 
+    let requirementForm = setupFormDialogEditor(
+      new PaginatorEditorBackend(this, csrfToken, true),
+      "demon-requirement-dialog",
+      "demon-requirement-pen",
+      this.output
+    );
+
+    requirementForm.addValidators({
+      "demon-requirement-edit": {
+        "Record requirement cannot be negative": rangeUnderflow,
+        "Record requirement cannot be larger than 100%": rangeOverflow,
+        "Record requirement must be a valid integer": badInput,
+        "Record requirement mustn't be a decimal": stepMismatch,
+        "Please enter a requirement": valueMissing,
+      },
+    });
+
+    requirementForm.addErrorOverride(42212, "demon-requirement-edit");
+
     let nameForm = setupFormDialogEditor(
       new PaginatorEditorBackend(this, csrfToken, true),
       "demon-name-dialog",
@@ -113,6 +133,14 @@ export class DemonManager extends FilteredPaginator {
       { true: true, false: false }
     );
 
+    this._completionOnly = setupDropdownEditor(
+      new PaginatorEditorBackend(this, csrfToken, true),
+      "edit-demon-completion-only",
+      "completion_only",
+      this.output,
+      { true: true, false: false }
+    );
+
     setupEditorDialog(new PlayerSelectionDialog("demon-verifier-dialog"), "demon-verifier-pen", new PaginatorEditorBackend(this, csrfToken, true), this.output, data => ({verifier: data.player}));
 
     setupEditorDialog(new PlayerSelectionDialog("demon-publisher-dialog"), "demon-publisher-pen", new PaginatorEditorBackend(this, csrfToken, true), this.output, data => ({publisher: data.player}));
@@ -129,6 +157,7 @@ export class DemonManager extends FilteredPaginator {
     this._name.innerText = this.currentObject.name;
     this._position.innerText = this.currentObject.position;
     this._fps.innerText = this.currentObject.fps;
+    this._requirement.innerText = this.currentObject.requirement + "%";
 
     var embeddedVideo = embedVideo(this.currentObject.video);
 
@@ -160,6 +189,7 @@ export class DemonManager extends FilteredPaginator {
 
       
     this._hidden.selectSilently(this.currentObject.hidden.toString());
+    this._completionOnly.selectSilently(this.currentObject.completion_only.toString());
 
     while (this._creators.lastChild) {
       this._creators.removeChild(this._creators.lastChild);
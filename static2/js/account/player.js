@@ -8,6 +8,10 @@ import {
   PaginatorEditorBackend,
   setupDropdownEditor,
   Viewer,
+  Output,
+  get,
+  patch,
+  del,
 } from "../modules/form.mjs";
 import { recordManager, initialize as initRecords } from "./records.js";
 
@@ -24,6 +28,7 @@ class PlayerManager extends FilteredPaginator {
 
     this._id = document.getElementById("player-player-id");
     this._name = document.getElementById("player-player-name");
+    this._claimed = document.getElementById("player-claimed");
 
     this._banned = setupDropdownEditor(
       new PaginatorEditorBackend(this, csrfToken, true),
@@ -53,6 +58,7 @@ class PlayerManager extends FilteredPaginator {
 
     this._id.innerText = this.currentObject.id;
     this._name.innerText = this.currentObject.name;
+    this._claimed.innerText = this.currentObject.claimed ? "yes" : "no";
 
     this._banned.selectSilently(this.currentObject.banned.toString());
 
@@ -95,8 +101,90 @@ function setupPlayerSearchPlayerIdForm() {
   });
 }
 
+function setupPendingClaims(csrfToken) {
+  let output = new Output(document.getElementById("player-claims-panel"));
+  let claimList = document.getElementById("player-claims");
+
+  // Modifying a claim requires its current etag, which the listing doesn't provide
+  function withEtag(claim, action) {
+    return get("/api/v1/players/claims/" + claim.id + "/").then((response) =>
+      action(response.headers["etag"])
+    );
+  }
+
+  function renderClaim(claim) {
+    let li = document.createElement("li");
+    li.classList.add("overlined", "pad");
+
+    let deny = document.createElement("i");
+    deny.classList.add("fa", "fa-times", "clickable");
+    deny.style.float = "right";
+    deny.title = "Deny";
+    deny.addEventListener("click", () => {
+      withEtag(claim, (etag) =>
+        del("/api/v1/players/claims/" + claim.id + "/", {
+          "X-CSRF-TOKEN": csrfToken,
+          "If-Match": etag,
+        })
+      )
+        .then(() => claimList.removeChild(li))
+        .catch(displayError(output));
+    });
+
+    let verify = document.createElement("i");
+    verify.classList.add("fa", "fa-check", "clickable");
+    verify.style.float = "right";
+    verify.style.marginRight = "10px";
+    verify.title = "Verify";
+    verify.addEventListener("click", () => {
+      withEtag(claim, (etag) =>
+        patch(
+          "/api/v1/players/claims/" + claim.id + "/",
+          { "X-CSRF-TOKEN": csrfToken, "If-Match": etag },
+          { verified: true }
+        )
+      )
+        .then(() => claimList.removeChild(li))
+        .catch(displayError(output));
+    });
+
+    let title = document.createElement("b");
+    title.innerText = claim.player.name + " (Player #" + claim.player.id + ")";
+
+    let details = document.createElement("p");
+    details.style.margin = "5px 0 0";
+    details.innerText =
+      "Claimed by " +
+      claim.member.name +
+      " (User #" +
+      claim.member.id +
+      ")\nClaimed at: " +
+      claim.created_at;
+
+    li.appendChild(deny);
+    li.appendChild(verify);
+    li.appendChild(title);
+    li.appendChild(details);
+
+    claimList.appendChild(li);
+  }
+
+  get("/api/v1/players/claims/?verified=false&limit=100")
+    .then((response) => {
+      if (response.data.length == 0) {
+        let li = document.createElement("li");
+        li.innerText = "There are no pending claims";
+        claimList.appendChild(li);
+      }
+
+      response.data.forEach(renderClaim);
+    })
+    .catch(displayError(output));
+}
+
 export function initialize(csrfToken, tabber) {
   setupPlayerSearchPlayerIdForm();
+  setupPendingClaims(csrfToken);
 
   playerManager = new PlayerManager(csrfToken);
   playerManager.initialize();
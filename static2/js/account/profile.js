@@ -8,11 +8,28 @@ import {
   Output,
   typeMismatch,
   del,
+  get,
+  patch,
   displayError,
 } from "../modules/form.mjs";
 import { EditorBackend } from "../modules/form.mjs";
 import { setupFormDialogEditor } from "../modules/form.mjs";
 
+// Builds the headers for authenticating via basic auth with the given password. If the user has
+// two-factor authentication enabled, the code entered into the input with the given id is sent along
+function basicAuth(password, secondFactorId) {
+  let headers = {
+    Authorization: "Basic " + btoa(window.username + ":" + password),
+  };
+  let secondFactor = document.querySelector("#" + secondFactorId + " input");
+
+  if (secondFactor) {
+    headers["X-TOTP-Code"] = secondFactor.value;
+  }
+
+  return headers;
+}
+
 function setupGetAccessToken() {
   var accessTokenArea = document.getElementById("token-area");
   var accessToken = document.getElementById("access-token");
@@ -40,10 +57,7 @@ function setupGetAccessToken() {
   });
 
   loginForm.onSubmit(function () {
-    post("/api/v1/auth/", {
-      Authorization:
-        "Basic " + btoa(window.username + ":" + loginPassword.value),
-    })
+    post("/api/v1/auth/", basicAuth(loginPassword.value, "login-2fa"))
       .then((response) => {
         loginPassword.value = "";
         accessToken.innerHTML = response.data.token;
@@ -53,6 +67,8 @@ function setupGetAccessToken() {
       .catch((response) => {
         if (response.data.code == 40100) {
           loginPassword.errorText = "Invalid credentials";
+        } else if (response.data.code == 40102) {
+          loginForm.input("login-2fa").errorText = response.data.message;
         } else {
           loginForm.setError(response.data.message);
         }
@@ -61,10 +77,11 @@ function setupGetAccessToken() {
 }
 
 class ProfileEditorBackend extends EditorBackend {
-  constructor(passwordInput) {
+  constructor(passwordInput, secondFactorId) {
     super();
 
     this._pw = passwordInput;
+    this._secondFactorId = secondFactorId;
     this._displayName = document.getElementById("profile-display-name");
     this._youtube = document.getElementById("profile-youtube-channel");
   }
@@ -76,7 +93,7 @@ class ProfileEditorBackend extends EditorBackend {
   headers() {
     return {
       "If-Match": window.etag,
-      Authorization: "Basic " + btoa(window.username + ":" + this._pw.value),
+      ...basicAuth(this._pw.value, this._secondFactorId),
     };
   }
 
@@ -101,10 +118,17 @@ class ProfileEditorBackend extends EditorBackend {
   }
 }
 
+// The recovery email isn't part of the user object returned by the server, so just reload the page to display the new one
+class EmailEditorBackend extends ProfileEditorBackend {
+  onSuccess(response) {
+    window.location.reload();
+  }
+}
+
 function setupEditAccount() {
   let output = new Output(document.getElementById("things"));
   let editDisplayNameForm = setupFormDialogEditor(
-    new ProfileEditorBackend(document.getElementById("auth-dn")), // not pretty, but oh well
+    new ProfileEditorBackend(document.getElementById("auth-dn"), "auth-dn-2fa"), // not pretty, but oh well
     "edit-dn-dialog",
     "display-name-pen",
     output
@@ -118,9 +142,10 @@ function setupEditAccount() {
   });
 
   editDisplayNameForm.addErrorOverride(40100, "auth-dn");
+  editDisplayNameForm.addErrorOverride(40102, "auth-dn-2fa");
 
   let editYoutubeForm = setupFormDialogEditor(
-    new ProfileEditorBackend(document.getElementById("auth-yt")), // not pretty, but oh well
+    new ProfileEditorBackend(document.getElementById("auth-yt"), "auth-yt-2fa"), // not pretty, but oh well
     "edit-yt-dialog",
     "youtube-pen",
     output
@@ -137,10 +162,35 @@ function setupEditAccount() {
   });
 
   editYoutubeForm.addErrorOverride(40100, "auth-yt");
+  editYoutubeForm.addErrorOverride(40102, "auth-yt-2fa");
   editYoutubeForm.addErrorOverride(42225, "edit-yt");
 
+  // Only rendered if password resets via email are enabled
+  if (document.getElementById("edit-email-dialog")) {
+    let editEmailForm = setupFormDialogEditor(
+      new EmailEditorBackend(document.getElementById("auth-email"), "auth-email-2fa"),
+      "edit-email-dialog",
+      "email-pen",
+      output
+    );
+
+    editEmailForm.addValidators({
+      "edit-email": {
+        "Please enter a valid email address": typeMismatch,
+      },
+      "auth-email": {
+        "Password required": valueMissing,
+        "Password too short. It needs to be at least 10 characters long.": tooShort,
+      },
+    });
+
+    editEmailForm.addErrorOverride(40100, "auth-email");
+    editEmailForm.addErrorOverride(40102, "auth-email-2fa");
+    editEmailForm.addErrorOverride(42235, "edit-email");
+  }
+
   let changePasswordForm = setupFormDialogEditor(
-    new ProfileEditorBackend(document.getElementById("auth-pw")), // not pretty, but oh well
+    new ProfileEditorBackend(document.getElementById("auth-pw"), "auth-pw-2fa"), // not pretty, but oh well
     "edit-pw-dialog",
     "change-password",
     output
@@ -163,6 +213,9 @@ function setupEditAccount() {
   });
 
   changePasswordForm.addErrorOverride(40100, "auth-pw");
+  changePasswordForm.addErrorOverride(40102, "auth-pw-2fa");
+  changePasswordForm.addErrorOverride(42204, "edit-pw");
+  changePasswordForm.addErrorOverride(42236, "edit-pw");
 
   var deleteAccountDialog = document.getElementById("delete-acc-dialog");
   var deleteAccountForm = new Form(
@@ -179,11 +232,12 @@ function setupEditAccount() {
   });
 
   deleteAccountForm.addErrorOverride(40100, "auth-delete");
+  deleteAccountForm.addErrorOverride(40102, "auth-delete-2fa");
 
   deleteAccountForm.onSubmit(() => {
     del("/api/v1/auth/me/", {
       "If-Match": window.etag,
-      Authorization: "Basic " + btoa(window.username + ":" + deleteAuth.value),
+      ...basicAuth(deleteAuth.value, "auth-delete-2fa"),
     })
       .then(() => window.location.reload())
       .catch(displayError(deleteAccountForm));
@@ -215,19 +269,491 @@ function setupInvalidateToken() {
   });
 
   invalidateForm.addErrorOverride(40100, "invalidate-auth-password");
+  invalidateForm.addErrorOverride(40102, "invalidate-auth-2fa");
 
   invalidateForm.onSubmit(function () {
-    post("/api/v1/auth/invalidate/", {
-      Authorization:
-        "Basic " + btoa(window.username + ":" + invalidatePassword.value),
-    })
+    post(
+      "/api/v1/auth/invalidate/",
+      basicAuth(invalidatePassword.value, "invalidate-auth-2fa")
+    )
       .then(() => window.location.reload())
       .catch(displayError(invalidateForm));
   });
 }
 
-export function initialize() {
+const SCOPE_NAMES = {
+  0x1: "Extended Access",
+  0x2: "List Helper",
+  0x4: "List Moderator",
+  0x8: "List Administrator",
+  0x2000: "Moderator",
+  0x4000: "Administrator",
+};
+
+function scopesToString(scopes) {
+  let names = Object.keys(SCOPE_NAMES)
+    .filter((bit) => (scopes & bit) == bit)
+    .map((bit) => SCOPE_NAMES[bit]);
+
+  return names.length ? names.join(", ") : "None";
+}
+
+function setupAccessTokens(csrfToken) {
+  let panel = document.getElementById("access-tokens-panel");
+  let output = new Output(panel);
+  let tokenList = document.getElementById("access-tokens");
+  let createButton = document.getElementById("create-access-token");
+  let newTokenArea = document.getElementById("new-access-token-area");
+  let newToken = document.getElementById("new-access-token");
+
+  let htmlTokenForm = document.getElementById("access-token-form");
+  let tokenForm = new Form(htmlTokenForm);
+
+  let tokenName = tokenForm.input("access-token-name");
+  let tokenExpiry = tokenForm.input("access-token-expiry");
+  let tokenPassword = tokenForm.input("access-token-password");
+
+  tokenPassword.clearOnInvalid = true;
+  tokenForm.addValidators({
+    "access-token-name": {
+      "Please give your token a name": valueMissing,
+    },
+    "access-token-password": {
+      "Password required": valueMissing,
+      "Password too short. It needs to be at least 10 characters long.": tooShort,
+    },
+  });
+
+  tokenForm.addErrorOverride(40100, "access-token-password");
+  tokenForm.addErrorOverride(40102, "access-token-2fa");
+  tokenForm.addErrorOverride(40301, "access-token-name");
+
+  function renderToken(token) {
+    let li = document.createElement("li");
+    li.classList.add("overlined", "pad");
+
+    let revoke = document.createElement("i");
+    revoke.classList.add("fa", "fa-times", "clickable");
+    revoke.style.float = "right";
+    revoke.title = "Revoke";
+    revoke.addEventListener("click", () => {
+      del("/api/v1/auth/me/tokens/" + token.id + "/", {
+        "X-CSRF-TOKEN": csrfToken,
+      })
+        .then(() => tokenList.removeChild(li))
+        .catch(displayError(output));
+    });
+
+    let name = document.createElement("b");
+    name.innerText = token.name;
+
+    let details = document.createElement("p");
+    details.style.margin = "5px 0 0";
+    details.innerText =
+      "Scopes: " +
+      scopesToString(token.scopes) +
+      "\nCreated: " +
+      token.created_at +
+      "\nExpires: " +
+      (token.expires_at || "Never") +
+      "\nLast used: " +
+      (token.last_used || "Never");
+
+    li.appendChild(revoke);
+    li.appendChild(name);
+    li.appendChild(details);
+
+    tokenList.appendChild(li);
+  }
+
+  get("/api/v1/auth/me/tokens/")
+    .then((response) => response.data.forEach(renderToken))
+    .catch(displayError(output));
+
+  createButton.addEventListener(
+    "click",
+    () => {
+      createButton.style.display = "none";
+      newTokenArea.style.display = "none";
+      htmlTokenForm.style.display = "block";
+    },
+    false
+  );
+
+  let scopeCheckboxes = htmlTokenForm.querySelectorAll("input[data-scope]");
+
+  tokenForm.onSubmit(function () {
+    let scopes = 0;
+
+    for (let checkbox of scopeCheckboxes) {
+      if (checkbox.checked) {
+        scopes |= parseInt(checkbox.dataset.scope);
+      }
+    }
+
+    let data = {
+      name: tokenName.value,
+      scopes: scopes,
+      expires_at: tokenExpiry.value ? tokenExpiry.value + "T00:00:00" : null,
+    };
+
+    post(
+      "/api/v1/auth/me/tokens/",
+      basicAuth(tokenPassword.value, "access-token-2fa"),
+      data
+    )
+      .then((response) => {
+        tokenForm.clear();
+        scopeCheckboxes.forEach((checkbox) => (checkbox.checked = false));
+        renderToken(response.data.data);
+        newToken.innerHTML = response.data.token;
+        htmlTokenForm.style.display = "none";
+        newTokenArea.style.display = "block";
+        createButton.style.display = "";
+      })
+      .catch(displayError(tokenForm));
+  });
+}
+
+function showRecoveryCodes(codes) {
+  document.getElementById("recovery-codes").innerHTML = codes.join("\n");
+  document.getElementById("recovery-codes-area").style.display = "block";
+}
+
+function setupTwoFactor() {
+  let output = new Output(document.getElementById("two-factor-panel"));
+
+  let enableButton = document.getElementById("enable-two-factor");
+
+  if (enableButton) {
+    let htmlEnrollForm = document.getElementById("two-factor-enroll-form");
+    let enrollForm = new Form(htmlEnrollForm);
+    let enrollPassword = enrollForm.input("two-factor-enroll-password");
+
+    let htmlConfirmForm = document.getElementById("two-factor-confirm-form");
+    let confirmForm = new Form(htmlConfirmForm);
+    let confirmCode = confirmForm.input("two-factor-confirm-code");
+
+    enrollPassword.clearOnInvalid = true;
+    enrollForm.addValidators({
+      "two-factor-enroll-password": {
+        "Password required": valueMissing,
+        "Password too short. It needs to be at least 10 characters long.": tooShort,
+      },
+    });
+    enrollForm.addErrorOverride(40100, "two-factor-enroll-password");
+
+    confirmForm.addValidators({
+      "two-factor-confirm-code": {
+        "Please enter the code from your authenticator app": valueMissing,
+      },
+    });
+    confirmForm.addErrorOverride(40102, "two-factor-confirm-code");
+
+    enableButton.addEventListener(
+      "click",
+      () => {
+        enableButton.style.display = "none";
+        htmlEnrollForm.style.display = "block";
+      },
+      false
+    );
+
+    // The password has to be sent along with the confirmation as well, so we hold on to it until then
+    let password = null;
+
+    enrollForm.onSubmit(() => {
+      post("/api/v1/auth/me/2fa/", basicAuth(enrollPassword.value))
+        .then((response) => {
+          password = enrollPassword.value;
+          enrollForm.clear();
+
+          document.getElementById("two-factor-secret").innerHTML =
+            response.data.secret;
+          document.getElementById("two-factor-uri").href =
+            response.data.uri;
+
+          htmlEnrollForm.style.display = "none";
+          htmlConfirmForm.style.display = "block";
+        })
+        .catch(displayError(enrollForm));
+    });
+
+    confirmForm.onSubmit(() => {
+      post("/api/v1/auth/me/2fa/confirm/", basicAuth(password), {
+        code: confirmCode.value,
+      })
+        .then((response) => {
+          htmlConfirmForm.style.display = "none";
+          output.setSuccess(
+            "Two-factor authentication enabled! Reload the page once you have stored your recovery codes."
+          );
+          showRecoveryCodes(response.data.recovery_codes);
+        })
+        .catch(displayError(confirmForm));
+    });
+  } else {
+    let htmlManageForm = document.getElementById("two-factor-manage-form");
+    let manageForm = new Form(htmlManageForm);
+    let managePassword = manageForm.input("two-factor-manage-password");
+
+    managePassword.clearOnInvalid = true;
+    manageForm.addValidators({
+      "two-factor-manage-password": {
+        "Password required": valueMissing,
+        "Password too short. It needs to be at least 10 characters long.": tooShort,
+      },
+      "two-factor-manage-2fa": {
+        "Please enter a code from your authenticator app or a recovery code": valueMissing,
+      },
+    });
+    manageForm.addErrorOverride(40100, "two-factor-manage-password");
+    manageForm.addErrorOverride(40102, "two-factor-manage-2fa");
+
+    // Whether the form was opened by the "disable" button, as opposed to the "regenerate recovery codes" one
+    let disabling = false;
+
+    for (let [buttonId, disable] of [
+      ["regenerate-recovery-codes", false],
+      ["disable-two-factor", true],
+    ]) {
+      document.getElementById(buttonId).addEventListener(
+        "click",
+        () => {
+          disabling = disable;
+          htmlManageForm.style.display = "block";
+        },
+        false
+      );
+    }
+
+    manageForm.onSubmit(() => {
+      let headers = basicAuth(managePassword.value, "two-factor-manage-2fa");
+
+      if (disabling) {
+        del("/api/v1/auth/me/2fa/", headers)
+          .then(() => window.location.reload())
+          .catch(displayError(manageForm));
+      } else {
+        post("/api/v1/auth/me/2fa/recovery-codes/", headers)
+          .then((response) => {
+            manageForm.clear();
+            htmlManageForm.style.display = "none";
+            showRecoveryCodes(response.data.recovery_codes);
+          })
+          .catch(displayError(manageForm));
+      }
+    });
+  }
+}
+
+function setupSessions(csrfToken) {
+  let output = new Output(document.getElementById("sessions-panel"));
+  let sessionList = document.getElementById("sessions");
+
+  function renderSession(session) {
+    let li = document.createElement("li");
+    li.classList.add("overlined", "pad");
+
+    let revoke = document.createElement("i");
+    revoke.classList.add("fa", "fa-times", "clickable");
+    revoke.style.float = "right";
+    revoke.title = "Revoke";
+    revoke.addEventListener("click", () => {
+      del("/api/v1/auth/me/sessions/" + session.id + "/", {
+        "X-CSRF-TOKEN": csrfToken,
+      })
+        .then(() => {
+          // Revoking the current session logs us out
+          if (session.current) {
+            window.location.reload();
+          } else {
+            sessionList.removeChild(li);
+          }
+        })
+        .catch(displayError(output));
+    });
+
+    let device = document.createElement("b");
+    device.innerText =
+      (session.user_agent || "Unknown device") +
+      (session.current ? " (this device)" : "");
+
+    let details = document.createElement("p");
+    details.style.margin = "5px 0 0";
+    details.innerText =
+      "IP address: " +
+      session.ip_address +
+      "\nLogged in: " +
+      session.created_at +
+      "\nLast active: " +
+      session.last_used;
+
+    li.appendChild(revoke);
+    li.appendChild(device);
+    li.appendChild(details);
+
+    sessionList.appendChild(li);
+  }
+
+  get("/api/v1/auth/me/sessions/")
+    .then((response) => response.data.forEach(renderSession))
+    .catch(displayError(output));
+}
+
+function setupSubmissions() {
+  let output = new Output(document.getElementById("submissions-panel"));
+  let submissionList = document.getElementById("submissions");
+
+  function renderSubmission(record) {
+    let li = document.createElement("li");
+    li.classList.add("overlined", "pad");
+
+    let status = document.createElement("i");
+    status.style.float = "right";
+    status.innerText = record.status;
+
+    let title = document.createElement("b");
+    title.innerText =
+      record.player.name + " - " + record.progress + "% on " + record.demon.name;
+
+    let details = document.createElement("p");
+    details.style.margin = "5px 0 0";
+    details.innerText =
+      "Record #" + record.id + (record.video ? "\nVideo: " + record.video : "");
+
+    li.appendChild(status);
+    li.appendChild(title);
+    li.appendChild(details);
+
+    submissionList.appendChild(li);
+  }
+
+  get("/api/v1/auth/me/submissions/")
+    .then((response) => {
+      if (response.data.length == 0) {
+        let li = document.createElement("li");
+        li.innerText = "You haven't submitted any records while logged in yet";
+        submissionList.appendChild(li);
+      }
+
+      response.data.forEach(renderSubmission);
+    })
+    .catch(displayError(output));
+}
+
+function setupClaim(csrfToken) {
+  let output = new Output(document.getElementById("claim-panel"));
+  let status = document.getElementById("claim-status");
+  let withdraw = document.getElementById("claim-withdraw");
+
+  let htmlClaimForm = document.getElementById("claim-form");
+  let claimForm = new Form(htmlClaimForm);
+  let playerName = claimForm.input("claim-player-name");
+
+  let htmlPlayerForm = document.getElementById("claimed-player-form");
+  let playerForm = new Form(htmlPlayerForm);
+  let nationality = playerForm.input("claimed-player-nationality");
+  let youtubeChannel = playerForm.input("claimed-player-youtube");
+
+  playerName.addValidator(valueMissing, "Player name required");
+  youtubeChannel.addValidator(typeMismatch, "Please enter a valid URL");
+
+  let claim = null;
+  let etag = null;
+
+  function showClaim(newClaim) {
+    claim = newClaim;
+
+    htmlClaimForm.style.display = claim ? "none" : "block";
+    htmlPlayerForm.style.display = claim && claim.verified ? "block" : "none";
+    withdraw.style.display = claim ? "inline-block" : "none";
+
+    if (!claim) {
+      status.innerText = "You haven't claimed a player yet";
+    } else if (!claim.verified) {
+      status.innerText =
+        "Your claim on " +
+        claim.player.name +
+        " is waiting to be verified by a list moderator";
+    } else {
+      status.innerText = "You are " + claim.player.name + " on the demonlist";
+
+      get("/api/v1/players/" + claim.player.id + "/")
+        .then((response) => {
+          let player = response.data.data;
+
+          etag = response.headers["etag"];
+          nationality.value = player.nationality
+            ? player.nationality.country_code
+            : "";
+          youtubeChannel.value = player.youtube_channel || "";
+        })
+        .catch(displayError(output));
+    }
+  }
+
+  claimForm.onSubmit(function () {
+    get("/api/v1/players/?name=" + encodeURIComponent(playerName.value))
+      .then((response) => {
+        if (response.data.length == 0) {
+          playerName.errorText = "No player with this name exists";
+          return;
+        }
+
+        return post(
+          "/api/v1/players/" + response.data[0].id + "/claims/",
+          { "X-CSRF-TOKEN": csrfToken }
+        ).then((response) => {
+          playerName.value = "";
+          showClaim(response.data.data);
+        });
+      })
+      .catch(displayError(claimForm));
+  });
+
+  playerForm.onSubmit(function () {
+    patch(
+      "/api/v1/players/" + claim.player.id + "/",
+      { "X-CSRF-TOKEN": csrfToken, "If-Match": etag },
+      {
+        nationality: nationality.value || null,
+        youtube_channel: youtubeChannel.value || null,
+      }
+    )
+      .then((response) => {
+        if (response.status == 200) {
+          etag = response.headers["etag"];
+          playerForm.setSuccess("Player updated!");
+        } else {
+          playerForm.setSuccess("Nothing changed!");
+        }
+      })
+      .catch(displayError(playerForm));
+  });
+
+  withdraw.addEventListener("click", () => {
+    del("/api/v1/auth/me/claim/", { "X-CSRF-TOKEN": csrfToken })
+      .then(() => showClaim(null))
+      .catch(displayError(output));
+  });
+
+  get("/api/v1/auth/me/claim/")
+    .then((response) => showClaim(response.data.data))
+    .catch(
+      displayError(output, {
+        40401: () => showClaim(null),
+      })
+    );
+}
+
+export function initialize(csrfToken) {
   setupGetAccessToken();
   setupEditAccount();
   setupInvalidateToken();
+  setupAccessTokens(csrfToken);
+  setupTwoFactor();
+  setupSessions(csrfToken);
+  setupClaim(csrfToken);
+  setupSubmissions();
 }
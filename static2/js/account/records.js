@@ -43,6 +43,8 @@ class RecordManager extends Paginator {
     this._demon = document.getElementById("record-demon");
     this._holder = document.getElementById("record-holder");
     this._submitter = document.getElementById("record-submitter");
+    this._validation = document.getElementById("record-validation");
+    this._claim = document.getElementById("record-claim");
     this._notes = document.getElementById("record-notes");
     this._raw_footage = document.getElementById("record-raw-footage")
     this._tok = tok; // FIXME: bad
@@ -77,6 +79,39 @@ class RecordManager extends Paginator {
     setupEditorDialog(new PlayerSelectionDialog("record-holder-dialog"), "record-holder-pen", new PaginatorEditorBackend(this, this._tok, true), this.output);
     this.initDemonDialog();
 
+    document.getElementById("record-validation-refresh").addEventListener('click', () => {
+      post("/api/v1/records/" + this.currentObject.id + "/validation/", {"X-CSRF-TOKEN": this._tok})
+          .then(response => {
+            this.currentObject.validation = response.data;
+            this._validation.innerHTML = describeValidation(response.data);
+            this.output.setSuccess("Queued video check!");
+          })
+          .catch(displayError(this.output));
+    });
+
+    // Claims the record if it isn't claimed, releases the claim otherwise (the server tells us if we aren't allowed to)
+    document.getElementById("record-claim-toggle").addEventListener('click', () => {
+      let endpoint = "/api/v1/records/" + this.currentObject.id + "/claim/";
+
+      if (this.currentObject.claim) {
+        del(endpoint, {"X-CSRF-TOKEN": this._tok})
+            .then(() => {
+              this.currentObject.claim = null;
+              this._claim.innerHTML = describeClaim(null);
+              this.output.setSuccess("Released claim!");
+            })
+            .catch(displayError(this.output));
+      } else {
+        post(endpoint, {"X-CSRF-TOKEN": this._tok})
+            .then(response => {
+              this.currentObject.claim = response.data;
+              this._claim.innerHTML = describeClaim(response.data);
+              this.output.setSuccess("Claimed record!");
+            })
+            .catch(displayError(this.output));
+      }
+    });
+
     document.getElementById("record-copy-info").addEventListener('click', () => {
       navigator.clipboard.writeText(this.currentObject.id + ", " + this._holder.innerText + ", " + this.currentObject.video)
           .then(() => this.output.setSuccess("Copied record data to clipboard!"))
@@ -110,7 +145,17 @@ class RecordManager extends Paginator {
       new PaginatorEditorBackend(this, this._tok, false),
       "record-video-dialog",
       "record-video-pen",
-      this.output
+      this.output,
+      {
+        // Offer to jump to the record that already uses the video
+        40906: error => {
+          form.input("record-video-edit").errorText = error.message + ' (<a class="link" id="record-video-conflict">show record</a>)';
+
+          document.getElementById("record-video-conflict").addEventListener("click", () => {
+            this.selectArbitrary(error.data.id).catch(displayError(this.output));
+          });
+        }
+      }
     );
 
     form.addValidators({
@@ -119,7 +164,7 @@ class RecordManager extends Paginator {
       },
     });
 
-    for (let errorCode of [42222, 42223, 42224, 42225]) {
+    for (let errorCode of [42222, 42223, 42224, 42225, 40910]) {
       form.addErrorOverride(errorCode, "record-video-edit");
     }
   }
@@ -138,7 +183,7 @@ class RecordManager extends Paginator {
       },
     });
 
-    for (let errorCode of [42222, 42223, 42224, 42225]) {
+    for (let errorCode of [42222, 42223, 42225, 42233]) {
       form.addErrorOverride(errorCode, "record-raw-edit");
     }
   }
@@ -177,9 +222,13 @@ class RecordManager extends Paginator {
       this._video_link.style.display = "none";
     }
 
-    this._raw_footage.href = this.currentObject.raw_footage;
-    this._raw_footage.innerHTML = this.currentObject.raw_footage;
-    this._raw_footage.style.display = "initial";
+    if (this.currentObject.raw_footage) {
+      this._raw_footage.href = this.currentObject.raw_footage.url;
+      this._raw_footage.innerHTML = this.currentObject.raw_footage.url;
+      this._raw_footage.style.display = "initial";
+    } else {
+      this._raw_footage.style.display = "none";
+    }
 
     this._id.innerHTML = this.currentObject.id;
     this._demon.innerHTML =
@@ -191,6 +240,8 @@ class RecordManager extends Paginator {
       ")";
     this._status.selectSilently(this.currentObject.status);
     this._submitter.innerHTML = this.currentObject.submitter.id;
+    this._validation.innerHTML = describeValidation(this.currentObject.validation);
+    this._claim.innerHTML = describeClaim(this.currentObject.claim);
 
     // clear notes
     while (this._notes.firstChild) {
@@ -205,15 +256,35 @@ class RecordManager extends Paginator {
   }
 }
 
+function describeValidation(validation) {
+  if (validation === null || validation === undefined) {
+    return "-";
+  }
+
+  if (validation.last_error !== null && validation.status !== "valid") {
+    return validation.status + " (" + validation.last_error + ")";
+  }
+
+  return validation.status;
+}
+
+function describeClaim(claim) {
+  if (claim === null || claim === undefined) {
+    return "-";
+  }
+
+  return (claim.claimed_by.name || claim.claimed_by.id) + " (until " + claim.expires_at.replace("T", " ") + " UTC)";
+}
+
 function createNoteHtml(note, csrfToken) {
   let noteDiv = document.createElement("div");
 
   noteDiv.classList.add("white");
   noteDiv.classList.add("hover");
 
-  // only add option to delete notes if you're list admin (and yes, server sided validation is also in place. I am just too lazy to write permission error handling)
+  // only add option to delete notes if you can manage other people's notes (and yes, server sided validation is also in place. I am just too lazy to write permission error handling)
   let isAdmin =
-    (window.permissions & 0x8) == 0x8 || window.username == note.author;
+    (window.capabilities & 0x200) == 0x200 || window.username == note.author;
 
   if (isAdmin) {
     var closeX = document.createElement("span");
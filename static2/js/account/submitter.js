@@ -21,6 +21,8 @@ function generateSubmitter(submitter) {
 
   if (submitter.banned) {
     li.style.backgroundColor = "rgba(255, 161, 174, .3)";
+  } else if (submitter.statistics.trust == "flagged") {
+    li.style.backgroundColor = "rgba(255, 216, 161, .3)";
   } else {
     li.style.backgroundColor = "rgba( 198, 255, 161, .3)";
   }
@@ -41,6 +43,13 @@ class SubmitterManager extends Paginator {
     );
 
     this._id = document.getElementById("submitter-submitter-id");
+    this._submitted = document.getElementById("submitter-submitted");
+    this._approved = document.getElementById("submitter-approved");
+    this._rejected = document.getElementById("submitter-rejected");
+    this._trust = document.getElementById("submitter-trust");
+    this._firstSeen = document.getElementById("submitter-first-seen");
+    this._lastSeen = document.getElementById("submitter-last-seen");
+    this._member = document.getElementById("submitter-member");
     this._banned = setupDropdownEditor(
       new PaginatorEditorBackend(this, csrfToken, true),
       "edit-submitter-banned",
@@ -57,7 +66,23 @@ class SubmitterManager extends Paginator {
       return;
     }
 
+    let statistics = this.currentObject.statistics;
+
     this._id.innerText = this.currentObject.id;
+    this._submitted.innerText = statistics.submitted;
+    this._approved.innerText = statistics.approved;
+    this._rejected.innerText =
+      statistics.rejected +
+      " (" +
+      Math.round(statistics.rejection_ratio * 100) +
+      "%)";
+    this._trust.innerText = statistics.trust;
+    this._firstSeen.innerText = statistics.first_seen || "-";
+    this._lastSeen.innerText = statistics.last_seen || "-";
+    this._member.innerText =
+      this.currentObject.member === null
+        ? "-"
+        : "User #" + this.currentObject.member;
     this._banned.selectSilently(this.currentObject.banned.toString());
   }
 }
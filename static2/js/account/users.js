@@ -3,7 +3,9 @@
 import {
   del,
   displayError,
+  get,
   patch,
+  post,
   valueMissing,
   FilteredPaginator,
   Form,
@@ -59,6 +61,25 @@ function setupPatchUserPermissionsForm(csrfToken) {
         .catch(displayError(editForm));
     });
   }
+
+  let passwordResetButton = document.getElementById("issue-password-reset");
+
+  if (passwordResetButton) {
+    // Same as above, only pointercrate admins can issue password reset tokens
+    passwordResetButton.addEventListener("click", () => {
+      post("/api/v1/users/" + selectedUser.id + "/password-reset/", {
+        "X-CSRF-TOKEN": csrfToken,
+      })
+        .then((response) => {
+          let reset = response.data.data;
+
+          editForm.setSuccess(
+            "Password reset token (valid until " + reset.expires_at + " UTC): <b>" + reset.token + "</b>"
+          );
+        })
+        .catch(displayError(editForm));
+    });
+  }
 }
 
 function setupUserByIdForm() {
@@ -151,9 +172,49 @@ class UserPaginator extends FilteredPaginator {
   }
 }
 
+function setupSecurityPolicyForm(csrfToken) {
+  let htmlPolicyForm = document.getElementById("security-policy-form");
+
+  // Only generated server sided for pointercrate admins
+  if (!htmlPolicyForm) return;
+
+  let policyForm = new Form(htmlPolicyForm);
+  let requires2fa = policyForm.input("policy-list-team-2fa");
+  let etag;
+
+  get("/api/v1/auth/policy/")
+    .then((response) => {
+      etag = response.headers["etag"];
+      requires2fa.value = response.data.data.list_team_requires_2fa;
+    })
+    .catch(displayError(policyForm));
+
+  policyForm.onSubmit(function () {
+    patch(
+      "/api/v1/auth/policy/",
+      {
+        "X-CSRF-TOKEN": csrfToken,
+        "If-Match": etag,
+      },
+      { list_team_requires_2fa: requires2fa.value }
+    )
+      .then((response) => {
+        if (response.status == 200) {
+          etag = response.headers["etag"];
+
+          policyForm.setSuccess("Successfully updated security policy!");
+        } else {
+          policyForm.setSuccess("No changes made!");
+        }
+      })
+      .catch(displayError(policyForm));
+  });
+}
+
 export function initialize(csrfToken) {
   setupPatchUserPermissionsForm(csrfToken);
   setupUserByIdForm();
+  setupSecurityPolicyForm(csrfToken);
 
   userPaginator = new UserPaginator();
   userPaginator.initialize();
@@ -5,6 +5,7 @@ function initializeLoginForm() {
 
   var loginUsername = loginForm.input("login-username");
   var loginPassword = loginForm.input("login-password");
+  var loginTotp = loginForm.input("login-totp");
 
   loginUsername.addValidator(valueMissing, "Username required");
   loginUsername.addValidator(
@@ -20,16 +21,28 @@ function initializeLoginForm() {
   );
 
   loginForm.onSubmit(function(event) {
-    post("/login/", {
+    let headers = {
       Authorization:
         "Basic " + btoa(loginUsername.value + ":" + loginPassword.value)
-    })
+    };
+
+    // Only shown (and sent) once the server told us that this account has two-factor authentication enabled
+    if (loginTotp.span.style.display !== "none") {
+      headers["X-TOTP-Code"] = loginTotp.value;
+    }
+
+    post("/login/", headers)
       .then(response => {
         window.location = "/account/";
       })
       .catch(response => {
         console.log(response);
-        if (response.status === 401) {
+        if (response.data.code === 40101) {
+          loginTotp.span.style.display = "";
+          loginTotp.errorText = "Please enter the code from your authenticator app, or one of your recovery codes";
+        } else if (response.data.code === 40102) {
+          loginTotp.errorText = response.data.message;
+        } else if (response.status === 401) {
           loginPassword.errorText = "Invalid credentials";
         } else {
           loginForm.setError(response.data.message);
@@ -38,6 +51,69 @@ function initializeLoginForm() {
   });
 }
 
+function initializePasswordReset() {
+  var resetPanel = document.getElementById("reset-password");
+  var resetForm = new Form(document.getElementById("reset-form"));
+
+  document.getElementById("forgot-password").addEventListener("click", () => {
+    resetPanel.style.display = resetPanel.style.display === "none" ? "" : "none";
+  });
+
+  var resetUsername = resetForm.input("reset-username");
+  var resetToken = resetForm.input("reset-token");
+  var resetPassword = resetForm.input("reset-new-password");
+  var resetPasswordRepeat = resetForm.input("reset-new-password-repeat");
+
+  resetUsername.addValidator(valueMissing, "Username required");
+  resetToken.addValidator(valueMissing, "Reset token required");
+
+  resetPassword.addValidator(valueMissing, "Password required");
+  resetPassword.addValidator(
+    tooShort,
+    "Password too short. It needs to be at least 10 characters long."
+  );
+
+  resetPasswordRepeat.addValidator(
+    rpp => rpp.value == resetPassword.value,
+    "Passwords don't match"
+  );
+
+  // Only rendered if the server is able to send emails
+  var requestReset = document.getElementById("request-reset");
+
+  if (requestReset) {
+    requestReset.addEventListener("click", () => {
+      if (!resetUsername.validate()) return;
+
+      post("/api/v1/auth/reset/request/", {}, { username: resetUsername.value })
+        .then(() => {
+          resetForm.setSuccess(
+            "If this account has a recovery email address set, a reset token has been sent to it"
+          );
+        })
+        .catch(response => resetForm.setError(response.data.message));
+    });
+  }
+
+  resetForm.onSubmit(function(event) {
+    post("/api/v1/auth/reset/", {}, resetForm.serialize())
+      .then(() => {
+        resetForm.setSuccess(
+          "Your password has been reset. You can now log in using your new password"
+        );
+      })
+      .catch(response => {
+        if (response.data.code === 40103) {
+          resetToken.errorText = response.data.message;
+        } else if (response.data.code === 42204 || response.data.code === 42236) {
+          resetPassword.errorText = response.data.message;
+        } else {
+          resetForm.setError(response.data.message);
+        }
+      });
+  });
+}
+
 function intializeRegisterForm() {
   var registerForm = new Form(document.getElementById("register-form"));
 
@@ -75,6 +151,8 @@ function intializeRegisterForm() {
       .catch(response => {
         if (response.status === 409) {
           registerUsername.errorText = "This username is already taken. Please choose another one";
+        } else if (response.data.code === 42204 || response.data.code === 42236) {
+          registerPassword.errorText = response.data.message;
         } else {
           registerForm.setError(response.data.message);
         }
@@ -85,4 +163,5 @@ function intializeRegisterForm() {
 $(document).ready(function() {
   initializeLoginForm();
   intializeRegisterForm();
+  initializePasswordReset();
 });
@@ -15,20 +15,53 @@ import {
   setupFormDialogEditor, FormDialog, setupEditorDialog,
 } from "./form.mjs";
 
+/**
+ * Generates the embed URL for the given video. Needs to be kept in sync with the `VideoProvider`
+ * implementations in `src/video/`. Videos are always in their canonical form here.
+ */
 export function embedVideo(video) {
   if (!video) return;
-  // welcome to incredibly fragile string parsing with stadust
-  // see pointercrate::video::embed for a proper implementation of this
 
-  if (video.startsWith("https://www.youtube")) {
-    return "https://www.youtube.com/embed/" + video.substring(32);
-  }
+  let url = new URL(video);
+  let segments = url.pathname.split("/").filter((segment) => segment.length);
 
-  if (video.startsWith("https://www.twitch")) {
-    return (
-      "https://player.twitch.tv/?autoplay=false&parent=pointercrate.com&video=" +
-      video.substring(29)
-    );
+  switch (url.hostname) {
+    case "www.youtube.com":
+      if (segments[0] == "shorts") {
+        return "https://www.youtube.com/embed/" + segments[1];
+      }
+      return "https://www.youtube.com/embed/" + url.searchParams.get("v");
+    case "www.twitch.tv":
+      return (
+        "https://player.twitch.tv/?autoplay=false&parent=" +
+        window.location.hostname +
+        "&video=" +
+        segments[1]
+      );
+    case "vimeo.com":
+      return "https://player.vimeo.com/video/" + segments[0];
+    case "www.bilibili.com":
+      if (segments[1].startsWith("BV")) {
+        return (
+          "https://player.bilibili.com/player.html?bvid=" +
+          segments[1] +
+          "&autoplay=0"
+        );
+      }
+      if (segments[1].startsWith("av")) {
+        return (
+          "https://player.bilibili.com/player.html?aid=" +
+          segments[1].substring(2) +
+          "&autoplay=0"
+        );
+      }
+      return;
+    case "medal.tv":
+      return "https://medal.tv/clip/" + segments[1];
+    case "streamable.com":
+      return "https://streamable.com/e/" + segments[0];
+    case "drive.google.com":
+      return "https://drive.google.com/file/d/" + segments[2] + "/preview";
   }
 }
 
@@ -37,7 +70,7 @@ export function initializeRecordSubmitter(csrf = null, submitApproved = false) {
 
   var demon = submissionForm.input("id_demon");
   var player = submissionForm.input("id_player");
-  var progress = 100;
+  var progress = submissionForm.input("id_progress");
   var video = submissionForm.input("id_video");
   var raw = submissionForm.input("id_raw_footage");
 
@@ -52,6 +85,12 @@ export function initializeRecordSubmitter(csrf = null, submitApproved = false) {
     "Due to Geometry Dash's limitations I know that no player has such a long name"
   );
 
+  progress.addValidator(valueMissing, "Please specify the record's progress");
+  progress.addValidator(rangeUnderflow, "Record progress cannot be negative");
+  progress.addValidator(rangeOverflow, "Record progress cannot be larger than 100%");
+  progress.addValidator(badInput, "Record progress must be a valid integer");
+  progress.addValidator(stepMismatch, "Record progress mustn't be a decimal");
+
   video.addValidator(
     valueMissing,
     "Please specify a video so we can check the records validity"
@@ -68,8 +107,6 @@ export function initializeRecordSubmitter(csrf = null, submitApproved = false) {
     let data = submissionForm.serialize();
     let headers = {};
 
-    data.progress = 100;
-
     if (submitApproved) {
       data.status = "approved";
       headers["X-CSRF-TOKEN"] = csrf;
@@ -95,8 +132,13 @@ export function initializeRecordSubmitter(csrf = null, submitApproved = false) {
           case 42223:
           case 42224:
           case 42225:
+          case 40906:
+          case 40910:
             video.errorText = response.data.message;
             break;
+          case 42233:
+            raw.errorText = response.data.message;
+            break;
           default:
             submissionForm.setError(response.data.message)
         }
@@ -163,9 +205,13 @@ export class StatsViewer extends FilteredPaginator {
 
     var playerData = response.data.data;
 
-    if (playerData.nationality == null) {
-      this._name.textContent = playerData.name;
-    } else {
+    while (this._name.lastChild) {
+      this._name.removeChild(this._name.lastChild);
+    }
+
+    this._name.textContent = playerData.name + " ";
+
+    if (playerData.nationality != null) {
       let flagClass =
         "flag-icon-" + playerData.nationality.country_code.toLowerCase();
 
@@ -173,14 +219,29 @@ export class StatsViewer extends FilteredPaginator {
       span.classList.add("flag-icon", flagClass);
       span.title = playerData.nationality.nation;
 
-      while (this._name.lastChild) {
-        this._name.removeChild(this._name.lastChild);
-      }
-
-      this._name.textContent = playerData.name + " ";
       this._name.appendChild(span);
     }
 
+    if (playerData.claimed) {
+      let badge = document.createElement("i");
+      badge.classList.add("fa", "fa-check-circle");
+      badge.title = "This player has been claimed by their owner";
+      badge.style.marginLeft = "5px";
+
+      this._name.appendChild(badge);
+    }
+
+    if (playerData.youtube_channel != null) {
+      let link = document.createElement("a");
+      link.classList.add("fa", "fa-youtube-play");
+      link.href = playerData.youtube_channel;
+      link.target = "_blank";
+      link.title = "YouTube channel";
+      link.style.marginLeft = "5px";
+
+      this._name.appendChild(link);
+    }
+
     formatDemonsInto(this._created, playerData.created);
     formatDemonsInto(this._published, playerData.published);
     formatDemonsInto(this._verified, playerData.verified);
@@ -190,16 +251,14 @@ export class StatsViewer extends FilteredPaginator {
     beaten.sort((r1, r2) => r1.demon.name.localeCompare(r2.demon.name));
 
     let legacy = beaten.filter(
-      (record) => record.demon.position > window.extended_list_length
+      (record) => sectionKind(record.demon.position) == "legacy"
     ).length;
     let extended = beaten.filter(
-      (record) =>
-        record.demon.position > window.list_length &&
-        record.demon.position <= window.extended_list_length
+      (record) => sectionKind(record.demon.position) == "extended"
     ).length;
 
-    let verifiedExtended = playerData.verified.filter(demon => demon.position <= window.extended_list_length && demon.position > window.list_length).length;
-    let verifiedLegacy = playerData.verified.filter(demon => demon.position > window.extended_list_length).length;
+    let verifiedExtended = playerData.verified.filter(demon => sectionKind(demon.position) == "extended").length;
+    let verifiedLegacy = playerData.verified.filter(demon => sectionKind(demon.position) == "legacy").length;
 
     formatRecordsInto(this._beaten, beaten);
 
@@ -336,6 +395,13 @@ export function generateRecord(record) {
   );
   li.appendChild(document.createElement("br"));
 
+  if (record.claim) {
+    let claim = document.createElement("i");
+    claim.appendChild(document.createTextNode("Claimed by " + (record.claim.claimed_by.name || record.claim.claimed_by.id)));
+    li.appendChild(claim);
+    li.appendChild(document.createElement("br"));
+  }
+
   return li;
 }
 
@@ -368,12 +434,43 @@ function generateStatsViewerPlayer(player) {
   return li;
 }
 
+/**
+ * Returns the list section the given position belongs to, or undefined if it is in none
+ */
+function sectionOf(position) {
+  return (window.list_sections || []).find(
+    (section) =>
+      position >= section.first_position &&
+      (section.last_position === null || position <= section.last_position)
+  );
+}
+
+/**
+ * Classifies a position as "main" (first section of the list), "legacy" (any legacy section) or
+ * "extended" (everything else)
+ */
+function sectionKind(position) {
+  let section = sectionOf(position);
+
+  if (section === undefined) {
+    return "extended";
+  }
+  if (section.legacy) {
+    return "legacy";
+  }
+  if (section === window.list_sections[0]) {
+    return "main";
+  }
+  return "extended";
+}
+
 function formatDemon(demon, link) {
   var element;
+  var kind = sectionKind(demon.position);
 
-  if (demon.position <= window.list_length) {
+  if (kind == "main") {
     element = document.createElement("b");
-  } else if (demon.position <= window.extended_list_length) {
+  } else if (kind == "extended") {
     element = document.createElement("span");
   } else {
     element = document.createElement("i");
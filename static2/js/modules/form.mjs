@@ -276,7 +276,7 @@ export class DropdownDialog extends Dialog {
   }
 }
 
-export function setupEditorDialog(dialog, buttonId, backend, output, dataTransform = x => x) {
+export function setupEditorDialog(dialog, buttonId, backend, output, dataTransform = x => x, specialCodes = {}) {
   document.getElementById(buttonId)
       .addEventListener("click", () => dialog.open());
 
@@ -291,16 +291,16 @@ export function setupEditorDialog(dialog, buttonId, backend, output, dataTransfo
         })
         .catch(response => {
           // FIXME: only works for form dialogs!
-          displayError(dialog.form)(response);
+          displayError(dialog.form, specialCodes)(response);
           throw response;
         });
   };
 }
 
-export function setupFormDialogEditor(backend, dialogId, buttonId, output) {
+export function setupFormDialogEditor(backend, dialogId, buttonId, output, specialCodes = {}) {
   let editor = new FormDialog(dialogId);
 
-  setupEditorDialog(editor, buttonId, backend, output);
+  setupEditorDialog(editor, buttonId, backend, output, x => x, specialCodes);
 
   return editor.form;
 }
@@ -333,7 +333,7 @@ export class Paginator extends Output {
     // external selection listeners
     this.selectionListeners = [];
 
-    // The endpoint which will be paginated. By storing this, we assume that the 'Links' header never redirects
+    // The endpoint which will be paginated. By storing this, we assume that the 'Link' header never redirects
     // us to a different endpoint (this is the case with the pointercrate API)
     this.endpoint = this.html.dataset.endpoint;
     // The endpoint from which the actual objects will be retrieved. By default equal to the pagination endpoint.
@@ -343,11 +343,11 @@ export class Paginator extends Output {
     this.currentLink = this.endpoint + "?" + $.param(queryData);
     // The query data for the first request. Pagination may only update the 'before' and 'after' parameter,
     // meaning everything else will always stay the same.
-    // Storing this means we won't have to parse the query data of the links from the 'Links' header, and allows
+    // Storing this means we won't have to parse the query data of the links from the 'Link' header, and allows
     // us to easily update some parameters later on
     this.queryData = queryData;
 
-    // The (parsed) values of the HTTP 'Links' header, telling us how what requests to make then next or prev is clicked
+    // The (parsed) values of the HTTP 'Link' header, telling us how what requests to make then next or prev is clicked
     this.links = undefined;
     // The callback that constructs list entries for us
     this.itemConstructor = itemConstructor;
@@ -433,7 +433,7 @@ export class Paginator extends Output {
   }
 
   handleResponse(response) {
-    this.links = parsePagination(response.headers["links"]);
+    this.links = parsePagination(response.headers["link"]);
     this.list.scrollTop = 0;
 
     // Clear the current list.
@@ -514,9 +514,10 @@ function parsePagination(linkHeader) {
   var links = {};
   if (linkHeader) {
     for (var link of linkHeader.split(",")) {
-      var s = link.split(";");
+      // Each link has the form <url>; rel="page"
+      var match = link.match(/<([^>]*)>\s*;\s*rel="?([a-z]+)"?/);
 
-      links[s[1].substring(5)] = s[0].substring(1, s[0].length - 1);
+      if (match) links[match[2]] = match[1];
     }
   }
   return links;
@@ -1132,7 +1133,39 @@ const UNEXPECTED_REDIRECT = {
   data: null,
 };
 
+// The refresh currently in progress, if any. All requests failing due to an expired access token
+// wait for the same refresh, since each refresh token can only be used once
+let pendingRefresh = null;
+
+function refresh() {
+  if (pendingRefresh === null) {
+    pendingRefresh = sendRequest("POST", "/login/refresh/").finally(() => {
+      pendingRefresh = null;
+    });
+  }
+
+  return pendingRefresh;
+}
+
 function mkReq(method, endpoint, headers = {}, data = null) {
+  // The web interface authenticates via a short-lived access token stored in a cookie. If it expired, we
+  // use the refresh token cookie to get a new one and try again (requests with an explicit 'Authorization'
+  // header are not cookie based, so a 401 there has nothing to do with an expired access token)
+  let cookieAuthenticated = !("Authorization" in headers);
+
+  return sendRequest(method, endpoint, headers, data).catch((response) => {
+    if (cookieAuthenticated && response.status == 401 && response.data.code == 40100) {
+      return refresh().then(
+        () => sendRequest(method, endpoint, headers, data),
+        () => Promise.reject(response)
+      );
+    }
+
+    return Promise.reject(response);
+  });
+}
+
+function sendRequest(method, endpoint, headers = {}, data = null) {
   headers["Content-Type"] = "application/json";
   headers["Accept"] = "application/json";
 
@@ -8,6 +8,7 @@ import {
 } from "./account/submitter.js";
 import { TabbedPane } from "./modules/tab.mjs";
 import { initialize as initDemons, demonManager } from "./account/demon.js";
+import { initialize as initAuditLog, auditLogViewer } from "./account/audit.js";
 
 let usersInitialized = false;
 let playersInitialized = false;
@@ -23,7 +24,7 @@ $(document).ready(function () {
     "account-tab-selection"
   );
 
-  initProfile();
+  initProfile(csrfToken);
 
   accountTabber.addSwitchListener("2", () => {
     if (!usersInitialized) {
@@ -57,4 +58,10 @@ $(document).ready(function () {
       initSubmitters(csrfToken, accountTabber);
     }
   });
+
+  accountTabber.addSwitchListener("7", () => {
+    if (!auditLogViewer) {
+      initAuditLog();
+    }
+  });
 });